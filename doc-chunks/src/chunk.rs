@@ -22,10 +22,15 @@ pub enum ContentOrigin {
     CargoManifestDescription(PathBuf),
     /// A common mark file at given path.
     CommonMarkFile(PathBuf),
+    /// The comments of a configuration file, i.e. `.toml`, `.yaml` or `.json5`,
+    /// or of a script.
+    ConfigFile(PathBuf),
     /// A rustdoc comment, part of file reference by path in span.
     RustDocTest(PathBuf, Span),
     /// Full rust source file.
     RustSourceFile(PathBuf),
+    /// Rust source read from the standard input, which has no path.
+    Stdin,
     /// A test entity for a rust file, with no meaning outside of test.
     TestEntityRust,
     /// A test entity for a cmark file, with no meaning outside of test.
@@ -39,12 +44,18 @@ impl ContentOrigin {
     /// available, which resolve to static paths: `TestEntityRust` variant
     /// becomes `/tmp/test/entity.rs`, `TestEntityCommonMark` variant becomes
     /// `/tmp/test/entity.md`.
+    ///
+    /// Content from `Stdin` resolves to `-`, the commandline argument it was
+    /// requested with, use [`file`](Self::file) to tell files from other
+    /// sources.
     pub fn as_path(&self) -> &Path {
         match self {
             Self::CargoManifestDescription(path) => path.as_path(),
             Self::CommonMarkFile(path) => path.as_path(),
+            Self::ConfigFile(path) => path.as_path(),
             Self::RustDocTest(path, _) => path.as_path(),
             Self::RustSourceFile(path) => path.as_path(),
+            Self::Stdin => Path::new("-"),
             Self::TestEntityCommonMark => {
                 lazy_static::lazy_static! {
                     static ref TEST_ENTITY_CMARK: PathBuf = PathBuf::from("/tmp/test/entity.md");
@@ -59,11 +70,22 @@ impl ContentOrigin {
             }
         }
     }
+
+    /// The file the content was read from, `None` for the standard input.
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            Self::Stdin => None,
+            origin => Some(origin.as_path()),
+        }
+    }
 }
 
 impl fmt::Display for ContentOrigin {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(formatter, "{}", self.as_path().display())
+        match self {
+            Self::Stdin => formatter.write_str("<stdin>"),
+            origin => write!(formatter, "{}", origin.as_path().display()),
+        }
     }
 }
 
@@ -80,6 +102,9 @@ pub struct CheckableChunk {
     source_mapping: IndexMap<Range, Span>,
     /// Track what kind of comment the chunk is.
     variant: CommentVariant,
+    /// Character ranges of the common mark erased content, which are blanked
+    /// before checking, see [`CheckableChunk::with_masked`].
+    masked: Vec<Range>,
 }
 
 impl std::hash::Hash for CheckableChunk {
@@ -90,6 +115,7 @@ impl std::hash::Hash for CheckableChunk {
             t.hash(hasher);
         });
         self.variant.hash(hasher);
+        self.masked.hash(hasher);
     }
 }
 
@@ -119,9 +145,27 @@ impl CheckableChunk {
             content,
             source_mapping,
             variant,
+            masked: Vec::new(),
+        }
+    }
+
+    /// The same chunk, but with the character ranges `masked` of its common
+    /// mark erased content replaced by spaces, see
+    /// [`PlainOverlay::erase_cmark`]. The length of the erased content and
+    /// with it all ranges and spans stay as they are, so findings within the
+    /// masked copy apply to `self` as well.
+    pub fn with_masked(&self, masked: Vec<Range>) -> Self {
+        Self {
+            masked,
+            ..self.clone()
         }
     }
 
+    /// The masked character ranges of the common mark erased content.
+    pub fn masked(&self) -> &[Range] {
+        &self.masked
+    }
+
     /// Find which part of the range maps to which span. Note that Range can
     /// very well be split into multiple fragments where each of them can be
     /// mapped to a potentially non-continuous span.
@@ -161,8 +205,8 @@ impl CheckableChunk {
             })
             .inspect(|(fragment_span, fragment_range, sub_fragment_range)| {
                 let (fragment_span, fragment_range, sub_fragment_range) = (
-                    fragment_span.clone(),
-                    fragment_range.clone(),
+                    **fragment_span,
+                    (*fragment_range).clone(),
                     sub_fragment_range.clone(),
                 );
                 log::trace!(target: "find_spans",
@@ -188,12 +232,17 @@ impl CheckableChunk {
                     log::trace!(target: "find_spans","sub fragment is zero, dropping!");
                     return None;
                 }
-                if let Some(span_len) = fragment_span.one_line_len() {
-                    debug_assert_eq!(span_len, fragment_range.len());
-                }
                 Some((fragment_span, fragment_range, sub_fragment_range))
             })
             .filter_map(|(fragment_span, fragment_range, sub_fragment_range)| {
+                // an unescaped escape sequence, see
+                // `TrimmedLiteral::unescaped_segments`, covers more source
+                // than content and is only ever reported as a whole
+                if let Some(span_len) = fragment_span.one_line_len() {
+                    if span_len != fragment_range.len() {
+                        return Some((sub_fragment_range, *fragment_span));
+                    }
+                }
                 // take the full fragment string, we need to count newlines before and after
                 let s = sub_char_range(self.as_str(), fragment_range.clone());
 
@@ -238,7 +287,25 @@ impl CheckableChunk {
 
                 Some((sub_fragment_range, sub_fragment_span))
             })
-            .collect::<IndexMap<_, _>>()
+            .fold(IndexMap::new(), |mut acc, (range, span)| {
+                // a word continued in the next `#[doc = ".."]` literal is
+                // covered by a single span, see `LiteralSet::into_chunk`
+                if let CommentVariant::MacroDocEqStr(..) = self.variant {
+                    if let Some((last_range, last_span)) = acc.pop() {
+                        if last_range.end == range.start {
+                            let span = Span {
+                                start: last_span.start,
+                                end: span.end,
+                            };
+                            acc.insert(last_range.start..range.end, span);
+                            return acc;
+                        }
+                        acc.insert(last_range, last_span);
+                    }
+                }
+                acc.insert(range, span);
+                acc
+            })
     }
 
     /// Extract all spans which at least partially overlap with range, i.e.
@@ -389,6 +456,115 @@ impl CheckableChunk {
     pub fn variant(&self) -> CommentVariant {
         self.variant.clone()
     }
+
+    /// The comments of the rust code examples in fenced code blocks, which are
+    /// rendered along with the documentation, as chunks of their own. The
+    /// code itself is not part of them. Their spans refer to the source
+    /// `self` was extracted from.
+    pub fn example_comments(&self) -> Vec<CheckableChunk> {
+        let mut chunks = Vec::new();
+        for code in self.rust_fences() {
+            // the comments are located relative to the code of the fence, the
+            // fence relative to the content of `self`
+            let offset = self.content[..code.start].chars().count();
+            let code = &self.content[code];
+            let mut line_starts = vec![0];
+            for (idx, c) in code.chars().enumerate() {
+                if c == '\n' {
+                    line_starts.push(idx + 1);
+                }
+            }
+            let to_content = |at: &LineColumn| {
+                line_starts
+                    .get(at.line.checked_sub(1)?)
+                    .map(|start| offset + start + at.column)
+            };
+            for set in extract_developer_comments(code) {
+                let comment = Self::from_literalset(set);
+                let mut source_mapping = IndexMap::new();
+                for (range, span) in comment.iter() {
+                    let (start, end) = match (to_content(&span.start), to_content(&span.end)) {
+                        (Some(start), Some(end)) => (start, end + 1),
+                        _ => continue,
+                    };
+                    for (fragment, span) in self.find_spans(start..end) {
+                        let fragment = (range.start + fragment.start - start)
+                            ..(range.start + fragment.end - start);
+                        source_mapping.insert(fragment, span);
+                    }
+                }
+                if !source_mapping.is_empty() {
+                    chunks.push(Self::from_string(
+                        comment.content,
+                        source_mapping,
+                        comment.variant,
+                    ));
+                }
+            }
+        }
+        chunks
+    }
+
+    /// Byte ranges of the code of fenced rust code blocks within the content.
+    fn rust_fences(&self) -> Vec<core::ops::Range<usize>> {
+        use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+
+        // rustdoc considers fences without a language rust, a markdown file
+        // has to be explicit
+        let implicit = self.variant.category() != CommentVariantCategory::CommonMark;
+        let mut fences = Vec::new();
+        let mut code: Option<core::ops::Range<usize>> = None;
+        let mut rust = false;
+        for (event, range) in Parser::new(&self.content).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                    rust = is_rust_fence(&info, implicit);
+                }
+                Event::Text(_) if rust => {
+                    code = Some(code.map_or(range.clone(), |code| code.start..range.end));
+                }
+                Event::End(Tag::CodeBlock(_)) => {
+                    fences.extend(code.take());
+                    rust = false;
+                }
+                _ => {}
+            }
+        }
+        fences
+    }
+
+    /// Whether escape sequences of a `#[doc = ".."]` string were unescaped, so
+    /// parts of the content are shorter than the source they map to.
+    pub fn is_unescaped(&self) -> bool {
+        self.source_mapping.iter().any(|(range, span)| {
+            span.one_line_len()
+                .map_or(false, |span_len| span_len != range.len())
+        })
+    }
+}
+
+/// Whether the info string of a fenced code block denotes rust code, the
+/// attributes of rustdoc such as `ignore` or `edition2021` are accepted.
+/// Without any language, the fence is rust if `implicit`.
+fn is_rust_fence(info: &str, implicit: bool) -> bool {
+    let mut tokens = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .peekable();
+    if tokens.peek().is_none() {
+        return implicit;
+    }
+    let mut rust = implicit;
+    for token in tokens {
+        match token {
+            "rust" => rust = true,
+            "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness"
+            | "allow_fail" => {}
+            token if token.starts_with("edition") || token.starts_with("ignore-") => {}
+            _ => return false,
+        }
+    }
+    rust
 }
 
 /// Convert the clusters of one file into a source description as well as well
@@ -6,7 +6,7 @@ use syn::Macro;
 use syn::Token;
 
 use super::{LiteralSet, TokenTree, TrimmedLiteral};
-use crate::developer::extract_developer_comments;
+use crate::developer::{extract_developer_comments, extract_doc_comments};
 
 use crate::errors::*;
 use crate::Span;
@@ -55,6 +55,42 @@ impl syn::parse::Parse for DocComment {
     }
 }
 
+/// The path of an `include_str!("..")` invocation, none for any other macro.
+fn included_path(mac: &Macro) -> Option<String> {
+    if !mac.path.is_ident("include_str") {
+        return None;
+    }
+    syn::parse2::<LitStr>(mac.tokens.clone())
+        .ok()
+        .map(|path| path.value())
+}
+
+/// The paths of the `#[doc = include_str!("..")]` attributes of `source`, as
+/// written, relative to the file they appear in. Empty if `source` does not
+/// parse.
+pub fn included_docs(source: &str) -> Vec<String> {
+    fn collect(stream: proc_macro2::TokenStream, acc: &mut Vec<String>) {
+        for tree in stream {
+            if let TokenTree::Group(group) = tree {
+                match syn::parse2::<DocComment>(group.stream()) {
+                    Ok(DocComment {
+                        content: DocContent::Macro(mac),
+                        ..
+                    }) => acc.extend(included_path(&mac)),
+                    Ok(_) => {}
+                    Err(_) => collect(group.stream(), acc),
+                }
+            }
+        }
+    }
+
+    let mut acc = Vec::new();
+    if let Ok(stream) = syn::parse_str::<proc_macro2::TokenStream>(source) {
+        collect(stream, &mut acc);
+    }
+    acc
+}
+
 /// Cluster comments together, such they appear as continuous text blocks.
 #[derive(Debug)]
 pub struct Clusters {
@@ -148,6 +184,23 @@ impl Clusters {
         chunk.ensure_sorted();
         Ok(chunk)
     }
+
+    /// Load clusters from a `&str` that might not parse, recovering the doc
+    /// comments with an error tolerant lexer. Optionally loads developer
+    /// comments as well.
+    pub fn load_from_str_tolerant(source: &str, doc_comments: bool, dev_comments: bool) -> Self {
+        let mut chunk = Self {
+            set: Vec::with_capacity(64),
+        };
+        if doc_comments {
+            chunk.set.extend(extract_doc_comments(source));
+        }
+        if dev_comments {
+            chunk.parse_developer_comments(source);
+        }
+        chunk.ensure_sorted();
+        chunk
+    }
 }
 
 #[cfg(test)]
@@ -182,6 +235,21 @@ struct X;
         dbg!(&clusters.set[0]);
     }
 
+    #[test]
+    fn included() {
+        static CONTENT: &str = r#####"
+#![doc = include_str!("../README.md")]
+#![doc = concat!("A", "B")]
+
+mod m {
+    #[doc = include_str!(r"doc/x.md")]
+    struct X;
+}
+"#####;
+        assert_eq!(included_docs(CONTENT), vec!["../README.md", "doc/x.md"]);
+        assert!(included_docs("/// No includes.\nstruct X;").is_empty());
+    }
+
     #[test]
     fn space_in_code_block_does_not_break_cluster() {
         static CONTENT: &str = r#####"
@@ -196,4 +264,42 @@ struct DefinitelyNotZ;
         assert_eq!(clusters.set.len(), 1);
         dbg!(&clusters.set[0]);
     }
+
+    #[test]
+    fn nested_items() {
+        static CONTENT: &str = r#####"
+trait Tr {
+    /// Outer doc of f.
+    fn f(&self) {
+        //! Inner doc of f.
+        /// Doc of g, nested.
+        fn g() {}
+        /// Doc of a module
+        /// within a function.
+        mod nested {
+            #![doc = "Inner doc of nested."]
+            /// Doc of h.
+            fn h() {}
+        }
+    }
+}
+"#####;
+        let clusters = Clusters::load_from_str(CONTENT, true, false).unwrap();
+        let sets = clusters
+            .set
+            .iter()
+            .map(|set| (set.coverage, set.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sets,
+            vec![
+                ((3, 3), " Outer doc of f.".to_owned()),
+                ((5, 5), " Inner doc of f.".to_owned()),
+                ((6, 6), " Doc of g, nested.".to_owned()),
+                ((8, 9), " Doc of a module\n within a function.".to_owned()),
+                ((11, 11), "Inner doc of nested.".to_owned()),
+                ((12, 12), " Doc of h.".to_owned()),
+            ]
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+use ra_ap_syntax::ast::{CommentPlacement, CommentShape};
 use ra_ap_syntax::{ast, AstToken};
 
 use regex::Regex;
@@ -145,6 +146,219 @@ pub fn extract_developer_comments(source: &str) -> Vec<LiteralSet> {
     comments
 }
 
+/// Extracts the line comments starting with `prefix`, i.e. `#` of `.toml` and
+/// `.yaml` files or scripts, to `LiteralSet`s. A comment starts at the
+/// beginning of a line or after whitespace, unless it is quoted. A leading
+/// shebang, `#!`, is not a comment. A comment after code is a set of its own.
+pub fn extract_line_comments(source: &str, prefix: &str) -> Vec<LiteralSet> {
+    let mut sets = Vec::<LiteralSet>::new();
+    let mut multiline = None;
+    let mut previous_trailing = false;
+    for (idx, line) in source.lines().enumerate() {
+        if idx == 0 && line.starts_with("#!") {
+            continue;
+        }
+        let start = match comment_start(line, &[prefix], &mut multiline) {
+            Some((start, _prefix)) => start,
+            None => continue,
+        };
+        let literal = match TrimmedLiteral::from(
+            CommentVariant::LineComment(prefix.to_owned()),
+            &line[start..],
+            prefix.len(),
+            0,
+            idx + 1,
+            line[..start].chars().count(),
+        ) {
+            Err(err) => {
+                log::trace!(
+                    "Failed to create literal from comment in line {} due to \"{}\"",
+                    idx + 1,
+                    err
+                );
+                continue;
+            }
+            Ok(l) => l,
+        };
+        // a comment after code does not continue the comment lines around it
+        let trailing = !line[..start].trim().is_empty();
+        match sets.last_mut() {
+            Some(last) if !trailing && !previous_trailing => {
+                if let Err(literal) = last.add_adjacent(literal) {
+                    sets.push(LiteralSet::from(literal));
+                }
+            }
+            _ => sets.push(LiteralSet::from(literal)),
+        }
+        previous_trailing = trailing;
+    }
+    sets
+}
+
+/// Extracts the `//` and `/* */` comments of a `.json5` file to `LiteralSet`s,
+/// skipping the quoted strings, i.e. `'see // here'`, like
+/// [`extract_line_comments`].
+pub fn extract_json5_comments(source: &str) -> Vec<LiteralSet> {
+    let mut tokens = Vec::new();
+    let mut multiline = None;
+    let mut pos = 0;
+    while pos < source.len() {
+        let line_end = source[pos..]
+            .find('\n')
+            .map_or(source.len(), |idx| pos + idx);
+        let line = &source[pos..line_end];
+        let (begin, end) = match comment_start(
+            line,
+            &[LINE_COMMENT_PREFIX, BLOCK_COMMENT_PREFIX],
+            &mut multiline,
+        ) {
+            Some((start, LINE_COMMENT_PREFIX)) => (pos + start, line_end),
+            Some((start, _block)) => {
+                let begin = pos + start;
+                let end = source[begin + 2..]
+                    .find(BLOCK_COMMENT_POSTFIX)
+                    .map_or(source.len(), |idx| begin + 2 + idx + 2);
+                (begin, end)
+            }
+            None => {
+                pos = line_end + 1;
+                continue;
+            }
+        };
+        tokens.push(TokenWithType::from(TokenWithLineColumn {
+            content: source[begin..end].to_owned(),
+            line: count_lines(&source[..begin]),
+            column: calculate_column(&source[..begin]),
+        }));
+        // the code after a block comment may start another one
+        pos = if end == line_end { end + 1 } else { end };
+    }
+    construct_literal_sets(tokens)
+}
+
+/// Byte offset of the first of `prefixes` starting a comment in `line`, if
+/// any, and the prefix. `multiline` tracks the delimiter of a multiline string,
+/// `"""` or `'''`, across lines.
+fn comment_start<'p>(
+    line: &str,
+    prefixes: &[&'p str],
+    multiline: &mut Option<&'static [u8]>,
+) -> Option<(usize, &'p str)> {
+    let bytes = line.as_bytes();
+    let mut quote = None;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let rest = &bytes[idx..];
+        if let Some(delimiter) = *multiline {
+            if rest.starts_with(delimiter) {
+                *multiline = None;
+                idx += delimiter.len();
+            } else {
+                idx += 1;
+            }
+            continue;
+        }
+        match quote {
+            Some(b'"') if bytes[idx] == b'\\' => idx += 1,
+            Some(q) if bytes[idx] == q => quote = None,
+            Some(_) => {}
+            None => {
+                // quotes only open a string where a value starts, unlike the
+                // apostrophe of i.e. `it's` in a YAML plain scalar
+                let opens = idx == 0
+                    || matches!(
+                        bytes[idx - 1],
+                        b' ' | b'\t' | b'=' | b':' | b'[' | b'{' | b'(' | b','
+                    );
+                if opens && rest.starts_with(b"\"\"\"") {
+                    *multiline = Some(b"\"\"\"");
+                    idx += 3;
+                    continue;
+                }
+                if opens && rest.starts_with(b"'''") {
+                    *multiline = Some(b"'''");
+                    idx += 3;
+                    continue;
+                }
+                if idx == 0 || bytes[idx - 1].is_ascii_whitespace() {
+                    if let Some(prefix) = prefixes
+                        .iter()
+                        .find(|prefix| rest.starts_with(prefix.as_bytes()))
+                    {
+                        return Some((idx, prefix));
+                    }
+                }
+                match bytes[idx] {
+                    q @ (b'"' | b'\'') if opens => quote = Some(q),
+                    _ => {}
+                }
+            }
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// Extracts the doc comments of `source` to `LiteralSet`s with the error
+/// tolerant lexer, for sources `syn` refuses to parse, i.e. with syntax errors
+/// or unstable syntax. Doc attributes, `#[doc = ".."]`, are not recovered.
+pub fn extract_doc_comments(source: &str) -> Vec<LiteralSet> {
+    let parse = ast::SourceFile::parse(source);
+    let mut sets = Vec::<LiteralSet>::new();
+    let comments = parse
+        .syntax_node()
+        .descendants_with_tokens()
+        .filter_map(|nort| nort.into_token().and_then(ast::Comment::cast));
+    for comment in comments {
+        let kind = comment.kind();
+        let variant = match (kind.shape, kind.doc) {
+            (CommentShape::Line, Some(CommentPlacement::Outer)) => CommentVariant::TripleSlash,
+            (CommentShape::Line, Some(CommentPlacement::Inner)) => CommentVariant::DoubleSlashEM,
+            (CommentShape::Block, Some(CommentPlacement::Outer)) => {
+                CommentVariant::SlashAsteriskAsterisk
+            }
+            (CommentShape::Block, Some(CommentPlacement::Inner)) => CommentVariant::SlashAsteriskEM,
+            (_, None) => continue,
+        };
+        let location = usize::from(comment.syntax().text_range().start());
+        let line = count_lines(&source[..location]);
+        let column = calculate_column(&source[..location]);
+        let pre = variant.prefix_len();
+        let content = comment.text();
+        let literal = match kind.shape {
+            CommentShape::Line => TrimmedLiteral::from(variant, content, pre, 0, line, column),
+            CommentShape::Block => {
+                match literal_set_from_block(variant, content, pre, line, column) {
+                    Ok(set) => sets.push(set),
+                    Err(err) => log::trace!("Failed to recover doc block comment: {}", err),
+                }
+                continue;
+            }
+        };
+        let literal = match literal {
+            Err(err) => {
+                log::trace!(
+                    "Failed to create literal from doc comment with content \"{}\" due to \"{}\"",
+                    content,
+                    err
+                );
+                continue;
+            }
+            Ok(l) => l,
+        };
+        // adjacent doc line comments form one set
+        match sets.last_mut() {
+            Some(last) => {
+                if let Err(literal) = last.add_adjacent(literal) {
+                    sets.push(LiteralSet::from(literal));
+                }
+            }
+            None => sets.push(LiteralSet::from(literal)),
+        }
+    }
+    sets
+}
+
 /// Creates a series of `TokenWithType`s from a source string
 fn source_to_iter<'a>(source: &'a str) -> impl Iterator<Item = TokenWithType> + 'a {
     let parse = ast::SourceFile::parse(source);
@@ -189,36 +403,47 @@ fn calculate_column(fragment: &str) -> usize {
 fn literal_set_from_block_comment(
     token: &TokenWithType,
 ) -> std::result::Result<LiteralSet, String> {
-    let number_of_lines = token.content.split("\n").count();
-    let mut lines = token.content.split("\n");
+    literal_set_from_block(
+        CommentVariant::SlashStar,
+        &token.content,
+        token.kind.pre_in_chars(),
+        token.line,
+        token.column,
+    )
+}
+
+/// Creates a `LiteralSet` of `variant` from the `content` of a block comment
+/// starting at `line` and `column`, one literal per line, where the first line
+/// starts with a prefix of `pre` characters.
+fn literal_set_from_block(
+    variant: CommentVariant,
+    content: &str,
+    pre: usize,
+    line: usize,
+    column: usize,
+) -> std::result::Result<LiteralSet, String> {
+    let post = TokenType::BlockComment.post_in_chars();
+    let number_of_lines = content.split("\n").count();
+    let mut lines = content.split("\n");
     if number_of_lines == 1 {
-        let literal = match TrimmedLiteral::from(
-        CommentVariant::SlashStar, &token.content, token.kind.pre_in_chars(),
-        token.kind.post_in_chars(), token.line, token.column) {
-      Err(s) => return Err(format!(
-          "Failed to create literal from single line block comment, content \"{}\" - caused by \"{}\"",
-          token.content, s)),
-      Ok(l) => l
-    };
+        let literal = match TrimmedLiteral::from(variant, content, pre, post, line, column) {
+            Err(s) => return Err(format!(
+                "Failed to create literal from single line block comment, content \"{}\" - caused by \"{}\"",
+                content, s)),
+            Ok(l) => l
+        };
         Ok(LiteralSet::from(literal))
     } else {
         let next_line = match lines.next() {
             None => {
                 return Err(format!(
                     "BUG! Expected block comment \"{}\" to have at least two lines",
-                    token.content
+                    content
                 ))
             }
             Some(l) => l,
         };
-        let literal = match TrimmedLiteral::from(
-            CommentVariant::SlashStar,
-            next_line,
-            token.kind.pre_in_chars(),
-            0,
-            token.line,
-            token.column,
-        ) {
+        let literal = match TrimmedLiteral::from(variant.clone(), next_line, pre, 0, line, column) {
             Err(s) => {
                 return Err(format!(
                     "Failed to create literal from block comment with content \"{}\" \
@@ -229,30 +454,24 @@ fn literal_set_from_block_comment(
             Ok(l) => l,
         };
         let mut literal_set = LiteralSet::from(literal);
-        let mut line_number = token.line;
+        let mut line_number = line;
         while let Some(next_line) = lines.next() {
             line_number += 1;
             let post = if next_line.ends_with(BLOCK_COMMENT_POSTFIX) {
-                TokenType::BlockComment.post_in_chars()
+                post
             } else {
                 0
             };
-            let literal = match TrimmedLiteral::from(
-                CommentVariant::SlashStar,
-                next_line,
-                0,
-                post,
-                line_number,
-                0,
-            ) {
-                Err(s) => {
-                    return Err(format!(
-                        "Failed to create literal from content \"{}\" due to error \"{}\"",
-                        next_line, s
-                    ))
-                }
-                Ok(l) => l,
-            };
+            let literal =
+                match TrimmedLiteral::from(variant.clone(), next_line, 0, post, line_number, 0) {
+                    Err(s) => {
+                        return Err(format!(
+                            "Failed to create literal from content \"{}\" due to error \"{}\"",
+                            next_line, s
+                        ))
+                    }
+                    Ok(l) => l,
+                };
             match literal_set.add_adjacent(literal) {
                 Ok(_) => (),
                 Err(_) => {
@@ -830,4 +1049,110 @@ mod tests {
             assert!(literal.as_str().contains(content_2));
         }
     }
+
+    #[test]
+    fn doc_comments_of_unparseable_source() {
+        let source = "/// First line\n/// second line.\nfn broken( {\n    //! Inner\n    /** Block\n    doc */\n    // developer\n";
+        assert!(syn::parse_str::<proc_macro2::TokenStream>(source).is_err());
+        let sets = extract_doc_comments(source);
+        assert_eq!(sets.len(), 3);
+        let texts = sets
+            .iter()
+            .map(|set| {
+                set.literals()
+                    .iter()
+                    .map(|literal| literal.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                vec![" First line", " second line."],
+                vec![" Inner"],
+                vec![" Block", "    doc "]
+            ]
+        );
+        assert_eq!(sets[0].literals()[0].variant(), CommentVariant::TripleSlash);
+        assert_eq!(sets[1].literals()[0].span().start.line, 4);
+        assert_eq!(sets[1].literals()[0].span().start.column, 7);
+    }
+
+    #[test]
+    fn line_comments_of_config_files() {
+        let source = r#"# A comment
+# continued.
+key = "not # a comment" # trailing
+url: http://example.com/#anchor
+text: it's fine # plain
+multi = """
+# inside a string
+"""
+"#;
+        let sets = extract_line_comments(source, "#");
+        let texts = sets
+            .iter()
+            .map(|set| {
+                set.literals()
+                    .iter()
+                    .map(|literal| literal.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                vec![" A comment", " continued."],
+                vec![" trailing"],
+                vec![" plain"]
+            ]
+        );
+        let trailing = sets[1].literals()[0];
+        assert_eq!(trailing.span().start.line, 3);
+        assert_eq!(trailing.span().start.column, 25);
+    }
+
+    #[test]
+    fn trailing_comments_are_separate() {
+        let source = "# Settings.\nkey = 1 # the key\n# Other\n# settings.\nother = 2\n";
+        let texts = extract_line_comments(source, "#")
+            .iter()
+            .map(|set| {
+                set.literals()
+                    .iter()
+                    .map(|literal| literal.as_str().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                vec![" Settings.".to_owned()],
+                vec![" the key".to_owned()],
+                vec![" Other".to_owned(), " settings.".to_owned()],
+            ]
+        );
+    }
+
+    #[test]
+    fn json5_strings_are_no_comments() {
+        let source = "{\n  // The key.\n  key: 'see // here', /* inline */ other: \"a /* b\",\n  /* Block\n   comment. */\n}\n";
+        let texts = extract_json5_comments(source)
+            .iter()
+            .map(|set| {
+                set.literals()
+                    .iter()
+                    .map(|literal| literal.as_str().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                vec![" The key.".to_owned()],
+                vec![" inline ".to_owned()],
+                vec![" Block".to_owned(), "   comment. ".to_owned()],
+            ]
+        );
+    }
 }
@@ -17,6 +17,9 @@ pub enum Error {
     #[error("{0}")]
     Span(String),
 
+    #[error("Comments of {0} can not be extracted, unknown configuration file type")]
+    UnknownConfigFile(std::path::PathBuf),
+
     #[error("BUG: Found a range {}..{} which that does not exist in its own source mapping: {:?}", .line_range.start, .line_range.end, .source_mapping)]
     InvalidLineRange {
         line_range: Range,
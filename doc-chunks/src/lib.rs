@@ -28,7 +28,7 @@ use indexmap::IndexMap;
 use proc_macro2::TokenTree;
 use rayon::prelude::*;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use toml::Spanned;
 
 /// Range based on `usize`, simplification.
@@ -43,10 +43,12 @@ pub fn apply_offset(range: &mut Range, offset: usize) {
 pub mod chunk;
 pub mod cluster;
 mod developer;
+use self::developer::{extract_developer_comments, extract_json5_comments, extract_line_comments};
 pub mod errors;
 pub mod literal;
 pub mod literalset;
 pub mod markdown;
+pub mod source_map;
 
 pub use chunk::*;
 pub use cluster::*;
@@ -54,6 +56,7 @@ pub use errors::*;
 pub use literal::*;
 pub use literalset::*;
 pub use markdown::*;
+pub use source_map::*;
 
 /// Collection of all the documentation entries across the project
 #[derive(Debug, Clone)]
@@ -113,16 +116,57 @@ impl Documentation {
     }
 
     /// Adds a set of `CheckableChunk`s to the documentation to be checked.
+    /// Chunks the origin already has are skipped, so adding the same file
+    /// twice checks it once.
     pub fn add_inner(&mut self, origin: ContentOrigin, mut chunks: Vec<CheckableChunk>) {
         self.index
             .entry(origin)
             .and_modify(|acc: &mut Vec<CheckableChunk>| {
+                chunks.retain(|chunk| !acc.contains(chunk));
                 acc.append(&mut chunks);
             })
             .or_insert_with(|| chunks);
         // Ok(()) TODO make this failable
     }
 
+    /// Retain only the chunks for which `keep` returns `true`, origins left
+    /// without any chunks are removed.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&ContentOrigin, &CheckableChunk) -> bool,
+    {
+        self.index.retain(|origin, chunks| {
+            chunks.retain(|chunk| keep(origin, chunk));
+            !chunks.is_empty()
+        });
+    }
+
+    /// Remove the chunks of all origins within the file at `path`, to replace
+    /// them with the ones of its current content.
+    pub fn remove_file(&mut self, path: &std::path::Path) {
+        self.index
+            .retain(|origin, _chunks| origin.as_path() != path);
+    }
+
+    /// Adds the comments of the fenced rust code examples of all doc comments
+    /// and common mark content as chunks of their own, see
+    /// [`CheckableChunk::example_comments`].
+    pub fn add_example_comments(&mut self) {
+        for chunks in self.index.values_mut() {
+            let examples = chunks
+                .iter()
+                .filter(|chunk| {
+                    matches!(
+                        chunk.variant().category(),
+                        CommentVariantCategory::Doc | CommentVariantCategory::CommonMark
+                    )
+                })
+                .flat_map(CheckableChunk::example_comments)
+                .collect::<Vec<_>>();
+            chunks.extend(examples);
+        }
+    }
+
     /// Adds a rust content str to the documentation.
     pub fn add_rust(
         &mut self,
@@ -138,6 +182,50 @@ impl Documentation {
         Ok(())
     }
 
+    /// Adds a rust content str to the documentation, like
+    /// [`Documentation::add_rust`], but also for content with syntax errors.
+    /// Doc attributes are only recovered if the content parses.
+    pub fn add_rust_tolerant(
+        &mut self,
+        origin: ContentOrigin,
+        content: &str,
+        doc_comments: bool,
+        dev_comments: bool,
+    ) {
+        let cluster = Clusters::load_from_str_tolerant(content, doc_comments, dev_comments);
+
+        let chunks = Vec::<CheckableChunk>::from(cluster);
+        self.add_inner(origin, chunks);
+    }
+
+    /// Adds the comments of a configuration file, `#` comments of `.toml` and
+    /// `.yaml` files, `//` and `/* */` comments of `.json5` files.
+    pub fn add_config_file(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
+        let extension = origin
+            .as_path()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let mut set = match extension.as_deref() {
+            Some("toml" | "yaml" | "yml") => extract_line_comments(content, "#"),
+            Some("json5") => extract_json5_comments(content),
+            _ => return Err(Error::UnknownConfigFile(origin.as_path().to_owned())),
+        };
+        set.sort_by_key(|ls| ls.coverage);
+
+        let chunks = Vec::<CheckableChunk>::from(Clusters { set });
+        self.add_inner(origin, chunks);
+        Ok(())
+    }
+
+    /// Adds the line comments of a script or any other file, which start with
+    /// `prefix`, i.e. `#` or `--`.
+    pub fn add_line_comments(&mut self, origin: ContentOrigin, content: &str, prefix: &str) {
+        let set = extract_line_comments(content, prefix);
+        let chunks = Vec::<CheckableChunk>::from(Clusters { set });
+        self.add_inner(origin, chunks);
+    }
+
     /// Adds a content string to the documentation sourced from the
     /// `description` field in a `Cargo.toml` manifest.
     pub fn add_cargo_manifest_description(
@@ -228,17 +316,12 @@ impl Documentation {
 
     /// Adds a common mark content str to the documentation.
     pub fn add_commonmark(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
-        // extract the full content span and range
+        // extract the full content span and range, the span ends at the last
+        // char, which might be a trailing line break
         let start = LineColumn { line: 1, column: 0 };
-        let end = content
-            .lines()
-            .enumerate()
+        let end = util::iter_with_line_column(content)
             .last()
-            .map(|(idx, linecontent)| (idx + 1, linecontent))
-            .map(|(linenumber, linecontent)| LineColumn {
-                line: linenumber,
-                column: linecontent.chars().count().saturating_sub(1),
-            })
+            .map(|(_c, _byte_offset, _idx, cursor)| cursor)
             .ok_or_else(|| {
                 Error::Span(
                     "Common mark / markdown file does not contain a single line".to_string(),
@@ -260,6 +343,44 @@ impl Documentation {
         Ok(())
     }
 
+    /// Adds text extracted by other means than parsing rust or markdown, i.e.
+    /// by a `tree-sitter` based extractor or a comment scanner for another
+    /// language. Each text must appear verbatim at its `Span` in the origin,
+    /// it is checked as markdown.
+    pub fn add_chunks(&mut self, origin: ContentOrigin, chunks: Vec<(String, Span)>) -> Result<()> {
+        let chunks = chunks
+            .into_iter()
+            .filter(|(text, _span)| !text.is_empty())
+            .map(|(text, span)| {
+                let len = text.chars().count();
+                if span.is_multiline() {
+                    let lines = text.lines().count();
+                    if lines != span.end.line - span.start.line + 1 {
+                        return Err(Error::Span(format!(
+                            "Text of {} lines does not fit {:?}",
+                            lines, span
+                        )));
+                    }
+                } else if span.one_line_len() != Some(len) {
+                    return Err(Error::Span(format!(
+                        "Text of {} characters does not fit {:?}",
+                        len, span
+                    )));
+                }
+                let source_mapping = indexmap::indexmap! {
+                    0..len => span
+                };
+                Ok(CheckableChunk::from_string(
+                    text,
+                    source_mapping,
+                    CommentVariant::Unknown,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.add_inner(origin, chunks);
+        Ok(())
+    }
+
     /// Obtain the set of chunks for a particular origin.
     #[inline(always)]
     pub fn get(&self, origin: &ContentOrigin) -> Option<&[CheckableChunk]> {
@@ -272,6 +393,31 @@ impl Documentation {
         self.index.len()
     }
 
+    /// Load the documentation of the file at `path`, depending on its kind:
+    /// the doc comments of rust sources, and with `dev_comments` their other
+    /// comments as well, common mark files, the `description` of `Cargo.toml`
+    /// manifests and the comments of other configuration files.
+    pub fn from_path(path: impl AsRef<Path>, dev_comments: bool) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs_err::read_to_string(path)?;
+        let mut docs = Self::new();
+        let path = path.to_owned();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("rs") => docs.add_rust(
+                ContentOrigin::RustSourceFile(path),
+                &content,
+                true,
+                dev_comments,
+            )?,
+            Some("md") => docs.add_commonmark(ContentOrigin::CommonMarkFile(path), &content)?,
+            _ if path.file_name() == Some("Cargo.toml".as_ref()) => {
+                docs.add_cargo_manifest_description(path, &content)?
+            }
+            _ => docs.add_config_file(ContentOrigin::ConfigFile(path), &content)?,
+        }
+        Ok(docs)
+    }
+
     /// Load a document from a single string with a defined origin.
     pub fn load_from_str(
         origin: ContentOrigin,
@@ -290,13 +436,14 @@ impl Documentation {
                     Ok(())
                 }
             }
-            origin @ ContentOrigin::RustSourceFile(_) => {
+            origin @ (ContentOrigin::RustSourceFile(_) | ContentOrigin::Stdin) => {
                 docs.add_rust(origin, content, doc_comments, dev_comments)
             }
             ContentOrigin::CargoManifestDescription(path) => {
                 docs.add_cargo_manifest_description(path, content)
             }
             origin @ ContentOrigin::CommonMarkFile(_) => docs.add_commonmark(origin, content),
+            origin @ ContentOrigin::ConfigFile(_) => docs.add_config_file(origin, content),
             origin @ ContentOrigin::TestEntityRust => {
                 docs.add_rust(origin, content, doc_comments, dev_comments)
             }
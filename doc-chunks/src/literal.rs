@@ -51,6 +51,9 @@ pub enum CommentVariant {
     Unknown,
     /// Toml entry
     TomlEntry,
+    /// Line comment of a configuration file or script, with its prefix, i.e.
+    /// `#` or `--`
+    LineComment(String),
 }
 
 impl Default for CommentVariant {
@@ -74,6 +77,16 @@ impl CommentVariant {
             _ => CommentVariantCategory::Dev,
         }
     }
+    /// Whether this is an inner doc comment, `//!`, `/*!` or `#![doc = ..]`,
+    /// which documents the enclosing item rather than the following one.
+    pub fn is_inner(&self) -> bool {
+        match self {
+            Self::DoubleSlashEM | Self::SlashAsteriskEM => true,
+            Self::MacroDocEqStr(prefix, _) => prefix.starts_with("#!"),
+            _ => false,
+        }
+    }
+
     /// Return the prefix string.
     ///
     /// Does not include whitespaces for `///` and `//!` variants!
@@ -98,6 +111,7 @@ impl CommentVariant {
             CommentVariant::SlashAsteriskEM => "/*!".to_string(),
             CommentVariant::SlashAsteriskAsterisk => "/**".to_string(),
             CommentVariant::TomlEntry => "".to_owned(),
+            CommentVariant::LineComment(prefix) => prefix.clone(),
             unhandled => unreachable!(
                 "String representation for comment variant {:?} exists. qed",
                 unhandled
@@ -306,7 +320,7 @@ fn detect_comment_variant(
             static ref BOUNDED_RAW_STR: Regex =
                 Regex::new(r##"^(r(#*)")(?:.*\s*)+?(?=(?:"\2))("\2)\s*\]?\s*$"##)
                     .expect("BOUNEDED_RAW_STR regex compiles");
-            static ref BOUNDED_STR: Regex = Regex::new(r##"^"(?:.(?!"\\"))*?"*\s*\]?\s*"$"##)
+            static ref BOUNDED_STR: Regex = Regex::new(r##"(?s)^"(?:.(?!"\\"))*?"*\s*\]?\s*"$"##)
                 .expect("BOUNEDED_STR regex compiles");
         };
 
@@ -549,6 +563,191 @@ impl TrimmedLiteral {
         self.variant.clone()
     }
 
+    /// Split a block comment with a conventional star gutter, as in
+    ///
+    /// ```text
+    /// /**
+    ///  * Content
+    ///  */
+    /// ```
+    ///
+    /// into its lines without the gutter, each with the exact span of the
+    /// remaining content. Lines without content have no span. Returns `None`
+    /// if the literal is not such a block comment.
+    pub(crate) fn star_gutter_lines(&self) -> Option<Vec<(&str, Option<Span>)>> {
+        match self.variant {
+            CommentVariant::SlashAsterisk
+            | CommentVariant::SlashAsteriskAsterisk
+            | CommentVariant::SlashAsteriskEM => {}
+            _ => return None,
+        }
+        let content = self.as_str();
+        let mut lines = content.split('\n');
+        let first = lines.next()?;
+        let rest = lines.collect::<Vec<_>>();
+        let gutter = |line: &str| -> Option<usize> {
+            let indentation = line.len() - line.trim_start().len();
+            let after = line[indentation..].strip_prefix('*')?;
+            Some(indentation + 1 + usize::from(after.starts_with(' ')))
+        };
+        // the line closing the comment is commonly without content
+        let decorated = rest
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .all(|line| gutter(line).is_some());
+        if rest.is_empty() || !decorated || rest.iter().all(|line| line.trim().is_empty()) {
+            return None;
+        }
+
+        let span_of = |line: usize, column: usize, s: &str| {
+            let n = s.chars().count();
+            (n > 0).then(|| Span {
+                start: LineColumn { line, column },
+                end: LineColumn {
+                    line,
+                    column: column + n - 1,
+                },
+            })
+        };
+        let mut acc = Vec::with_capacity(rest.len() + 1);
+        acc.push((
+            first,
+            span_of(self.span.start.line, self.span.start.column, first),
+        ));
+        for (idx, line) in rest.into_iter().enumerate() {
+            let line_no = self.span.start.line + idx + 1;
+            match gutter(line) {
+                Some(skip) => {
+                    // gutters are ASCII, bytes equal chars
+                    let stripped = &line[skip..];
+                    acc.push((stripped, span_of(line_no, skip, stripped)));
+                }
+                None => acc.push(("", None)),
+            }
+        }
+        Some(acc)
+    }
+
+    /// Split the content of a `#[doc = ".."]` string with escape sequences,
+    /// i.e. `\"` or `\n`, into its unescaped segments, each with the span it
+    /// covers in the source. A segment is a run of verbatim characters within
+    /// one line, a single escape sequence or a line break, the latter and line
+    /// continuations have no span. Returns `None` if there is nothing to
+    /// unescape, which includes all raw strings.
+    pub(crate) fn unescaped_segments(&self) -> Option<Vec<(String, Option<Span>)>> {
+        if !matches!(self.variant, CommentVariant::MacroDocEqStr(_, 0)) {
+            return None;
+        }
+        let content = self.as_str();
+        if !content.contains('\\') {
+            return None;
+        }
+
+        let mut acc = Vec::<(String, Option<Span>)>::with_capacity(8);
+        let mut cursor = self.span.start;
+        let mut run = String::new();
+        let mut run_start = cursor;
+        let flush =
+            |acc: &mut Vec<(String, Option<Span>)>, run: &mut String, run_start: LineColumn| {
+                if !run.is_empty() {
+                    let n = run.chars().count();
+                    let end = LineColumn {
+                        line: run_start.line,
+                        column: run_start.column + n - 1,
+                    };
+                    acc.push((
+                        std::mem::take(run),
+                        Some(Span {
+                            start: run_start,
+                            end,
+                        }),
+                    ));
+                }
+            };
+        let mut chars = content.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' => {
+                    flush(&mut acc, &mut run, run_start);
+                    acc.push(("\n".to_owned(), None));
+                    cursor = LineColumn {
+                        line: cursor.line + 1,
+                        column: 0,
+                    };
+                    run_start = cursor;
+                }
+                '\\' => {
+                    flush(&mut acc, &mut run, run_start);
+                    let start = cursor;
+                    let mut len = 1;
+                    let unescaped = match chars.next() {
+                        Some('n') => Some('\n'),
+                        Some('r') => Some('\r'),
+                        Some('t') => Some('\t'),
+                        Some('0') => Some('\0'),
+                        Some(c @ ('\\' | '"' | '\'')) => Some(c),
+                        Some('x') => {
+                            let hex = chars.by_ref().take(2).collect::<String>();
+                            len += hex.chars().count();
+                            u8::from_str_radix(&hex, 16).ok().map(char::from)
+                        }
+                        Some('u') => {
+                            let mut hex = String::new();
+                            for c in chars.by_ref() {
+                                len += 1;
+                                if c == '}' {
+                                    break;
+                                }
+                                hex.push(c);
+                            }
+                            u32::from_str_radix(hex.trim_start_matches('{'), 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                        }
+                        Some('\n') => {
+                            // a line continuation skips the leading whitespace
+                            // of the next line
+                            cursor = LineColumn {
+                                line: cursor.line + 1,
+                                column: 0,
+                            };
+                            while let Some(c) = chars.next_if(|c| c.is_whitespace()) {
+                                if c == '\n' {
+                                    cursor.line += 1;
+                                    cursor.column = 0;
+                                } else {
+                                    cursor.column += 1;
+                                }
+                            }
+                            acc.push((String::new(), None));
+                            run_start = cursor;
+                            continue;
+                        }
+                        Some(_) | None => None,
+                    };
+                    len += 1;
+                    let end = LineColumn {
+                        line: start.line,
+                        column: start.column + len - 1,
+                    };
+                    cursor.column += len;
+                    run_start = cursor;
+                    match unescaped {
+                        Some(c) => acc.push((c.to_string(), Some(Span { start, end }))),
+                        // not a valid string literal, keep the content as is
+                        None => return None,
+                    }
+                }
+                c => {
+                    run.push(c);
+                    cursor.column += 1;
+                }
+            }
+        }
+        flush(&mut acc, &mut run, run_start);
+        Some(acc)
+    }
+
     /// Display helper, mostly used for debug investigations
     #[allow(unused)]
     pub(crate) fn display(&self, highlight: Range) -> TrimmedLiteralDisplay {
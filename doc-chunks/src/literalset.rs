@@ -40,6 +40,11 @@ impl LiteralSet {
             );
             return Err(literal);
         }
+        // inner and outer docs on adjacent lines belong to different items,
+        // i.e. a function and the first item nested within its body
+        if literal.variant().is_inner() != self.variant.is_inner() {
+            return Err(literal);
+        }
         let previous_line = literal.span().end.line;
         if previous_line == self.coverage.1 + 1 {
             self.coverage.1 += 1;
@@ -49,8 +54,8 @@ impl LiteralSet {
 
         let next_line = literal.span().start.line;
         if next_line + 1 == self.coverage.0 {
-            self.literals.push(literal);
-            self.coverage.1 -= 1;
+            self.literals.insert(0, literal);
+            self.coverage.0 -= 1;
             return Ok(());
         }
 
@@ -80,9 +85,51 @@ impl LiteralSet {
             // for use with `Range`
             let mut start; // inclusive
             let mut end; // exclusive
+
+            // doc attributes written like `///` comments start with a space
+            let indented = self.literals[0].as_str().starts_with(char::is_whitespace);
             let mut it = self.literals.iter();
             let mut next = it.next();
             while let Some(literal) = next {
+                if let Some(segments) = literal.unescaped_segments() {
+                    for (segment, span) in segments {
+                        start = cursor;
+                        cursor += segment.chars().count();
+                        end = cursor;
+                        if let Some(span) = span {
+                            source_mapping.insert(Range { start, end }, span);
+                        }
+                        content.push_str(&segment);
+                    }
+                    next = it.next();
+                    if next.is_some() {
+                        content.push('\n');
+                        cursor += 1;
+                    }
+                    continue;
+                }
+                if let Some(lines) = literal.star_gutter_lines() {
+                    let n_lines = lines.len();
+                    for (idx, (line, span)) in lines.into_iter().enumerate() {
+                        start = cursor;
+                        cursor += line.chars().count();
+                        end = cursor;
+                        if let Some(span) = span {
+                            source_mapping.insert(Range { start, end }, span);
+                        }
+                        content.push_str(line);
+                        if idx + 1 < n_lines {
+                            content.push('\n');
+                            cursor += 1;
+                        }
+                    }
+                    next = it.next();
+                    if next.is_some() {
+                        content.push('\n');
+                        cursor += 1;
+                    }
+                    continue;
+                }
                 start = cursor;
                 cursor += literal.len_in_chars();
                 end = cursor;
@@ -103,10 +150,13 @@ impl LiteralSet {
                 content.push_str(literal.as_str());
                 // the newline is _not_ covered by a span, after all it's inserted by us!
                 next = it.next();
-                if next.is_some() {
-                    // for the last, skip the newline
-                    content.push('\n');
-                    cursor += 1;
+                if let Some(next) = next {
+                    // for the last, skip the newline, as well as for words that
+                    // continue in the next literal
+                    if !continues_word(indented, literal, next) {
+                        content.push('\n');
+                        cursor += 1;
+                    }
                 }
             }
         }
@@ -120,6 +170,16 @@ impl LiteralSet {
     }
 }
 
+/// A word split across two `#[doc = ".."]` attributes, as left behind by
+/// mechanical line wrapping, is joined without a newline. Only the
+/// continuation lacks the leading space of an indented set.
+fn continues_word(indented: bool, literal: &TrimmedLiteral, next: &TrimmedLiteral) -> bool {
+    indented
+        && matches!(literal.variant(), CommentVariant::MacroDocEqStr(..))
+        && literal.as_str().ends_with(char::is_alphanumeric)
+        && next.as_str().starts_with(char::is_alphanumeric)
+}
+
 impl<'s> fmt::Display for LiteralSet {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let n = self.len();
@@ -149,9 +209,10 @@ macro_rules! chyrp_dbg {
 /// Example:
 ///
 /// ```rust
-/// let x = chryp_up!(["some", "thing"])
+/// # use doc_chunks::chyrp_up;
+/// let x = chyrp_up!(["some", "thing"]);
 /// let y = r##"#[doc=r#"some
-/// thing"#
+/// thing"#]
 /// struct ChyrpChyrp;"##;
 ///
 /// assert_eq!(x,y);
@@ -172,10 +233,11 @@ macro_rules! chyrp_up {
 /// Example:
 ///
 /// ```rust
-/// let x = fluff_up!(["some", "thing"])
+/// # use doc_chunks::fluff_up;
+/// let x = fluff_up!(["some", "thing"]);
 /// let y = r#"/// some
 /// /// thing
-/// struct Fluff;"##;
+/// struct Fluff;"#;
 ///
 /// assert_eq!(x,y);
 /// ```
@@ -192,33 +254,14 @@ macro_rules! fluff_up {
     };
 }
 
-pub mod tests {
-    use super::*;
-    use crate::tests::annotated_literals;
-
-    pub fn gen_literal_set(source: &str) -> LiteralSet {
-        let literals = dbg!(annotated_literals(dbg!(source)));
-
-        let mut iter = dbg!(literals).into_iter();
-        let literal = iter
-            .next()
-            .expect("Must have at least one item in laterals");
-        let mut cls = LiteralSet::from(literal);
-
-        for literal in iter {
-            assert!(cls.add_adjacent(literal).is_ok());
-        }
-        dbg!(cls)
-    }
-}
-
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
 
-    use crate::tests::annotated_literals;
+    use crate::tests::{annotated_literals, gen_literal_set};
     use crate::util::load_span_from;
     use crate::util::sub_chars;
+    use crate::{LineColumn, Span};
 
     #[test]
     fn fluff_one() {
@@ -343,6 +386,105 @@ struct Vikings;
         test_raw!(["+ 12 + x0"] ; 9..10, "0");
     }
 
+    #[test]
+    fn word_continued_in_next_doc_attribute() {
+        const RAW: &str = r#"#[doc = " A spel"]
+#[doc = "ling mistake."]
+struct Wrapped;
+"#;
+        let chunk = gen_literal_set(RAW).into_chunk();
+        assert_eq!(chunk.as_str(), " A spelling mistake.");
+
+        let spans = chunk.find_spans(3..11);
+        assert_eq!(spans.len(), 1);
+        let (range, span) = spans.first().unwrap();
+        assert_eq!(range, &(3..11));
+        assert_eq!(
+            span,
+            &Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 12
+                },
+                end: LineColumn {
+                    line: 2,
+                    column: 12
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn star_gutter_is_stripped() {
+        const RAW: &str = r#"/**
+ * A spelling
+ *
+ * mistake.
+ */
+struct Decorated;
+"#;
+        let chunk = gen_literal_set(RAW).into_chunk();
+        assert_eq!(chunk.as_str(), "\nA spelling\n\nmistake.\n");
+
+        let spans = chunk.find_spans(3..11);
+        assert_eq!(spans.len(), 1);
+        let (_range, span) = spans.first().unwrap();
+        assert_eq!(
+            span,
+            &Span {
+                start: LineColumn { line: 2, column: 5 },
+                end: LineColumn {
+                    line: 2,
+                    column: 12
+                },
+            }
+        );
+        assert_eq!(load_span_from(RAW.as_bytes(), *span).unwrap(), "spelling");
+        let (_range, span) = chunk.find_spans(13..21).into_iter().next().unwrap();
+        assert_eq!(load_span_from(RAW.as_bytes(), span).unwrap(), "mistake.");
+    }
+
+    #[test]
+    fn escapes_are_unescaped() {
+        const RAW: &str = r#"#[doc = "A \"quoted\" wrod\x21\nand \u{e9}t\u{e9} \
+    continued."]
+#[doc = r"Raw \n stays."]
+struct Escaped;
+"#;
+        let chunk = gen_literal_set(RAW).into_chunk();
+        assert_eq!(
+            chunk.as_str(),
+            "A \"quoted\" wrod!\nand été continued.\nRaw \\n stays."
+        );
+        assert!(chunk.is_unescaped());
+
+        let lookup = |word: &str| {
+            let start = chunk.as_str().find(word).unwrap();
+            let start = chunk.as_str()[..start].chars().count();
+            let range = start..start + word.chars().count();
+            chunk
+                .find_spans(range)
+                .values()
+                .map(|span| load_span_from(RAW.as_bytes(), *span).unwrap())
+                .collect::<String>()
+        };
+        assert_eq!(lookup("wrod"), "wrod");
+        assert_eq!(lookup("\"quoted\""), r#"\"quoted\""#);
+        assert_eq!(lookup("été"), r"\u{e9}t\u{e9}");
+        assert_eq!(lookup("continued"), "continued");
+        assert_eq!(lookup("stays"), "stays");
+    }
+
+    #[test]
+    fn separate_words_in_doc_attributes() {
+        const RAW: &str = r#"#[doc = "First"]
+#[doc = "line"]
+struct Unindented;
+"#;
+        let chunk = gen_literal_set(RAW).into_chunk();
+        assert_eq!(chunk.as_str(), "First\nline");
+    }
+
     #[test]
     fn literal_set_into_chunk() {
         let _ = env_logger::builder()
@@ -9,8 +9,8 @@ use indexmap::IndexMap;
 use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
 
 use crate::util::sub_chars;
-use crate::Span;
 use crate::{CheckableChunk, Range};
+use crate::{SourceMap, Span};
 
 /// Describes whether there is a matching segment in the source, of if it is a
 /// placeholder for i.e. a code block or inline code. These placeholders are
@@ -81,21 +81,14 @@ pub struct PlainOverlay<'a> {
     raw: &'a CheckableChunk,
     /// The rendered string with all common mark annotations removed.
     plain: String,
-    // require a sorted map, so we have the chance of binary search
-    // key: plain string range
-    // value: the corresponding areas in the full cmark
-    mapping: IndexMap<Range, SourceRange>,
+    /// The origin of the fragments of `plain` in the full cmark.
+    mapping: SourceMap,
 }
 
 impl<'a> PlainOverlay<'a> {
     /// Track the origin of the annotation free content string fragments in the
     /// common mark formatted text, to the fragments in the plain string.
-    fn track(
-        s: &str,
-        cmark_range: SourceRange,
-        plain_acc: &mut String,
-        mapping: &mut IndexMap<Range, SourceRange>,
-    ) {
+    fn track(s: &str, cmark_range: SourceRange, plain_acc: &mut String, mapping: &mut SourceMap) {
         // map the range within the plain data,
         // which is fed to the checker,
         // back to the repr with markdown modifiers
@@ -125,7 +118,14 @@ impl<'a> PlainOverlay<'a> {
                 }
             }
         };
-        let _ = mapping.insert(plain_range, cmark_range);
+        mapping.insert(plain_range, cmark_range);
+    }
+
+    /// Append a space to `plain`, unless it is empty or ends with whitespace.
+    fn separate(plain: &mut String) {
+        if plain.chars().last().map_or(false, |c| !c.is_whitespace()) {
+            plain.push(' ');
+        }
     }
 
     /// Append n newlines to the current state string `plain`.
@@ -136,9 +136,9 @@ impl<'a> PlainOverlay<'a> {
     }
 
     /// Ranges are mapped `cmark reduced/plain -> raw`.
-    pub fn extract_plain_with_mapping(cmark: &str) -> (String, IndexMap<Range, SourceRange>) {
+    pub fn extract_plain_with_mapping(cmark: &str) -> (String, SourceMap) {
         let mut plain = String::with_capacity(cmark.len());
-        let mut mapping = indexmap::IndexMap::with_capacity(128);
+        let mut mapping = SourceMap::with_capacity(128);
 
         let broken_link_handler = &mut |_broken: pulldown_cmark::BrokenLink| -> Option<(
             pulldown_cmark::CowStr,
@@ -226,6 +226,10 @@ impl<'a> PlainOverlay<'a> {
                         // make sure nested lists are not clumped together
                         Self::newlines(&mut plain, 1);
                     }
+                    Tag::Image(..) => {
+                        // adjacent badges must not be clumped together
+                        Self::separate(&mut plain);
+                    }
                     _ => {}
                 },
                 Event::End(tag) => {
@@ -234,16 +238,24 @@ impl<'a> PlainOverlay<'a> {
                             skip_table_text = false;
                             Self::newlines(&mut plain, 1);
                         }
-                        Tag::Link(_link_type, _url, _title) => {
-                            // the actual rendered content is in a text section
-                        }
-                        Tag::Image(_link_type, _url, title) => {
-                            Self::track(
-                                &title,
-                                SourceRange::Direct(char_range),
-                                &mut plain,
-                                &mut mapping,
-                            );
+                        Tag::Link(_link_type, _url, title)
+                        | Tag::Image(_link_type, _url, title) => {
+                            // the link text or alt text was tracked already,
+                            // the url is never checked, i.e. for badges
+                            let source = &cmark[byte_range.clone()];
+                            if let Some(offset) = Some(&title)
+                                .filter(|t| !t.is_empty())
+                                .and_then(|t| source.rfind(t.as_ref()))
+                            {
+                                let start = char_range.start + source[..offset].chars().count();
+                                Self::separate(&mut plain);
+                                Self::track(
+                                    &title,
+                                    SourceRange::Direct(start..(start + title.chars().count())),
+                                    &mut plain,
+                                    &mut mapping,
+                                );
+                            }
                         }
                         Tag::Heading(_n, _fragment, _klasses) => {
                             Self::newlines(&mut plain, 2);
@@ -371,7 +383,21 @@ impl<'a> PlainOverlay<'a> {
     // TODO consider returning a `Vec<PlainOverlay<'a>>` to account for list items
     // or other non-linear information which might not pass a grammar check as a whole
     pub fn erase_cmark(chunk: &'a CheckableChunk) -> Self {
-        let (plain, mapping) = Self::extract_plain_with_mapping(chunk.as_str());
+        let (mut plain, mapping) = Self::extract_plain_with_mapping(chunk.as_str());
+        if !chunk.masked().is_empty() {
+            // line breaks are kept, they separate the sentences
+            plain = plain
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| {
+                    if c != '\n' && chunk.masked().iter().any(|range| range.contains(&idx)) {
+                        ' '
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+        }
         Self {
             raw: chunk,
             plain,
@@ -383,80 +409,43 @@ impl<'a> PlainOverlay<'a> {
     /// map cmark reduced / plain back to raw ranges, which are then mapped back
     /// to `Span`s. The returned key `Ranges` are in the condensed domain.
     pub fn find_spans(&self, condensed_range: Range) -> IndexMap<Range, Span> {
-        let mut active = false;
-        let Range { start, end } = condensed_range;
-        let n = self.mapping.len();
+        let start = condensed_range.start;
+        // only the fragment the range starts in is resolved, the remainder
+        // would duplicate the replacement of a suggestion
         self.mapping
-            .iter()
-            .skip_while(|(sub, _raw)| sub.end <= start)
-            .take_while(|(sub, _raw)| sub.start < end)
-            .inspect(|x| {
-                log::trace!(">>> item {:?} ∈ {:?}", &condensed_range, x.0);
+            .to_source(condensed_range.clone())
+            .take_while(|(sub, _raw)| sub.start == start)
+            .fold(IndexMap::<Range, Span>::new(), |mut acc, (sub, raw)| {
+                log::trace!("convert:  cmark-erased={:?} -> raw={:?}", sub, raw);
+                let resolved = self.raw.find_spans(raw.clone());
+                log::trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
+                acc.extend(resolved);
+                acc
             })
-            .filter(|(sub, _)| {
-                // could possibly happen on empty documentation lines with `///`
-                !sub.is_empty()
-            })
-            .filter(|(_, raw)| {
-                // aliases are not required for span search
-                if let SourceRange::Direct(_) = raw {
-                    true
-                } else {
-                    false
-                }
-            })
-            .fold(
-                IndexMap::<Range, Span>::with_capacity(n),
-                |mut acc, (sub, raw)| {
-                    fn recombine(range: Range, offset: usize, len: usize) -> Range {
-                        Range {
-                            start: range.start + offset,
-                            end: range.start + offset + len,
-                        }
-                    }
+    }
 
-                    let _ = if sub.contains(&start) {
-                        // calculate the offset between our `condensed_range.start` and
-                        // the `sub` which is one entry in the mappings
-                        let offset = start - sub.start;
-                        let overlay_range = if sub.contains(&(end - 1)) {
-                            // complete start to end
-                            active = false;
-                            start..end
-                        } else {
-                            // only start, continue taking until end
-                            active = true;
-                            start..sub.end
-                        };
-                        let raw = recombine(raw.range(), offset, overlay_range.len());
-                        Some((overlay_range, raw))
-                    // TODO must be implemented properly
-                    // } else if active {
-                    //     let offset = sub.end - end;
-                    //     if sub.contains(&(end - 1)) {
-                    //         active = false;
-                    //         Some((sub.start..end, offset))
-                    //     } else {
-                    //         Some((sub.clone(), offset))
-                    //     }
-                    } else {
-                        None
-                    }
-                    .and_then(|(sub, raw)| {
-                        log::trace!("convert:  cmark-erased={:?} -> raw={:?}", sub, raw);
-
-                        if raw.is_empty() {
-                            log::warn!("linear range to spans: {:?} empty!", raw);
-                        } else {
-                            let resolved = self.raw.find_spans(raw.clone());
-                            log::trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
-                            acc.extend(resolved.into_iter());
-                        }
-                        Some(())
-                    });
-                    acc
-                },
-            )
+    /// The mapping of the plain representation to the raw content.
+    pub fn source_map(&self) -> &SourceMap {
+        &self.mapping
+    }
+
+    /// The chunk the plain representation was extracted from.
+    pub fn raw(&self) -> &'a CheckableChunk {
+        self.raw
+    }
+
+    /// The ranges within the plain representation which are placeholders, i.e.
+    /// for inline code.
+    pub fn placeholders(&self) -> impl Iterator<Item = Range> + '_ {
+        self.mapping
+            .placeholders()
+            .map(|(plain, _alias)| plain.clone())
+    }
+
+    /// The ranges within the plain representation which map directly to the
+    /// raw content, the complement of the [placeholders](Self::placeholders).
+    pub fn direct(&self) -> impl Iterator<Item = Range> + '_ {
+        self.mapping.direct().map(|(plain, _raw)| plain.clone())
     }
 
     /// Obtains a reference to the plain, cmark erased representation.
@@ -0,0 +1,194 @@
+//! Bidirectional mapping between the plain, common mark erased representation
+//! and the content it was extracted from.
+//!
+//! Each entry maps a range of the plain string to a range of the source, both
+//! in characters. [`SourceRange::Direct`] entries are verbatim copies, so a
+//! sub-range of either side maps linearly to the other one. Placeholders, i.e.
+//! for inline code, have no counterpart within the source and are skipped by
+//! both directions.
+
+use indexmap::IndexMap;
+
+use crate::{Range, SourceRange};
+
+/// Map of plain ranges to source ranges, ordered by the plain ranges.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    // require a sorted map, so we have the chance of binary search
+    // key: plain string range
+    // value: the corresponding areas in the source
+    entries: IndexMap<Range, SourceRange>,
+}
+
+/// The intersection of `a` and `b`, none if they do not overlap.
+fn intersection(a: &Range, b: &Range) -> Option<Range> {
+    let start = a.start.max(b.start);
+    let end = a.end.min(b.end);
+    (start < end).then(|| start..end)
+}
+
+/// Shift `sub`, which is part of `from`, linearly into `to`, without exceeding
+/// it.
+fn translate(sub: &Range, from: &Range, to: &Range) -> Range {
+    let start = (to.start + (sub.start - from.start)).min(to.end);
+    let end = (to.start + (sub.end - from.start)).min(to.end);
+    start..end
+}
+
+impl SourceMap {
+    /// Create an empty map with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: IndexMap::with_capacity(capacity),
+        }
+    }
+
+    /// Track that `plain` originates from `source`. Entries must be inserted
+    /// in order of `plain`.
+    pub fn insert(&mut self, plain: Range, source: SourceRange) {
+        debug_assert!(self
+            .entries
+            .last()
+            .map_or(true, |(last, _)| last.end <= plain.start));
+        let _ = self.entries.insert(plain, source);
+    }
+
+    /// Remove the last entry.
+    pub fn pop(&mut self) -> Option<(Range, SourceRange)> {
+        self.entries.pop()
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all entries, placeholders included, ordered by the plain
+    /// ranges.
+    pub fn iter(&self) -> impl Iterator<Item = (&Range, &SourceRange)> {
+        self.entries.iter()
+    }
+
+    /// Iterate over the entries which are verbatim copies of the source.
+    pub fn direct(&self) -> impl Iterator<Item = (&Range, &Range)> {
+        self.entries
+            .iter()
+            .filter_map(|(plain, source)| match source {
+                SourceRange::Direct(source) => Some((plain, source)),
+                SourceRange::Alias(..) => None,
+            })
+    }
+
+    /// Iterate over the placeholder entries, with the plain range and the alias.
+    pub fn placeholders(&self) -> impl Iterator<Item = (&Range, &str)> {
+        self.entries
+            .iter()
+            .filter_map(|(plain, source)| match source {
+                SourceRange::Alias(_, alias) => Some((plain, alias.as_str())),
+                SourceRange::Direct(_) => None,
+            })
+    }
+
+    /// Map the `plain` range to the source, yielding a pair of the covered
+    /// part of `plain` and its source range for each entry it overlaps.
+    /// Parts of `plain` without a source counterpart are skipped.
+    pub fn to_source(&self, plain: Range) -> impl Iterator<Item = (Range, Range)> + '_ {
+        // entries are ordered and disjoint, so start with the first one not
+        // ending before `plain`
+        let first = self.entries.keys().position(|sub| sub.end > plain.start);
+        let first = first.unwrap_or(self.entries.len());
+        self.entries
+            .iter()
+            .skip(first)
+            .take_while(move |(sub, _)| sub.start < plain.end)
+            .filter_map(move |(sub, source)| match source {
+                SourceRange::Direct(source) => intersection(sub, &plain)
+                    .map(|covered| (covered.clone(), translate(&covered, sub, source))),
+                SourceRange::Alias(..) => None,
+            })
+            .filter(|(_, source)| !source.is_empty())
+    }
+
+    /// Map the `source` range to the plain representation, yielding a pair of
+    /// a covered part of `source` and its plain range for each entry it
+    /// overlaps. The inverse of [`to_source`](Self::to_source).
+    pub fn to_plain(&self, source: Range) -> impl Iterator<Item = (Range, Range)> + '_ {
+        self.direct()
+            .filter_map(move |(plain, sub)| {
+                intersection(sub, &source)
+                    .map(|covered| (covered.clone(), translate(&covered, sub, plain)))
+            })
+            .filter(|(_, plain)| !plain.is_empty())
+    }
+}
+
+impl IntoIterator for SourceMap {
+    type Item = (Range, SourceRange);
+    type IntoIter = indexmap::map::IntoIter<Range, SourceRange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SourceMap {
+    type Item = (&'a Range, &'a SourceRange);
+    type IntoIter = indexmap::map::Iter<'a, Range, SourceRange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> SourceMap {
+        // `ab **cd** `ef` gh` erased to `ab cd ef gh`
+        let mut map = SourceMap::with_capacity(4);
+        map.insert(0..3, SourceRange::Direct(0..3));
+        map.insert(3..5, SourceRange::Direct(5..7));
+        map.insert(5..6, SourceRange::Direct(9..10));
+        map.insert(6..8, SourceRange::Alias(11..13, "ef".to_owned()));
+        map.insert(8..11, SourceRange::Direct(14..17));
+        map
+    }
+
+    #[test]
+    fn both_directions() {
+        let map = map();
+        assert_eq!(map.to_source(1..2).collect::<Vec<_>>(), vec![(1..2, 1..2)]);
+        assert_eq!(
+            map.to_source(1..5).collect::<Vec<_>>(),
+            vec![(1..3, 1..3), (3..5, 5..7)]
+        );
+        // the placeholder is skipped
+        assert_eq!(
+            map.to_source(4..10).collect::<Vec<_>>(),
+            vec![(4..5, 6..7), (5..6, 9..10), (8..10, 14..16)]
+        );
+        assert_eq!(map.to_source(6..8).count(), 0);
+        assert_eq!(map.to_source(20..30).count(), 0);
+
+        assert_eq!(map.to_plain(5..7).collect::<Vec<_>>(), vec![(5..7, 3..5)]);
+        // the markup is skipped
+        assert_eq!(
+            map.to_plain(2..6).collect::<Vec<_>>(),
+            vec![(2..3, 2..3), (5..6, 3..4)]
+        );
+        assert_eq!(map.to_plain(3..5).count(), 0);
+        assert_eq!(map.to_plain(11..13).count(), 0);
+
+        assert_eq!(map.direct().count(), 4);
+        assert_eq!(
+            map.placeholders().collect::<Vec<_>>(),
+            vec![(&(6..8), "ef")]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Span, TrimmedLiteral};
+use crate::{LiteralSet, Span, TrimmedLiteral};
 
 pub fn annotated_literals_raw<'a>(
     source: &'a str,
@@ -32,3 +32,18 @@ pub fn annotated_literals(source: &str) -> Vec<TrimmedLiteral> {
         })
         .collect()
 }
+
+pub fn gen_literal_set(source: &str) -> LiteralSet {
+    let literals = dbg!(annotated_literals(dbg!(source)));
+
+    let mut iter = dbg!(literals).into_iter();
+    let literal = iter
+        .next()
+        .expect("Must have at least one item in laterals");
+    let mut cls = LiteralSet::from(literal);
+
+    for literal in iter {
+        assert!(cls.add_adjacent(literal).is_ok());
+    }
+    dbg!(cls)
+}
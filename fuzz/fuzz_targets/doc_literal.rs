@@ -0,0 +1,40 @@
+//! Wrap arbitrary lines into one of the doc comment variants, then erase, map
+//! and partially replace them like `erase_cmark`.
+
+#![no_main]
+
+use cargo_spellcheck::roundtrip::check_partial_replacement;
+use cargo_spellcheck::ContentOrigin;
+use libfuzzer_sys::fuzz_target;
+
+fn wrap(content: &str, variant: u8) -> String {
+    let mut source = String::with_capacity(content.len() * 2);
+    match variant % 4 {
+        0 => content
+            .lines()
+            .for_each(|line| source.push_str(&format!("/// {line}\n"))),
+        1 => content
+            .lines()
+            .for_each(|line| source.push_str(&format!("//! {line}\n"))),
+        2 => content.lines().for_each(|line| {
+            source.push_str(&format!("#[doc = r#\"{}\"#]\n", line.replace("\"#", "\" #")))
+        }),
+        _ => {
+            source.push_str("/**\n");
+            content
+                .lines()
+                .for_each(|line| source.push_str(&format!(" * {}\n", line.replace("*/", "* /"))));
+            source.push_str(" */\n");
+        }
+    }
+    source.push_str("struct Fluff;\n");
+    source
+}
+
+fuzz_target!(|input: (String, Vec<u8>)| {
+    let (content, pick) = input;
+    let source = wrap(&content, pick.first().copied().unwrap_or_default());
+    if let Err(e) = check_partial_replacement(ContentOrigin::TestEntityRust, &source, &pick) {
+        panic!("{}", e);
+    }
+});
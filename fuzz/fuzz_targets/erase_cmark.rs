@@ -0,0 +1,16 @@
+//! Erase arbitrary markdown, map arbitrary ranges of the plain content back
+//! and replace some of them.
+
+#![no_main]
+
+use cargo_spellcheck::roundtrip::check_partial_replacement;
+use cargo_spellcheck::ContentOrigin;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (String, Vec<u8>)| {
+    let (content, pick) = input;
+    if let Err(e) = check_partial_replacement(ContentOrigin::TestEntityCommonMark, &content, &pick)
+    {
+        panic!("{}", e);
+    }
+});
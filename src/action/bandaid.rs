@@ -3,7 +3,7 @@
 //! A `BandAid` covers the mistake with a suggested replacement, as picked by
 //! the user.
 
-use crate::documentation::Span;
+use crate::documentation::{CommentVariant, Span};
 
 /// A chosen suggestion for a certain span
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,6 +19,48 @@ impl BandAid {
     pub fn covers_line(&self, line: usize) -> bool {
         self.span.covers_line(line)
     }
+
+    /// Replacements spanning multiple lines are indented with spaces, one per
+    /// column. Use the indentation of the line in `source` the bandaid starts
+    /// on instead, so indentation with tabs is retained.
+    pub(crate) fn with_source_indentation(mut self, source: &str) -> Self {
+        let indentation = source
+            .lines()
+            .nth(self.span.start.line.saturating_sub(1))
+            .map(|line| {
+                line.chars()
+                    .take_while(|c| c.is_whitespace())
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+        if !self.content.contains('\n') || indentation.chars().all(|c| c == ' ') {
+            return self;
+        }
+        let generated = " ".repeat(indentation.chars().count());
+        let mut lines = self.content.split('\n');
+        let mut content = lines.next().unwrap_or_default().to_owned();
+        for line in lines {
+            content.push('\n');
+            match line.strip_prefix(generated.as_str()) {
+                Some(rest) => {
+                    content.push_str(&indentation);
+                    content.push_str(rest);
+                }
+                None => content.push_str(line),
+            }
+        }
+        self.content = content;
+        self
+    }
+
+    /// Escape the content for the chunk of `variant` it replaces a part of,
+    /// required for the `".."` string of a `#[doc = ".."]` attribute.
+    pub(crate) fn escaped_for(mut self, variant: &CommentVariant) -> Self {
+        if let CommentVariant::MacroDocEqStr(_, 0) = variant {
+            self.content = self.content.replace('\\', "\\\\").replace('"', "\\\"");
+        }
+        self
+    }
 }
 
 impl From<(String, &Span)> for BandAid {
@@ -33,10 +75,66 @@ impl From<(String, &Span)> for BandAid {
 #[cfg(test)]
 pub(crate) mod tests {
 
+    use super::BandAid;
     use crate::util::load_span_from;
+    use crate::CommentVariant;
 
     use crate::{LineColumn, Span};
 
+    #[test]
+    fn tab_indentation_is_retained() {
+        const SOURCE: &str = "\t/// A long line.\nstruct X;\n";
+        let bandaid = BandAid {
+            content: "A long\n\t/// line.".to_owned(),
+            span: Span {
+                start: LineColumn { line: 1, column: 5 },
+                end: LineColumn {
+                    line: 1,
+                    column: 16,
+                },
+            },
+        };
+        // one space per column is generated
+        let generated = BandAid {
+            content: "A long\n /// line.".to_owned(),
+            ..bandaid.clone()
+        };
+        assert_eq!(generated.with_source_indentation(SOURCE), bandaid);
+        assert_eq!(
+            bandaid
+                .clone()
+                .with_source_indentation("    /// A long line."),
+            bandaid
+        );
+    }
+
+    #[test]
+    fn doc_attribute_strings_are_escaped() {
+        let bandaid = BandAid {
+            content: r#"a "quoted" \ word"#.to_owned(),
+            span: Span {
+                start: LineColumn { line: 1, column: 9 },
+                end: LineColumn {
+                    line: 1,
+                    column: 12,
+                },
+            },
+        };
+        let escaped = bandaid
+            .clone()
+            .escaped_for(&CommentVariant::MacroDocEqStr("#[doc=".to_owned(), 0));
+        assert_eq!(escaped.content, r#"a \"quoted\" \\ word"#);
+        // raw strings and comments take the content as is
+        let raw = bandaid
+            .clone()
+            .escaped_for(&CommentVariant::MacroDocEqStr("#[doc=".to_owned(), 2));
+        assert_eq!(raw, bandaid);
+        assert_eq!(
+            bandaid.clone().escaped_for(&CommentVariant::TripleSlash),
+            bandaid
+        );
+    }
+
     #[test]
     fn span_helper_integrity() {
         const SOURCE: &str = r#"0
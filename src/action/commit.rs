@@ -0,0 +1,116 @@
+//! Commit the applied fixes, see `--commit`.
+//!
+//! Bot workflows run `fix` and commit the outcome. The commit only contains
+//! the fixed files and its message lists the corrected words per file.
+
+use super::history::{Record, Resolution};
+use crate::errors::*;
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+/// The subject of the commit, unless given with `--commit-message`.
+pub(crate) const DEFAULT_SUBJECT: &str = "Fix spelling";
+
+/// The commit message, `subject` followed by the corrections per file, with
+/// paths relative to `cwd`.
+fn message(subject: &str, records: &[Record], cwd: &Path) -> String {
+    let mut files = indexmap::IndexMap::<&str, Vec<String>>::new();
+    for record in records {
+        if let Resolution::Replaced(ref content) = record.resolution {
+            files
+                .entry(&record.path)
+                .or_default()
+                .push(format!("{} → {}", record.word, content));
+        }
+    }
+    let mut acc = format!("{}\n", subject);
+    for (path, corrections) in files {
+        let path = Path::new(path).strip_prefix(cwd).unwrap_or(Path::new(path));
+        let _ = write!(acc, "\n* {}: {}", path.display(), corrections.join(", "));
+    }
+    acc.push('\n');
+    acc
+}
+
+/// Commit the files changed according to `records`, the resolutions of this
+/// run, with a message starting with `subject`, in the repository of
+/// `base_dir`.
+pub(crate) fn commit(subject: &str, records: &[Record], base_dir: &Path) -> Result<()> {
+    let mut paths = records
+        .iter()
+        .filter(|record| matches!(record.resolution, Resolution::Replaced(_)))
+        .map(|record| record.path.as_str())
+        .collect::<Vec<_>>();
+    paths.sort_unstable();
+    paths.dedup();
+    if paths.is_empty() {
+        log::info!("No fixes were applied, nothing to commit");
+        return Ok(());
+    }
+    let git = |args: &[&str]| -> Result<()> {
+        let output = Command::new("git")
+            .current_dir(base_dir)
+            .args(args)
+            .arg("--")
+            .args(&paths)
+            .output()
+            .wrap_err("Failed to run `git`")?;
+        if !output.status.success() {
+            bail!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    };
+    git(&["add"])?;
+    git(&[
+        "commit",
+        "--quiet",
+        "-m",
+        &message(subject, records, base_dir),
+    ])?;
+    log::info!("Committed the fixes of {} files", paths.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrections_per_file() {
+        let record = |word: &str, path: &str, resolution| Record {
+            word: word.to_owned(),
+            path: path.to_owned(),
+            resolution,
+            detector: None,
+            candidate: None,
+        };
+        let records = [
+            record(
+                "speling",
+                "/repo/src/lib.rs",
+                Resolution::Replaced("spelling".to_owned()),
+            ),
+            record("tokio", "/repo/src/lib.rs", Resolution::Skipped),
+            record(
+                "wrod",
+                "/repo/src/lib.rs",
+                Resolution::Replaced("word".to_owned()),
+            ),
+            record(
+                "teh",
+                "/repo/README.md",
+                Resolution::Replaced("the".to_owned()),
+            ),
+        ];
+        assert_eq!(
+            message(DEFAULT_SUBJECT, &records, Path::new("/repo")),
+            "Fix spelling\n\n* src/lib.rs: speling → spelling, wrod → word\n* README.md: teh → the\n"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Fixes picked once and applied later, see `--decisions`.
+//!
+//! A `fix` run given `--decisions <FILE>` writes the picked fixes, and the
+//! words to add to the dictionary, to a JSON file rather than to the checked
+//! files. The file can be reviewed, committed or handed to someone else, and
+//! `--apply from <FILE>` then applies exactly these fixes, without checking again.
+//! Each fix records the text it replaces, a file changed since is refused
+//! rather than patched at the wrong place.
+
+use super::interactive::UserPicked;
+use super::BandAid;
+use crate::errors::*;
+use crate::util::load_span_from;
+use crate::{ContentOrigin, LineColumn, Span};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Version of the decisions file format.
+const VERSION: u32 = 1;
+
+/// A single fix, lines and columns are 1-based and the end is inclusive, like
+/// the ones of `--format json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Fix {
+    /// Relative to the working directory if within, so the file can be
+    /// applied in another checkout.
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    /// The text which is replaced.
+    original: String,
+    replacement: String,
+}
+
+impl Fix {
+    fn span(&self) -> Span {
+        Span {
+            start: LineColumn {
+                line: self.line,
+                column: self.column.saturating_sub(1),
+            },
+            end: LineColumn {
+                line: self.end_line,
+                column: self.end_column.saturating_sub(1),
+            },
+        }
+    }
+}
+
+/// The content of a decisions file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Decisions {
+    version: u32,
+    fixes: Vec<Fix>,
+    /// Words to add to the dictionary.
+    #[serde(default)]
+    words: Vec<String>,
+}
+
+/// The text `span` covers in the file at `path`.
+fn original(path: &Path, span: Span) -> Result<String> {
+    let file = fs::File::open(path)?;
+    Ok(load_span_from(file, span)?)
+}
+
+/// Write the fixes and words of `picked` to `path`, with the paths relative to
+/// `base_dir`, returns the number of fixes.
+pub(crate) fn export(path: &Path, picked: &UserPicked, base_dir: &Path) -> Result<usize> {
+    let cwd = base_dir
+        .canonicalize()
+        .unwrap_or_else(|_| base_dir.to_owned());
+    let mut fixes = Vec::with_capacity(picked.total_count());
+    for (origin, bandaids) in &picked.bandaids {
+        let file = origin.file().ok_or_else(|| {
+            eyre!(
+                "The fixes of {} can not be written to a decisions file",
+                origin
+            )
+        })?;
+        for bandaid in bandaids {
+            let span = bandaid.span;
+            fixes.push(Fix {
+                path: file.strip_prefix(&cwd).unwrap_or(file).to_owned(),
+                line: span.start.line,
+                column: span.start.column + 1,
+                end_line: span.end.line,
+                end_column: span.end.column + 1,
+                original: original(file, span)?,
+                replacement: bandaid.content.clone(),
+            });
+        }
+    }
+    let decisions = Decisions {
+        version: VERSION,
+        fixes,
+        words: picked.words.clone(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&decisions)? + "\n")?;
+    Ok(decisions.fixes.len())
+}
+
+/// Read the fixes and words of the decisions file at `path`, after verifying
+/// each fix still covers the text it was picked for. Relative paths are
+/// resolved against `base_dir`.
+pub(crate) fn import(path: &Path, base_dir: &Path) -> Result<UserPicked> {
+    let content = fs::read_to_string(path)?;
+    let decisions = serde_json::from_str::<Decisions>(&content)
+        .wrap_err_with(|| eyre!("Failed to parse decisions file {}", path.display()))?;
+    if decisions.version != VERSION {
+        bail!(
+            "Unsupported version {} of decisions file {}, expected {}",
+            decisions.version,
+            path.display(),
+            VERSION
+        );
+    }
+    let mut picked = UserPicked::default();
+    for fix in decisions.fixes {
+        let span = fix.span();
+        let file = base_dir.join(&fix.path);
+        if original(&file, span)? != fix.original {
+            bail!(
+                "{}:{} changed since the decisions were made, `{}` is gone",
+                fix.path.display(),
+                fix.line,
+                fix.original
+            );
+        }
+        // the kind of file does not matter for writing the fixes
+        let origin = ContentOrigin::RustSourceFile(file);
+        picked.add_bandaid(&origin, BandAid::from((fix.replacement, &span)));
+    }
+    // the patches of a file have to be applied in order
+    for bandaids in picked.bandaids.values_mut() {
+        bandaids.sort_by_key(|bandaid| bandaid.span.start);
+    }
+    picked.words = decisions.words;
+    Ok(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_and_stale_decisions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let lib = dir.join("lib.rs");
+        fs::write(&lib, "/// A teh\n/// wrod.\nstruct X;\n").unwrap();
+        let span = |line, column, end_column| Span {
+            start: LineColumn { line, column },
+            end: LineColumn {
+                line,
+                column: end_column,
+            },
+        };
+
+        let mut picked = UserPicked::default();
+        let origin = ContentOrigin::RustSourceFile(lib.clone());
+        picked.add_bandaid(&origin, BandAid::from(("word".to_owned(), &span(2, 4, 7))));
+        picked.add_bandaid(&origin, BandAid::from(("the".to_owned(), &span(1, 6, 8))));
+        picked.words.push("teh".to_owned());
+        let decisions = dir.join("decisions.json");
+        assert_eq!(export(&decisions, &picked, &dir).unwrap(), 2);
+        assert!(fs::read_to_string(&decisions)
+            .unwrap()
+            .contains("\"original\": \"wrod\""));
+
+        // the paths are relative to the base directory
+        assert!(fs::read_to_string(&decisions)
+            .unwrap()
+            .contains("\"path\": \"lib.rs\""));
+        let imported = import(&decisions, &dir).unwrap();
+        let bandaids = &imported.bandaids[&origin];
+        assert_eq!(bandaids[0].content, "the");
+        assert_eq!(bandaids[0].span, span(1, 6, 8));
+        assert_eq!(bandaids[1].span, span(2, 4, 7));
+        assert_eq!(imported.words, vec!["teh"]);
+
+        fs::write(&lib, "/// A teh\n/// word.\nstruct X;\n").unwrap();
+        assert!(import(&decisions, &dir).is_err());
+    }
+}
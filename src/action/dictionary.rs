@@ -0,0 +1,73 @@
+//! Adding words to a custom hunspell dictionary.
+//!
+//! Words accepted as correct during `fix` are appended to the first of the
+//! configured `extra_dictionaries`, so they are not flagged again. The word
+//! count in the first line of the dictionary is kept up to date, everything
+//! else is left as written, unlike the project dictionary of
+//! [`learn`](crate::dict::learn).
+
+use crate::errors::*;
+
+use fs_err as fs;
+use std::path::Path;
+
+/// Add `words` to the dictionary at `path`, skipping those already present.
+///
+/// Returns the number of words added.
+pub(crate) fn add_words(path: &Path, words: &[String]) -> Result<usize> {
+    let content = if path.is_file() {
+        fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let mut lines = content.lines().collect::<Vec<_>>();
+    // the first line is the count of entries, if present
+    let has_count = lines
+        .first()
+        .map_or(false, |line| line.trim().parse::<usize>().is_ok());
+    if has_count {
+        lines.remove(0);
+    }
+    let added = crate::dict::unknown_words(words, |word| {
+        lines
+            .iter()
+            .any(|line| line.split('/').next() == Some(word))
+    });
+    if added.is_empty() {
+        return Ok(0);
+    }
+    lines.extend(added.iter());
+    let mut acc = format!("{}\n", lines.len());
+    for line in lines {
+        acc.push_str(line);
+        acc.push('\n');
+    }
+    fs::write(path, acc)
+        .wrap_err_with(|| eyre!("Failed to update dictionary {}", path.display()))?;
+    Ok(added.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_is_updated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let path = dir.join("custom.dic");
+        fs::write(&path, "2\nAPI/MS\ncrate\n").unwrap();
+
+        let words = ["tokio", "API", "tokio", "serde"].map(ToOwned::to_owned);
+        assert_eq!(add_words(&path, &words).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "4\nAPI/MS\ncrate\ntokio\nserde\n"
+        );
+        assert_eq!(add_words(&path, &words).unwrap(), 0);
+
+        let fresh = dir.join("fresh.dic");
+        assert_eq!(add_words(&fresh, &words[..1]).unwrap(), 1);
+        assert_eq!(fs::read_to_string(&fresh).unwrap(), "1\ntokio\n");
+    }
+}
@@ -0,0 +1,322 @@
+//! GitHub Actions entrypoint, see `--action`.
+//!
+//! Reads the standard environment of a workflow run, checks only the files
+//! changed by the triggering pull request or push, reports every finding as an
+//! annotation and writes a job summary as well as the counts as step outputs,
+//! so no wrapper script is required.
+
+use super::template::{FileReport, Report};
+use crate::errors::*;
+
+use fs_err as fs;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Findings listed in the job summary at most, all are annotated.
+const SUMMARY_LIMIT: usize = 100;
+
+/// The parts of the workflow environment that are of interest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Workflow {
+    /// The checkout, `GITHUB_WORKSPACE`.
+    workspace: PathBuf,
+    /// The JSON of the triggering event, `GITHUB_EVENT_PATH`.
+    event_path: Option<PathBuf>,
+    /// `GITHUB_EVENT_NAME`.
+    event_name: Option<String>,
+    /// `GITHUB_STEP_SUMMARY`.
+    summary: Option<PathBuf>,
+    /// `GITHUB_OUTPUT`.
+    output: Option<PathBuf>,
+}
+
+impl Workflow {
+    /// The workspace is `base_dir` outside of an Actions run.
+    pub(crate) fn from_env(base_dir: &Path) -> Result<Self> {
+        Self::from_vars(|var| std::env::var(var).ok(), base_dir)
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>, base_dir: &Path) -> Result<Self> {
+        if var("GITHUB_ACTIONS").as_deref() != Some("true") {
+            bail!("`--action` requires a GitHub Actions run, `GITHUB_ACTIONS` is not set");
+        }
+        let workspace = match var("GITHUB_WORKSPACE") {
+            Some(workspace) => PathBuf::from(workspace),
+            None => base_dir.to_owned(),
+        };
+        Ok(Self {
+            workspace,
+            event_path: var("GITHUB_EVENT_PATH").map(PathBuf::from),
+            event_name: var("GITHUB_EVENT_NAME"),
+            summary: var("GITHUB_STEP_SUMMARY").map(PathBuf::from),
+            output: var("GITHUB_OUTPUT").map(PathBuf::from),
+        })
+    }
+
+    /// The commit the changes are relative to, from the event payload.
+    fn base(&self) -> Option<String> {
+        let event = fs::read_to_string(self.event_path.as_ref()?).ok()?;
+        let event = serde_json::from_str::<serde_json::Value>(&event).ok()?;
+        let base = match self.event_name.as_deref()? {
+            "pull_request" | "pull_request_target" => event.pointer("/pull_request/base/sha"),
+            "push" => event.pointer("/before"),
+            _ => None,
+        }?
+        .as_str()?;
+        // the first push of a branch has no parent
+        if base.chars().all(|c| c == '0') {
+            return None;
+        }
+        Some(base.to_owned())
+    }
+
+    /// The files with checkable content changed by the event, or `None` if
+    /// the changes can not be determined and everything is to be checked.
+    pub(crate) fn changed_files(&self) -> Result<Option<Vec<PathBuf>>> {
+        let base = if let Some(base) = self.base() {
+            base
+        } else {
+            log::info!("Base of the changes is unknown, checking everything");
+            return Ok(None);
+        };
+        let listing = Command::new("git")
+            .current_dir(&self.workspace)
+            .args(["diff", "--name-only", "--diff-filter=ACMR", "-z"])
+            .arg(format!("{}...HEAD", base))
+            .output()
+            .wrap_err("Failed to run `git diff`")?;
+        if !listing.status.success() {
+            log::warn!(
+                "Failed to list the changes since {}, checking everything: {}",
+                base,
+                String::from_utf8_lossy(&listing.stderr).trim()
+            );
+            return Ok(None);
+        }
+        Ok(Some(
+            String::from_utf8_lossy(&listing.stdout)
+                .split('\0')
+                .filter(|path| {
+                    Path::new(path).extension().map_or(false, |ext| {
+                        ext.eq_ignore_ascii_case("rs") || ext.eq_ignore_ascii_case("md")
+                    })
+                })
+                .map(|path| self.workspace.join(path))
+                .collect(),
+        ))
+    }
+
+    /// Report `report` as annotations, job summary and step outputs.
+    pub(crate) fn publish(&self, report: &Report) -> Result<()> {
+        print_annotations(report, &self.workspace);
+        if let Some(ref path) = self.summary {
+            append(path, &summary(report, &self.workspace))?;
+        }
+        if let Some(ref path) = self.output {
+            let counts = &report.counts;
+            append(
+                path,
+                &format!(
+                    "errors={}\nwarnings={}\nnotes={}\ntotal={}\nfiles={}\n",
+                    counts.error, counts.warning, counts.note, counts.total, report.file_count
+                ),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the annotations of all findings of `report`.
+fn print_annotations(report: &Report, workspace: &Path) {
+    for file in &report.files {
+        for annotation in annotations(file, workspace) {
+            println!("{}", annotation);
+        }
+    }
+}
+
+/// Report `report` as annotations only, see `--format github`. Paths are
+/// relative to `GITHUB_WORKSPACE`, or `base_dir` outside of an Actions run.
+pub(crate) fn annotate(report: &Report, base_dir: &Path) {
+    let workspace =
+        std::env::var_os("GITHUB_WORKSPACE").map_or_else(|| base_dir.to_owned(), PathBuf::from);
+    print_annotations(report, &workspace);
+}
+
+fn append(path: &Path, content: &str) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// Escape the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+pub(super) fn relative<'a>(path: &'a str, workspace: &Path) -> &'a str {
+    Path::new(path)
+        .strip_prefix(workspace)
+        .ok()
+        .and_then(Path::to_str)
+        .unwrap_or(path)
+}
+
+/// One `::error` workflow command per finding, `::warning` and `::notice` for
+/// the lesser severities.
+fn annotations(file: &FileReport, workspace: &Path) -> Vec<String> {
+    let path = escape_property(relative(&file.path, workspace));
+    file.findings
+        .iter()
+        .map(|finding| {
+            let level = match finding.severity {
+                "error" => "error",
+                "warning" => "warning",
+                _ => "notice",
+            };
+            let mut message = format!("`{}`", finding.text);
+            if let Some(ref description) = finding.description {
+                message.push_str(": ");
+                message.push_str(description);
+            }
+            if !finding.replacements.is_empty() {
+                message.push_str("\nReplacements: ");
+                message.push_str(&finding.replacements.join(", "));
+            }
+            format!(
+                "::{} file={},line={},endLine={},col={},endColumn={},title={}::{}",
+                level,
+                path,
+                finding.line,
+                finding.end_line,
+                finding.column,
+                finding.end_column,
+                escape_property(&format!("spellcheck({})", finding.detector)),
+                escape_data(&message)
+            )
+        })
+        .collect()
+}
+
+/// The markdown of the job summary.
+fn summary(report: &Report, workspace: &Path) -> String {
+    let counts = &report.counts;
+    let mut acc = format!(
+        "## Spellcheck\n\n| errors | warnings | notes | files |\n| --- | --- | --- | --- |\n| {} | {} | {} | {} |\n",
+        counts.error, counts.warning, counts.note, report.file_count
+    );
+    let findings = report.files.iter().flat_map(|file| {
+        file.findings
+            .iter()
+            .map(move |finding| (file, relative(&file.path, workspace), finding))
+    });
+    for (listed, (file, path, finding)) in findings.enumerate() {
+        if listed == 0 {
+            acc.push('\n');
+        }
+        if listed == SUMMARY_LIMIT {
+            let _ = writeln!(
+                acc,
+                "\n… and {} more, see the annotations.",
+                counts.total - SUMMARY_LIMIT
+            );
+            break;
+        }
+        acc.push_str("- ");
+        if let Some(ref package) = file.package {
+            let _ = write!(acc, "[{}] ", package);
+        }
+        let _ = writeln!(
+            acc,
+            "`{}:{}:{}` {}: `{}`",
+            path, finding.line, finding.column, finding.severity, finding.text
+        );
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, ContentOrigin, Detector, Documentation, LineColumn, Span};
+    use crate::{Suggestion, Tally};
+    use std::collections::HashMap;
+
+    #[test]
+    fn environment() {
+        let vars = HashMap::from([
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_WORKSPACE", "/checkout"),
+            ("GITHUB_EVENT_NAME", "push"),
+        ]);
+        let workflow =
+            Workflow::from_vars(|var| vars.get(var).map(|v| (*v).to_owned()), Path::new("/"))
+                .unwrap();
+        assert_eq!(workflow.workspace, PathBuf::from("/checkout"));
+        assert_eq!(workflow.event_name.as_deref(), Some("push"));
+        assert_eq!(workflow.base(), None);
+        assert!(Workflow::from_vars(|_| None, Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn annotations_and_summary() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some wrod."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let suggestions = vec![Suggestion {
+            detector: Detector::Hunspell,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 14,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 17,
+                },
+            },
+            range: 5..9,
+            replacements: vec!["word".to_owned(), "wood".to_owned()],
+            description: Some("Possible spelling mistake found.".to_owned()),
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        }];
+        let tally = Tally::from_file(&suggestions);
+        let report = Report::new(vec![FileReport::new(&origin, &suggestions)], &tally);
+
+        let workspace = Path::new("/tmp/test");
+        assert_eq!(
+            annotations(&report.files[0], workspace),
+            vec!["::error file=entity.rs,line=1,endLine=1,col=15,endColumn=18,title=spellcheck(Hunspell)::`wrod`: Possible spelling mistake found.%0AReplacements: word, wood"]
+        );
+        assert_eq!(
+            summary(&report, workspace),
+            "## Spellcheck\n\n| errors | warnings | notes | files |\n| --- | --- | --- | --- |\n| 1 | 0 | 0 | 1 |\n\n- `entity.rs:1:15` error: `wrod`\n"
+        );
+
+        let report = Report::new(
+            vec![FileReport::new(&origin, &suggestions).with_package(Some("entity".to_owned()))],
+            &tally,
+        );
+        assert!(
+            summary(&report, workspace).ends_with("\n- [entity] `entity.rs:1:15` error: `wrod`\n")
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Guard against concurrent modification of the same tree.
+//!
+//! Two instances applying fixes to the same files, i.e. an editor plugin and a
+//! script, would overwrite each others changes based on stale content. Before
+//! writing anything, an exclusive advisory lock for the tree is obtained, which
+//! is a file in the cache directory named after the tree root. The root is the
+//! git working tree or the cargo workspace, so runs on different files or
+//! subdirectories of it exclude each other as well.
+//!
+//! Like `cargo fix`, modifications of a git working tree with uncommitted
+//! changes are refused, unless `--allow-dirty` or `--allow-staged` is passed,
+//! so the rewrites are not mixed with work in progress.
+
+use crate::checker::project_dir;
+use crate::errors::*;
+
+use fs_err as fs;
+use hex::ToHex;
+use sha2::Digest;
+use std::fmt::Write as _;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The directory the `paths` given on the commandline belong to.
+fn tree_root(paths: &[PathBuf]) -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    let path = match paths.first() {
+        Some(path) => cwd.join(path),
+        None => return Ok(cwd),
+    };
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Ok(path.parent().map(Path::to_path_buf).unwrap_or(cwd))
+    }
+}
+
+/// Whether the cargo manifest in `dir` declares a workspace.
+fn is_workspace(dir: &Path) -> bool {
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Value>().ok())
+        .map_or(false, |manifest| manifest.get("workspace").is_some())
+}
+
+/// The root of the tree `dir` belongs to, which is the same for all of its
+/// files: the git working tree, else the enclosing cargo workspace or
+/// package, else `dir` itself.
+fn lock_root(dir: &Path) -> PathBuf {
+    let toplevel = Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output();
+    if let Ok(toplevel) = toplevel {
+        if toplevel.status.success() {
+            let toplevel = PathBuf::from(String::from_utf8_lossy(&toplevel.stdout).trim());
+            return fs::canonicalize(&toplevel).unwrap_or(toplevel);
+        }
+    }
+    let manifests = dir
+        .ancestors()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .collect::<Vec<_>>();
+    manifests
+        .iter()
+        .find(|dir| is_workspace(dir))
+        .or_else(|| manifests.first())
+        .map_or_else(|| dir.to_owned(), |dir| dir.to_path_buf())
+}
+
+/// The lock of one tree, see [`TreeLock::acquire`].
+pub(crate) struct TreeLock {
+    root: PathBuf,
+    lock: fd_lock::RwLock<fs::File>,
+}
+
+impl TreeLock {
+    /// Prepare the lock for the tree `paths` belong to.
+    pub(crate) fn new(paths: &[PathBuf]) -> Result<Self> {
+        Self::with_lock_dir(paths, &project_dir()?.cache_dir().join("locks"))
+    }
+
+    fn with_lock_dir(paths: &[PathBuf], lock_dir: &Path) -> Result<Self> {
+        let root = lock_root(&tree_root(paths)?);
+        fs::create_dir_all(lock_dir)?;
+        let digest = sha2::Sha256::digest(root.display().to_string().as_bytes());
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(lock_dir.join(digest.as_slice().encode_hex::<String>()))?;
+        Ok(Self {
+            root,
+            lock: fd_lock::RwLock::new(file),
+        })
+    }
+
+    /// Obtain the lock, fails if another instance holds it. The lock is
+    /// released once the guard is dropped.
+    pub(crate) fn acquire(&mut self) -> Result<fd_lock::RwLockWriteGuard<'_, fs::File>> {
+        let root = &self.root;
+        self.lock.try_write().map_err(|err| {
+            if err.kind() == ErrorKind::WouldBlock {
+                eyre!(
+                    "Another instance is currently modifying files in {}, refusing to run concurrently",
+                    root.display()
+                )
+            } else {
+                eyre!(err).wrap_err("Failed to lock the tree for modification")
+            }
+        })
+    }
+}
+
+/// The uncommitted changes listed by `git status --porcelain -z`, with how
+/// they are uncommitted, `dirty` or `staged`. Staged changes are omitted if
+/// `allow_staged` is set.
+fn uncommitted(status: &str, allow_staged: bool) -> Vec<(&str, &'static str)> {
+    let mut entries = status.split('\0').filter(|entry| entry.len() > 3);
+    let mut acc = Vec::new();
+    while let Some(entry) = entries.next() {
+        let (index, worktree, path) = (
+            entry.as_bytes()[0],
+            entry.as_bytes()[1],
+            entry.get(3..).unwrap_or_default(),
+        );
+        // renames and copies are followed by the original path
+        if matches!(index, b'R' | b'C') {
+            let _ = entries.next();
+        }
+        if worktree != b' ' {
+            acc.push((path, "dirty"));
+        } else if !allow_staged {
+            acc.push((path, "staged"));
+        }
+    }
+    acc
+}
+
+/// Refuse to modify the git working tree `paths` belong to if it has
+/// uncommitted changes, see the module documentation. Trees that are not
+/// under git version control are not checked.
+pub(crate) fn ensure_committed(
+    paths: &[PathBuf],
+    allow_dirty: bool,
+    allow_staged: bool,
+) -> Result<()> {
+    if allow_dirty {
+        return Ok(());
+    }
+    let root = tree_root(paths)?;
+    let status = match Command::new("git")
+        .current_dir(&root)
+        .args(["status", "--porcelain", "-z"])
+        .output()
+    {
+        Ok(status) if status.status.success() => status,
+        _ => {
+            log::debug!("{} is not a git working tree", root.display());
+            return Ok(());
+        }
+    };
+    let status = String::from_utf8_lossy(&status.stdout);
+    let uncommitted = uncommitted(&status, allow_staged);
+    if uncommitted.is_empty() {
+        return Ok(());
+    }
+    let mut files = String::new();
+    for (path, how) in uncommitted {
+        let _ = write!(files, "\n  * {} ({})", path, how);
+    }
+    bail!(
+        "The working tree has uncommitted changes, which would be mixed with the fixes. Pass `--allow-dirty` or `--allow-staged`, or commit the changes to these files:\n{}",
+        files
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_runs_are_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lock_dir = tmp.path().to_owned();
+        let paths = vec![PathBuf::from(env!("CARGO_MANIFEST_DIR"))];
+        let mut first = TreeLock::with_lock_dir(&paths, &lock_dir).unwrap();
+        let mut second = TreeLock::with_lock_dir(&paths, &lock_dir).unwrap();
+        {
+            let _guard = first.acquire().unwrap();
+            assert!(second.acquire().is_err());
+        }
+        assert!(second.acquire().is_ok());
+    }
+
+    #[test]
+    fn files_of_one_tree_share_the_lock() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lock_dir = tmp.path().to_owned();
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut first = TreeLock::with_lock_dir(&[root.join("src")], &lock_dir).unwrap();
+        let mut second =
+            TreeLock::with_lock_dir(&[root.join("doc-chunks/src/lib.rs")], &lock_dir).unwrap();
+        assert_eq!(first.root, second.root);
+        let _guard = first.acquire().unwrap();
+        assert!(second.acquire().is_err());
+    }
+
+    #[test]
+    fn uncommitted_changes() {
+        let status = "M  staged.rs\0 M dirty.rs\0MM both.rs\0?? new.md\0R  renamed.rs\0old.rs\0";
+        assert_eq!(
+            uncommitted(status, false),
+            vec![
+                ("staged.rs", "staged"),
+                ("dirty.rs", "dirty"),
+                ("both.rs", "dirty"),
+                ("new.md", "dirty"),
+                ("renamed.rs", "staged"),
+            ]
+        );
+        assert_eq!(
+            uncommitted(status, true),
+            vec![
+                ("dirty.rs", "dirty"),
+                ("both.rs", "dirty"),
+                ("new.md", "dirty")
+            ]
+        );
+        assert!(uncommitted("", false).is_empty());
+    }
+}
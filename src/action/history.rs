@@ -0,0 +1,297 @@
+//! Persistent record of how findings were resolved, see `--show-history`.
+//!
+//! Every finding presented by `fix` is appended to a JSON lines file in the
+//! user data directory once the picked fixes are written, with the replacement
+//! or the fact it was skipped. The resolutions of a word across all runs state
+//! how a team handles its typos, a base for custom replacement maps.
+//!
+//! Replacements also record which of the candidates was picked, `--stats`
+//! aggregates them per checker, to tune the ranking of the candidates.
+
+use crate::errors::*;
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// How a finding was resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Resolution {
+    /// Replaced with the given content.
+    Replaced(String),
+    /// Left as is.
+    Skipped,
+    /// Added to the custom dictionary.
+    Learned,
+}
+
+/// A finding and its resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Record {
+    /// The flagged word, or phrase.
+    pub(crate) word: String,
+    /// The file the word was found in.
+    pub(crate) path: String,
+    pub(crate) resolution: Resolution,
+    /// The checker which flagged the word, absent in records of earlier
+    /// versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) detector: Option<String>,
+    /// Index of the picked candidate if replaced with one, rather than with
+    /// custom input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) candidate: Option<usize>,
+}
+
+/// The history file in the user data directory.
+pub(crate) fn default_path() -> Result<PathBuf> {
+    Ok(crate::checker::project_dir()?
+        .data_dir()
+        .join("history.jsonl"))
+}
+
+/// Append `records` to the history at `path`.
+pub(crate) fn append(path: &Path, records: &[Record]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut acc = String::new();
+    for record in records {
+        acc.push_str(&serde_json::to_string(record)?);
+        acc.push('\n');
+    }
+    file.write_all(acc.as_bytes())?;
+    Ok(())
+}
+
+/// All records in the history at `path`, lines that fail to parse are
+/// skipped.
+fn load(path: &Path) -> Result<Vec<Record>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Record>(line).ok())
+        .collect())
+}
+
+/// All records of `word` in the history at `path`.
+pub(crate) fn lookup(path: &Path, word: &str) -> Result<Vec<Record>> {
+    let mut records = load(path)?;
+    records.retain(|record| record.word == word);
+    Ok(records)
+}
+
+/// The resolutions of `word`, the most frequent first, with the files it was
+/// found in.
+pub(crate) fn describe(word: &str, records: &[Record]) -> String {
+    if records.is_empty() {
+        return format!("`{}` was never resolved by `fix`.\n", word);
+    }
+    let mut counts = indexmap::IndexMap::<&Resolution, usize>::new();
+    let mut paths = indexmap::IndexSet::<&str>::new();
+    for record in records {
+        *counts.entry(&record.resolution).or_default() += 1;
+        paths.insert(&record.path);
+    }
+    counts.sort_by(|_, count1, _, count2| count2.cmp(count1));
+    let mut acc = format!("`{}` was\n", word);
+    for (resolution, count) in counts {
+        let times = if count == 1 { "time" } else { "times" };
+        match resolution {
+            Resolution::Replaced(content) => {
+                let _ = writeln!(acc, "  replaced with `{}` {} {}", content, count, times);
+            }
+            Resolution::Skipped => {
+                let _ = writeln!(acc, "  skipped {} {}", count, times);
+            }
+            Resolution::Learned => {
+                let _ = writeln!(acc, "  added to the dictionary {} {}", count, times);
+            }
+        }
+    }
+    acc.push_str("in\n");
+    for path in paths {
+        let _ = writeln!(acc, "  {}", path);
+    }
+    acc
+}
+
+/// Print the resolutions of `word` in the history of the user.
+pub(crate) fn show(word: &str) -> Result<()> {
+    let records = lookup(&default_path()?, word)?;
+    print!("{}", describe(word, &records));
+    Ok(())
+}
+
+/// How the findings of one checker were resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Stats {
+    /// Replaced with the first candidate.
+    pub(crate) first: usize,
+    /// Replaced with any other candidate.
+    pub(crate) later: usize,
+    /// Replaced with custom input.
+    pub(crate) custom: usize,
+    pub(crate) skipped: usize,
+    pub(crate) learned: usize,
+}
+
+/// Aggregate `records` by checker, records without one are not counted.
+pub(crate) fn aggregate(records: &[Record]) -> indexmap::IndexMap<&str, Stats> {
+    let mut acc = indexmap::IndexMap::<&str, Stats>::new();
+    for record in records {
+        let detector = match record.detector {
+            Some(ref detector) => detector.as_str(),
+            None => continue,
+        };
+        let stats = acc.entry(detector).or_default();
+        match (&record.resolution, record.candidate) {
+            (Resolution::Replaced(_), Some(0)) => stats.first += 1,
+            (Resolution::Replaced(_), Some(_)) => stats.later += 1,
+            (Resolution::Replaced(_), None) => stats.custom += 1,
+            (Resolution::Skipped, _) => stats.skipped += 1,
+            (Resolution::Learned, _) => stats.learned += 1,
+        }
+    }
+    acc.sort_keys();
+    acc
+}
+
+/// The aggregated resolutions, the shares of the replacements in percent.
+pub(crate) fn describe_stats(stats: &indexmap::IndexMap<&str, Stats>) -> String {
+    if stats.is_empty() {
+        return "No findings were resolved by `fix` yet.\n".to_owned();
+    }
+    let mut acc = String::new();
+    for (detector, stats) in stats {
+        let replaced = stats.first + stats.later + stats.custom;
+        let share = |count: usize| (count * 100).checked_div(replaced).unwrap_or_default();
+        let _ = writeln!(acc, "{}", detector);
+        let _ = writeln!(
+            acc,
+            "  first candidate {} ({}%)",
+            stats.first,
+            share(stats.first)
+        );
+        let _ = writeln!(
+            acc,
+            "  later candidate {} ({}%)",
+            stats.later,
+            share(stats.later)
+        );
+        let _ = writeln!(
+            acc,
+            "  custom input {} ({}%)",
+            stats.custom,
+            share(stats.custom)
+        );
+        let _ = writeln!(acc, "  skipped {}", stats.skipped);
+        let _ = writeln!(acc, "  added to the dictionary {}", stats.learned);
+    }
+    acc
+}
+
+/// Print how the findings of each checker were resolved.
+pub(crate) fn show_stats() -> Result<()> {
+    let records = load(&default_path()?)?;
+    print!("{}", describe_stats(&aggregate(&records)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_lookup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("history.jsonl");
+        let record = |word: &str, path: &str, resolution| Record {
+            word: word.to_owned(),
+            path: path.to_owned(),
+            resolution,
+            detector: None,
+            candidate: None,
+        };
+        append(
+            &path,
+            &[
+                record("speling", "src/lib.rs", Resolution::Skipped),
+                record(
+                    "speling",
+                    "README.md",
+                    Resolution::Replaced("spelling".to_owned()),
+                ),
+                record("wrod", "src/lib.rs", Resolution::Skipped),
+            ],
+        )
+        .unwrap();
+        append(
+            &path,
+            &[record(
+                "speling",
+                "src/lib.rs",
+                Resolution::Replaced("spelling".to_owned()),
+            )],
+        )
+        .unwrap();
+
+        let records = lookup(&path, "speling").unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            describe("speling", &records),
+            "`speling` was\n  replaced with `spelling` 2 times\n  skipped 1 time\nin\n  src/lib.rs\n  README.md\n"
+        );
+        assert!(lookup(&path, "unknown").unwrap().is_empty());
+    }
+
+    #[test]
+    fn candidate_stats() {
+        let record = |detector: Option<&str>, resolution, candidate| Record {
+            word: "speling".to_owned(),
+            path: "src/lib.rs".to_owned(),
+            resolution,
+            detector: detector.map(ToOwned::to_owned),
+            candidate,
+        };
+        let replaced = || Resolution::Replaced("spelling".to_owned());
+        let records = vec![
+            record(Some("Hunspell"), replaced(), Some(0)),
+            record(Some("Hunspell"), replaced(), Some(0)),
+            record(Some("Hunspell"), replaced(), Some(2)),
+            record(Some("Hunspell"), replaced(), None),
+            record(Some("Hunspell"), Resolution::Skipped, None),
+            record(Some("NlpRules"), Resolution::Learned, None),
+            record(None, replaced(), Some(0)),
+        ];
+        let stats = aggregate(&records);
+        assert_eq!(
+            stats["Hunspell"],
+            Stats {
+                first: 2,
+                later: 1,
+                custom: 1,
+                skipped: 1,
+                learned: 0,
+            }
+        );
+        assert_eq!(
+            describe_stats(&stats),
+            "Hunspell\n  first candidate 2 (50%)\n  later candidate 1 (25%)\n  custom input 1 (25%)\n  skipped 1\n  added to the dictionary 0\n\
+NlpRules\n  first candidate 0 (0%)\n  later candidate 0 (0%)\n  custom input 0 (0%)\n  skipped 0\n  added to the dictionary 1\n"
+        );
+    }
+}
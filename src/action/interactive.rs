@@ -13,22 +13,14 @@ use crossterm::{
     terminal, QueueableCommand,
 };
 
-use std::io::stdout;
-
-const HELP: &str = r##"y - apply this suggestion
-n - do not apply the suggested correction
-q - quit; do not stage this hunk or any of the remaining ones
-d - do not apply this suggestion and skip the rest of the file
-g - select a suggestion to go to
-j - leave this hunk undecided, see next undecided hunk
-J - leave this hunk undecided, see next hunk
-e - manually edit the current hunk
-? - print help
-
-
+use super::history::{Record, Resolution};
+use crate::checker::HunspellChecker;
+use crate::i18n::{tr, tr_args};
 
+use std::io::stdout;
 
-"##;
+/// Number of replacement candidates shown, unless requested otherwise.
+const MAX_CANDIDATES: usize = 7;
 
 /// Helper strict to assure we leave the terminals raw mode
 pub struct ScopedRaw;
@@ -82,8 +74,12 @@ pub(super) enum UserSelection {
     Previous,
     /// Print the help message and exit.
     Help,
+    /// Show all replacement candidates instead of the first few.
+    ShowMore,
     /// Skip the remaining fixes for the current file.
     SkipFile,
+    /// The flagged word is correct, add it to the custom dictionary.
+    AddToDictionary,
     /// continue as if whatever returned this was never called.
     Nop,
     /// Stop execution, forget all previous choices.
@@ -109,6 +105,12 @@ where
     pub pick_idx: usize,
     /// Total number of pickable slots.
     pub n_items: usize,
+    /// Show all replacement candidates, not only the first `MAX_CANDIDATES`.
+    pub show_all: bool,
+    /// Validates the custom replacement while it is typed, if any.
+    pub dictionary: Option<&'s HunspellChecker>,
+    /// The words of the custom replacement the dictionary does not know.
+    pub unknown: Vec<String>,
 }
 
 impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
@@ -128,8 +130,11 @@ impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
             ),
             // start at a suggestion, not the custom field or ticked suggestion
             pick_idx: 1_usize + (!suggestion.replacements.is_empty()) as usize,
-            // all items provided by the checkers plus the user provided
-            n_items: suggestion.replacements.len() + 2,
+            // all visible items provided by the checkers plus the user provided
+            n_items: suggestion.replacements.len().min(MAX_CANDIDATES) + 2,
+            show_all: false,
+            dictionary: None,
+            unknown: Vec::new(),
         }
     }
 }
@@ -138,6 +143,41 @@ impl<'s, 't> State<'s, 't>
 where
     't: 's,
 {
+    /// Validate the custom replacement with `dictionary`.
+    pub fn with_dictionary(mut self, dictionary: Option<&'s HunspellChecker>) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Look up the words of the custom replacement again, after an edit.
+    fn validate(&mut self) {
+        self.unknown = self
+            .dictionary
+            .map(|dictionary| dictionary.unknown_words(&self.custom_replacement))
+            .unwrap_or_default();
+    }
+
+    /// The replacement candidates to display.
+    pub fn visible_replacements(&self) -> &'s [String] {
+        let replacements = &self.suggestion.replacements[..];
+        if self.show_all {
+            replacements
+        } else {
+            &replacements[..replacements.len().min(MAX_CANDIDATES)]
+        }
+    }
+
+    /// Number of replacement candidates which are not displayed.
+    pub fn hidden_count(&self) -> usize {
+        self.suggestion.replacements.len() - self.visible_replacements().len()
+    }
+
+    /// Make all replacement candidates selectable.
+    pub fn show_all(&mut self) {
+        self.show_all = true;
+        self.n_items = self.suggestion.replacements.len() + 2;
+    }
+
     /// Selects the next line.
     pub fn select_next(&mut self) {
         self.pick_idx = (self.pick_idx + 1).rem_euclid(self.n_items);
@@ -164,6 +204,11 @@ where
 
     /// Convert the replacement to a `BandAid`
     pub fn to_bandaid(&self) -> BandAid {
+        self.to_unescaped_bandaid()
+            .escaped_for(&self.suggestion.chunk.variant())
+    }
+
+    fn to_unescaped_bandaid(&self) -> BandAid {
         if self.is_ticked_entry() {
             BandAid::from((self.backticked_original.clone(), &self.suggestion.span))
         } else if self.is_custom_entry() {
@@ -184,6 +229,10 @@ where
 pub struct UserPicked {
     /// Associates the bandaids to a content origin, or path respectively.
     pub bandaids: indexmap::IndexMap<ContentOrigin, Vec<BandAid>>,
+    /// How each presented suggestion was resolved, for the history.
+    pub(crate) history: Vec<Record>,
+    /// Words to add to the custom dictionary.
+    pub(crate) words: Vec<String>,
 }
 
 impl UserPicked {
@@ -226,6 +275,26 @@ impl UserPicked {
     /// Join two `UserPick`s.
     pub fn extend(&mut self, other: Self) {
         self.bandaids.extend(other.bandaids.into_iter());
+        self.history.extend(other.history);
+        self.words.extend(other.words);
+    }
+
+    /// Remember how `suggestion` was resolved.
+    fn record(&mut self, suggestion: &Suggestion<'_>, resolution: Resolution) {
+        let candidate = match resolution {
+            Resolution::Replaced(ref content) => suggestion
+                .replacements
+                .iter()
+                .position(|replacement| replacement == content),
+            _ => None,
+        };
+        self.history.push(Record {
+            word: sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+            path: suggestion.origin.to_string(),
+            resolution,
+            detector: Some(suggestion.detector.to_string()),
+            candidate,
+        });
     }
 
     /// Provide a replacement that was not provided by the backend
@@ -256,6 +325,7 @@ impl UserPicked {
                     state
                         .custom_replacement
                         .remove(state.cursor_offset as usize);
+                    state.validate();
                 }
             }
             KeyCode::Enter => {
@@ -271,6 +341,7 @@ impl UserPicked {
                     .custom_replacement
                     .insert(state.cursor_offset as usize, c);
                 state.cursor_offset += 1;
+                state.validate();
             }
             _ => {}
         }
@@ -305,6 +376,12 @@ impl UserPicked {
         custom.background_color = Some(Color::Black);
         custom.foreground_color = Some(Color::Yellow);
 
+        let mut more = ContentStyle::new();
+        more.foreground_color = Some(Color::DarkGrey);
+
+        let mut unknown = ContentStyle::new();
+        unknown.foreground_color = Some(Color::Red);
+
         // render all replacements in a vertical list
 
         stdout.queue(cursor::SavePosition)?;
@@ -318,6 +395,19 @@ impl UserPicked {
             state.custom_replacement.as_str()
         };
 
+        let hidden = state.hidden_count();
+        let more_content = tr_args("interactive-more", &[("hidden", &hidden)]);
+        // the custom replacement must not trade one typo for another
+        let unknown_content = (!state.unknown.is_empty()).then(|| {
+            format!(
+                "  {}",
+                tr_args(
+                    "interactive-unknown",
+                    &[("words", &state.unknown.join(", "))]
+                )
+            )
+        });
+
         std::iter::once((&custom, custom_content))
             .chain(std::iter::once((
                 &others,
@@ -325,11 +415,11 @@ impl UserPicked {
             )))
             .chain(
                 state
-                    .suggestion
-                    .replacements
+                    .visible_replacements()
                     .iter()
                     .map(|s| (&others, s.as_str())),
             )
+            .chain((hidden > 0).then(|| (&more, more_content.as_str())))
             .enumerate()
             .map(|(idx, (style, content))| {
                 (
@@ -355,7 +445,13 @@ impl UserPicked {
                 } else {
                     cmd.queue(cursor::MoveToColumn(4))?
                 }
-                .queue(item)
+                .queue(item)?;
+                match unknown_content {
+                    Some(ref content) if idx == 0 => cmd.queue(PrintStyledContent(
+                        StyledContent::new(unknown, content.as_str()),
+                    )),
+                    _ => Ok(cmd),
+                }
             })?;
 
         stdout.queue(cursor::RestorePosition)?.flush()?;
@@ -364,12 +460,7 @@ impl UserPicked {
     }
 
     /// Wait for user input and process it into a `UserSelection` enum.
-    fn user_input(
-        &self,
-        state: &mut State,
-        running_idx: usize,
-        total: usize,
-    ) -> Result<UserSelection> {
+    fn user_input(&self, state: &mut State, question: String) -> Result<UserSelection> {
         let skip = {
             let _guard = ScopedRaw::new();
 
@@ -377,12 +468,6 @@ impl UserPicked {
             boring.foreground_color = Some(Color::Blue);
             boring.attributes = Attribute::Bold.into();
 
-            let question = format!(
-                "({nth}/{of_n}) Apply this suggestion [y,n,q,a,d,j,e,?]?",
-                nth = running_idx + 1,
-                of_n = total
-            );
-
             // a new suggestion, so prepare for the number of items that are visible
             // and also overwrite the last lines of the regular print which would
             // already contain the suggestions
@@ -392,8 +477,8 @@ impl UserPicked {
             const ERASE: u16 = 4;
             // lines used by the question
             const QUESTION: u16 = 3;
-            let extra_rows_to_flush =
-                (state.n_items.saturating_sub((ERASE - QUESTION) as usize)) as u16;
+            let n_lines = state.n_items + usize::from(state.hidden_count() > 0);
+            let extra_rows_to_flush = (n_lines.saturating_sub((ERASE - QUESTION) as usize)) as u16;
             stdout()
                 .queue(cursor::Hide)?
                 .queue(cursor::MoveUp(ERASE))? // erase the 5 last lines of suggestion print
@@ -474,10 +559,15 @@ impl UserPicked {
                     return Ok(UserSelection::Abort)
                 }
                 KeyCode::Char('d') => return Ok(UserSelection::SkipFile),
+                KeyCode::Char('a') => return Ok(UserSelection::AddToDictionary),
                 KeyCode::Char('e') => {
                     // jump to the user input entry
                     state.select_custom();
                 }
+                KeyCode::Char('m') if state.hidden_count() > 0 => {
+                    state.show_all();
+                    return Ok(UserSelection::ShowMore);
+                }
                 KeyCode::Char('?') => return Ok(UserSelection::Help),
                 x => {
                     log::trace!("Unexpected input {:?}", x);
@@ -490,6 +580,7 @@ impl UserPicked {
     pub(super) fn select_interactive<'s>(
         origin: ContentOrigin,
         suggestions: Vec<Suggestion<'s>>,
+        dictionary: Option<&HunspellChecker>,
     ) -> Result<(Self, UserSelection)> {
         let count = suggestions.len();
         let mut picked = UserPicked::default();
@@ -526,12 +617,21 @@ impl UserPicked {
                 log::trace!("BUG: Suggestion did not contain a replacement, skip");
                 continue;
             }
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            if picked.words.contains(&word) {
+                log::debug!("{} was added to the dictionary, skip", word);
+                continue;
+            }
             println!("{}", suggestion);
 
-            let mut state = State::from(suggestion);
+            let mut state = State::from(suggestion).with_dictionary(dictionary);
 
             'inner: loop {
-                match picked.user_input(&mut state, idx, count)? {
+                let question = tr_args(
+                    "interactive-question",
+                    &[("nth", &(idx + 1)), ("total", &count)],
+                );
+                match picked.user_input(&mut state, question)? {
                     usel @ UserSelection::Abort | usel @ UserSelection::Quit => {
                         let _ = ScopedRaw::restore_terminal();
                         return Ok((picked, usel));
@@ -542,17 +642,251 @@ impl UserPicked {
                         continue 'inner;
                     }
                     UserSelection::Help => {
-                        println!("{}", HELP);
+                        // trailing lines are overwritten by the next question
+                        println!("{}\n\n\n\n\n", tr("interactive-help"));
+                        continue 'inner;
+                    }
+                    UserSelection::ShowMore => {
+                        println!("{}", suggestion);
                         continue 'inner;
                     }
                     UserSelection::Replacement(bandaid) => {
+                        picked.record(suggestion, Resolution::Replaced(bandaid.content.clone()));
                         picked.add_bandaid(&origin, bandaid);
                     }
-                    UserSelection::Nop | UserSelection::Skip => {}
+                    UserSelection::Skip => picked.record(suggestion, Resolution::Skipped),
+                    UserSelection::AddToDictionary => {
+                        picked.record(suggestion, Resolution::Learned);
+                        picked.words.push(word);
+                    }
+                    UserSelection::Nop => {}
                 };
                 break 'inner;
             }
         }
         Ok((picked, UserSelection::Nop))
     }
+    /// Resolve `suggestions` of all files once per word, the answer applies to
+    /// every occurrence of it, see `--by-word`.
+    pub(super) fn select_by_word(
+        suggestions: &[Suggestion<'_>],
+        dictionary: Option<&HunspellChecker>,
+    ) -> Result<(Self, UserSelection)> {
+        let words = by_word(suggestions);
+        let count = words.len();
+        let mut picked = UserPicked::default();
+        let mut selection = UserSelection::Nop;
+        'outer: for (idx, (word, occurrences)) in words.into_iter().enumerate() {
+            let first = occurrences[0];
+            println!("{}", first);
+            let mut state = State::from(first).with_dictionary(dictionary);
+            'inner: loop {
+                let question = tr_args(
+                    "interactive-question-word",
+                    &[
+                        ("nth", &(idx + 1)),
+                        ("total", &count),
+                        ("count", &occurrences.len()),
+                        ("word", &word),
+                    ],
+                );
+                match picked.user_input(&mut state, question)? {
+                    usel @ UserSelection::Abort | usel @ UserSelection::Quit => {
+                        let _ = ScopedRaw::restore_terminal();
+                        selection = usel;
+                        break 'outer;
+                    }
+                    UserSelection::Previous => {
+                        log::warn!("Going back is not supported when fixing by word");
+                        continue 'inner;
+                    }
+                    UserSelection::Help => {
+                        println!("{}\n\n\n\n\n", tr("interactive-help"));
+                        continue 'inner;
+                    }
+                    UserSelection::ShowMore => {
+                        println!("{}", first);
+                        continue 'inner;
+                    }
+                    UserSelection::Replacement(_bandaid) => {
+                        let content = state.to_unescaped_bandaid().content;
+                        picked.replace_all(&occurrences, &content);
+                    }
+                    UserSelection::Skip | UserSelection::SkipFile => {
+                        for occurrence in &occurrences {
+                            picked.record(occurrence, Resolution::Skipped);
+                        }
+                    }
+                    UserSelection::AddToDictionary => {
+                        for occurrence in &occurrences {
+                            picked.record(occurrence, Resolution::Learned);
+                        }
+                        picked.words.push(word);
+                    }
+                    UserSelection::Nop => {}
+                };
+                break 'inner;
+            }
+        }
+        // the patches of a file have to be applied in order
+        for bandaids in picked.bandaids.values_mut() {
+            bandaids.sort_by_key(|bandaid| bandaid.span.start);
+        }
+        Ok((picked, selection))
+    }
+
+    /// Pick the first replacement of each of `suggestions` without asking, see
+    /// `--apply first`. Findings overlapping one picked before are left as is,
+    /// so the fixes of a file can be applied one after another.
+    pub(super) fn select_first(suggestions: &[Suggestion<'_>]) -> Self {
+        let mut picked = UserPicked::default();
+        for suggestion in suggestions {
+            let replacement = match suggestion.replacements.first() {
+                Some(replacement) => replacement,
+                None => continue,
+            };
+            let overlaps = picked
+                .bandaids
+                .get(&suggestion.origin)
+                .map_or(false, |bandaids| {
+                    bandaids.iter().any(|bandaid| {
+                        bandaid.span.start <= suggestion.span.end
+                            && suggestion.span.start <= bandaid.span.end
+                    })
+                });
+            if overlaps {
+                log::debug!(
+                    "Skipping a finding overlapping a picked one in {}",
+                    suggestion.origin
+                );
+                continue;
+            }
+            let bandaid = BandAid::from((replacement.clone(), &suggestion.span))
+                .escaped_for(&suggestion.chunk.variant());
+            picked.record(suggestion, Resolution::Replaced(replacement.clone()));
+            picked.add_bandaid(&suggestion.origin, bandaid);
+        }
+        for bandaids in picked.bandaids.values_mut() {
+            bandaids.sort_by_key(|bandaid| bandaid.span.start);
+        }
+        picked
+    }
+
+    /// Replace all `occurrences` of a word with `content`, escaped for the
+    /// chunk of each.
+    fn replace_all(&mut self, occurrences: &[&Suggestion<'_>], content: &str) {
+        for occurrence in occurrences {
+            let bandaid = BandAid::from((content.to_owned(), &occurrence.span))
+                .escaped_for(&occurrence.chunk.variant());
+            self.record(occurrence, Resolution::Replaced(content.to_owned()));
+            self.add_bandaid(&occurrence.origin, bandaid);
+        }
+    }
+}
+
+/// The findings of `suggestions` grouped by the flagged word, the most
+/// frequent first. Findings without replacements can not be fixed and are left
+/// out.
+fn by_word<'s, 't>(suggestions: &'s [Suggestion<'t>]) -> Vec<(String, Vec<&'s Suggestion<'t>>)> {
+    let mut words = indexmap::IndexMap::<String, Vec<&Suggestion<'t>>>::new();
+    for suggestion in suggestions {
+        if suggestion.replacements.is_empty() {
+            continue;
+        }
+        let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+        words.entry(word).or_default().push(suggestion);
+    }
+    let mut words = words.into_iter().collect::<Vec<_>>();
+    // stable, so equally frequent words keep the order of their first
+    // occurrence
+    words.sort_by_key(|(_word, occurrences)| std::cmp::Reverse(occurrences.len()));
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, Detector};
+
+    #[test]
+    fn all_occurrences_of_a_word() {
+        let a = ContentOrigin::RustSourceFile("a.rs".into());
+        let b = ContentOrigin::RustSourceFile("b.rs".into());
+        let mut docs =
+            Documentation::load_from_str(a.clone(), chyrp_up!("A teh wrod."), true, false);
+        docs.extend(Documentation::load_from_str(
+            b.clone(),
+            chyrp_up!("The wrod."),
+            true,
+            false,
+        ));
+        let suggestion = |origin: &ContentOrigin, range: Range, replacements: &[&str]| {
+            let chunk = &docs.get(origin).unwrap()[0];
+            let span = chunk
+                .find_spans(range.clone())
+                .values()
+                .next()
+                .copied()
+                .unwrap();
+            Suggestion {
+                detector: Detector::Hunspell,
+                origin: origin.clone(),
+                chunk,
+                span,
+                range,
+                replacements: replacements.iter().map(|r| (*r).to_owned()).collect(),
+                description: None,
+                rule: None,
+                help: None,
+                severity: None,
+                also: Vec::new(),
+            }
+        };
+        let suggestions = vec![
+            suggestion(&a, 2..5, &["the"]),
+            suggestion(&a, 6..10, &["word"]),
+            suggestion(&b, 4..8, &["word"]),
+            suggestion(&b, 0..3, &[]),
+        ];
+        let words = by_word(&suggestions);
+        assert_eq!(
+            words
+                .iter()
+                .map(|(word, occurrences)| (word.as_str(), occurrences.len()))
+                .collect::<Vec<_>>(),
+            vec![("wrod", 2), ("teh", 1)]
+        );
+
+        let mut picked = UserPicked::default();
+        picked.replace_all(&words[0].1, "word");
+        assert_eq!(picked.total_count(), 2);
+        assert_eq!(picked.history.len(), 2);
+        assert_eq!(picked.bandaids[&a][0].content, "word");
+        assert_eq!(picked.bandaids[&b][0].span, suggestions[2].span);
+
+        // the overlapping phrase is left for another run
+        let mut suggestions = suggestions;
+        suggestions.push(suggestion(&a, 2..10, &["the word"]));
+        let first = UserPicked::select_first(&suggestions);
+        assert_eq!(first.total_count(), 3);
+        assert_eq!(
+            first
+                .history
+                .iter()
+                .map(|record| (record.resolution.clone(), record.candidate))
+                .collect::<Vec<_>>(),
+            vec![
+                (Resolution::Replaced("the".to_owned()), Some(0)),
+                (Resolution::Replaced("word".to_owned()), Some(0)),
+                (Resolution::Replaced("word".to_owned()), Some(0)),
+            ]
+        );
+        assert_eq!(
+            first.bandaids[&a]
+                .iter()
+                .map(|bandaid| bandaid.content.as_str())
+                .collect::<Vec<_>>(),
+            vec!["the", "word"]
+        );
+    }
 }
@@ -0,0 +1,576 @@
+//! A language server, see `cargo spellcheck lsp`.
+//!
+//! Speaks the language server protocol on `stdin` and `stdout`, so editors
+//! show the findings of the open rust sources and markdown files as
+//! diagnostics while typing. Documents are synced incrementally, every change
+//! checks the whole document again, with the checkers set up once. Code
+//! actions offer the replacement candidates of the findings at the cursor.
+//! Once the config or a dictionary changed, i.e. by `learn`, the checkers are
+//! reloaded and the diagnostics of all open documents are published again.
+
+use crate::checker::{Checker, CheckerSet};
+use crate::errors::*;
+use crate::util::sub_chars;
+use crate::{BandAid, Config, ContentOrigin, Documentation, Severity, Span, Suggestion};
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// The source of the published diagnostics.
+const SOURCE: &str = "cargo-spellcheck";
+
+/// Read the next message from `reader`, none at the end of the input.
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .wrap_err("Invalid content length")?,
+                );
+            }
+        }
+    }
+    let mut content = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// Write `message` to `writer`.
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn response(id: &Value, result: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn notification(method: &str, params: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// The path of a `file://` URI, percent decoded.
+fn path_of(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = path
+            .get(idx + 1..idx + 3)
+            .filter(|_| bytes[idx] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            idx += 3;
+        } else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+    // drive letters come as `/C:/..`
+    #[cfg(windows)]
+    let path = path.trim_start_matches('/').to_owned();
+    Some(PathBuf::from(path))
+}
+
+/// The origin of the document at `path`, none if it is not checked.
+fn origin_of(path: PathBuf) -> Option<ContentOrigin> {
+    match path.extension()?.to_str()? {
+        "rs" => Some(ContentOrigin::RustSourceFile(path)),
+        "md" => Some(ContentOrigin::CommonMarkFile(path)),
+        _ => None,
+    }
+}
+
+/// A number of the protocol, `0` if it is missing.
+fn number(value: &Value) -> usize {
+    value
+        .as_u64()
+        .and_then(|n| usize::try_from(n).ok())
+        .unwrap_or_default()
+}
+
+/// The line and character of a position of the protocol.
+fn line_character(position: &Value) -> (usize, usize) {
+    (number(&position["line"]), number(&position["character"]))
+}
+
+/// The position of the protocol of the `column`th character of the `line`th
+/// line of `lines`, as in a [`Span`]. Characters are counted in UTF-16 code
+/// units.
+fn position(lines: &[&str], line: usize, column: usize) -> Value {
+    let line = line.saturating_sub(1);
+    let character = lines.get(line).map_or(0, |text| {
+        text.chars()
+            .take(column)
+            .map(char::len_utf16)
+            .sum::<usize>()
+    });
+    json!({ "line": line, "character": character })
+}
+
+/// The byte offset of the `position` of the protocol within `text`.
+fn offset(text: &str, position: &Value) -> usize {
+    let (line, character) = line_character(position);
+    let start = text
+        .split_inclusive('\n')
+        .take(line)
+        .map(str::len)
+        .sum::<usize>();
+    let mut units = 0;
+    for (idx, c) in text[start..].char_indices() {
+        if units >= character || c == '\n' {
+            return start + idx;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// A finding of a document, ready to be published.
+#[derive(Debug, Clone)]
+struct Finding {
+    range: Value,
+    severity: u8,
+    code: String,
+    message: String,
+    /// The replacement candidates, escaped for the comment they are in.
+    replacements: Vec<String>,
+}
+
+impl Finding {
+    fn new(lines: &[&str], suggestion: &Suggestion<'_>) -> Self {
+        let Span { start, end } = suggestion.span;
+        let mut message = format!(
+            "`{}`",
+            sub_chars(suggestion.chunk.as_str(), suggestion.range.clone())
+        );
+        if let Some(ref description) = suggestion.description {
+            message.push_str(": ");
+            message.push_str(description);
+        }
+        Self {
+            // the end of a span is inclusive
+            range: json!({
+                "start": position(lines, start.line, start.column),
+                "end": position(lines, end.line, end.column + 1),
+            }),
+            severity: match suggestion.severity() {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                Severity::Note => 3,
+            },
+            code: suggestion.detector.to_string(),
+            message,
+            replacements: suggestion
+                .replacements
+                .iter()
+                .map(|replacement| {
+                    BandAid::from((replacement.clone(), &suggestion.span))
+                        .escaped_for(&suggestion.chunk.variant())
+                        .content
+                })
+                .collect(),
+        }
+    }
+
+    fn diagnostic(&self) -> Value {
+        json!({
+            "range": self.range,
+            "severity": self.severity,
+            "code": self.code,
+            "source": SOURCE,
+            "message": self.message,
+        })
+    }
+
+    /// Whether the finding overlaps the `range` of the protocol.
+    fn overlaps(&self, range: &Value) -> bool {
+        line_character(&self.range["start"]) <= line_character(&range["end"])
+            && line_character(&range["start"]) <= line_character(&self.range["end"])
+    }
+}
+
+/// The state of the server.
+struct Server {
+    checkers: CheckerSet,
+    /// Loads the config again, once it or a dictionary changed.
+    reload: Box<dyn Fn() -> Result<Config>>,
+    dev_comments: bool,
+    /// The content of the open documents by URI.
+    documents: HashMap<String, String>,
+    /// The findings of the open documents by URI.
+    findings: HashMap<String, Vec<Finding>>,
+}
+
+impl Server {
+    fn new(
+        checkers: CheckerSet,
+        reload: impl Fn() -> Result<Config> + 'static,
+        dev_comments: bool,
+    ) -> Self {
+        Self {
+            checkers,
+            reload: Box::new(reload),
+            dev_comments,
+            documents: HashMap::new(),
+            findings: HashMap::new(),
+        }
+    }
+
+    /// Check the open document `uri`, returning the notification publishing
+    /// its findings.
+    fn check(&mut self, uri: &str) -> Result<Value> {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        let mut findings = Vec::new();
+        if let Some(origin) = path_of(uri).and_then(origin_of) {
+            let docs = Documentation::load_from_str(origin.clone(), text, true, self.dev_comments);
+            let lines = text.lines().collect::<Vec<_>>();
+            // the positions of other origins do not refer to this document
+            for (origin, chunks) in docs.iter().filter(|(other, _)| **other == origin) {
+                for suggestion in self.checkers.checkers().check(origin, &chunks[..])? {
+                    findings.push(Finding::new(&lines, &suggestion));
+                }
+            }
+        }
+        let diagnostics = findings.iter().map(Finding::diagnostic).collect::<Vec<_>>();
+        self.findings.insert(uri.to_owned(), findings);
+        Ok(notification(
+            "textDocument/publishDiagnostics",
+            &json!({ "uri": uri, "diagnostics": diagnostics }),
+        ))
+    }
+
+    /// Reload the checkers if the config or a dictionary changed, returning the
+    /// notifications publishing the findings of all open documents again.
+    fn reload(&mut self) -> Vec<Value> {
+        match self.checkers.reload_with(&self.reload) {
+            Ok(true) => {}
+            Ok(false) => return Vec::new(),
+            Err(e) => {
+                log::warn!("Failed to reload the checkers: {:?}", e);
+                return Vec::new();
+            }
+        }
+        let mut uris = self.documents.keys().cloned().collect::<Vec<_>>();
+        uris.sort();
+        uris.iter()
+            .filter_map(|uri| match self.check(uri) {
+                Ok(notification) => Some(notification),
+                Err(e) => {
+                    log::warn!("Failed to check {}: {:?}", uri, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Apply the `changes` of a `didChange` notification to the document
+    /// `uri`, either replacing a range or the whole content.
+    fn change(&mut self, uri: &str, changes: &[Value]) {
+        let text = self.documents.entry(uri.to_owned()).or_default();
+        for change in changes {
+            let content = change["text"].as_str().unwrap_or_default();
+            match change.get("range") {
+                Some(range) => {
+                    let start = offset(text, &range["start"]);
+                    let end = offset(text, &range["end"]).max(start);
+                    text.replace_range(start..end, content);
+                }
+                None => *text = content.to_owned(),
+            }
+        }
+    }
+
+    /// The code actions for the findings of the document `uri` within the
+    /// `range` of the protocol, one for each replacement candidate.
+    fn code_actions(&self, uri: &str, range: &Value) -> Value {
+        let findings = self.findings.get(uri).map_or(&[][..], Vec::as_slice);
+        let actions = findings
+            .iter()
+            .filter(|finding| finding.overlaps(range))
+            .flat_map(|finding| {
+                finding
+                    .replacements
+                    .iter()
+                    .enumerate()
+                    .map(move |(idx, replacement)| {
+                        json!({
+                            "title": format!("Replace with `{}`", replacement),
+                            "kind": "quickfix",
+                            "isPreferred": idx == 0,
+                            "diagnostics": [finding.diagnostic()],
+                            "edit": {
+                                "changes": {
+                                    uri: [{ "range": finding.range, "newText": replacement }],
+                                },
+                            },
+                        })
+                    })
+            })
+            .collect::<Vec<_>>();
+        Value::Array(actions)
+    }
+
+    /// Handle `message`, returning the messages to send in response.
+    fn handle(&mut self, message: &Value) -> Result<Vec<Value>> {
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let mut outgoing = Vec::new();
+        match (message["method"].as_str().unwrap_or_default(), id) {
+            ("initialize", Some(id)) => outgoing.push(response(
+                &id,
+                &json!({
+                    "capabilities": {
+                        // incremental
+                        "textDocumentSync": { "openClose": true, "change": 2 },
+                        "codeActionProvider": true,
+                    },
+                    "serverInfo": { "name": SOURCE, "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )),
+            ("shutdown", Some(id)) => outgoing.push(response(&id, &Value::Null)),
+            ("textDocument/codeAction", Some(id)) => {
+                outgoing.push(response(&id, &self.code_actions(uri, &params["range"])));
+            }
+            (method, Some(id)) => {
+                outgoing.push(error(&id, -32601, &format!("Unknown method {}", method)));
+            }
+            ("textDocument/didOpen", None) => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_owned(), text.to_owned());
+                outgoing.push(self.check(uri)?);
+            }
+            ("textDocument/didChange", None) => {
+                let changes = params["contentChanges"].as_array();
+                self.change(uri, changes.map_or(&[][..], Vec::as_slice));
+                outgoing.push(self.check(uri)?);
+            }
+            ("textDocument/didClose", None) => {
+                self.documents.remove(uri);
+                self.findings.remove(uri);
+                outgoing.push(notification(
+                    "textDocument/publishDiagnostics",
+                    &json!({ "uri": uri, "diagnostics": [] }),
+                ));
+            }
+            (method, None) => log::trace!("Ignoring {}", method),
+        }
+        Ok(outgoing)
+    }
+
+    /// Serve the messages of `reader` until the client exits.
+    fn run(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> Result<()> {
+        while let Some(message) = read_message(reader)? {
+            if message["method"] == "exit" {
+                break;
+            }
+            for message in self.reload() {
+                write_message(writer, &message)?;
+            }
+            let outgoing = self.handle(&message).unwrap_or_else(|e| {
+                log::warn!("Failed to handle {}: {:?}", message["method"], e);
+                message
+                    .get("id")
+                    .map(|id| vec![error(id, -32603, &e.to_string())])
+                    .unwrap_or_default()
+            });
+            for message in outgoing {
+                write_message(writer, &message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the language server on `stdin` and `stdout`, until the client exits.
+/// The checkers are created with the config from `config_path` again by
+/// `reload` once it or one of the dictionaries changed.
+pub(crate) fn serve(
+    config: Config,
+    config_path: Option<PathBuf>,
+    dev_comments: bool,
+    reload: impl Fn() -> Result<Config> + 'static,
+) -> Result<()> {
+    let checkers = CheckerSet::create(config, config_path, None)?;
+    let mut server = Server::new(checkers, reload, dev_comments);
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    server.run(&mut stdin.lock(), &mut stdout.lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framed(messages: &[Value]) -> Vec<u8> {
+        let mut input = Vec::new();
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        input
+    }
+
+    #[test]
+    fn incremental_changes() {
+        let mut text = "/// A wörd.\nstruct A;\n".to_owned();
+        let position = |line, character| json!({ "line": line, "character": character });
+        assert_eq!(offset(&text, &position(0, 7)), 7);
+        // `ö` is a single code unit, but two bytes
+        assert_eq!(offset(&text, &position(0, 8)), 9);
+        assert_eq!(offset(&text, &position(0, 99)), 12);
+        assert_eq!(offset(&text, &position(1, 0)), 13);
+        text.replace_range(
+            offset(&text, &position(0, 6))..offset(&text, &position(0, 10)),
+            "wrod",
+        );
+        assert_eq!(text, "/// A wrod.\nstruct A;\n");
+        assert_eq!(
+            path_of("file:///tmp/some%20dir/lib.rs"),
+            Some(PathBuf::from("/tmp/some dir/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn diagnostics_and_code_actions() {
+        let uri = "file:///tmp/lsp/lib.rs";
+        let input = framed(&[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            notification(
+                "textDocument/didOpen",
+                &json!({ "textDocument": { "uri": uri, "text": "/// A word.\nstruct A;\n" } }),
+            ),
+            notification(
+                "textDocument/didChange",
+                &json!({
+                    "textDocument": { "uri": uri },
+                    "contentChanges": [{
+                        "range": {
+                            "start": { "line": 0, "character": 6 },
+                            "end": { "line": 0, "character": 10 },
+                        },
+                        "text": "wrod",
+                    }],
+                }),
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "textDocument/codeAction",
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "range": {
+                        "start": { "line": 0, "character": 7 },
+                        "end": { "line": 0, "character": 7 },
+                    },
+                },
+            }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+            notification("exit", &Value::Null),
+        ]);
+        let config = Config {
+            nlprules: None,
+            ..Config::default()
+        };
+        let checkers = CheckerSet::new(&config).unwrap();
+        let mut server = Server::new(checkers, move || Ok(config.clone()), false);
+        let mut output = Vec::new();
+        server.run(&mut &input[..], &mut output).unwrap();
+
+        let mut output = &output[..];
+        let mut messages = Vec::new();
+        while let Some(message) = read_message(&mut output).unwrap() {
+            messages.push(message);
+        }
+        assert_eq!(messages.len(), 5);
+        assert_eq!(
+            messages[0]["result"]["capabilities"]["codeActionProvider"],
+            true
+        );
+        assert_eq!(messages[1]["params"]["diagnostics"], json!([]));
+        let diagnostics = &messages[2]["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({
+                "start": { "line": 0, "character": 6 },
+                "end": { "line": 0, "character": 10 },
+            })
+        );
+        let actions = messages[3]["result"].as_array().unwrap();
+        assert!(actions
+            .iter()
+            .any(|action| { action["edit"]["changes"][uri][0]["newText"] == "word" }));
+        assert_eq!(messages[4]["id"], 3);
+    }
+
+    #[test]
+    fn reload_publishes_again() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let dir = dir.canonicalize().unwrap();
+        let project = dir.join("project.txt");
+        let path = dir.join("spellcheck.toml");
+        fs_err::write(
+            &path,
+            format!(
+                "[Hunspell]\nuse_builtin = true\nproject_dictionary = {:?}\n",
+                project
+            ),
+        )
+        .unwrap();
+        let load = {
+            let path = path.clone();
+            move || Ok(Config::load_from(&path)?.unwrap())
+        };
+        let mut server = Server::new(CheckerSet::from_path(&path).unwrap(), load, false);
+
+        let uri = format!("file://{}", dir.join("lib.rs").display());
+        let opened = server
+            .handle(&notification(
+                "textDocument/didOpen",
+                &json!({ "textDocument": { "uri": uri, "text": "/// A wrod.\nstruct A;\n" } }),
+            ))
+            .unwrap();
+        let diagnostics = opened[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(server.reload().is_empty());
+
+        crate::dict::learn(&project, &["wrod".to_owned()]).unwrap();
+        let published = server.reload();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0]["params"]["uri"], uri);
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+    }
+}
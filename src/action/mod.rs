@@ -2,22 +2,42 @@
 
 use super::*;
 use crate::checker::Checkers;
+use crate::config::args::ApplyStrategy;
 use crate::errors::*;
 use crate::reflow::Reflow;
+use crate::traverse::bindings::Provenance;
 
 use fs_err as fs;
-use futures::stream::{self, StreamExt, TryStreamExt};
-use rayon::iter::ParallelIterator;
+use futures::stream::{self, StreamExt};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub mod bandaid;
+pub(crate) mod commit;
+mod decisions;
+pub(crate) mod dictionary;
+mod github;
+mod guard;
+pub(crate) mod history;
 pub mod interactive;
+pub(crate) mod lsp;
+mod reporter;
+mod sarif;
+mod template;
+mod verify;
+mod watch;
 
 pub(crate) use bandaid::*;
+pub(crate) use github::Workflow;
+pub(crate) use guard::{ensure_committed, TreeLock};
+pub(crate) use watch::watch;
 
 use interactive::{UserPicked, UserSelection};
+pub use reporter::Reporter;
+use template::{FileReport, Renderer, Report};
 
 /// State of conclusion.
 #[derive(Debug, Clone, Copy)]
@@ -29,6 +49,8 @@ pub enum Finish {
     /// Completion of the check run, with the resulting number of mistakes
     /// accumulated.
     MistakeCount(usize),
+    /// Completion of a `check` run, with the per severity counts.
+    Checked(Tally),
 }
 
 impl Finish {
@@ -36,6 +58,7 @@ impl Finish {
     pub fn found_any(&self) -> bool {
         match *self {
             Self::MistakeCount(n) if n > 0 => true,
+            Self::Checked(tally) if tally.total() > 0 => true,
             _ => false,
         }
     }
@@ -217,6 +240,38 @@ where
     Ok(())
 }
 
+/// A patched file, written next to the original which it is about to replace.
+#[derive(Debug)]
+struct Staged {
+    tmp: PathBuf,
+    path: PathBuf,
+    /// The content and permissions of the original, to restore it.
+    original: String,
+    permissions: std::fs::Permissions,
+}
+
+impl Staged {
+    /// Replace the original.
+    fn swap(&self) -> Result<()> {
+        fs::rename(&self.tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Write the original back, after it was replaced.
+    fn restore(&self) -> Result<()> {
+        fs::write(&self.path, &self.original)?;
+        fs::set_permissions(&self.path, self.permissions.clone())?;
+        Ok(())
+    }
+
+    /// Keep the original.
+    fn discard(self) {
+        if let Err(e) = fs::remove_file(&self.tmp) {
+            log::warn!("Failed to remove {}: {}", self.tmp.display(), e);
+        }
+    }
+}
+
 /// Mode in which `cargo-spellcheck` operates.
 ///
 /// Eventually to be used directly in parsing arguments.
@@ -237,17 +292,38 @@ pub enum Action {
 }
 
 impl Action {
+    /// Whether the action writes to the checked files.
+    pub(crate) fn modifies_files(&self) -> bool {
+        matches!(self, Self::Fix | Self::Reflow)
+    }
+
     /// Apply bandaids to the file represented by content origin.
     pub fn write_changes_to_disk(
         &self,
         origin: ContentOrigin,
         bandaids: impl IntoIterator<Item = BandAid>,
     ) -> Result<()> {
+        let th = crate::TinHat::on();
+        self.stage(origin, bandaids)?.swap()?;
+        // Writing for this file is done, unblock the signal handler.
+        drop(th);
+        Ok(())
+    }
+
+    /// Write the patched file represented by content origin next to the
+    /// original, without replacing it yet.
+    fn stage(
+        &self,
+        origin: ContentOrigin,
+        bandaids: impl IntoIterator<Item = BandAid>,
+    ) -> Result<Staged> {
         match origin {
             ContentOrigin::CargoManifestDescription(path) => self.correct_file(path, bandaids),
             ContentOrigin::CommonMarkFile(path) => self.correct_file(path, bandaids),
+            ContentOrigin::ConfigFile(path) => self.correct_file(path, bandaids),
             ContentOrigin::RustSourceFile(path) => self.correct_file(path, bandaids),
             ContentOrigin::RustDocTest(path, _span) => self.correct_file(path, bandaids),
+            ContentOrigin::Stdin => bail!("Changes to the standard input can not be written back"),
             ContentOrigin::TestEntityRust => unreachable!("Use a proper file"),
             ContentOrigin::TestEntityCommonMark => unreachable!("Use a proper file"),
         }
@@ -259,11 +335,11 @@ impl Action {
         &self,
         path: PathBuf,
         bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
+    ) -> Result<Staged> {
         let path = fs::canonicalize(path.as_path())?;
-        let path = path.as_path();
         log::trace!("Attempting to open {} as read", path.display());
-        let ro = fs::OpenOptions::new().read(true).open(path)?;
+        let ro = fs::OpenOptions::new().read(true).open(&path)?;
+        let permissions = ro.metadata()?.permissions();
 
         let mut reader = std::io::BufReader::new(ro);
 
@@ -286,28 +362,36 @@ impl Action {
         let mut content = String::with_capacity(2e6 as usize);
         reader.get_mut().read_to_string(&mut content)?;
 
-        {
-            let th = crate::TinHat::on();
-
-            apply_patches(
-                bandaids.into_iter().map(|x| Patch::from(x)),
-                content.as_str(), // FIXME for efficiency, correct_lines should integrate with `BufRead` instead of a `String` buffer
-                &mut writer,
-            )?;
-
-            writer.flush()?;
-            // Required for windows support, which does not allow
-            // to move a file while it is opened, see
-            // <https://github.com/drahnr/cargo-spellcheck/issues/251>
-            drop(writer);
-            drop(reader);
-            fs::rename(tmp, path)?;
-
-            // Writing for this file is done, unblock the signal handler.
-            drop(th);
+        apply_patches(
+            bandaids
+                .into_iter()
+                .map(|x| Patch::from(x.with_source_indentation(&content))),
+            content.as_str(), // FIXME for efficiency, correct_lines should integrate with `BufRead` instead of a `String` buffer
+            &mut writer,
+        )?;
+
+        writer.flush()?;
+        // Required for windows support, which does not allow
+        // to move a file while it is opened, see
+        // <https://github.com/drahnr/cargo-spellcheck/issues/251>
+        drop(writer);
+        drop(reader);
+        // the original is only replaced by a fixed file which still parses
+        let verified = fs::read_to_string(&tmp)
+            .wrap_err("Failed to read back the fixed file")
+            .and_then(|patched| verify::verify(&path, &content, &patched));
+        let staged = Staged {
+            tmp,
+            path,
+            original: content,
+            permissions,
+        };
+        if let Err(e) = verified {
+            let path = staged.path.clone();
+            staged.discard();
+            return Err(e.wrap_err(format!("Rolled back the fixes of {}", path.display())));
         }
-
-        Ok(())
+        Ok(staged)
     }
 
     /// Consumingly apply the user picked changes to a file.
@@ -318,23 +402,121 @@ impl Action {
         &self,
         userpicked: interactive::UserPicked,
     ) -> Result<()> {
+        self.write_picks(userpicked, false)
+    }
+
+    /// Apply the user picked changes like
+    /// [`write_user_pick_changes_to_disk`](Self::write_user_pick_changes_to_disk),
+    /// with `atomic` either all files or none are changed, see `--atomic`.
+    fn write_picks(&self, userpicked: interactive::UserPicked, atomic: bool) -> Result<()> {
+        // files are independent, so they are written in parallel, a file which
+        // fails to verify is left as is, while the others are written, unless
+        // `atomic`
+        let mut failed = Vec::new();
         if userpicked.total_count() > 0 {
             log::debug!("Writing changes back to disk");
-            for (origin, bandaids) in userpicked.bandaids.into_iter() {
-                self.write_changes_to_disk(origin, bandaids.into_iter())?;
+            let bandaids = userpicked.bandaids.into_iter().collect::<Vec<_>>();
+            if atomic {
+                failed = self.write_atomically(bandaids);
+            } else {
+                failed = bandaids
+                    .into_par_iter()
+                    .filter_map(|(origin, bandaids)| {
+                        self.write_changes_to_disk(origin, bandaids).err()
+                    })
+                    .collect::<Vec<_>>();
             }
         } else {
             log::debug!("No band aids to apply");
         }
-        Ok(())
+        if let Err(e) =
+            history::default_path().and_then(|path| history::append(&path, &userpicked.history))
+        {
+            log::warn!("Failed to record the resolutions in the history: {}", e);
+        }
+        match failed.len() {
+            0 => Ok(()),
+            1 => Err(failed.remove(0)),
+            n => {
+                for e in &failed {
+                    log::error!("{:?}", e);
+                }
+                bail!("Failed to write the fixes of {} files, see above", n)
+            }
+        }
     }
+
+    /// Stage all files, and only swap them in if every one of them verifies,
+    /// returning the errors otherwise. If swapping in one fails, the ones
+    /// swapped in already are restored.
+    fn write_atomically(
+        &self,
+        bandaids: Vec<(ContentOrigin, Vec<BandAid>)>,
+    ) -> Vec<color_eyre::eyre::Report> {
+        let (staged, failed): (Vec<_>, Vec<_>) = bandaids
+            .into_par_iter()
+            .map(|(origin, bandaids)| self.stage(origin, bandaids))
+            .partition(Result::is_ok);
+        let staged = staged
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            for staged in staged {
+                staged.discard();
+            }
+            log::warn!("Not writing any fixes, since some files fail to verify");
+            return failed.into_iter().filter_map(Result::err).collect();
+        }
+        let th = crate::TinHat::on();
+        let mut failed = Vec::new();
+        for (idx, file) in staged.iter().enumerate() {
+            if let Err(e) = file.swap() {
+                failed.push(e.wrap_err(format!("Failed to replace {}", file.path.display())));
+                for swapped in &staged[..idx] {
+                    if let Err(e) = swapped.restore() {
+                        failed.push(
+                            e.wrap_err(format!("Failed to restore {}", swapped.path.display())),
+                        );
+                    }
+                }
+                // including the one which failed to replace the original
+                for pending in staged.into_iter().skip(idx) {
+                    pending.discard();
+                }
+                break;
+            }
+        }
+        drop(th);
+        failed
+    }
+
     /// Run the requested action.
-    pub async fn run(self, documents: Documentation, config: Config) -> Result<Finish> {
+    ///
+    /// Findings of `check` are reported with `reporter`.
+    pub async fn run(
+        self,
+        documents: Documentation,
+        config: Config,
+        reporter: Reporter,
+    ) -> Result<Finish> {
+        self.run_filtered(documents, config, reporter, None).await
+    }
+
+    /// Run the requested action like [`run`](Self::run), with the findings of
+    /// `check` and `fix` passed through `filter`.
+    pub async fn run_filtered(
+        self,
+        documents: Documentation,
+        config: Config,
+        reporter: Reporter,
+        filter: Option<Arc<dyn SuggestionFilter>>,
+    ) -> Result<Finish> {
         let fin = match self {
             Self::ListFiles { .. } => self.run_list_files(documents, &config).await?,
             Self::Reflow { .. } => self.run_reflow(documents, config).await?,
-            Self::Check { .. } => self.run_check(documents, config).await?,
-            Self::Fix { .. } => self.run_fix_interactive(documents, config).await?,
+            Self::Check { .. } => self.run_check(documents, config, reporter, filter).await?,
+            Self::Fix { .. } => self.run_fix_interactive(documents, config, filter).await?,
         };
         Ok(fin)
     }
@@ -348,127 +530,292 @@ impl Action {
     }
 
     /// Run the requested action _interactively_, waiting for user input.
-    async fn run_fix_interactive(self, documents: Documentation, config: Config) -> Result<Finish> {
-        let n_cpus = num_cpus::get();
-
-        let checkers = Checkers::new(config)?;
-
-        let n = documents.entry_count();
-        log::debug!("Running checkers on all documents {}", n);
-        let mut pick_stream = stream::iter(documents.iter().enumerate())
-            .map(|(mut idx, (origin, chunks))| {
-                // align the debug output with the user output
-                idx += 1;
-                log::trace!("Running checkers on {}/{},{:?}", idx, n, &origin);
-                let suggestions = checkers.check(origin, &chunks[..]);
-                async move { Ok::<_, color_eyre::eyre::Report>((idx, origin, suggestions?)) }
-            })
-            .buffered(n_cpus)
-            .fuse();
-
-        let mut collected_picks = UserPicked::default();
-        while let Some(result) = pick_stream.next().await {
-            match result {
-                Ok((idx, origin, suggestions)) => {
-                    let (picked, user_sel) =
-                        interactive::UserPicked::select_interactive(origin.clone(), suggestions)?;
-
-                    match user_sel {
-                        UserSelection::Quit => break,
-                        UserSelection::Abort => return Ok(Finish::Abort),
-                        UserSelection::Nop if !picked.is_empty() => {
-                            log::debug!(
-                                "User picked patches to be applied for {}/{},{:?}",
-                                idx,
-                                n,
-                                &origin
-                            );
-                            collected_picks.extend(picked);
-                        }
-                        UserSelection::Nop => {
-                            log::debug!("Nothing to do for {}/{},{:?}", idx, n, &origin);
-                        }
-                        _ => unreachable!(
-                            "All other variants are only internal to `select_interactive`. qed"
-                        ),
-                    }
-                }
-                Err(e) => Err(e)?,
+    async fn run_fix_interactive(
+        self,
+        documents: Documentation,
+        config: Config,
+        filter: Option<Arc<dyn SuggestionFilter>>,
+    ) -> Result<Finish> {
+        // accepted words go to the project dictionary if there is one, to the
+        // first extra dictionary otherwise, and to a new project dictionary if
+        // neither exists
+        let cwd = config.base_dir()?;
+        let project_dictionary = config.hunspell.as_ref().map_or_else(
+            || cwd.join(crate::dict::PROJECT_DICTIONARY),
+            |hunspell| hunspell.project_dictionary(&cwd),
+        );
+        let dictionary = config
+            .hunspell
+            .as_ref()
+            .and_then(|hunspell| hunspell.extra_dictionaries().next().cloned())
+            .filter(|_| !project_dictionary.is_file());
+        let commit = config.commit.clone();
+        let atomic = config.atomic;
+        let by_word = config.by_word;
+        let apply = config.apply.clone();
+        let decisions_file = config.decisions.clone();
+
+        let picked = if let Some(ApplyStrategy::From(ref file)) = apply {
+            Some(decisions::import(file, &cwd)?)
+        } else {
+            let checkers = Checkers::new(config)?.with_filter(filter);
+            if apply == Some(ApplyStrategy::First) {
+                Some(pick_first(&documents, &checkers)?)
+            } else if by_word {
+                pick_by_word(&documents, &checkers)?
+            } else {
+                pick_per_file(&documents, &checkers).await?
             }
-        }
+        };
+        let mut collected_picks = match picked {
+            Some(picked) => picked,
+            None => return Ok(Finish::Abort),
+        };
         let total = collected_picks.total_count();
+        // a dry run, the picks are only recorded
+        if let Some(ref file) = decisions_file {
+            let count = decisions::export(file, &collected_picks, &cwd)?;
+            log::info!("Wrote {} fixes to {}", count, file.display());
+            return Ok(Finish::MistakeCount(total));
+        }
+        let words = std::mem::take(&mut collected_picks.words);
+        let history = collected_picks.history.clone();
         // clustering per file is not reasonable
         // since user abort (`<CTRL>-C` or `q`) should not
         // leave any residue on disk.
-        self.write_user_pick_changes_to_disk(collected_picks)?;
+        self.write_picks(collected_picks, atomic)?;
+        if let Some(subject) = commit {
+            commit::commit(&subject, &history, &cwd)?;
+        }
+        match dictionary {
+            Some(dictionary) if !words.is_empty() => {
+                let added = dictionary::add_words(&dictionary, &words)?;
+                log::info!("Added {} words to {}", added, dictionary.display());
+            }
+            None if !words.is_empty() => {
+                let added = crate::dict::learn(&project_dictionary, &words)?;
+                log::info!("Added {} words to {}", added, project_dictionary.display());
+            }
+            _ => {}
+        }
 
         Ok(Finish::MistakeCount(total))
     }
 
     /// Run the requested action.
-    async fn run_check(self, documents: Documentation, config: Config) -> Result<Finish> {
-        let n_cpus = num_cpus::get();
-
-        let checkers = Checkers::new(config)?;
+    async fn run_check(
+        self,
+        documents: Documentation,
+        config: Config,
+        reporter: Reporter,
+        filter: Option<Arc<dyn SuggestionFilter>>,
+    ) -> Result<Finish> {
+        let renderer = match reporter {
+            Reporter::Template(ref path) => Some(Renderer::load(path)?),
+            _ => None,
+        };
+        let base_dir = config.base_dir()?;
+        let workflow = (reporter == Reporter::GitHub)
+            .then(|| Workflow::from_env(&base_dir))
+            .transpose()?;
+        let collects = reporter.collects();
+
+        let bindings = config.files.bindings;
+        let checkers = Checkers::new(config)?.with_filter(filter);
+        let packages = crate::traverse::packages::packages(
+            documents.iter().map(|(origin, _chunks)| origin.as_path()),
+        );
+        let packages = &packages;
 
-        // TODO per file clustering might make sense here
-        let mistakes_count = stream::iter(documents.iter().enumerate())
-            .map(move |(idx, (origin, chunks))| {
-                let suggestions = checkers.check(origin, &chunks[..]);
-                async move { Ok::<_, color_eyre::eyre::Report>((idx, origin, suggestions?)) }
-            })
-            .buffered(n_cpus)
-            .try_fold(0_usize, |acc, (_idx, origin, suggestions)| async move {
-                let n = suggestions.len();
-                let path = origin.as_path();
-                if n == 0 {
-                    log::info!("✅ {}", path.display());
-                } else {
-                    log::info!("❌ {} : {}", path.display(), n);
+        // files are checked in parallel, the findings are reported in path
+        // order regardless of which file finished first
+        let mut checked = documents
+            .par_iter()
+            .map(|(origin, chunks)| {
+                let mut suggestions = checkers.check(origin, &chunks[..])?;
+                if let Some(provenance) = bindings.then(|| Provenance::load(origin)).flatten() {
+                    for suggestion in &mut suggestions {
+                        provenance.annotate(suggestion);
+                    }
                 }
+                Ok::<_, color_eyre::eyre::Report>((origin, suggestions))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        checked.sort_by(|(origin1, _), (origin2, _)| origin1.as_path().cmp(origin2.as_path()));
+
+        let mut tally = Tally::default();
+        let mut reports = Vec::new();
+        for (origin, suggestions) in checked {
+            let file_tally = Tally::from_file(&suggestions);
+            let path = origin.as_path();
+            if file_tally.total() == 0 {
+                log::info!("✅ {}", path.display());
+                continue;
+            }
+            log::info!("❌ {} : {}", path.display(), file_tally.total());
+            let package = packages.get(path);
+            if collects {
+                reports.push(FileReport::new(origin, &suggestions).with_package(package.cloned()));
+            } else {
+                println!(
+                    "{}",
+                    file_tally.package_header(origin, package.map(String::as_str))
+                );
                 for suggestion in suggestions {
                     println!("{}", suggestion);
                 }
-                Ok::<_, color_eyre::eyre::Report>(acc + n)
-            })
-            .await?;
-        if mistakes_count > 0 {
-            Ok(Finish::MistakeCount(mistakes_count))
-        } else {
-            Ok(Finish::Success)
+            }
+            tally = tally + file_tally;
+        }
+        let report = Report::new(reports, &tally);
+        if let Some(renderer) = renderer {
+            print!("{}", renderer.render(&report)?);
+        } else if let Some(workflow) = workflow {
+            workflow.publish(&report)?;
+        } else if reporter == Reporter::Json {
+            println!("{}", reporter::json(&report)?);
+        } else if reporter == Reporter::Annotations {
+            github::annotate(&report, &base_dir);
+        } else if reporter == Reporter::Sarif {
+            println!("{}", sarif::sarif(&report, &base_dir)?);
+        } else if tally.total() > 0 {
+            print!("{}", tally);
         }
+        Ok(Finish::Checked(tally))
     }
 
     /// Run the requested action.
     async fn run_reflow(self, documents: Documentation, config: Config) -> Result<Finish> {
-        let reflow_config = config.reflow.clone().unwrap_or_default();
-        let reflow = Reflow::new(reflow_config)?;
-
-        let _ = documents
-            .into_par_iter()
-            .map(|(origin, chunks)| {
-                let mut picked = UserPicked::default();
-                let suggestions = reflow.check(&origin, &chunks[..])?;
-                for suggestion in suggestions {
-                    let bandaids = suggestion.replacements.first().map(|replacement| {
-                        let bandaid =
-                            super::BandAid::from((replacement.to_owned(), &suggestion.span));
-                        bandaid
-                    });
+        let reflow_config = config
+            .reflow
+            .clone()
+            .unwrap_or_default()
+            .with_rustfmt_width(&config.base_dir()?)?;
+        let mut reflow = Reflow::new(reflow_config)?;
+        if let Some(hunspell) = config.hunspell.as_ref() {
+            match crate::checker::HunspellChecker::new(hunspell, &config.base_dir()?) {
+                Ok(dictionary) => reflow = reflow.with_dictionary(dictionary),
+                Err(e) => log::warn!(
+                    "Reflowing without a dictionary, hyphens of soft wrapped words are kept: {}",
+                    e
+                ),
+            }
+        }
 
-                    picked.add_bandaids(&origin, bandaids);
-                }
-                Ok::<_, color_eyre::eyre::Report>(picked)
-            })
-            .try_for_each(move |picked| {
+        let atomic = config.atomic;
+        let picks = documents.into_par_iter().map(|(origin, chunks)| {
+            let mut picked = UserPicked::default();
+            let suggestions = reflow.check(&origin, &chunks[..])?;
+            for suggestion in suggestions {
+                let bandaids = suggestion.replacements.first().map(|replacement| {
+                    let bandaid = super::BandAid::from((replacement.to_owned(), &suggestion.span));
+                    bandaid
+                });
+
+                picked.add_bandaids(&origin, bandaids);
+            }
+            Ok::<_, color_eyre::eyre::Report>(picked)
+        });
+        if atomic {
+            let mut collected_picks = UserPicked::default();
+            for picked in picks.collect::<Result<Vec<_>>>()? {
+                collected_picks.extend(picked);
+            }
+            self.write_picks(collected_picks, true)?;
+        } else {
+            picks.try_for_each(move |picked| {
                 self.write_user_pick_changes_to_disk(picked?)?;
                 Ok::<_, color_eyre::eyre::Report>(())
             })?;
+        }
 
         Ok(Finish::Success)
     }
 }
 
+/// Check `documents` file by file, letting the user pick the fixes of each
+/// file once it is checked. None if the user aborted.
+async fn pick_per_file(
+    documents: &Documentation,
+    checkers: &Checkers,
+) -> Result<Option<UserPicked>> {
+    let n_cpus = num_cpus::get();
+    let n = documents.entry_count();
+    log::debug!("Running checkers on all documents {}", n);
+    let mut pick_stream = stream::iter(documents.iter().enumerate())
+        .map(|(mut idx, (origin, chunks))| {
+            // align the debug output with the user output
+            idx += 1;
+            log::trace!("Running checkers on {}/{},{:?}", idx, n, &origin);
+            let suggestions = checkers.check(origin, &chunks[..]);
+            async move { Ok::<_, color_eyre::eyre::Report>((idx, origin, suggestions?)) }
+        })
+        .buffered(n_cpus)
+        .fuse();
+
+    let mut collected_picks = UserPicked::default();
+    while let Some(result) = pick_stream.next().await {
+        match result {
+            Ok((idx, origin, suggestions)) => {
+                let (picked, user_sel) = interactive::UserPicked::select_interactive(
+                    origin.clone(),
+                    suggestions,
+                    checkers.dictionary(),
+                )?;
+
+                match user_sel {
+                    UserSelection::Quit => break,
+                    UserSelection::Abort => return Ok(None),
+                    UserSelection::Nop if !picked.is_empty() => {
+                        log::debug!(
+                            "User picked patches to be applied for {}/{},{:?}",
+                            idx,
+                            n,
+                            &origin
+                        );
+                        collected_picks.extend(picked);
+                    }
+                    UserSelection::Nop => {
+                        log::debug!("Nothing to do for {}/{},{:?}", idx, n, &origin);
+                    }
+                    _ => unreachable!(
+                        "All other variants are only internal to `select_interactive`. qed"
+                    ),
+                }
+            }
+            Err(e) => Err(e)?,
+        }
+    }
+    Ok(Some(collected_picks))
+}
+
+/// The findings of all of `documents`, ordered by path.
+fn check_all<'s>(documents: &'s Documentation, checkers: &Checkers) -> Result<Vec<Suggestion<'s>>> {
+    let mut checked = documents
+        .par_iter()
+        .map(|(origin, chunks)| Ok((origin, checkers.check(origin, &chunks[..])?)))
+        .collect::<Result<Vec<_>>>()?;
+    checked.sort_by(|(origin1, _), (origin2, _)| origin1.as_path().cmp(origin2.as_path()));
+    Ok(checked
+        .into_iter()
+        .flat_map(|(_origin, suggestions)| suggestions)
+        .collect())
+}
+
+/// Check all of `documents`, picking the first replacement of every finding.
+fn pick_first(documents: &Documentation, checkers: &Checkers) -> Result<UserPicked> {
+    Ok(UserPicked::select_first(&check_all(documents, checkers)?))
+}
+
+/// Check all of `documents`, then let the user pick the fixes once per word.
+/// None if the user aborted.
+fn pick_by_word(documents: &Documentation, checkers: &Checkers) -> Result<Option<UserPicked>> {
+    match UserPicked::select_by_word(&check_all(documents, checkers)?, checkers.dictionary())? {
+        (_picked, UserSelection::Abort) => Ok(None),
+        (picked, _) => Ok(Some(picked)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -489,6 +836,26 @@ mod tests {
         };
     }
 
+    #[test]
+    fn replacement_within_escaped_doc_string() {
+        const SOURCE: &str = "#[doc = \"A \\\"quoted\\\" wrod.\"]\nstruct A;\n";
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        assert!(chunk.is_unescaped());
+        let start = chunk.as_str().find("wrod").unwrap();
+        let spans = chunk.find_spans(start..start + 4);
+        assert_eq!(spans.len(), 1);
+        let (_range, span) = spans.first().unwrap();
+        // the unescaped content maps to the source, the replacement is escaped
+        let bandaid = BandAid::from((r#""word""#.to_owned(), span)).escaped_for(&chunk.variant());
+        verify_correction!(
+            "#[doc = \"A \\\"quoted\\\" wrod.\"]\nstruct A;\n",
+            vec![bandaid],
+            "#[doc = \"A \\\"quoted\\\" \\\"word\\\".\"]\nstruct A;\n"
+        );
+    }
+
     #[test]
     fn patch_full() {
         let _ = env_logger::Builder::new()
@@ -610,4 +977,93 @@ Icecream truck"#
         }];
         verify_correction!("A🐢C", patches, "A🐢CQ");
     }
+
+    #[test]
+    fn atomic_fixes_all_or_nothing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        let good = root.join("good.rs");
+        let bad = root.join("bad.rs");
+        fs::write(&good, "/// A wrod.\nstruct A;\n").unwrap();
+        fs::write(&bad, "/// A wrod.\nstruct B;\n").unwrap();
+        let span = Span {
+            start: LineColumn { line: 1, column: 6 },
+            end: LineColumn { line: 1, column: 9 },
+        };
+        // a replacement which breaks the syntax
+        let broken = Span {
+            start: LineColumn { line: 2, column: 7 },
+            end: LineColumn { line: 2, column: 7 },
+        };
+        let bandaids = || {
+            vec![
+                (
+                    ContentOrigin::RustSourceFile(good.clone()),
+                    vec![BandAid::from(("word".to_owned(), &span))],
+                ),
+                (
+                    ContentOrigin::RustSourceFile(bad.clone()),
+                    vec![BandAid::from(("B {".to_owned(), &broken))],
+                ),
+            ]
+        };
+        let failed = Action::Fix.write_atomically(bandaids());
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&good).unwrap(),
+            "/// A wrod.\nstruct A;\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&bad).unwrap(),
+            "/// A wrod.\nstruct B;\n"
+        );
+
+        // without, the good one is written nonetheless
+        for (origin, bandaids) in bandaids() {
+            let _ = Action::Fix.write_changes_to_disk(origin, bandaids);
+        }
+        assert_eq!(
+            fs::read_to_string(&good).unwrap(),
+            "/// A word.\nstruct A;\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&bad).unwrap(),
+            "/// A wrod.\nstruct B;\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restore_keeps_the_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        let path = root.join("lib.rs");
+        fs::write(&path, "/// A wrod.\nstruct A;\n").unwrap();
+        fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let span = Span {
+            start: LineColumn { line: 1, column: 6 },
+            end: LineColumn { line: 1, column: 9 },
+        };
+        let staged = Action::Fix
+            .stage(
+                ContentOrigin::RustSourceFile(path.clone()),
+                vec![BandAid::from(("word".to_owned(), &span))],
+            )
+            .unwrap();
+        staged.swap().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// A word.\nstruct A;\n"
+        );
+        fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        staged.restore().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// A wrod.\nstruct A;\n"
+        );
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
 }
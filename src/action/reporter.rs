@@ -0,0 +1,126 @@
+//! How the findings of `check` are reported, see `--format`.
+//!
+//! Besides the built-in human readable format, findings are collected into a
+//! [`Report`] first, which is rendered with a user provided template, printed
+//! as JSON or SARIF log or published to a GitHub Actions workflow run.
+
+use super::template::Report;
+use crate::errors::*;
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Version of the JSON schema, bumped on incompatible changes.
+const JSON_VERSION: u32 = 1;
+
+/// The output of `check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reporter {
+    /// Human readable, with the context of each finding.
+    Human,
+    /// Rendered with the handlebars template at the given path.
+    Template(PathBuf),
+    /// A JSON document, for editor plugins and CI systems.
+    Json,
+    /// A SARIF log, for code scanning.
+    Sarif,
+    /// Annotations, a job summary and step outputs of a GitHub Actions run.
+    GitHub,
+    /// Only the annotations of a GitHub Actions run.
+    Annotations,
+}
+
+impl Default for Reporter {
+    fn default() -> Self {
+        Self::Human
+    }
+}
+
+impl Reporter {
+    /// Whether the findings are collected into a [`Report`] rather than
+    /// printed as they are found.
+    pub(crate) fn collects(&self) -> bool {
+        *self != Self::Human
+    }
+}
+
+/// The JSON document `--format json` prints.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    version: u32,
+    #[serde(flatten)]
+    report: &'a Report,
+}
+
+/// Serialize `report` as JSON.
+pub(crate) fn json(report: &Report) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&JsonReport {
+        version: JSON_VERSION,
+        report,
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::template::FileReport;
+    use crate::{chyrp_up, ContentOrigin, Detector, Documentation, LineColumn, Span};
+    use crate::{Suggestion, Tally};
+
+    #[test]
+    fn json_schema() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some wrod."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let suggestions = vec![Suggestion {
+            detector: Detector::Hunspell,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 14,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 17,
+                },
+            },
+            range: 5..9,
+            replacements: vec!["word".to_owned()],
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        }];
+        let tally = Tally::from_file(&suggestions);
+        let report = Report::new(vec![FileReport::new(&origin, &suggestions)], &tally);
+
+        let value = serde_json::from_str::<serde_json::Value>(&json(&report).unwrap()).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["file_count"], 1);
+        assert_eq!(value["counts"]["error"], 1);
+        let file = &value["files"][0];
+        assert_eq!(file["path"], "/tmp/test/entity.rs");
+        assert_eq!(
+            file["findings"][0],
+            serde_json::json!({
+                "detector": "Hunspell",
+                "also": [],
+                "severity": "error",
+                "comment": "doc",
+                "text": "wrod",
+                "line": 1,
+                "column": 15,
+                "end_line": 1,
+                "end_column": 18,
+                "description": null,
+                "replacements": ["word"],
+                "rule": null,
+                "help": null,
+            })
+        );
+    }
+}
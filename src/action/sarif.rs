@@ -0,0 +1,304 @@
+//! A [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+//! log of the findings of `check`, see `--format sarif`.
+//!
+//! Uploaded to GitHub code scanning, each finding shows up as an alert. There
+//! is one rule per checker backend, the locations are relative to the
+//! working directory, which is expected to be the root of the repository, and
+//! the replacement candidates are offered as fixes.
+
+use super::github::relative;
+use super::template::{Finding, Report};
+use crate::errors::*;
+
+use serde::Serialize;
+use std::path::Path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+/// Base of all artifact locations.
+const SRCROOT: &str = "%SRCROOT%";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    original_uri_base_ids: serde_json::Value,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    short_description: Text,
+}
+
+#[derive(Serialize)]
+struct Text {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: Text,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+/// Lines and columns are 1-based, the end column is exclusive.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Text,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Text,
+}
+
+/// The message of `finding`, the flagged text and the description if any.
+fn message(finding: &Finding) -> String {
+    let mut message = format!("`{}`", finding.text);
+    if let Some(ref description) = finding.description {
+        message.push_str(": ");
+        message.push_str(description);
+    }
+    message
+}
+
+/// Convert `finding`, the rule of which is the `rule_index`th one.
+fn result(finding: &Finding, rule_index: usize, artifact: &ArtifactLocation) -> SarifResult {
+    let region = Region {
+        start_line: finding.line,
+        start_column: finding.column,
+        end_line: finding.end_line,
+        // the end column of a finding is inclusive
+        end_column: finding.end_column + 1,
+    };
+    let fixes = finding
+        .replacements
+        .iter()
+        .map(|replacement| Fix {
+            description: Text {
+                text: format!("Replace with `{}`", replacement),
+            },
+            artifact_changes: vec![ArtifactChange {
+                artifact_location: artifact.clone(),
+                replacements: vec![Replacement {
+                    deleted_region: region.clone(),
+                    inserted_content: Text {
+                        text: replacement.clone(),
+                    },
+                }],
+            }],
+        })
+        .collect();
+    SarifResult {
+        rule_id: finding.detector.clone(),
+        rule_index,
+        level: match finding.severity {
+            "error" => "error",
+            "warning" => "warning",
+            _ => "note",
+        },
+        message: Text {
+            text: message(finding),
+        },
+        locations: vec![Location {
+            physical_location: PhysicalLocation {
+                artifact_location: artifact.clone(),
+                region,
+            },
+        }],
+        fixes,
+    }
+}
+
+/// The SARIF log of `report`, with paths relative to `workspace`.
+fn log(report: &Report, workspace: &Path) -> Log {
+    let mut rules = Vec::<Rule>::new();
+    let mut results = Vec::new();
+    for file in &report.files {
+        let artifact = ArtifactLocation {
+            uri: relative(&file.path, workspace).replace('\\', "/"),
+            uri_base_id: SRCROOT,
+        };
+        for finding in &file.findings {
+            let rule_index = rules
+                .iter()
+                .position(|rule| rule.id == finding.detector)
+                .unwrap_or_else(|| {
+                    rules.push(Rule {
+                        id: finding.detector.clone(),
+                        short_description: Text {
+                            text: format!("Findings of the {} checker", finding.detector),
+                        },
+                    });
+                    rules.len() - 1
+                });
+            results.push(result(finding, rule_index, &artifact));
+        }
+    }
+    let base = url::Url::from_directory_path(workspace).map_or_else(
+        |()| serde_json::json!({}),
+        |url| serde_json::json!({ SRCROOT: { "uri": url.as_str() } }),
+    );
+    Log {
+        schema: SCHEMA,
+        version: VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            original_uri_base_ids: base,
+            results,
+        }],
+    }
+}
+
+/// Serialize `report` as SARIF log, with paths relative to `workspace`.
+pub(crate) fn sarif(report: &Report, workspace: &Path) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&log(report, workspace))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::template::FileReport;
+    use crate::{chyrp_up, ContentOrigin, Detector, Documentation, LineColumn, Span};
+    use crate::{Suggestion, Tally};
+
+    #[test]
+    fn sarif_log() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some wrod."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let suggestions = vec![Suggestion {
+            detector: Detector::Hunspell,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 14,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 17,
+                },
+            },
+            range: 5..9,
+            replacements: vec!["word".to_owned(), "wood".to_owned()],
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        }];
+        let tally = Tally::from_file(&suggestions);
+        let report = Report::new(vec![FileReport::new(&origin, &suggestions)], &tally);
+
+        let value = serde_json::from_str::<serde_json::Value>(
+            &sarif(&report, Path::new("/tmp/test")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(
+            run["originalUriBaseIds"]["%SRCROOT%"]["uri"],
+            "file:///tmp/test/"
+        );
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "Hunspell");
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "Hunspell");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["message"]["text"], "`wrod`");
+        let region = serde_json::json!({
+            "startLine": 1,
+            "startColumn": 15,
+            "endLine": 1,
+            "endColumn": 19,
+        });
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "entity.rs");
+        assert_eq!(location["region"], region);
+        assert_eq!(result["fixes"].as_array().unwrap().len(), 2);
+        let replacement = &result["fixes"][1]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"], region);
+        assert_eq!(replacement["insertedContent"]["text"], "wood");
+    }
+}
@@ -0,0 +1,214 @@
+//! User provided output templates.
+//!
+//! With `--format template --template <path>` the findings of `check` are
+//! rendered with a handlebars template instead of the built-in human readable
+//! format. The template receives a single [`Report`], see
+//! `docs/automation.md` for the available fields.
+
+use crate::documentation::CommentVariantCategory;
+use crate::errors::*;
+use crate::util::sub_chars;
+use crate::{ContentOrigin, Severity, Suggestion, Tally};
+
+use fs_err as fs;
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::path::Path;
+
+const TEMPLATE_NAME: &str = "report";
+
+/// Number of findings per severity.
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct Counts {
+    pub(super) error: usize,
+    pub(super) warning: usize,
+    pub(super) note: usize,
+    pub(super) total: usize,
+    /// Empty or bare URL documentation, included in the other counts.
+    pub(super) placeholder_docs: usize,
+}
+
+impl From<&Tally> for Counts {
+    fn from(tally: &Tally) -> Self {
+        Self {
+            error: tally.count(Severity::Error),
+            warning: tally.count(Severity::Warning),
+            note: tally.count(Severity::Note),
+            total: tally.total(),
+            placeholder_docs: tally.placeholder_docs(),
+        }
+    }
+}
+
+/// A single finding.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Finding {
+    pub(super) detector: String,
+    /// The other checkers which flagged the same span.
+    pub(super) also: Vec<String>,
+    pub(super) severity: &'static str,
+    /// The kind of content, `doc` or `dev` comments, `markdown` or `other`.
+    pub(super) comment: &'static str,
+    /// The flagged content.
+    pub(super) text: String,
+    pub(super) line: usize,
+    pub(super) column: usize,
+    pub(super) end_line: usize,
+    pub(super) end_column: usize,
+    pub(super) description: Option<String>,
+    pub(super) replacements: Vec<String>,
+    pub(super) rule: Option<String>,
+    pub(super) help: Option<String>,
+}
+
+impl From<&Suggestion<'_>> for Finding {
+    fn from(suggestion: &Suggestion<'_>) -> Self {
+        Self {
+            detector: suggestion.detector.to_string(),
+            also: suggestion.also.iter().map(ToString::to_string).collect(),
+            severity: suggestion.severity().as_str(),
+            comment: match suggestion.chunk.variant().category() {
+                CommentVariantCategory::Doc => "doc",
+                CommentVariantCategory::Dev => "dev",
+                CommentVariantCategory::CommonMark => "markdown",
+                CommentVariantCategory::Unmergable => "other",
+            },
+            text: sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+            line: suggestion.span.start.line,
+            // columns are presented 1-based
+            column: suggestion.span.start.column + 1,
+            end_line: suggestion.span.end.line,
+            end_column: suggestion.span.end.column + 1,
+            description: suggestion.description.clone(),
+            replacements: suggestion.replacements.clone(),
+            rule: suggestion.rule.clone(),
+            help: suggestion.help.clone(),
+        }
+    }
+}
+
+/// The findings of one file.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FileReport {
+    pub(super) path: String,
+    /// The package of the file, only set for workspaces.
+    pub(super) package: Option<String>,
+    pub(super) counts: Counts,
+    pub(super) findings: Vec<Finding>,
+}
+
+impl FileReport {
+    pub(crate) fn new(origin: &ContentOrigin, suggestions: &[Suggestion<'_>]) -> Self {
+        Self {
+            path: origin.as_path().display().to_string(),
+            package: None,
+            counts: Counts::from(&Tally::from_file(suggestions)),
+            findings: suggestions.iter().map(Finding::from).collect(),
+        }
+    }
+
+    pub(crate) fn with_package(self, package: Option<String>) -> Self {
+        Self { package, ..self }
+    }
+}
+
+/// Everything passed to the template.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Report {
+    /// Only files with findings.
+    pub(super) files: Vec<FileReport>,
+    pub(super) counts: Counts,
+    /// Number of files with findings.
+    pub(super) file_count: usize,
+}
+
+impl Report {
+    pub(crate) fn new(files: Vec<FileReport>, tally: &Tally) -> Self {
+        Self {
+            files,
+            counts: Counts::from(tally),
+            file_count: tally.files(),
+        }
+    }
+}
+
+/// Renders a [`Report`] with a user provided template.
+pub(crate) struct Renderer {
+    registry: Handlebars<'static>,
+}
+
+impl Renderer {
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let template = fs::read_to_string(path)?;
+        Self::from_str(&template)
+            .wrap_err_with(|| eyre!("Invalid output template {}", path.display()))
+    }
+
+    fn from_str(template: &str) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(true);
+        // reports are plain text, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+        registry.register_template_string(TEMPLATE_NAME, template)?;
+        Ok(Self { registry })
+    }
+
+    pub(crate) fn render(&self, report: &Report) -> Result<String> {
+        Ok(self.registry.render(TEMPLATE_NAME, report)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, Detector, Documentation, LineColumn, Span};
+
+    #[test]
+    fn render_report() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some wrod."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let suggestion = Suggestion {
+            detector: Detector::Hunspell,
+            origin: origin.clone(),
+            chunk,
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 14,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 17,
+                },
+            },
+            range: 5..9,
+            replacements: vec!["word".to_owned(), "wood".to_owned()],
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        };
+        let suggestions = vec![suggestion];
+        let tally = Tally::from_file(&suggestions);
+        let report = Report::new(vec![FileReport::new(&origin, &suggestions)], &tally);
+
+        let renderer = Renderer::from_str(
+            "{{#each files}}{{#each findings}}{{../path}}:{{line}}:{{column}} {{severity}} {{comment}} `{{text}}`{{#if replacements}} -> {{replacements.[0]}}{{/if}}\n{{/each}}{{/each}}{{counts.total}} in {{file_count}}",
+        )
+        .unwrap();
+        assert_eq!(
+            renderer.render(&report).unwrap(),
+            "/tmp/test/entity.rs:1:15 error doc `wrod` -> word\n1 in 1"
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let report = Report::new(Vec::new(), &Tally::default());
+        let renderer = Renderer::from_str("{{totals}}").unwrap();
+        assert!(renderer.render(&report).is_err());
+    }
+}
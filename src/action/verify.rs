@@ -0,0 +1,72 @@
+//! Verify a fixed file before it replaces the original.
+//!
+//! The patches are applied blindly, a replacement which ends a comment early
+//! or a miscalculated span break the file. The patched content is parsed again,
+//! rust sources with `syn` and TOML files, i.e. `Cargo.toml`, with `toml`. A
+//! file which already failed to parse before is not verified.
+
+use crate::errors::*;
+
+use std::path::Path;
+
+/// A parser of the files with a certain extension.
+type Parser = fn(&str) -> std::result::Result<(), String>;
+
+fn rust(content: &str) -> std::result::Result<(), String> {
+    syn::parse_file(content)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn toml(content: &str) -> std::result::Result<(), String> {
+    content
+        .parse::<toml::Value>()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// The parser of the file at `path`, none if its format is not verified.
+fn parser(path: &Path) -> Option<Parser> {
+    match path.extension()?.to_str()? {
+        "rs" => Some(rust),
+        "toml" => Some(toml),
+        _ => None,
+    }
+}
+
+/// Verify the `patched` content of the file at `path` still parses, if the
+/// `original` one did.
+pub(crate) fn verify(path: &Path, original: &str, patched: &str) -> Result<()> {
+    let parse = match parser(path) {
+        Some(parse) => parse,
+        None => return Ok(()),
+    };
+    if let Err(e) = parse(original) {
+        log::debug!(
+            "Not verifying {}, it did not parse before: {}",
+            path.display(),
+            e
+        );
+        return Ok(());
+    }
+    parse(patched).map_err(|e| eyre!("The fixed {} does not parse anymore: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_fixes_are_detected() {
+        let original = "/// A wrod.\nstruct A;\n";
+        let lib = Path::new("src/lib.rs");
+        assert!(verify(lib, original, "/// A word.\nstruct A;\n").is_ok());
+        assert!(verify(lib, original, "/// A word */\nstruct A;\n}").is_err());
+        // broken before already
+        assert!(verify(lib, "struct {", "struct {{").is_ok());
+        let manifest = Path::new("Cargo.toml");
+        assert!(verify(manifest, "# A wrod\n[package]\n", "# A word\n[package]\n").is_ok());
+        assert!(verify(manifest, "# A wrod\n[package]\n", "# A\nword\n[package]\n").is_err());
+        assert!(verify(Path::new("README.md"), "A wrod.", "[").is_ok());
+    }
+}
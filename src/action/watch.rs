@@ -0,0 +1,151 @@
+//! Recheck files whenever they change, see `--watch`.
+//!
+//! The checked files are polled for changes of their modification time, which
+//! works the same on every platform and file system, network mounts and
+//! containers included. A changed file is extracted again, its chunks replace
+//! the previous ones in the documentation index and only it is checked again,
+//! with the checkers set up once. The findings of removed files are dropped,
+//! until they reappear, new files are only picked up by a restart. Once the
+//! config or a dictionary changed, i.e. by `learn`, the checkers are reloaded
+//! and all files are checked again.
+
+use crate::checker::{Checker, CheckerSet, Checkers};
+use crate::errors::*;
+use crate::{Config, ContentOrigin, Documentation, Suggestion, Tally};
+
+use rayon::iter::ParallelIterator;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the files are polled.
+const INTERVAL: Duration = Duration::from_millis(500);
+
+/// The modification time of the file at `path`, none if it is gone.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs_err::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// The modification times of the files of `documents`.
+fn modification_times(documents: &Documentation) -> HashMap<PathBuf, Option<SystemTime>> {
+    documents
+        .iter()
+        .filter_map(|(origin, _chunks)| origin.file())
+        .map(|path| (path.to_owned(), modified(path)))
+        .collect()
+}
+
+/// Check `documents` and print the findings, the tally of each file is
+/// recorded in `tallies`.
+fn check(
+    checkers: &Checkers,
+    documents: &Documentation,
+    tallies: &mut HashMap<PathBuf, Tally>,
+) -> Result<()> {
+    let mut checked = documents
+        .par_iter()
+        .map(|(origin, chunks)| Ok((origin, checkers.check(origin, &chunks[..])?)))
+        .collect::<Result<Vec<(&ContentOrigin, Vec<Suggestion<'_>>)>>>()?;
+    checked.sort_by(|(origin1, _), (origin2, _)| origin1.as_path().cmp(origin2.as_path()));
+    for (origin, suggestions) in checked {
+        let file_tally = Tally::from_file(&suggestions);
+        let tally = tallies.entry(origin.as_path().to_owned()).or_default();
+        *tally = *tally + file_tally;
+        if file_tally.total() == 0 {
+            continue;
+        }
+        println!("{}", file_tally.package_header(origin, None));
+        for suggestion in suggestions {
+            println!("{}", suggestion);
+        }
+    }
+    Ok(())
+}
+
+/// Print the tally of all files.
+fn summary(tallies: &HashMap<PathBuf, Tally>) {
+    let tally = tallies
+        .values()
+        .fold(Tally::default(), |acc, tally| acc + *tally);
+    if tally.total() > 0 {
+        print!("{}", tally);
+    } else {
+        println!("✅ No findings");
+    }
+}
+
+/// Check `documents`, then recheck each file whenever it changed, extracting
+/// it again with `extract`, until interrupted. The checkers are created with
+/// the config from `config_path` again by `reload` once it or one of the
+/// dictionaries changed.
+pub(crate) fn watch(
+    mut documents: Documentation,
+    config: Config,
+    config_path: Option<PathBuf>,
+    reload: impl Fn() -> Result<Config>,
+    extract: impl Fn(&Path) -> Result<Documentation>,
+) -> Result<()> {
+    let mut checkers = CheckerSet::create(config, config_path, None)?;
+    let mut tallies = HashMap::new();
+    check(checkers.checkers(), &documents, &mut tallies)?;
+    summary(&tallies);
+    let mut times = modification_times(&documents);
+    log::info!("Watching {} files for changes", times.len());
+    loop {
+        std::thread::sleep(INTERVAL);
+        match checkers.reload_with(&reload) {
+            Ok(true) => {
+                println!("🔄 Reloaded the configuration and dictionaries");
+                tallies.clear();
+                check(checkers.checkers(), &documents, &mut tallies)?;
+                summary(&tallies);
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to reload the checkers: {:?}", e),
+        }
+        let changed = times
+            .iter_mut()
+            .filter_map(|(path, time)| {
+                let current = modified(path);
+                (current != *time).then(|| {
+                    *time = current;
+                    path.clone()
+                })
+            })
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            continue;
+        }
+        let mut fresh = Documentation::new();
+        for path in &changed {
+            documents.remove_file(path);
+            tallies.remove(path);
+            if !path.is_file() {
+                log::info!("{} was removed", path.display());
+                continue;
+            }
+            match extract(path) {
+                Ok(mut extracted) => {
+                    // a manifest pulls in its readme as well, which is
+                    // watched on its own
+                    extracted.retain(|origin, _chunk| origin.as_path() == path);
+                    fresh.extend(extracted);
+                }
+                Err(e) => log::warn!("Failed to extract {}: {:?}", path.display(), e),
+            }
+            // a file without any chunks is clean
+            tallies.insert(path.clone(), Tally::default());
+        }
+        println!(
+            "🔄 {}",
+            changed
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        check(checkers.checkers(), &fresh, &mut tallies)?;
+        documents.extend(fresh);
+        summary(&tallies);
+    }
+}
@@ -58,110 +58,106 @@ where
         })
     }
 
+    /// Fetch the cached value, or create and store it.
+    ///
+    /// Other instances may access the same cache file concurrently, so the
+    /// value is re-fetched once the exclusive lock is held, and only the first
+    /// instance creates it.
     pub fn fetch_or_update(
         &mut self,
         create: impl FnOnce(&str) -> Result<T>,
     ) -> Result<CachedValue<T>> {
         let total_start = Instant::now();
-        match self.fetch() {
-            Ok(Some(value)) => {
+        {
+            let guard = self.cache_file.read()?;
+            if let Some(value) = fetch(&self.what, &guard) {
                 let elapsed = total_start.elapsed();
-                Ok(CachedValue {
+                return Ok(CachedValue {
                     value,
-                    fetch: Some(elapsed.clone()),
+                    fetch: Some(elapsed),
                     update: None,
                     creation: None,
                     total: elapsed,
-                })
+                });
             }
-            Ok(None) => {
-                let fetch = Some(total_start.elapsed());
-
-                let creation_start = Instant::now();
-                let value = create(self.what.as_str())?;
-                let creation = Some(creation_start.elapsed());
-
-                let update_start = Instant::now();
-                if let Err(err) = self.update(&value) {
-                    log::warn!("Failed to write value to cached: {:?}", err);
-                }
-                let update = Some(update_start.elapsed());
-                let total = total_start.elapsed();
-                Ok(CachedValue {
-                    value,
-                    fetch,
-                    update,
-                    creation,
-                    total,
-                })
-            }
-            Err(err) => {
-                log::warn!("Overriding existing value that failed to load: {:?}", err);
+        }
 
-                let fetch = Some(total_start.elapsed());
+        let mut guard = self.cache_file.write()?;
+        if let Some(value) = fetch(&self.what, &guard) {
+            let elapsed = total_start.elapsed();
+            return Ok(CachedValue {
+                value,
+                fetch: Some(elapsed),
+                update: None,
+                creation: None,
+                total: elapsed,
+            });
+        }
+        let fetch = Some(total_start.elapsed());
 
-                let creation_start = Instant::now();
-                let value = create(self.what.as_str())?;
-                let creation = Some(creation_start.elapsed());
+        let creation_start = Instant::now();
+        let value = create(self.what.as_str())?;
+        let creation = Some(creation_start.elapsed());
 
-                let update_start = Instant::now();
-                if let Err(err) = self.update(&value) {
-                    log::warn!("Failed to update cached: {:?}", err);
-                }
-                let update = Some(update_start.elapsed());
-                let total = total_start.elapsed();
-                Ok(CachedValue {
-                    value,
-                    fetch,
-                    update,
-                    creation,
-                    total,
-                })
-            }
+        let update_start = Instant::now();
+        if let Err(err) = update(&self.what, &mut guard, &value) {
+            log::warn!("Failed to write value to cached: {:?}", err);
         }
+        let update = Some(update_start.elapsed());
+        let total = total_start.elapsed();
+        Ok(CachedValue {
+            value,
+            fetch,
+            update,
+            creation,
+            total,
+        })
     }
-    pub fn fetch(&mut self) -> Result<Option<T>> {
-        let guard = self.cache_file.read()?;
-        let buf = std::io::BufReader::new(guard.file());
-        // let buf = xz2::bufread::XzDecoder::new(buf);
-        match bincode::deserialize_from(buf) {
-            Ok(CacheEntry { what, val }) => {
-                if &what == &self.what {
-                    log::debug!("Cached value with matching what \"{}\"", &what);
-                    Ok(Some(val))
-                } else {
-                    log::warn!(
-                        "Cached value what \"{}\" does not match expect what \"{}\", removing",
-                        &what,
-                        &self.what
-                    );
-                    Ok(None)
-                }
-            }
-            Err(e) => {
-                log::warn!("Failed to load cached value: {:?}", e);
-                Ok(None)
+}
+
+/// Load the value stored in `file`, if it was stored for `what`.
+fn fetch<T: DeserializeOwned>(what: &str, mut file: &fs_err::File) -> Option<T> {
+    if let Err(err) = file.rewind() {
+        log::warn!("Failed to rewind cache file: {:?}", err);
+        return None;
+    }
+    let buf = std::io::BufReader::new(file);
+    // let buf = xz2::bufread::XzDecoder::new(buf);
+    match bincode::deserialize_from(buf) {
+        Ok(CacheEntry { what: stored, val }) => {
+            if stored == what {
+                log::debug!("Cached value with matching what \"{}\"", &stored);
+                Some(val)
+            } else {
+                log::warn!(
+                    "Cached value what \"{}\" does not match expect what \"{}\", removing",
+                    &stored,
+                    what
+                );
+                None
             }
         }
+        Err(e) => {
+            log::warn!("Failed to load cached value: {:?}", e);
+            None
+        }
     }
+}
 
-    pub fn update(&mut self, val: &T) -> Result<()> {
-        let mut write_guard = self.cache_file.write()?;
-
-        let entry = CacheEntry {
-            what: self.what.clone(),
-            val,
-        };
-        let encoded: Vec<u8> = bincode::serialize(&entry).unwrap();
-        let mut encoded = &encoded[..];
-        // let mut compressed = xz2::bufread::XzEncoder::new(&mut encoded, 6);
+/// Store `val` for `what` in `file`, the caller must hold the write lock.
+fn update<T: Serialize>(what: &str, file: &mut fs_err::File, val: &T) -> Result<()> {
+    let entry = CacheEntry {
+        what: what.to_owned(),
+        val,
+    };
+    let encoded: Vec<u8> = bincode::serialize(&entry)?;
+    let mut encoded = &encoded[..];
+    // let mut compressed = xz2::bufread::XzEncoder::new(&mut encoded, 6);
 
-        // effectively truncate, but without losing the lock
-        let file = write_guard.file_mut();
-        file.rewind()?;
-        std::io::copy(&mut encoded, file)?;
-        let loco = file.stream_position()?;
-        file.set_len(loco)?;
-        Ok(())
-    }
+    // effectively truncate, but without losing the lock
+    file.rewind()?;
+    std::io::copy(&mut encoded, file)?;
+    let loco = file.stream_position()?;
+    file.set_len(loco)?;
+    Ok(())
 }
@@ -0,0 +1,279 @@
+//! Clustering of overlapping grammar findings.
+//!
+//! Grammar backends commonly report several matches for the same words of a
+//! sentence. Overlapping spans can not be fixed one after another, so the
+//! matches are combined into one finding, which lists each of them in its
+//! description.
+//!
+//! Different checkers flagging the very same span, i.e. hunspell and a grammar
+//! backend the same misspelled word, are merged as well, into the finding of
+//! the checker which ran first, with the replacements of all of them.
+
+use crate::util::sub_chars;
+use crate::{CheckableChunk, Span, Suggestion};
+
+use std::fmt::Write as _;
+
+/// Whether `suggestion` overlaps any of `group`. Only findings on a single line
+/// are combined, so the replacements can be extended with the chunk content.
+fn overlaps(group: &[Suggestion<'_>], suggestion: &Suggestion<'_>) -> bool {
+    let single_line = |suggestion: &Suggestion<'_>| {
+        suggestion.span.start.line == suggestion.span.end.line
+            && !sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()).contains('\n')
+    };
+    single_line(suggestion)
+        && group.iter().all(|member| {
+            std::ptr::eq(member.chunk, suggestion.chunk)
+                && single_line(member)
+                && member.span.start.line == suggestion.span.start.line
+        })
+        && group
+            .iter()
+            .any(|member| member.range.end > suggestion.range.start)
+}
+
+/// Combine one group of overlapping suggestions of the same chunk.
+fn merge(mut group: Vec<Suggestion<'_>>) -> Suggestion<'_> {
+    if group.len() == 1 {
+        return group.remove(0);
+    }
+    let (range, span) = group.iter().skip(1).fold(
+        (group[0].range.clone(), group[0].span),
+        |(range, span), member| {
+            (
+                range.start.min(member.range.start)..range.end.max(member.range.end),
+                Span {
+                    start: span.start.min(member.span.start),
+                    end: span.end.max(member.span.end),
+                },
+            )
+        },
+    );
+
+    // the replacements of each match, extended to the combined range
+    let text = group[0].chunk.as_str();
+    let mut replacements = Vec::<String>::new();
+    for member in &group {
+        let before = sub_chars(text, range.start..member.range.start);
+        let after = sub_chars(text, member.range.end..range.end);
+        for replacement in &member.replacements {
+            let replacement = format!("{}{}{}", before, replacement, after);
+            if !replacements.contains(&replacement) {
+                replacements.push(replacement);
+            }
+        }
+    }
+
+    let mut description = format!("{} overlapping findings:", group.len());
+    for member in &group {
+        let _ = write!(
+            description,
+            "\n- {}",
+            member.description.as_deref().unwrap_or_default()
+        );
+        if let Some(ref rule) = member.rule {
+            let _ = write!(description, " ({})", rule);
+        }
+    }
+
+    let first = group.remove(0);
+    Suggestion {
+        span,
+        range,
+        replacements,
+        description: Some(description),
+        ..first
+    }
+}
+
+/// Combine the overlapping suggestions of one checker into one per group.
+pub(crate) fn cluster(mut suggestions: Vec<Suggestion<'_>>) -> Vec<Suggestion<'_>> {
+    suggestions.sort_by_key(|suggestion| {
+        (
+            suggestion.chunk as *const CheckableChunk as usize,
+            suggestion.range.start,
+        )
+    });
+    let mut groups = Vec::<Vec<Suggestion<'_>>>::with_capacity(suggestions.len());
+    for suggestion in suggestions {
+        match groups.last_mut() {
+            Some(group) if overlaps(group, &suggestion) => group.push(suggestion),
+            _ => groups.push(vec![suggestion]),
+        }
+    }
+    groups.into_iter().map(merge).collect()
+}
+
+/// Merge the findings of different checkers for the same span of the same
+/// file, keeping the order of the first of each. The merged finding keeps the
+/// rule and help of the first and the most severe of the severities, so this
+/// must only run after the rules were filtered and the severities resolved.
+pub(crate) fn merge_same_span(suggestions: Vec<Suggestion<'_>>) -> Vec<Suggestion<'_>> {
+    let mut merged = indexmap::IndexMap::<_, Vec<Suggestion<'_>>>::with_capacity(suggestions.len());
+    for suggestion in suggestions {
+        let key = (suggestion.origin.clone(), suggestion.span);
+        let findings = merged.entry(key).or_default();
+        match findings.iter_mut().find(|finding| {
+            !finding.also.contains(&suggestion.detector) && finding.detector != suggestion.detector
+        }) {
+            Some(finding) => {
+                // the most severe of the merged findings is kept
+                let severity = suggestion.severity();
+                if severity < finding.severity() {
+                    finding.severity = Some(severity);
+                }
+                finding.also.push(suggestion.detector);
+                for replacement in suggestion.replacements {
+                    if !finding.replacements.contains(&replacement) {
+                        finding.replacements.push(replacement);
+                    }
+                }
+                finding.description = finding.description.take().or(suggestion.description);
+            }
+            None => findings.push(suggestion),
+        }
+    }
+    merged
+        .into_iter()
+        .flat_map(|(_, findings)| findings)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, ContentOrigin, Detector, Documentation, LineColumn, Severity};
+
+    fn finding<'s>(
+        chunk: &'s CheckableChunk,
+        range: crate::Range,
+        replacement: &str,
+        rule: &str,
+    ) -> Suggestion<'s> {
+        let column = |offset: usize| LineColumn {
+            line: 1,
+            column: 9 + offset,
+        };
+        Suggestion {
+            detector: Detector::NlpRules,
+            origin: ContentOrigin::TestEntityRust,
+            chunk,
+            span: Span {
+                start: column(range.start),
+                end: column(range.end - 1),
+            },
+            range,
+            replacements: vec![replacement.to_owned()],
+            description: Some(format!("Violates {}.", rule)),
+            rule: Some(rule.to_owned()),
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn overlapping_matches_are_combined() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            chyrp_up!("Their is a error. Fine."),
+            true,
+            false,
+        );
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let clustered = cluster(vec![
+            finding(chunk, 9..16, "an error", "A_AN"),
+            finding(chunk, 0..8, "There is", "THEIR_IS"),
+            finding(chunk, 6..10, "is an", "IS_A"),
+            finding(chunk, 18..22, "Good", "FINE"),
+        ]);
+        assert_eq!(clustered.len(), 2);
+
+        let combined = &clustered[0];
+        assert_eq!(combined.range, 0..16);
+        assert_eq!(combined.span.start.column, 9);
+        assert_eq!(combined.span.end.column, 24);
+        assert_eq!(combined.rule.as_deref(), Some("THEIR_IS"));
+        assert_eq!(
+            combined.replacements,
+            vec!["There is a error", "Their is an error"]
+        );
+        assert_eq!(
+            combined.description.as_deref(),
+            Some("3 overlapping findings:\n- Violates THEIR_IS. (THEIR_IS)\n- Violates IS_A. (IS_A)\n- Violates A_AN. (A_AN)")
+        );
+        assert_eq!(clustered[1].range, 18..22);
+        assert_eq!(clustered[1].replacements, vec!["Good"]);
+    }
+
+    #[test]
+    fn same_span_of_different_checkers_is_merged() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("A wrod here."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let spelling = Suggestion {
+            detector: Detector::Hunspell,
+            rule: None,
+            description: None,
+            replacements: vec!["word".to_owned(), "wood".to_owned()],
+            ..finding(chunk, 2..6, "word", "MORFOLOGIK")
+        };
+        let merged = merge_same_span(vec![
+            spelling,
+            finding(chunk, 2..6, "world", "MORFOLOGIK"),
+            finding(chunk, 2..6, "word", "MORFOLOGIK"),
+            finding(chunk, 7..11, "there", "HERE"),
+        ]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].detector, Detector::Hunspell);
+        assert_eq!(merged[0].also, vec![Detector::NlpRules]);
+        assert_eq!(merged[0].replacements, vec!["word", "wood", "world"]);
+        // the rule of another checker does not apply to the spelling finding
+        assert_eq!(merged[0].rule, None);
+        assert_eq!(
+            merged[0].description.as_deref(),
+            Some("Violates MORFOLOGIK.")
+        );
+        // the same checker twice is not merged
+        assert!(merged[1].also.is_empty());
+        assert_eq!(merged[1].replacements, vec!["word"]);
+        assert_eq!(merged[2].range, 7..11);
+    }
+
+    #[test]
+    fn merged_finding_keeps_the_most_severe() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("A wrod here."), true, false);
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let spelling = |severity| Suggestion {
+            detector: Detector::Hunspell,
+            severity,
+            ..finding(chunk, 2..6, "word", "MORFOLOGIK")
+        };
+        let grammar = |severity| Suggestion {
+            severity,
+            ..finding(chunk, 2..6, "word", "MORFOLOGIK")
+        };
+        let merged = |first, second| {
+            let merged = merge_same_span(vec![first, second]);
+            assert_eq!(merged.len(), 1);
+            merged[0].severity()
+        };
+        // the first detector does not win by order alone
+        assert_eq!(
+            merged(spelling(Some(Severity::Note)), grammar(None)),
+            Severity::Warning
+        );
+        assert_eq!(merged(grammar(None), spelling(None)), Severity::Error);
+        assert_eq!(
+            merged(
+                grammar(Some(Severity::Error)),
+                spelling(Some(Severity::Note))
+            ),
+            Severity::Error
+        );
+    }
+}
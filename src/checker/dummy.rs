@@ -60,6 +60,10 @@ impl Checker for DummyChecker {
                     replacements,
                     chunk,
                     description: None,
+                    rule: None,
+                    help: None,
+                    severity: None,
+                    also: Vec::new(),
                 };
                 acc.push(suggestion);
             }
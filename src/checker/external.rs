@@ -0,0 +1,268 @@
+//! External subprocess checker
+//!
+//! Spawns a user provided command per file, writes all chunks of the file as
+//! JSON to its `stdin`, and parses the findings from its `stdout`. Allows to
+//! plug proprietary checkers, i.e. for company terminology, without forking.
+
+use super::{Checker, Detector, Suggestion};
+
+use crate::config::ExternalCheckerConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
+use crate::errors::*;
+use crate::Range;
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Request sent to the external checker, one per file.
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    /// The path of the file the chunks were extracted from.
+    origin: String,
+    chunks: Vec<RequestChunk<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestChunk<'a> {
+    /// Index of the chunk, to be referenced by findings.
+    index: usize,
+    /// Plain text content, with markdown removed.
+    content: &'a str,
+}
+
+/// Single finding as reported by the external checker.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Finding {
+    /// Index of the chunk the finding relates to.
+    chunk: usize,
+    /// Character offset of the first character, relative to the chunk
+    /// content.
+    start: usize,
+    /// Character offset past the last character.
+    end: usize,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    replacements: Vec<String>,
+    /// Identifier of the rule which triggered.
+    #[serde(default)]
+    rule: Option<String>,
+    /// Link to documentation about the rule.
+    #[serde(default)]
+    help: Option<String>,
+}
+
+pub struct ExternalChecker {
+    name: String,
+    command: PathBuf,
+    args: Vec<String>,
+    cheap: bool,
+    /// The working directory of the command.
+    dir: PathBuf,
+}
+
+impl ExternalChecker {
+    /// The command runs in `base_dir`.
+    pub fn new(config: &<Self as Checker>::Config, base_dir: &Path) -> Self {
+        Self {
+            name: config.name(),
+            command: config.command.clone(),
+            args: config.args.clone(),
+            cheap: config.cheap,
+            dir: base_dir.to_owned(),
+        }
+    }
+
+    /// Whether the checker runs in the first pass.
+    pub(crate) fn is_cheap(&self) -> bool {
+        self.cheap
+    }
+
+    /// Run the command to completion and return its `stdout`.
+    fn execute(&self, input: Vec<u8>) -> Result<Vec<u8>> {
+        let mut child = Command::new(&self.command)
+            .current_dir(&self.dir)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .wrap_err_with(|| {
+                eyre!(
+                    "Failed to spawn external checker {} ({})",
+                    self.name,
+                    self.command.display()
+                )
+            })?;
+
+        // write from a separate thread, the checker might start writing to
+        // `stdout` before consuming all of `stdin`
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| eyre!("Failed to open stdin of external checker {}", self.name))?;
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+        let output = child.wait_with_output()?;
+        if let Ok(Err(e)) = writer.join() {
+            log::debug!("External checker {} closed stdin early: {}", self.name, e);
+        }
+        if !output.status.success() {
+            bail!(
+                "External checker {} terminated with {}",
+                self.name,
+                output.status
+            );
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl Checker for ExternalChecker {
+    type Config = ExternalCheckerConfig;
+
+    fn detector() -> Detector {
+        Detector::External
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let plains = chunks
+            .iter()
+            .map(CheckableChunk::erase_cmark)
+            .collect::<Vec<PlainOverlay>>();
+
+        let request = Request {
+            origin: origin.as_path().display().to_string(),
+            chunks: plains
+                .iter()
+                .enumerate()
+                .map(|(index, plain)| RequestChunk {
+                    index,
+                    content: plain.as_str(),
+                })
+                .collect(),
+        };
+        let input = serde_json::to_vec(&request)?;
+        let output = self.execute(input)?;
+        let findings: Vec<Finding> = serde_json::from_slice(&output).wrap_err_with(|| {
+            eyre!("External checker {} produced malformed findings", self.name)
+        })?;
+
+        let mut acc = Vec::with_capacity(findings.len());
+        for finding in findings {
+            let (chunk, plain) = chunks
+                .get(finding.chunk)
+                .zip(plains.get(finding.chunk))
+                .ok_or_else(|| {
+                    eyre!(
+                        "External checker {} referenced non-existent chunk {}",
+                        self.name,
+                        finding.chunk
+                    )
+                })?;
+            let range: Range = finding.start..finding.end;
+            if range.is_empty() || range.end > plain.as_str().chars().count() {
+                log::warn!(
+                    "External checker {} reported an invalid range {:?}, ignoring",
+                    self.name,
+                    range
+                );
+                continue;
+            }
+            let description = finding
+                .message
+                .map(|message| format!("{}: {}", self.name, message));
+            for (range, span) in plain.find_spans(range) {
+                acc.push(Suggestion {
+                    detector: Detector::External,
+                    origin: origin.clone(),
+                    chunk,
+                    span,
+                    range,
+                    replacements: finding.replacements.clone(),
+                    description: description.clone(),
+                    rule: finding.rule.clone(),
+                    help: finding.help.clone(),
+                    severity: None,
+                    also: Vec::new(),
+                });
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+    use crate::{Documentation, LineColumn, Span};
+
+    fn checker(script: &str) -> ExternalChecker {
+        ExternalChecker::new(
+            &ExternalCheckerConfig {
+                name: Some("Terms".to_owned()),
+                command: PathBuf::from("sh"),
+                args: vec!["-c".to_owned(), script.to_owned()],
+                cheap: false,
+            },
+            &std::env::temp_dir(),
+        )
+    }
+
+    #[test]
+    fn findings_are_mapped_back() {
+        const SOURCE: &str = fluff_up!(["Use the *frobnicator* now."]);
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+
+        let checker = checker(
+            r#"cat > /dev/null; echo '[{"chunk": 0, "start": 8, "end": 19, "message": "Banned term", "replacements": ["widget"], "rule": "T001", "help": "https://example.com/T001"}]'"#,
+        );
+        let suggestions = checker.check(&origin, chunks).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.replacements, vec!["widget".to_owned()]);
+        assert_eq!(
+            suggestion.description.as_deref(),
+            Some("Terms: Banned term")
+        );
+        assert_eq!(suggestion.rule.as_deref(), Some("T001"));
+        assert_eq!(suggestion.help.as_deref(), Some("https://example.com/T001"));
+        assert_eq!(
+            suggestion.span,
+            Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 13
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 23
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn failing_command_is_an_error() {
+        const SOURCE: &str = fluff_up!(["Whatever."]);
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+
+        assert!(checker("exit 3").check(&origin, chunks).is_err());
+        assert!(checker("echo garbage").check(&origin, chunks).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Run filters given with `--only` and `--skip`.
+//!
+//! Each entry names either a checker, i.e. `hunspell` or the name of an
+//! external checker, or a rule. Rules match by their full id or by their class,
+//! the part before the `/`, so `STYLE` covers `STYLE/1`. Neither case nor `-`
+//! and `_` are distinguished, `dup-word` matches `DUP_WORD`.
+//!
+//! The `checkers` list of the configuration narrows the checkers down before,
+//! a checker it does not name never runs, whatever `--only` says.
+
+use crate::errors::*;
+use crate::{Detector, Suggestion};
+
+fn normalize(s: &str) -> String {
+    s.to_lowercase().replace('_', "-")
+}
+
+fn matches_rule(entry: &str, rule: Option<&str>) -> bool {
+    rule.map_or(false, |rule| {
+        let rule = normalize(rule);
+        rule == entry || rule.split('/').next() == Some(entry)
+    })
+}
+
+/// Checkers without rule ids can not contribute to a rule only run.
+fn emits_rules(detector: Detector) -> bool {
+    matches!(
+        detector,
+        Detector::NlpRules | Detector::LanguageTool | Detector::External | Detector::EmptyDocs
+    )
+}
+
+/// The `--only` and `--skip` entries, as given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RunFilter {
+    only: Vec<String>,
+    skip: Vec<String>,
+}
+
+impl RunFilter {
+    pub(crate) fn new(only: &[String], skip: &[String]) -> Self {
+        Self {
+            only: only.iter().map(|entry| normalize(entry)).collect(),
+            skip: skip.iter().map(|entry| normalize(entry)).collect(),
+        }
+    }
+
+    /// Tell checker entries from rule entries, `checkers` are the names of
+    /// all configured checkers with their detector.
+    pub(crate) fn resolve(&self, checkers: &[(String, Detector)]) -> Selection {
+        let lookup = |entry: &String| {
+            checkers
+                .iter()
+                .find(|(name, _)| normalize(name) == *entry)
+                .map(|(_, detector)| *detector)
+        };
+        let mut selection = Selection::default();
+        for entry in &self.only {
+            match lookup(entry) {
+                Some(detector) => {
+                    selection.only_checkers.push(entry.clone());
+                    selection.explicit.push(detector);
+                }
+                None => selection.only_rules.push(entry.clone()),
+            }
+        }
+        for entry in &self.skip {
+            match lookup(entry) {
+                Some(_) => selection.skip_checkers.push(entry.clone()),
+                None => selection.skip_rules.push(entry.clone()),
+            }
+        }
+        selection
+    }
+}
+
+/// A [`RunFilter`], resolved against the configured checkers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Selection {
+    only_checkers: Vec<String>,
+    only_rules: Vec<String>,
+    skip_checkers: Vec<String>,
+    skip_rules: Vec<String>,
+    /// Detectors of the checkers named in `--only`.
+    explicit: Vec<Detector>,
+    /// The checkers listed in the configuration, all if none.
+    enabled: Option<Vec<String>>,
+}
+
+impl Selection {
+    /// Only run the checkers named by `enabled`, a set, each of which must be
+    /// one of `checkers`. Those whose detector is not `configured` are warned
+    /// about, since they never run.
+    pub(crate) fn with_enabled(
+        mut self,
+        enabled: &[String],
+        checkers: &[(String, Detector)],
+        configured: impl Fn(Detector) -> bool,
+    ) -> Result<Self> {
+        let known = checkers
+            .iter()
+            .map(|(name, _)| normalize(name))
+            .collect::<Vec<_>>();
+        let enabled = enabled
+            .iter()
+            .map(|entry| {
+                let entry = normalize(entry);
+                match known.iter().position(|name| *name == entry) {
+                    Some(idx) => {
+                        if !configured(checkers[idx].1) {
+                            log::warn!(
+                                "Checker `{}` in `checkers` is not configured and does not run",
+                                entry
+                            );
+                        }
+                        Ok(entry)
+                    }
+                    None => Err(eyre!(
+                        "Unknown checker `{}` in `checkers`, expected one of {}, external and \
+                         wasm checkers go by their configured `name`",
+                        entry,
+                        known.join(", ")
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.enabled = Some(enabled);
+        Ok(self)
+    }
+
+    /// Reject the `--only` entries which neither name one of the `checkers`
+    /// nor any of the `rules` the checkers which run know, if those are known
+    /// ahead of time.
+    pub(crate) fn ensure_known_rules(
+        &self,
+        rules: impl FnOnce() -> Option<Vec<String>>,
+        checkers: &[(String, Detector)],
+    ) -> Result<()> {
+        if self.only_rules.is_empty() {
+            return Ok(());
+        }
+        let rules = match rules() {
+            Some(rules) => rules,
+            None => return Ok(()),
+        };
+        match self.only_rules.iter().find(|entry| {
+            !rules
+                .iter()
+                .any(|rule| matches_rule(entry, Some(rule.as_str())))
+        }) {
+            Some(entry) => Err(eyre!(
+                "Unknown checker or rule `{}` in `--only`, expected one of {} or a rule of a \
+                 checker which runs",
+                entry,
+                checkers
+                    .iter()
+                    .map(|(name, _)| normalize(name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the checker, known by any of `names`, is to be run at all.
+    pub(crate) fn runs(&self, detector: Detector, names: &[&str]) -> bool {
+        let names = names.iter().map(|name| normalize(name)).collect::<Vec<_>>();
+        if names.iter().any(|name| self.skip_checkers.contains(name)) {
+            return false;
+        }
+        if let Some(ref enabled) = self.enabled {
+            if !names.iter().any(|name| enabled.contains(name)) {
+                return false;
+            }
+        }
+        if self.only_checkers.is_empty() && self.only_rules.is_empty() {
+            return true;
+        }
+        names.iter().any(|name| self.only_checkers.contains(name))
+            || (!self.only_rules.is_empty() && emits_rules(detector))
+    }
+
+    /// Whether a finding of a checker that ran is reported.
+    pub(crate) fn keeps(&self, suggestion: &Suggestion<'_>) -> bool {
+        let rule = suggestion.rule.as_deref();
+        if self
+            .skip_rules
+            .iter()
+            .any(|entry| matches_rule(entry, rule))
+        {
+            return false;
+        }
+        self.only_rules.is_empty()
+            || self.explicit.contains(&suggestion.detector)
+            || self
+                .only_rules
+                .iter()
+                .any(|entry| matches_rule(entry, rule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckableChunk, CommentVariant, ContentOrigin, LineColumn, Span};
+
+    fn checkers() -> Vec<(String, Detector)> {
+        vec![
+            ("hunspell".to_owned(), Detector::Hunspell),
+            ("nlprules".to_owned(), Detector::NlpRules),
+            ("Terms".to_owned(), Detector::External),
+        ]
+    }
+
+    fn entries(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| (*entry).to_owned()).collect()
+    }
+
+    #[test]
+    fn checker_entries() {
+        let selection =
+            RunFilter::new(&entries(&["hunspell"]), &entries(&["terms"])).resolve(&checkers());
+        assert!(selection.runs(Detector::Hunspell, &["hunspell"]));
+        assert!(!selection.runs(Detector::NlpRules, &["nlprules"]));
+        assert!(!selection.runs(Detector::External, &["external", "Terms"]));
+    }
+
+    #[test]
+    fn enabled_checkers() {
+        let selection = RunFilter::default()
+            .resolve(&checkers())
+            .with_enabled(&entries(&["Hunspell", "terms"]), &checkers(), |_| true)
+            .unwrap();
+        assert!(selection.runs(Detector::Hunspell, &["hunspell"]));
+        assert!(!selection.runs(Detector::NlpRules, &["nlprules"]));
+        assert!(selection.runs(Detector::External, &["external", "Terms"]));
+
+        // `--only` can not bring back a checker the list leaves out
+        let selection = RunFilter::new(&entries(&["nlprules"]), &[])
+            .resolve(&checkers())
+            .with_enabled(&entries(&["hunspell"]), &checkers(), |_| true)
+            .unwrap();
+        assert!(!selection.runs(Detector::NlpRules, &["nlprules"]));
+
+        assert!(RunFilter::default()
+            .resolve(&checkers())
+            .with_enabled(&entries(&["hunspel"]), &checkers(), |_| true)
+            .is_err());
+
+        // a known but unconfigured checker is only warned about
+        let selection = RunFilter::default()
+            .resolve(&checkers())
+            .with_enabled(&entries(&["nlprules"]), &checkers(), |detector| {
+                detector != Detector::NlpRules
+            })
+            .unwrap();
+        assert!(!selection.runs(Detector::Hunspell, &["hunspell"]));
+    }
+
+    #[test]
+    fn unknown_rule_entries() {
+        let rules = || Some(vec!["DUP_WORD/1".to_owned(), "empty-doc".to_owned()]);
+        let selection =
+            RunFilter::new(&entries(&["dup-word", "Empty_Doc", "terms"]), &[]).resolve(&checkers());
+        assert!(selection.ensure_known_rules(rules, &checkers()).is_ok());
+
+        let selection = RunFilter::new(&entries(&["hunspel"]), &[]).resolve(&checkers());
+        let err = selection
+            .ensure_known_rules(rules, &checkers())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Unknown checker or rule `hunspel`"),
+            "{}",
+            err
+        );
+        // the rules of some checkers are only known while checking
+        assert!(selection.ensure_known_rules(|| None, &checkers()).is_ok());
+
+        // the rules are not even collected without rule entries
+        let selection = RunFilter::new(&entries(&["hunspell"]), &[]).resolve(&checkers());
+        assert!(selection
+            .ensure_known_rules(|| unreachable!(), &checkers())
+            .is_ok());
+    }
+
+    #[test]
+    fn rule_entries() {
+        let selection =
+            RunFilter::new(&entries(&["dup-word"]), &entries(&["STYLE"])).resolve(&checkers());
+        assert!(!selection.runs(Detector::Hunspell, &["hunspell"]));
+        assert!(selection.runs(Detector::NlpRules, &["nlprules"]));
+
+        let chunk =
+            CheckableChunk::from_str("x", indexmap::IndexMap::default(), CommentVariant::Unknown);
+        let suggestion = |rule: &str| Suggestion {
+            detector: Detector::NlpRules,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            span: Span {
+                start: LineColumn { line: 1, column: 0 },
+                end: LineColumn { line: 1, column: 0 },
+            },
+            range: 0..1,
+            replacements: Vec::new(),
+            description: None,
+            rule: Some(rule.to_owned()),
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        };
+        assert!(selection.keeps(&suggestion("DUP_WORD/2")));
+        assert!(!selection.keeps(&suggestion("STYLE/1")));
+        assert!(!selection.keeps(&suggestion("GRAMMAR/1")));
+    }
+}
@@ -0,0 +1,416 @@
+//! Cache of the findings of whole files, across runs.
+//!
+//! The findings of the checkers are kept on disk, per file, along with a
+//! digest of its chunks. A file whose chunks did not change since the last run
+//! is not checked again, its findings are taken from the cache. The findings
+//! follow moved files, an unknown file with the same chunks as a cached one,
+//! i.e. after a rename, gets its findings as well. The cache
+//! file is specific to a fingerprint of the configuration and the content of
+//! the dictionaries, so changing either of them starts afresh. The findings
+//! are cached before `--diff-base` and the severity overrides apply, and
+//! before the findings of different checkers for the same span are merged.
+
+use crate::errors::*;
+use crate::{CheckableChunk, Config, ContentOrigin, Detector, LineColumn, Range};
+use crate::{Severity, Span, Suggestion};
+
+use hex::ToHex;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::io::Seek;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Number of files kept.
+const CAPACITY: usize = 65_536;
+
+/// Version of the encoding of the cache file, bumped whenever [`Finding`] or
+/// [`Entry`] change, the encoding is not self-describing.
+const FORMAT: u32 = 2;
+
+/// A finding, relative to the chunks of its file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Finding {
+    /// Index of the chunk within the file.
+    chunk: usize,
+    detector: Detector,
+    /// Line and column of the start and the end of the span.
+    span: ((usize, usize), (usize, usize)),
+    range: Range,
+    replacements: Vec<String>,
+    description: Option<String>,
+    rule: Option<String>,
+    help: Option<String>,
+    severity: Option<Severity>,
+}
+
+impl Finding {
+    /// The part of `suggestion` kept in the cache, none if its chunk is not
+    /// one of `chunks`.
+    fn new(suggestion: &Suggestion<'_>, chunks: &[CheckableChunk]) -> Option<Self> {
+        let chunk = chunks
+            .iter()
+            .position(|chunk| std::ptr::eq(chunk, suggestion.chunk))?;
+        let Span { start, end } = suggestion.span;
+        Some(Self {
+            chunk,
+            detector: suggestion.detector,
+            span: ((start.line, start.column), (end.line, end.column)),
+            range: suggestion.range.clone(),
+            replacements: suggestion.replacements.clone(),
+            description: suggestion.description.clone(),
+            rule: suggestion.rule.clone(),
+            help: suggestion.help.clone(),
+            severity: suggestion.severity,
+        })
+    }
+
+    /// The suggestion within `chunks` of `origin`.
+    fn suggestion<'s>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'s [CheckableChunk],
+    ) -> Option<Suggestion<'s>> {
+        let ((start_line, start_column), (end_line, end_column)) = self.span;
+        Some(Suggestion {
+            detector: self.detector,
+            origin: origin.clone(),
+            chunk: chunks.get(self.chunk)?,
+            span: Span {
+                start: LineColumn {
+                    line: start_line,
+                    column: start_column,
+                },
+                end: LineColumn {
+                    line: end_line,
+                    column: end_column,
+                },
+            },
+            range: self.range.clone(),
+            replacements: self.replacements.clone(),
+            description: self.description.clone(),
+            rule: self.rule.clone(),
+            help: self.help.clone(),
+            severity: self.severity,
+            also: Vec::new(),
+        })
+    }
+}
+
+/// The findings of a file, and the digest of the chunks they belong to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry {
+    digest: String,
+    findings: Vec<Finding>,
+}
+
+/// The digest of `chunks`, their content and where they are located.
+fn digest(chunks: &[CheckableChunk]) -> String {
+    let mut hasher = sha2::Sha256::new();
+    for chunk in chunks {
+        hasher.update(format!("{:?}\0", chunk.variant()).as_bytes());
+        hasher.update(chunk.as_str().as_bytes());
+        for (range, span) in chunk.iter() {
+            hasher.update(
+                format!(
+                    "\0{}..{}@{}:{}-{}:{}",
+                    range.start,
+                    range.end,
+                    span.start.line,
+                    span.start.column,
+                    span.end.line,
+                    span.end.column
+                )
+                .as_bytes(),
+            );
+        }
+        hasher.update(b"\n");
+    }
+    hasher.finalize().as_slice().encode_hex::<String>()
+}
+
+/// The dictionaries the hunspell checker might load, see
+/// [`super::HunspellChecker::new`].
+fn dictionaries(config: &Config) -> Result<Vec<PathBuf>> {
+    let hunspell = match config.hunspell {
+        Some(ref hunspell) => hunspell,
+        None => return Ok(Vec::new()),
+    };
+    let lang = hunspell.lang().to_string();
+    let mut acc = Vec::new();
+    for search_dir in hunspell.search_dirs() {
+        acc.push(search_dir.join(&lang).with_extension("dic"));
+        acc.push(search_dir.join(&lang).with_extension("aff"));
+    }
+    acc.extend(hunspell.extra_dictionaries().cloned());
+    acc.push(hunspell.project_dictionary(&config.base_dir()?));
+    Ok(acc)
+}
+
+/// The fingerprint of everything besides the chunks the findings depend on:
+/// the version and the cache format, the configuration, the checkers and rules selected with
+/// `--only` and `--skip`, and the content of the dictionaries.
+fn fingerprint(config: &Config) -> Result<String> {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(FORMAT.to_le_bytes());
+    hasher.update(config.to_toml()?.as_bytes());
+    hasher.update(format!("{:?}", config.filter).as_bytes());
+    for path in dictionaries(config)? {
+        hasher.update(path.display().to_string().as_bytes());
+        // an absent dictionary is fine, one that appears is a change as well
+        hasher.update(fs_err::read(&path).unwrap_or_default());
+        hasher.update(b"\0");
+    }
+    Ok(hasher.finalize().as_slice().encode_hex::<String>())
+}
+
+/// Findings of previous runs, by file.
+pub(crate) struct FindingsCache {
+    /// The most recently used entries last.
+    memory: Mutex<IndexMap<String, Entry>>,
+    /// The file the entries are persisted to.
+    file: PathBuf,
+    /// Whether there are entries which are not persisted yet.
+    dirty: AtomicBool,
+}
+
+impl FindingsCache {
+    /// The cache specific to `config`, none unless it is enabled, see
+    /// `--no-cache`.
+    pub(crate) fn open(config: &Config) -> Result<Option<Self>> {
+        if !config.findings_cache {
+            return Ok(None);
+        }
+        let cache_dir = super::project_dir()?.cache_dir().join("findings");
+        fs_err::create_dir_all(&cache_dir)?;
+        Ok(Some(Self::at(&cache_dir.join(fingerprint(config)?))))
+    }
+
+    /// The cache persisted to `file`.
+    fn at(file: &Path) -> Self {
+        let entries = load(file);
+        log::debug!(
+            "Loaded the cached findings of {} files from {}",
+            entries.len(),
+            file.display()
+        );
+        Self {
+            memory: Mutex::new(entries),
+            file: file.to_owned(),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// The findings in `chunks` of `origin`, either cached or created by
+    /// `create` and cached.
+    pub(crate) fn fetch_or_insert<'s>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'s [CheckableChunk],
+        create: impl FnOnce() -> Result<Vec<Suggestion<'s>>>,
+    ) -> Result<Vec<Suggestion<'s>>> {
+        let key = origin.as_path().display().to_string();
+        let digest = digest(chunks);
+        {
+            let mut memory = self.memory.lock().unwrap();
+            let entry = match memory.shift_remove(&key) {
+                Some(entry) if entry.digest == digest => Some(entry),
+                _ => memory
+                    .values()
+                    .find(|entry| entry.digest == digest)
+                    .cloned(),
+            };
+            if let Some(entry) = entry {
+                let suggestions = entry
+                    .findings
+                    .iter()
+                    .map(|finding| finding.suggestion(origin, chunks))
+                    .collect::<Option<Vec<_>>>();
+                memory.insert(key.clone(), entry);
+                if let Some(suggestions) = suggestions {
+                    log::debug!("Using the cached findings of {}", origin);
+                    return Ok(suggestions);
+                }
+            }
+        }
+        // the lock is not held while checking, files are checked in parallel
+        let suggestions = create()?;
+        let findings = suggestions
+            .iter()
+            .map(|suggestion| Finding::new(suggestion, chunks))
+            .collect::<Option<Vec<_>>>();
+        // findings outside of the chunks cannot be restored, so neither are
+        // the other ones of the file
+        if let Some(findings) = findings {
+            let mut memory = self.memory.lock().unwrap();
+            memory.shift_remove(&key);
+            memory.insert(key, Entry { digest, findings });
+            while memory.len() > CAPACITY {
+                memory.shift_remove_index(0);
+            }
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        Ok(suggestions)
+    }
+
+    /// Merge the entries into the ones on disk, other runs might have stored
+    /// entries in the meantime.
+    fn persist(&self) -> Result<()> {
+        if !self.dirty.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let file = fs_err::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .open(&self.file)?;
+        let mut lock = fd_lock::RwLock::new(file);
+        let mut guard = lock.write()?;
+        let mut merged = load(&self.file);
+        for (path, entry) in self.memory.lock().unwrap().iter() {
+            merged.shift_remove(path);
+            merged.insert(path.clone(), entry.clone());
+        }
+        while merged.len() > CAPACITY {
+            merged.shift_remove_index(0);
+        }
+        let encoded = bincode::serialize(&merged.into_iter().collect::<Vec<_>>())?;
+        // effectively truncate, but without losing the lock
+        guard.rewind()?;
+        std::io::copy(&mut &encoded[..], &mut *guard)?;
+        let len = guard.stream_position()?;
+        guard.set_len(len)?;
+        Ok(())
+    }
+}
+
+impl Drop for FindingsCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.persist() {
+            log::warn!("Failed to persist the findings cache: {:?}", e);
+        }
+    }
+}
+
+/// The entries stored at `path`, none if it does not exist or is broken.
+fn load(path: &Path) -> IndexMap<String, Entry> {
+    let content = match fs_err::read(path) {
+        Ok(content) if !content.is_empty() => content,
+        _ => return IndexMap::new(),
+    };
+    match bincode::deserialize::<Vec<(String, Entry)>>(&content) {
+        Ok(entries) => entries.into_iter().collect(),
+        Err(e) => {
+            log::warn!("Ignoring broken findings cache {}: {:?}", path.display(), e);
+            IndexMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, Documentation};
+
+    #[test]
+    fn unchanged_files_are_not_checked_again() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("findings");
+        let origin = ContentOrigin::TestEntityRust;
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some wrod."), true, false);
+        let chunks = docs.get(&origin).unwrap();
+        let create = || {
+            Ok(vec![Suggestion {
+                detector: Detector::Hunspell,
+                origin: origin.clone(),
+                chunk: &chunks[0],
+                span: Span {
+                    start: LineColumn {
+                        line: 1,
+                        column: 14,
+                    },
+                    end: LineColumn {
+                        line: 1,
+                        column: 17,
+                    },
+                },
+                range: 5..9,
+                replacements: vec!["word".to_owned()],
+                description: None,
+                rule: None,
+                help: None,
+                severity: Some(Severity::Warning),
+                also: Vec::new(),
+            }])
+        };
+        let fresh = {
+            let cache = FindingsCache::at(&file);
+            cache.fetch_or_insert(&origin, chunks, create).unwrap()
+        };
+        // from disk, the checkers are not run
+        let cache = FindingsCache::at(&file);
+        let cached = cache
+            .fetch_or_insert(&origin, chunks, || unreachable!())
+            .unwrap();
+        assert_eq!(cached, fresh);
+        assert!(std::ptr::eq(cached[0].chunk, &chunks[0]));
+
+        // a changed file is checked again
+        let docs =
+            Documentation::load_from_str(origin.clone(), chyrp_up!("Some word."), true, false);
+        let changed = cache
+            .fetch_or_insert(&origin, docs.get(&origin).unwrap(), || Ok(Vec::new()))
+            .unwrap();
+        assert!(changed.is_empty());
+    }
+
+    #[test]
+    fn findings_follow_moved_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = FindingsCache::at(&tmp.path().join("findings"));
+        let content = chyrp_up!("Some wrod.");
+        let origin = ContentOrigin::RustSourceFile(PathBuf::from("src/old.rs"));
+        let docs = Documentation::load_from_str(origin.clone(), content, true, false);
+        let chunks = docs.get(&origin).unwrap();
+        let fresh = cache
+            .fetch_or_insert(&origin, chunks, || {
+                Ok(vec![Suggestion {
+                    detector: Detector::Hunspell,
+                    origin: origin.clone(),
+                    chunk: &chunks[0],
+                    span: Span {
+                        start: LineColumn {
+                            line: 1,
+                            column: 14,
+                        },
+                        end: LineColumn {
+                            line: 1,
+                            column: 17,
+                        },
+                    },
+                    range: 5..9,
+                    replacements: vec!["word".to_owned()],
+                    description: None,
+                    rule: None,
+                    help: None,
+                    severity: None,
+                    also: Vec::new(),
+                }])
+            })
+            .unwrap();
+
+        let moved = ContentOrigin::RustSourceFile(PathBuf::from("src/moved.rs"));
+        let docs = Documentation::load_from_str(moved.clone(), content, true, false);
+        let chunks = docs.get(&moved).unwrap();
+        let cached = cache
+            .fetch_or_insert(&moved, chunks, || unreachable!())
+            .unwrap();
+        assert_eq!(cached.len(), fresh.len());
+        assert_eq!(cached[0].origin, moved);
+        assert_eq!(cached[0].replacements, fresh[0].replacements);
+    }
+}
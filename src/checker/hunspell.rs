@@ -4,19 +4,21 @@
 //! the individual tokens against the dictionary using the defined affixes. Can
 //! handle multiple dictionaries.
 
-use super::{apply_tokenizer, Checker, Detector, Suggestion};
+use super::{apply_tokenizer, soft_wrapped, Checker, Detector, Suggestion};
 
-use crate::config::{Lang5, WrappedRegex};
+use crate::collation::Collator;
+use crate::config::{CheckIdentifiers, CodePlurals, Lang5, ProperNounsConfig, WrappedRegex};
 use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
 use crate::util::sub_chars;
-use crate::Range;
+use crate::{Range, Severity};
 
 use fs_err as fs;
 use io::Write;
 use lazy_static::lazy_static;
 
 use nlprule::Tokenizer;
-use std::io::{self, BufRead};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Seek};
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -26,7 +28,8 @@ use hunspell_rs::{CheckResult, Hunspell};
 use crate::errors::*;
 
 use super::quirks::{
-    replacements_contain_dashed, replacements_contain_dashless, transform, Transformed,
+    identifier_parts, is_code_plural, plural_of, replacements_contain_dashed,
+    replacements_contain_dashless, transform, Capitalization, DigitWords, ProperNouns, Transformed,
 };
 
 static BUILTIN_HUNSPELL_AFF: &[u8] = include_bytes!(concat!(
@@ -54,30 +57,31 @@ fn cache_builtin_inner(
         extension
     ));
     fs::create_dir_all(path.parent().unwrap())?;
-    // check if file exists
-    if let Ok(f) = fs::File::open(&path) {
-        // in case somebody else is currently writing to it
-        // wait for that to complete
-        let flock = fd_lock::RwLock::new(f);
-        let _ = flock.read()?;
-        return Ok(path);
-    }
+    // must not truncate before the lock is held, somebody else might be
+    // reading it right now
     let f = fs::OpenOptions::new()
-        .truncate(true)
         .create(true)
+        .read(true)
         .write(true)
         .open(&path)?;
     let mut flock = fd_lock::RwLock::new(f);
-    // if there are multiple instances, allow the first to write it all
-    if let Ok(mut f) = flock.try_write() {
+    {
+        // in case somebody else is currently writing to it
+        // wait for that to complete
+        let guard = flock.read()?;
+        if guard.file().metadata()?.len() == data.len() as u64 {
+            return Ok(path);
+        }
+    }
+    // if there are multiple instances, the first one writes it all, the
+    // others find it complete once they obtain the lock
+    let mut guard = flock.write()?;
+    let f = guard.file_mut();
+    if f.metadata()?.len() != data.len() as u64 {
+        f.set_len(0)?;
+        f.rewind()?;
         f.write_all(data)?;
-        return Ok(path);
     }
-
-    // .. but block execution until the first completed so
-    // there are no cases of partial data
-    let _ = flock.read()?;
-
     Ok(path)
 }
 
@@ -133,11 +137,30 @@ pub struct HunspellCheckerInner {
     allow_concatenated: bool,
     allow_dashed: bool,
     allow_emojis: bool,
+    /// Words mixing letters and digits to accept, if enabled.
+    digit_words: Option<DigitWords>,
+    /// Proper nouns only accepted with their exact capitalization, if any.
+    proper_nouns: Option<ProperNouns>,
+    code_plurals: CodePlurals,
+    check_identifiers: CheckIdentifiers,
     ignorelist: String,
+    /// Orders the replacement candidates, if enabled.
+    collator: Option<Collator>,
+}
+
+/// Add the words of the editor project dictionaries in `root` to the runtime
+/// dictionary.
+fn import_editor_dictionaries(hunspell: &mut Hunspell, root: &Path) -> Result<()> {
+    for word in crate::dict::editor_words(root)? {
+        if !hunspell.add(&word) {
+            log::warn!("Failed to add imported word {} to context", word);
+        }
+    }
+    Ok(())
 }
 
 impl HunspellCheckerInner {
-    fn new(config: &<HunspellChecker as Checker>::Config) -> Result<Self> {
+    fn new(config: &<HunspellChecker as Checker>::Config, base_dir: &Path) -> Result<Self> {
         // TODO allow override
         let (transform_regex, allow_concatenated, allow_dashed, allow_emojis) = {
             let quirks = &config.quirks;
@@ -253,14 +276,35 @@ impl HunspellCheckerInner {
                 )
             }
         }
+        if config.import_editor_dictionaries {
+            import_editor_dictionaries(&mut hunspell, base_dir)?;
+        }
+        let project_dictionary = config.project_dictionary(base_dir);
+        for word in crate::dict::project_words(&project_dictionary)? {
+            if !hunspell.add(&word) {
+                log::warn!(
+                    "Failed to add word {} of {} to context",
+                    word,
+                    project_dictionary.display()
+                );
+            }
+        }
         log::debug!("Dictionary setup completed successfully.");
+        let collator = config
+            .sort_candidates
+            .then(|| Collator::new(config.lang().lang.to_639_1().unwrap_or_default()));
         Ok(Self {
             hunspell: HunspellSafe::from(hunspell),
             transform_regex,
             allow_concatenated,
             allow_dashed,
             allow_emojis,
+            digit_words: config.quirks.digit_words().map(DigitWords::new),
+            proper_nouns: None,
+            code_plurals: config.quirks.code_plurals,
+            check_identifiers: config.quirks.check_identifiers,
             ignorelist,
+            collator,
         })
     }
 }
@@ -268,6 +312,14 @@ impl HunspellCheckerInner {
 #[derive(Clone)]
 pub struct HunspellChecker(pub Arc<HunspellCheckerInner>, pub Arc<Tokenizer>);
 
+impl std::fmt::Debug for HunspellChecker {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("HunspellChecker")
+            .finish_non_exhaustive()
+    }
+}
+
 impl std::ops::Deref for HunspellChecker {
     type Target = HunspellCheckerInner;
     fn deref(&self) -> &Self::Target {
@@ -276,12 +328,141 @@ impl std::ops::Deref for HunspellChecker {
 }
 
 impl HunspellChecker {
-    pub fn new(config: &<HunspellChecker as Checker>::Config) -> Result<Self> {
+    /// The editor and project dictionaries are looked up in `base_dir`.
+    pub fn new(config: &<HunspellChecker as Checker>::Config, base_dir: &Path) -> Result<Self> {
         let tokenizer = super::tokenizer::<&PathBuf>(None)?;
-        let inner = HunspellCheckerInner::new(config)?;
+        let inner = HunspellCheckerInner::new(config, base_dir)?;
         let hunspell = Arc::new(inner);
         Ok(HunspellChecker(hunspell, tokenizer))
     }
+
+    /// Accept the proper nouns of `config` only with their exact
+    /// capitalization, and flag the other ones if requested.
+    pub fn with_proper_nouns(mut self, config: &ProperNounsConfig) -> Self {
+        if !config.is_empty() {
+            Arc::make_mut(&mut self.0).proper_nouns =
+                Some(ProperNouns::new(&config.names, config.flag_variants));
+        }
+        self
+    }
+
+    /// The words of `text` the dictionary does not know, i.e. of a custom
+    /// replacement. Text in backticks is code and skipped.
+    pub(crate) fn unknown_words(&self, text: &str) -> Vec<String> {
+        text.split('`')
+            .step_by(2)
+            .flat_map(|prose| prose.split(|c: char| !(c.is_alphanumeric() || c == '\'')))
+            .filter(|word| word.chars().any(char::is_alphabetic))
+            .filter(|word| self.hunspell.check(word) != CheckResult::FoundInDictionary)
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+}
+
+impl HunspellChecker {
+    /// Handle `word` at `range` if it is the plural of a code identifier,
+    /// either inline code followed by a plural suffix, i.e. `` `Vec`s ``, or
+    /// the plural of one of the `identifiers`, i.e. `Vecs`. Returns `true`
+    /// iff it was handled.
+    fn code_plural<'s>(
+        &self,
+        location: &Location<'_, 's>,
+        placeholders: &[Range],
+        identifiers: &HashSet<String>,
+        word: &str,
+        range: &Range,
+        acc: &mut Vec<Suggestion<'s>>,
+    ) -> bool {
+        let instances = self.code_plurals == CodePlurals::Instances;
+        let code = placeholders
+            .iter()
+            .find(|code| code.start == range.start && code.end < range.end);
+        if let Some(code) = code {
+            if !is_code_plural(word, code.len()) {
+                return false;
+            }
+            if instances {
+                let identifier = sub_chars(location.plain.as_str(), code.clone());
+                let replacement = format!("`{}` instances", identifier);
+                location.push_style(
+                    code.end..range.end,
+                    " instances",
+                    &format!("Plural of a code identifier, write {}.", replacement),
+                    "code-plural",
+                    acc,
+                );
+            }
+            return true;
+        }
+        let identifier = match plural_of(word, identifiers) {
+            Some(identifier) => identifier,
+            None => return false,
+        };
+        // actual words, i.e. `Iterators`, are left alone
+        if self.hunspell.check(word) == CheckResult::FoundInDictionary {
+            return false;
+        }
+        if instances {
+            let replacement = format!("`{}` instances", identifier);
+            location.push_style(
+                range.clone(),
+                &replacement,
+                &format!("Plural of a code identifier, write {}.", replacement),
+                "code-plural",
+                acc,
+            );
+        }
+        true
+    }
+}
+
+impl HunspellChecker {
+    /// Handle `word` at `range` if it is shaped like a code identifier and
+    /// not in the dictionary as a whole, either accepting it or checking each
+    /// of its sub-words. Returns `true` iff it was handled.
+    fn identifier<'s>(
+        &self,
+        plain: &PlainOverlay,
+        chunk: &'s CheckableChunk,
+        origin: &ContentOrigin,
+        word: &str,
+        range: &Range,
+        acc: &mut Vec<Suggestion<'s>>,
+    ) -> bool {
+        if self.check_identifiers == CheckIdentifiers::Whole {
+            return false;
+        }
+        let hunspell = &*self.hunspell.0;
+        if hunspell.check(word) == CheckResult::FoundInDictionary {
+            return false;
+        }
+        let parts = match identifier_parts(word) {
+            Some(parts) => parts,
+            None => return false,
+        };
+        if self.check_identifiers == CheckIdentifiers::Skip {
+            log::trace!(target: "quirks", "Found an identifier, treating {} as ok", word);
+            return true;
+        }
+        // single letters, i.e. of `iPhone`, are no words on their own
+        for part in parts.into_iter().filter(|part| part.len() > 1) {
+            // the findings point at the sub-word within the identifier
+            let part = range.start + part.start..range.start + part.end;
+            obtain_suggestions(
+                plain,
+                chunk,
+                hunspell,
+                origin,
+                sub_chars(plain.as_str(), part.clone()),
+                part,
+                self.allow_concatenated,
+                self.allow_dashed,
+                self.allow_emojis,
+                acc,
+            );
+        }
+        true
+    }
 }
 
 impl Checker for HunspellChecker {
@@ -301,13 +482,49 @@ impl Checker for HunspellChecker {
     {
         let mut acc = Vec::with_capacity(chunks.len());
 
-        for chunk in chunks {
-            let plain = chunk.erase_cmark();
+        let plains = chunks
+            .iter()
+            .map(CheckableChunk::erase_cmark)
+            .collect::<Vec<_>>();
+        // the inline code of the file, the plurals of which are accepted
+        let identifiers = plains
+            .iter()
+            .flat_map(|plain| {
+                plain
+                    .placeholders()
+                    .map(move |placeholder| sub_chars(plain.as_str(), placeholder))
+            })
+            .filter(|identifier| identifier.chars().count() > 1)
+            .collect::<HashSet<_>>();
+        for (chunk, plain) in chunks.iter().zip(plains) {
             log::trace!("{:?}", &plain);
+            let placeholders = plain.placeholders().collect::<Vec<_>>();
+            let location = Location {
+                plain: &plain,
+                chunk,
+                origin,
+            };
             let txt = plain.as_str();
             let hunspell = &*self.hunspell.0;
 
-            'tokenization: for range in apply_tokenizer(&self.1, txt) {
+            let ranges = apply_tokenizer(&self.1, txt).collect::<Vec<_>>();
+            let mut idx = 0;
+            'tokenization: while let Some(range) = ranges.get(idx).cloned() {
+                idx += 1;
+                // rejoin words hyphenated at the end of a line, and the
+                // separate `-` token if the tokenizer split it off
+                let continued = match ranges.get(idx) {
+                    Some(next) if sub_chars(txt, next.clone()) == "-" => idx + 1,
+                    _ => idx,
+                };
+                if let Some(next) = ranges.get(continued) {
+                    if let Some(joined) = soft_wrapped(txt, &range, next) {
+                        if hunspell.check(&joined) == CheckResult::FoundInDictionary {
+                            idx = continued + 1;
+                            continue 'tokenization;
+                        }
+                    }
+                }
                 let word = sub_chars(txt, range.clone());
                 if range.len() == 1
                     && word
@@ -318,6 +535,43 @@ impl Checker for HunspellChecker {
                 {
                     continue 'tokenization;
                 }
+                if let Some(ref proper_nouns) = self.proper_nouns {
+                    match proper_nouns.capitalization(&word) {
+                        Capitalization::Exact => continue 'tokenization,
+                        Capitalization::Variant(canonical) if proper_nouns.flag_variants => {
+                            location.push_style(
+                                range,
+                                canonical,
+                                &format!("Proper noun, write it as {}.", canonical),
+                                "proper-noun",
+                                &mut acc,
+                            );
+                            continue 'tokenization;
+                        }
+                        _ => {}
+                    }
+                }
+                if self.code_plural(
+                    &location,
+                    &placeholders,
+                    &identifiers,
+                    &word,
+                    &range,
+                    &mut acc,
+                ) {
+                    continue 'tokenization;
+                }
+                if let Some(ref digit_words) = self.digit_words {
+                    if digit_words.accepts(&word, |letters| {
+                        hunspell.check(letters) == CheckResult::FoundInDictionary
+                    }) {
+                        log::trace!(target: "quirks", "Found a word mixing letters and digits, treating {} as ok", &word);
+                        continue 'tokenization;
+                    }
+                }
+                if self.identifier(&plain, chunk, origin, &word, &range, &mut acc) {
+                    continue 'tokenization;
+                }
                 if self.transform_regex.is_empty() {
                     obtain_suggestions(
                         &plain,
@@ -368,10 +622,51 @@ impl Checker for HunspellChecker {
                 }
             }
         }
+        if let Some(collator) = self.collator {
+            for suggestion in &mut acc {
+                collator.sort(&mut suggestion.replacements);
+            }
+        }
         Ok(acc)
     }
 }
 
+/// The chunk being checked, where the findings are located.
+struct Location<'a, 's> {
+    plain: &'a PlainOverlay<'a>,
+    chunk: &'s CheckableChunk,
+    origin: &'a ContentOrigin,
+}
+
+impl<'s> Location<'_, 's> {
+    /// Flag `range` of the plain text as a style finding, fixed by
+    /// `replacement`.
+    fn push_style(
+        &self,
+        range: Range,
+        replacement: &str,
+        description: &str,
+        rule: &str,
+        acc: &mut Vec<Suggestion<'s>>,
+    ) {
+        for (range, span) in self.plain.find_spans(range) {
+            acc.push(Suggestion {
+                detector: Detector::Hunspell,
+                range,
+                span,
+                origin: self.origin.clone(),
+                replacements: vec![replacement.to_owned()],
+                chunk: self.chunk,
+                description: Some(description.to_owned()),
+                rule: Some(rule.to_owned()),
+                help: None,
+                severity: Some(Severity::Warning),
+                also: Vec::new(),
+            });
+        }
+    }
+}
+
 fn obtain_suggestions<'s>(
     plain: &PlainOverlay,
     chunk: &'s CheckableChunk,
@@ -419,6 +714,10 @@ fn obtain_suggestions<'s>(
                     replacements: replacements.clone(),
                     chunk,
                     description: Some("Possible spelling mistake found.".to_owned()),
+                    rule: None,
+                    help: None,
+                    severity: None,
+                    also: Vec::new(),
                 })
             }
         }
@@ -548,6 +847,152 @@ bar
         }
     }
 
+    #[test]
+    fn unknown_words_of_a_replacement() {
+        let config = crate::config::HunspellConfig {
+            use_builtin: true,
+            ..Default::default()
+        };
+        let hunspell = HunspellChecker::new(&config, &crate::traverse::cwd().unwrap()).unwrap();
+        assert!(hunspell.unknown_words("the word").is_empty());
+        assert_eq!(
+            hunspell.unknown_words("the wrod, `fooo` is 42"),
+            vec!["wrod".to_owned()]
+        );
+    }
+
+    #[test]
+    fn proper_nouns() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = crate::Documentation::load_from_str(
+            origin.clone(),
+            crate::chyrp_up!("Use Spearowly, not spearowly."),
+            true,
+            false,
+        );
+        let chunks = docs.get(&origin).unwrap();
+        let config = crate::config::HunspellConfig {
+            use_builtin: true,
+            ..Default::default()
+        };
+        let flagged = |flag_variants| {
+            HunspellChecker::new(&config, &crate::traverse::cwd().unwrap())
+                .unwrap()
+                .with_proper_nouns(&ProperNounsConfig {
+                    names: vec!["Spearowly".to_owned()],
+                    flag_variants,
+                })
+                .check(&origin, chunks)
+                .unwrap()
+                .into_iter()
+                .map(|suggestion| {
+                    (
+                        sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                        suggestion.rule.clone(),
+                        suggestion.severity(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            flagged(true),
+            vec![(
+                "spearowly".to_owned(),
+                Some("proper-noun".to_owned()),
+                Severity::Warning
+            )]
+        );
+        // left to the dictionary
+        assert_eq!(
+            flagged(false),
+            vec![("spearowly".to_owned(), None, Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn code_plurals() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = crate::Documentation::load_from_str(
+            origin.clone(),
+            crate::chyrp_up!("Collects `Vec`s and `Box`es, more Vecs and Iterators."),
+            true,
+            false,
+        );
+        let chunks = docs.get(&origin).unwrap();
+        let flagged = |code_plurals| {
+            let mut config = crate::config::HunspellConfig {
+                use_builtin: true,
+                ..Default::default()
+            };
+            config.quirks.code_plurals = code_plurals;
+            HunspellChecker::new(&config, &crate::traverse::cwd().unwrap())
+                .unwrap()
+                .check(&origin, chunks)
+                .unwrap()
+                .into_iter()
+                .map(|suggestion| {
+                    (
+                        sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                        suggestion.replacements,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(flagged(CodePlurals::Accept), vec![]);
+        assert_eq!(
+            flagged(CodePlurals::Instances),
+            vec![
+                ("s".to_owned(), vec![" instances".to_owned()]),
+                ("es".to_owned(), vec![" instances".to_owned()]),
+                ("Vecs".to_owned(), vec!["`Vec` instances".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn identifiers() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = crate::Documentation::load_from_str(
+            origin.clone(),
+            crate::chyrp_up!("Yields a TokenStream, or a TokenStrem."),
+            true,
+            false,
+        );
+        let chunks = docs.get(&origin).unwrap();
+        let flagged = |check_identifiers| {
+            let mut config = crate::config::HunspellConfig {
+                use_builtin: true,
+                ..Default::default()
+            };
+            config.quirks.check_identifiers = check_identifiers;
+            HunspellChecker::new(&config, &crate::traverse::cwd().unwrap())
+                .unwrap()
+                .check(&origin, chunks)
+                .unwrap()
+                .into_iter()
+                .map(|suggestion| {
+                    (
+                        sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                        suggestion.span.start.column,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            flagged(CheckIdentifiers::Whole),
+            vec![
+                ("TokenStream".to_owned(), 18),
+                ("TokenStrem".to_owned(), 36)
+            ]
+        );
+        // the span of the sub-word within the identifier
+        assert_eq!(
+            flagged(CheckIdentifiers::Split),
+            vec![("Strem".to_owned(), 41)]
+        );
+        assert_eq!(flagged(CheckIdentifiers::Skip), vec![]);
+    }
+
     macro_rules! parametrized_vulgar_fraction_or_emoji {
         ($($name:ident: $value:expr,)*) => {
         $(
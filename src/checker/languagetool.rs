@@ -0,0 +1,414 @@
+//! A grammar checker querying a `LanguageTool` server over HTTP.
+//!
+//! The plain text of each chunk, with markdown erased, is sent to the
+//! `/v2/check` endpoint of the configured server. The offsets of the returned
+//! matches count UTF-16 code units and are converted to characters before
+//! they are mapped back onto the source. The matches are cached per sentence,
+//! see [`super::responses`], the sentences of a chunk which are not cached yet
+//! are sent in a single request, separated by blank lines.
+
+use super::responses::{denormalize, normalize, Fix, ResponseCache};
+use super::{sentences, Checker, Detector, Suggestion};
+use crate::config::LanguageToolConfig;
+use crate::util::sub_char_range;
+use crate::{CheckableChunk, ContentOrigin, Range};
+
+use crate::errors::*;
+
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Time a single request may take before it is considered failed.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Separates the sentences of a request, so no match spans two of them.
+const SEPARATOR: &str = "\n\n";
+
+#[derive(Deserialize, Debug)]
+struct Response {
+    matches: Vec<Match>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Match {
+    message: String,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Value>,
+    rule: Rule,
+}
+
+#[derive(Deserialize, Debug)]
+struct Rule {
+    id: String,
+    #[serde(default)]
+    urls: Vec<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Value {
+    value: String,
+}
+
+/// Run `f` on a thread of its own.
+///
+/// The blocking client of `reqwest` must not be used from within an async
+/// runtime, which is where `fix` runs the checkers.
+fn detached<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    std::thread::spawn(f)
+        .join()
+        .map_err(|_| eyre!("LanguageTool request thread panicked"))?
+}
+
+/// Convert the UTF-16 code unit range `offset..offset + length` of `text` to
+/// a character range.
+fn char_range(text: &str, offset: usize, length: usize) -> Option<Range> {
+    let mut units = 0;
+    let mut start = None;
+    for (idx, c) in text.chars().enumerate() {
+        if units == offset {
+            start = Some(idx);
+        }
+        if units == offset + length {
+            return Some(start?..idx);
+        }
+        units += c.len_utf16();
+    }
+    if units == offset + length {
+        let end = text.chars().count();
+        return Some(start.unwrap_or(end)..end);
+    }
+    None
+}
+
+pub(crate) struct LanguageToolChecker {
+    client: reqwest::blocking::Client,
+    endpoint: url::Url,
+    lang: String,
+    disabled_rules: String,
+    cache: ResponseCache,
+    /// Identifies the configuration for the disk cache.
+    fingerprint: String,
+}
+
+impl LanguageToolChecker {
+    pub fn new(config: &<Self as Checker>::Config) -> Result<Self> {
+        let endpoint = config
+            .url()
+            .join("v2/check")
+            .wrap_err_with(|| eyre!("Invalid LanguageTool url {}", config.url()))?;
+        let client = detached(|| {
+            Ok(reqwest::blocking::Client::builder()
+                .timeout(TIMEOUT)
+                .build()?)
+        })?;
+        Ok(Self {
+            client,
+            endpoint,
+            lang: config.lang.clone(),
+            disabled_rules: config.disabled_rules.join(","),
+            cache: ResponseCache::new(),
+            fingerprint: format!("{:?}", config),
+        })
+    }
+
+    /// Keep the cached matches on disk, to share them across runs.
+    pub fn with_disk_cache(mut self) -> Result<Self> {
+        self.cache = ResponseCache::with_disk("languagetool", &self.fingerprint)?;
+        Ok(self)
+    }
+
+    /// Query the server for the matches within `text`.
+    fn query(&self, text: String) -> Result<Vec<Match>> {
+        let client = self.client.clone();
+        let endpoint = self.endpoint.clone();
+        let mut form = vec![("text", text), ("language", self.lang.clone())];
+        if !self.disabled_rules.is_empty() {
+            form.push(("disabledRules", self.disabled_rules.clone()));
+        }
+        let response = detached(move || {
+            let response = client
+                .post(endpoint.clone())
+                .form(&form)
+                .send()
+                .and_then(reqwest::blocking::Response::error_for_status)
+                .wrap_err_with(|| eyre!("LanguageTool request to {} failed", endpoint))?;
+            Ok(response.json::<Response>()?)
+        })?;
+        Ok(response.matches)
+    }
+
+    /// The matches of the server in each of the normalized `sentences`, which
+    /// are sent in a single request.
+    fn fixes(&self, origin: &ContentOrigin, sentences: &[&str]) -> Result<Vec<Vec<Fix>>> {
+        // the start of each sentence within the request in UTF-16 code units
+        let mut starts = Vec::with_capacity(sentences.len());
+        let mut units = 0;
+        for sentence in sentences {
+            starts.push(units);
+            units += sentence.encode_utf16().count() + SEPARATOR.len();
+        }
+        let mut acc = vec![Vec::new(); sentences.len()];
+        for fix in self.query(sentences.join(SEPARATOR))? {
+            let idx = starts
+                .partition_point(|start| *start <= fix.offset)
+                .saturating_sub(1);
+            let range = char_range(sentences[idx], fix.offset - starts[idx], fix.length);
+            if range.is_none() {
+                log::debug!(
+                    "LanguageTool match {}+{} is out of the bounds of a sentence in {}",
+                    fix.offset,
+                    fix.length,
+                    origin
+                );
+            }
+            acc[idx].extend(range.map(|range| {
+                Fix {
+                    range,
+                    message: fix.message,
+                    replacements: fix
+                        .replacements
+                        .into_iter()
+                        .map(|replacement| replacement.value)
+                        .collect(),
+                    rule: fix.rule.id,
+                    help: fix.rule.urls.into_iter().next().map(|url| url.value),
+                }
+            }));
+        }
+        Ok(acc)
+    }
+
+    /// Check the plain text of `chunk`, sentence by sentence.
+    fn check_chunk<'a>(
+        &self,
+        origin: &ContentOrigin,
+        chunk: &'a CheckableChunk,
+    ) -> Result<Vec<Suggestion<'a>>> {
+        let plain = chunk.erase_cmark();
+        let txt = plain.as_str();
+        let placeholders = plain.placeholders().collect::<Vec<_>>();
+        let sentences = sentences::segment(txt, &placeholders)
+            .into_iter()
+            .filter_map(|sentence| {
+                let offset = sentence.start;
+                let (normalized, offsets) = normalize(sub_char_range(txt, sentence));
+                (!normalized.is_empty()).then(|| (offset, normalized, offsets))
+            })
+            .collect::<Vec<_>>();
+
+        let mut fixes = sentences
+            .iter()
+            .map(|(_offset, normalized, _offsets)| self.cache.fetch(normalized))
+            .collect::<Vec<_>>();
+        let uncached = sentences
+            .iter()
+            .zip(&fixes)
+            .filter(|(_sentence, fixes)| fixes.is_none())
+            .map(|((_offset, normalized, _offsets), _fixes)| normalized.as_str())
+            .collect::<Vec<_>>();
+        if !uncached.is_empty() {
+            let mut queried = self.fixes(origin, &uncached)?.into_iter();
+            for ((_offset, normalized, _offsets), fixes) in sentences.iter().zip(&mut fixes) {
+                if fixes.is_none() {
+                    let queried = queried.next().unwrap_or_default();
+                    self.cache.insert(normalized.clone(), queried.clone());
+                    *fixes = Some(queried);
+                }
+            }
+        }
+
+        let mut acc = Vec::new();
+        for ((offset, _normalized, offsets), fixes) in sentences.iter().zip(fixes) {
+            for fix in fixes.unwrap_or_default() {
+                let range = match denormalize(&fix.range, offsets) {
+                    Some(range) => (offset + range.start)..(offset + range.end),
+                    None => continue,
+                };
+                // placeholders are not the actual content, i.e. inline code
+                if placeholders.iter().any(|placeholder| {
+                    placeholder.start < range.end && range.start < placeholder.end
+                }) {
+                    continue;
+                }
+                acc.extend(
+                    plain
+                        .find_spans(range)
+                        .into_iter()
+                        .map(|(range, span)| Suggestion {
+                            detector: Detector::LanguageTool,
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: fix.replacements.clone(),
+                            chunk,
+                            description: Some(fix.message.clone()),
+                            rule: Some(fix.rule.clone()),
+                            help: fix.help.clone(),
+                            severity: None,
+                            also: Vec::new(),
+                        }),
+                );
+            }
+        }
+        Ok(acc)
+    }
+}
+
+impl Checker for LanguageToolChecker {
+    type Config = LanguageToolConfig;
+
+    fn detector() -> Detector {
+        Detector::LanguageTool
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            acc.extend(self.check_chunk(origin, chunk)?);
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, Documentation};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn utf16_offsets() {
+        let text = "A 🦀 crab is is here.";
+        // the crab is two UTF-16 code units
+        assert_eq!(char_range(text, 10, 5), Some(9..14));
+        assert_eq!(char_range(text, 0, 1), Some(0..1));
+        assert_eq!(char_range(text, 18, 4), None);
+    }
+
+    /// Serve a single request with `body`, returning the request body.
+    fn serve(body: &'static str) -> (url::Url, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; length];
+            reader.read_exact(&mut request).unwrap();
+            let _ = write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn matches_to_suggestions() {
+        let (url, handle) = serve(
+            r#"{"matches":[{"message":"Possible typo: you repeated a word","offset":13,"length":5,
+"replacements":[{"value":"is"}],"rule":{"id":"ENGLISH_WORD_REPEAT_RULE","urls":[]}},
+{"message":"Inline code","offset":19,"length":5,"replacements":[],"rule":{"id":"OTHER"}}]}"#,
+        );
+        let config = LanguageToolConfig {
+            url,
+            lang: "en-US".to_owned(),
+            disabled_rules: vec!["WHITESPACE_RULE".to_owned(), "COMMA".to_owned()],
+        };
+        let checker = LanguageToolChecker::new(&config).unwrap();
+
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            chyrp_up!("The 🦀 crab is is `here`."),
+            true,
+            false,
+        );
+        let suggestions = checker.check(&origin, docs.get(&origin).unwrap()).unwrap();
+        let request = handle.join().unwrap();
+        assert!(request.contains("language=en-US"), "{}", request);
+        assert!(
+            request.contains("disabledRules=WHITESPACE_RULE%2CCOMMA"),
+            "{}",
+            request
+        );
+
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.rule.as_deref(), Some("ENGLISH_WORD_REPEAT_RULE"));
+        assert_eq!(suggestion.replacements, vec!["is"]);
+        assert_eq!(suggestion.span.start.line, 1);
+        assert_eq!(suggestion.span.start.column, 21);
+        assert_eq!(suggestion.span.end.column, 25);
+
+        // the server is gone, the matches are cached
+        let cached = checker.check(&origin, docs.get(&origin).unwrap()).unwrap();
+        assert_eq!(cached, suggestions);
+    }
+
+    #[test]
+    fn uncached_sentences_share_a_request() {
+        // the second sentence starts after the first one and the separator
+        let (url, handle) = serve(
+            r#"{"matches":[{"message":"Possible typo: you repeated a word","offset":25,"length":5,
+"replacements":[{"value":"is"}],"rule":{"id":"ENGLISH_WORD_REPEAT_RULE","urls":[]}}]}"#,
+        );
+        let config = LanguageToolConfig {
+            url,
+            lang: "en-US".to_owned(),
+            disabled_rules: Vec::new(),
+        };
+        let checker = LanguageToolChecker::new(&config).unwrap();
+
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            chyrp_up!("It is is here. The crab is is there."),
+            true,
+            false,
+        );
+        let suggestions = checker.check(&origin, docs.get(&origin).unwrap()).unwrap();
+        let request = handle.join().unwrap();
+        assert!(
+            request.contains("text=It+is+is+here.%0A%0AThe+crab+is+is+there."),
+            "{}",
+            request
+        );
+
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.span.start.column, 33);
+        assert_eq!(suggestion.span.end.column, 37);
+
+        // the server is gone, both sentences are cached
+        let cached = checker.check(&origin, docs.get(&origin).unwrap()).unwrap();
+        assert_eq!(cached, suggestions);
+    }
+}
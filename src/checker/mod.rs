@@ -3,17 +3,48 @@
 //! Trait to handle additional trackers. Contains also helpers to avoid
 //! re-implementing generic algorithms again and again, i.e. tokenization.
 
-use crate::{CheckableChunk, Config, ContentOrigin, Detector, Suggestion};
+use crate::config::{Overrides, Unpublished};
+use crate::filters::Pipeline;
+use crate::traverse::Changes;
+use crate::{
+    CheckableChunk, Config, ContentOrigin, Detector, Severity, Suggestion, SuggestionFilter,
+};
 
 use crate::errors::*;
 
+use std::path::Path;
+use std::sync::Arc;
+
 mod cached;
 use self::cached::Cached;
 
+mod cluster;
+use self::cluster::{cluster, merge_same_span};
+
+mod filter;
+pub(crate) use self::filter::RunFilter;
+use self::filter::Selection;
+
+mod findings;
+use self::findings::FindingsCache;
+
+mod set;
+pub use self::set::{Checked, CheckerSet};
+
 mod tokenize;
+pub(crate) use self::external::ExternalChecker;
 pub(crate) use self::hunspell::HunspellChecker;
+#[cfg(feature = "languagetool")]
+pub(crate) use self::languagetool::LanguageToolChecker;
 pub(crate) use self::nlprules::NlpRulesChecker;
 pub(crate) use self::tokenize::*;
+#[cfg(feature = "wasm")]
+pub(crate) use self::wasm::WasmChecker;
+
+mod external;
+mod placeholder;
+pub(crate) use self::placeholder::is_placeholder_doc;
+use self::placeholder::PlaceholderChecker;
 
 #[cfg(feature = "hunspell")]
 mod hunspell;
@@ -21,15 +52,35 @@ mod hunspell;
 #[cfg(feature = "nlprules")]
 mod nlprules;
 
+#[cfg(any(feature = "nlprules", feature = "languagetool"))]
+mod sentences;
+
+#[cfg(any(feature = "nlprules", feature = "languagetool"))]
+mod responses;
+
+#[cfg(feature = "languagetool")]
+mod languagetool;
+
 #[cfg(feature = "hunspell")]
 mod quirks;
+pub(crate) use self::quirks::identifier_parts;
+
+#[cfg(feature = "wasm")]
+mod wasm;
 
 /// Implementation for a checker
 pub trait Checker {
+    /// The configuration the checker is created from.
     type Config;
 
+    /// The detector the findings of the checker are attributed to.
     fn detector() -> Detector;
 
+    /// The findings in `chunks` of the content from `origin`.
+    ///
+    /// # Errors
+    ///
+    /// If the backend of the checker fails.
     fn check<'a, 's>(
         &self,
         origin: &ContentOrigin,
@@ -45,23 +96,108 @@ pub trait Checker {
 pub struct Checkers {
     hunspell: Option<HunspellChecker>,
     nlprules: Option<NlpRulesChecker>,
+    #[cfg(feature = "languagetool")]
+    languagetool: Option<LanguageToolChecker>,
+    external: Vec<ExternalChecker>,
+    #[cfg(feature = "wasm")]
+    wasm: Vec<WasmChecker>,
+    placeholder: Option<PlaceholderChecker>,
+    selection: Selection,
+    skip_flagged: bool,
+    /// Chunks with fewer words are not grammar checked.
+    grammar_min_words: usize,
+    /// Masks the parts of the chunks which never produce findings.
+    filters: Option<Pipeline>,
+    filter: Option<Arc<dyn SuggestionFilter>>,
+    severities: Overrides,
+    /// Report the findings of unpublished packages as notes.
+    unpublished_notes: bool,
+    /// Only findings on these lines are kept, see `--diff-base`.
+    changes: Option<Changes>,
+    /// Findings of the files unchanged since a previous run, see `--no-cache`.
+    findings: Option<FindingsCache>,
+}
+
+/// The names `--only` and `--skip` select checkers by.
+fn names(config: &Config) -> Vec<(String, Detector)> {
+    let mut names = vec![
+        ("hunspell".to_owned(), Detector::Hunspell),
+        ("nlprules".to_owned(), Detector::NlpRules),
+        ("languagetool".to_owned(), Detector::LanguageTool),
+        ("external".to_owned(), Detector::External),
+        ("wasm".to_owned(), Detector::Wasm),
+        ("empty-docs".to_owned(), Detector::EmptyDocs),
+    ];
+    names.extend(
+        config
+            .checker
+            .external
+            .iter()
+            .map(|external| (external.name(), Detector::External)),
+    );
+    names.extend(
+        config
+            .checker
+            .wasm
+            .iter()
+            .map(|wasm| (wasm.name(), Detector::Wasm)),
+    );
+    names
+}
+
+/// The external checkers selected to run.
+fn external(config: &Config, selection: &Selection, base_dir: &Path) -> Vec<ExternalChecker> {
+    config
+        .checker
+        .external
+        .iter()
+        .filter(|external| {
+            selection.runs(Detector::External, &["external", external.name().as_str()])
+        })
+        .map(|external| {
+            log::debug!("Enabling external checker {}.", external.name());
+            ExternalChecker::new(external, base_dir)
+        })
+        .collect()
+}
+
+/// The number of words of the plain, markdown erased `chunk`.
+fn words(chunk: &CheckableChunk) -> usize {
+    chunk.erase_cmark().as_str().split_whitespace().count()
 }
 
 impl Checkers {
+    /// Create all checkers enabled by `config`, loading their dictionaries
+    /// and rules.
+    ///
+    /// # Errors
+    ///
+    /// If a dictionary, rule set or remote server can not be loaded.
     pub fn new(config: Config) -> Result<Self> {
+        let names = names(&config);
+        let mut selection = config.filter.resolve(&names);
+        if let Some(ref enabled) = config.checkers {
+            selection =
+                selection.with_enabled(enabled, &names, |detector| config.is_enabled(detector))?;
+        }
+        let skip_flagged = config.checker.skip_flagged;
+        let base_dir = config.base_dir()?;
+
         macro_rules! create_checker {
-            ($feature:literal, $checker:ty, $config:expr, $checker_config:expr) => {
+            ($feature:literal, $checker:ty, $checker_config:expr $(, $arg:expr)*) => {
                 if !cfg!(feature = $feature) {
                     log::debug!("Feature {} is disabled by compilation.", $feature);
                     None
                 } else {
                     #[cfg(feature = $feature)]
                     {
-                        let config = $config;
                         let detector = <$checker>::detector();
-                        if config.is_enabled(detector) {
+                        if !selection.runs(detector, &[$feature]) {
+                            log::debug!("Checker {} is filtered out.", detector);
+                            None
+                        } else if let Some(checker_config) = $checker_config {
                             log::debug!("Enabling {} checks.", detector);
-                            Some(<$checker>::new($checker_config.unwrap())?)
+                            Some(<$checker>::new(checker_config $(, $arg)*)?)
                         } else {
                             log::debug!("Checker {} is disabled by configuration.", detector);
                             None
@@ -74,16 +210,270 @@ impl Checkers {
         let hunspell = create_checker!(
             "hunspell",
             HunspellChecker,
-            &config,
-            config.hunspell.as_ref()
+            config.hunspell.as_ref(),
+            &base_dir
         );
-        let nlprules = create_checker!(
-            "nlprules",
-            NlpRulesChecker,
-            &config,
-            config.nlprules.as_ref()
+        let nlprules = create_checker!("nlprules", NlpRulesChecker, config.nlprules.as_ref());
+        #[cfg(feature = "languagetool")]
+        let languagetool = create_checker!(
+            "languagetool",
+            LanguageToolChecker,
+            config.languagetool.as_ref()
         );
-        Ok(Self { hunspell, nlprules })
+        #[cfg(not(feature = "languagetool"))]
+        if config.languagetool.is_some() {
+            log::warn!(
+                "LanguageTool is configured, but feature `languagetool` is disabled by compilation."
+            );
+        }
+        let external = external(&config, &selection, &base_dir);
+
+        #[cfg(feature = "wasm")]
+        let wasm = config
+            .checker
+            .wasm
+            .iter()
+            .filter(|wasm| selection.runs(Detector::Wasm, &["wasm", wasm.name().as_str()]))
+            .map(|wasm| {
+                log::debug!("Enabling wasm checker {}.", wasm.name());
+                WasmChecker::new(wasm)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        #[cfg(not(feature = "wasm"))]
+        if !config.checker.wasm.is_empty() {
+            log::warn!(
+                "Wasm checkers are configured, but feature `wasm` is disabled by compilation."
+            );
+        }
+
+        let placeholder = PlaceholderChecker::new(&config, &selection);
+
+        let checkers = Self {
+            hunspell: hunspell.map(|hunspell| hunspell.with_proper_nouns(&config.words.proper)),
+            nlprules,
+            #[cfg(feature = "languagetool")]
+            languagetool,
+            external,
+            #[cfg(feature = "wasm")]
+            wasm,
+            placeholder,
+            selection,
+            skip_flagged,
+            grammar_min_words: config.checker.grammar_min_words,
+            filters: Pipeline::new(&config),
+            filter: None,
+            severities: config.severity_overrides.compile(&base_dir)?,
+            unpublished_notes: config.files.unpublished == Unpublished::Note,
+            changes: config.changes.clone(),
+            findings: FindingsCache::open(&config)?,
+        };
+        checkers
+            .selection
+            .ensure_known_rules(|| checkers.known_rules(), &names)?;
+        if config.checker.grammar_cache {
+            checkers.with_disk_cache()
+        } else {
+            Ok(checkers)
+        }
+    }
+
+    /// The rules of the checkers which run, none if one of them only learns
+    /// its rules while checking.
+    fn known_rules(&self) -> Option<Vec<String>> {
+        #[cfg(feature = "languagetool")]
+        let remote = self.languagetool.is_some();
+        #[cfg(not(feature = "languagetool"))]
+        let remote = false;
+        #[cfg(feature = "wasm")]
+        let remote = remote || !self.wasm.is_empty();
+        if remote || !self.external.is_empty() {
+            return None;
+        }
+        let mut rules = self
+            .nlprules
+            .iter()
+            .flat_map(NlpRulesChecker::rule_ids)
+            .collect::<Vec<_>>();
+        rules.extend(
+            self.placeholder
+                .iter()
+                .flat_map(PlaceholderChecker::rules)
+                .map(ToOwned::to_owned),
+        );
+        Some(rules)
+    }
+
+    /// Keep the cached findings of the grammar checkers on disk.
+    fn with_disk_cache(mut self) -> Result<Self> {
+        #[cfg(feature = "nlprules")]
+        {
+            self.nlprules = self
+                .nlprules
+                .map(NlpRulesChecker::with_disk_cache)
+                .transpose()?;
+        }
+        #[cfg(feature = "languagetool")]
+        {
+            self.languagetool = self
+                .languagetool
+                .map(LanguageToolChecker::with_disk_cache)
+                .transpose()?;
+        }
+        Ok(self)
+    }
+
+    /// The dictionary of the spelling checker, if it runs.
+    pub(crate) fn dictionary(&self) -> Option<&HunspellChecker> {
+        self.hunspell.as_ref()
+    }
+
+    /// Pass all suggestions through `filter`.
+    pub(crate) fn with_filter(mut self, filter: Option<Arc<dyn SuggestionFilter>>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// With `skip_flagged`, the findings are passed through the filter right
+    /// after checking, so a finding the filter drops does not flag its chunk.
+    /// Every finding is filtered exactly once either way.
+    fn filters_early(&self) -> bool {
+        self.skip_flagged && self.filter.is_some()
+    }
+
+    /// Pass `suggestions` through the filter, if any.
+    fn filtered<'s>(&self, suggestions: Vec<Suggestion<'s>>) -> Vec<Suggestion<'s>> {
+        match self.filter {
+            Some(ref filter) => suggestions
+                .into_iter()
+                .filter_map(|suggestion| filter.filter(suggestion))
+                .collect(),
+            None => suggestions,
+        }
+    }
+
+    fn within_changes(&self, suggestion: &Suggestion<'_>) -> bool {
+        self.changes.as_ref().map_or(true, |changes| {
+            let span = &suggestion.span;
+            changes.touches(suggestion.origin.as_path(), span.start.line..=span.end.line)
+        })
+    }
+
+    /// Run the checkers on the masked copies of `chunks`, see `[filters]`,
+    /// before the findings are filtered.
+    fn run<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let filters = match self.filters {
+            Some(ref filters) => filters,
+            None => return self.run_masked(origin, chunks),
+        };
+        let masked = filters.mask(chunks);
+        let collective = self.run_masked(origin, &masked)?;
+        Ok(collective
+            .into_iter()
+            .filter_map(|suggestion| {
+                let idx = masked
+                    .iter()
+                    .position(|chunk| std::ptr::eq(chunk, suggestion.chunk))?;
+                Some(suggestion.rebind(&chunks[idx]))
+            })
+            .collect())
+    }
+
+    /// Run the checkers on `chunks`, which are masked already.
+    fn run_masked<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        // cheap checkers run first, so the expensive ones can skip the chunks
+        // they flagged
+        let mut collective = Vec::<Suggestion<'s>>::with_capacity(chunks.len());
+        if let Some(ref hunspell) = self.hunspell {
+            collective.extend(hunspell.check(origin, chunks)?);
+        }
+        #[cfg(feature = "wasm")]
+        for wasm in &self.wasm {
+            collective.extend(wasm.check(origin, chunks)?);
+        }
+        for external in self.external.iter().filter(|external| external.is_cheap()) {
+            collective.extend(external.check(origin, chunks)?);
+        }
+        if let Some(ref placeholder) = self.placeholder {
+            collective.extend(placeholder.check(origin, chunks)?);
+        }
+        collective.retain(|suggestion| self.selection.keeps(suggestion));
+        if self.filters_early() {
+            collective = self.filtered(collective);
+        }
+
+        let runs = if self.skip_flagged {
+            // a finding which is dropped later on does not flag its chunk
+            let reported = collective
+                .iter()
+                .filter(|suggestion| self.within_changes(suggestion))
+                .collect::<Vec<_>>();
+            let flagged = |chunk: &CheckableChunk| {
+                reported
+                    .iter()
+                    .any(|suggestion| std::ptr::eq(suggestion.chunk, chunk))
+            };
+            let runs = chunks
+                .split(|chunk| flagged(chunk))
+                .filter(|run| !run.is_empty())
+                .collect::<Vec<_>>();
+            log::debug!(
+                "Skipping {} of {} chunks flagged by cheap checkers in {}",
+                chunks.len() - runs.iter().map(|run| run.len()).sum::<usize>(),
+                chunks.len(),
+                origin
+            );
+            runs
+        } else {
+            vec![chunks]
+        };
+
+        let mut expensive = Vec::<Vec<Suggestion<'s>>>::new();
+        for run in runs {
+            let sentences = run
+                .split(|chunk| words(chunk) < self.grammar_min_words)
+                .filter(|sentences| !sentences.is_empty());
+            for sentences in sentences {
+                if let Some(ref nlprule) = self.nlprules {
+                    expensive.push(nlprule.check(origin, sentences)?);
+                }
+                #[cfg(feature = "languagetool")]
+                if let Some(ref languagetool) = self.languagetool {
+                    expensive.push(languagetool.check(origin, sentences)?);
+                }
+            }
+            for external in self.external.iter().filter(|external| !external.is_cheap()) {
+                expensive.push(external.check(origin, run)?);
+            }
+        }
+        // grammar findings are clustered per checker, after the rules are
+        // filtered, so a skipped rule does not hide the others
+        let expensive = expensive
+            .into_iter()
+            .flat_map(|mut suggestions| {
+                suggestions.retain(|suggestion| self.selection.keeps(suggestion));
+                cluster(suggestions)
+            })
+            .collect::<Vec<_>>();
+        if self.filters_early() {
+            collective.extend(self.filtered(expensive));
+        } else {
+            collective.extend(expensive);
+        }
+        Ok(collective)
     }
 }
 
@@ -102,14 +492,34 @@ impl Checker for Checkers {
     where
         'a: 's,
     {
-        let mut collective = Vec::<Suggestion<'s>>::with_capacity(chunks.len());
-        if let Some(ref hunspell) = self.hunspell {
-            collective.extend(hunspell.check(origin, chunks)?);
+        // which chunks are skipped depends on the filter and the changes, which
+        // are not part of the cached state
+        let findings = self
+            .findings
+            .as_ref()
+            .filter(|_| !self.skip_flagged || self.filter.is_none() && self.changes.is_none());
+        let mut collective = match findings {
+            Some(findings) => {
+                findings.fetch_or_insert(origin, chunks, || self.run(origin, chunks))?
+            }
+            None => self.run(origin, chunks)?,
+        };
+        if !self.filters_early() {
+            collective = self.filtered(collective);
         }
-        if let Some(ref nlprule) = self.nlprules {
-            collective.extend(nlprule.check(origin, chunks)?);
+        collective.retain(|suggestion| self.within_changes(suggestion));
+        let laxer = (self.unpublished_notes
+            && !collective.is_empty()
+            && crate::traverse::packages::in_unpublished(origin.as_path()))
+        .then(|| Severity::Note);
+        for suggestion in &mut collective {
+            suggestion.severity = self
+                .severities
+                .resolve(suggestion)
+                .or(laxer)
+                .or(suggestion.severity);
         }
-
+        let mut collective = merge_same_span(collective);
         collective.sort();
 
         Ok(collective)
@@ -265,4 +675,155 @@ pub mod tests {
         ];
         extraction_test_body(dbg!(SIMPLE), EXPECTED_SPANS);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn expensive_checkers_skip_flagged_chunks() {
+        // findings of the cheap checker which are filtered do not count
+        #[derive(Default)]
+        struct DropCheap(std::sync::atomic::AtomicUsize);
+        impl SuggestionFilter for DropCheap {
+            fn filter<'s>(&self, suggestion: Suggestion<'s>) -> Option<Suggestion<'s>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let cheap = suggestion
+                    .description
+                    .as_deref()
+                    .map_or(false, |description| description.starts_with("Cheap"));
+                (!cheap).then(|| suggestion)
+            }
+        }
+        const SOURCE: &str = "/// First item.\nstruct A;\n\n/// Second item.\nstruct B;\n";
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        // both flag the first word of the first chunk they are given
+        let external = |name: &str, cheap: bool| {
+            crate::config::ExternalCheckerConfig {
+            name: Some(name.to_owned()),
+            command: PathBuf::from("sh"),
+            args: vec![
+                "-c".to_owned(),
+                r#"cat > /dev/null; echo '[{"chunk": 0, "start": 0, "end": 5, "message": "flagged"}]'"#.to_owned(),
+            ],
+            cheap,
+        }
+        };
+        let checked = |skip_flagged: bool, filter: Option<Arc<dyn SuggestionFilter>>| {
+            let mut config = Config {
+                hunspell: None,
+                nlprules: None,
+                ..Config::default()
+            };
+            config.checker.external = vec![external("Cheap", true), external("Expensive", false)];
+            config.checker.skip_flagged = skip_flagged;
+            let checkers = Checkers::new(config).unwrap().with_filter(filter);
+            checkers
+                .check(&origin, chunks)
+                .unwrap()
+                .into_iter()
+                .map(|suggestion| {
+                    let idx = chunks
+                        .iter()
+                        .position(|chunk| std::ptr::eq(chunk, suggestion.chunk))
+                        .unwrap();
+                    (suggestion.description.unwrap(), idx)
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut all = checked(false, None);
+        all.sort();
+        assert_eq!(
+            all,
+            vec![
+                ("Cheap: flagged".to_owned(), 0),
+                ("Expensive: flagged".to_owned(), 0)
+            ]
+        );
+        let mut skipped = checked(true, None);
+        skipped.sort();
+        assert_eq!(
+            skipped,
+            vec![
+                ("Cheap: flagged".to_owned(), 0),
+                ("Expensive: flagged".to_owned(), 1)
+            ]
+        );
+        let filter = Arc::new(DropCheap::default());
+        assert_eq!(
+            checked(true, Some(filter.clone())),
+            vec![("Expensive: flagged".to_owned(), 0)]
+        );
+        // every finding is filtered once
+        assert_eq!(filter.0.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn unknown_only_entries_are_rejected() {
+        let checkers = |only: &str| {
+            let mut config = Config {
+                hunspell: None,
+                nlprules: None,
+                ..Config::default()
+            };
+            config.checker.empty_docs = true;
+            config.filter = RunFilter::new(&[only.to_owned()], &[]);
+            Checkers::new(config)
+        };
+        assert!(checkers("empty-doc").is_ok());
+        assert!(checkers("empty-docs").is_ok());
+        assert!(checkers("hunspel").is_err());
+    }
+
+    #[test]
+    fn short_chunks_are_not_grammar_checked() {
+        const SOURCE: &str =
+            "/// See `Foo`.\nstruct A;\n\n/// A **reasonably** long sentence.\nstruct B;\n";
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+        assert_eq!(chunks.iter().map(words).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(Config::default().checker.grammar_min_words, 4);
+    }
+
+    #[test]
+    fn short_chunks_skip_the_grammar_checkers() {
+        const SOURCE: &str =
+            "/// She was not been heer.\nstruct A;\n\n/// She was not been here since Monday.\nstruct B;\n";
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+        assert_eq!(chunks.iter().map(words).collect::<Vec<_>>(), vec![5, 7]);
+
+        let checked = |grammar_min_words: usize| {
+            let mut config = Config {
+                nlprules: Some(crate::config::NlpRulesConfig::default()),
+                ..Config::default()
+            };
+            config.checker.grammar_min_words = grammar_min_words;
+            let checkers = Checkers::new(config).unwrap();
+            let mut checked = checkers
+                .check(&origin, chunks)
+                .unwrap()
+                .into_iter()
+                .map(|suggestion| (suggestion.detector, suggestion.span.start.line))
+                .collect::<Vec<_>>();
+            checked.sort_by_key(|(detector, line)| (*line, detector.to_string()));
+            checked
+        };
+        assert_eq!(
+            checked(0),
+            vec![
+                (Detector::Hunspell, 1),
+                (Detector::NlpRules, 1),
+                (Detector::NlpRules, 4)
+            ]
+        );
+        // the spelling of the short chunk is still checked
+        assert_eq!(
+            checked(6),
+            vec![(Detector::Hunspell, 1), (Detector::NlpRules, 4)]
+        );
+    }
 }
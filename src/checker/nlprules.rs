@@ -1,9 +1,13 @@
 //! A NLP based rule checker base on `nlprule`
 //!
-//! Does check grammar, and is supposed to only check for grammar. Sentence
-//! splitting is done in hand-waving way. To be improved.
+//! Does check grammar, and is supposed to only check for grammar. Each chunk is
+//! split into sentences by [`super::sentences`] first, which knows about
+//! abbreviations and inline code. The findings are cached per sentence, see
+//! [`super::responses`].
 
-use super::{Checker, Detector, Suggestion};
+use super::responses::{denormalize, normalize, Fix, ResponseCache};
+use super::{sentences, Checker, Detector, Suggestion};
+use crate::util::sub_char_range;
 use crate::{CheckableChunk, ContentOrigin};
 
 use crate::errors::*;
@@ -64,16 +68,89 @@ pub(crate) fn filtered_rules<P: AsRef<Path> + Clone>(
     }
 }
 
+/// Whether one of `entries` names the rule `id`, either in full or by a prefix
+/// ending at a `/`. Case is not distinguished.
+fn names(entries: &[String], id: &str) -> bool {
+    let id = id.to_lowercase();
+    entries.iter().any(|entry| {
+        let entry = entry.to_lowercase();
+        id == entry || (id.starts_with(&entry) && id[entry.len()..].starts_with('/'))
+    })
+}
+
+/// The `rules` selected by the `enabled_rules` and `disabled_rules` of
+/// `config`.
+fn selected_rules(rules: Arc<Rules>, config: &crate::config::NlpRulesConfig) -> Arc<Rules> {
+    if config.enabled_rules.is_empty() && config.disabled_rules.is_empty() {
+        return rules;
+    }
+    let selected = rules
+        .rules()
+        .iter()
+        .filter(|rule| {
+            let id = rule.id().to_string();
+            (config.enabled_rules.is_empty() || names(&config.enabled_rules, &id))
+                && !names(&config.disabled_rules, &id)
+        })
+        .cloned()
+        .collect::<Rules>();
+    log::debug!(
+        "Applying {} of {} rules",
+        selected.rules().len(),
+        rules.rules().len()
+    );
+    Arc::new(selected)
+}
+
+/// Link to the rule description, either provided by the rule itself or the
+/// `LanguageTool` community page of the rule group the rule was derived from.
+fn help_url(urls: &HashMap<String, String>, rule: &str) -> Option<String> {
+    urls.get(rule).cloned().or_else(|| {
+        let group = rule.split('/').nth(1)?;
+        Some(format!(
+            "https://community.languagetool.org/rule/show/{}?lang=en",
+            group
+        ))
+    })
+}
+
 pub(crate) struct NlpRulesChecker {
     tokenizer: Arc<Tokenizer>,
     rules: Arc<Rules>,
+    /// Documentation links of the rules which provide one, by rule id.
+    urls: HashMap<String, String>,
+    cache: ResponseCache,
+    /// Identifies the configuration for the disk cache.
+    fingerprint: String,
 }
 
 impl NlpRulesChecker {
     pub fn new(config: &<Self as Checker>::Config) -> Result<Self> {
         let tokenizer = super::tokenizer(config.override_tokenizer.as_ref())?;
-        let rules = filtered_rules(config.override_tokenizer.as_ref())?;
-        Ok(Self { tokenizer, rules })
+        let rules = selected_rules(filtered_rules(config.override_rules.as_ref())?, config);
+        let urls = rules
+            .rules()
+            .iter()
+            .filter_map(|rule| Some((rule.id().to_string(), rule.url()?.to_owned())))
+            .collect();
+        Ok(Self {
+            tokenizer,
+            rules,
+            urls,
+            cache: ResponseCache::new(),
+            fingerprint: format!("{:?}", config),
+        })
+    }
+
+    /// The ids of the selected rules.
+    pub(crate) fn rule_ids(&self) -> impl Iterator<Item = String> + '_ {
+        self.rules.rules().iter().map(|rule| rule.id().to_string())
+    }
+
+    /// Keep the cached findings on disk, to share them across runs.
+    pub fn with_disk_cache(mut self) -> Result<Self> {
+        self.cache = ResponseCache::with_disk("nlprules", &self.fingerprint)?;
+        Ok(self)
     }
 }
 
@@ -95,61 +172,113 @@ impl Checker for NlpRulesChecker {
         let mut acc = Vec::with_capacity(chunks.len());
 
         for chunk in chunks {
-            acc.extend(check_chunk(
-                origin.clone(),
-                chunk,
-                &self.tokenizer,
-                &self.rules,
-            ));
+            acc.extend(check_chunk(origin.clone(), chunk, self)?);
         }
 
         Ok(acc)
     }
 }
 
+/// The findings of the rules in the normalized `sentence`.
+fn suggest(checker: &NlpRulesChecker, sentence: &str) -> Vec<Fix> {
+    checker
+        .rules
+        .suggest(sentence, &checker.tokenizer)
+        .into_iter()
+        .filter_map(|fix| {
+            let range = fix.span().char().clone();
+            if range.start > range.end {
+                log::debug!(
+                    "BUG: crate nlprule yielded a negative range {:?}, please file a bug",
+                    range
+                );
+                return None;
+            }
+            Some(Fix {
+                range,
+                message: fix.message().to_owned(),
+                replacements: fix.replacements().to_vec(),
+                rule: fix.source().to_owned(),
+                help: help_url(&checker.urls, fix.source()),
+            })
+        })
+        .collect()
+}
+
 /// Check the plain text contained in chunk, which can be one or more sentences.
 fn check_chunk<'a>(
     origin: ContentOrigin,
     chunk: &'a CheckableChunk,
-    tokenizer: &Tokenizer,
-    rules: &Rules,
-) -> Vec<Suggestion<'a>> {
+    checker: &NlpRulesChecker,
+) -> Result<Vec<Suggestion<'a>>> {
     let plain = chunk.erase_cmark();
     log::trace!("{:?}", &plain);
     let txt = plain.as_str();
 
     let mut acc = Vec::with_capacity(32);
 
-    let nlpfixes = rules.suggest(txt, tokenizer);
-    if nlpfixes.is_empty() {
-        return Vec::new();
-    }
-
-    'nlp: for fix in nlpfixes {
-        let message = fix.message();
-        let replacements = fix.replacements();
-        let start = fix.span().char().start;
-        let end = fix.span().char().end;
-        if start > end {
-            log::debug!("BUG: crate nlprule yielded a negative range {:?} for chunk in {}, please file a bug", start..end, &origin);
-            continue 'nlp;
+    let placeholders = plain.placeholders().collect::<Vec<_>>();
+    for sentence in sentences::segment(txt, &placeholders) {
+        let offset = sentence.start;
+        let (normalized, offsets) = normalize(sub_char_range(txt, sentence));
+        if normalized.is_empty() {
+            continue;
+        }
+        let fixes = checker
+            .cache
+            .fetch_or_insert(&normalized, |normalized| Ok(suggest(checker, normalized)))?;
+        for fix in fixes {
+            let range = match denormalize(&fix.range, &offsets) {
+                Some(range) => (offset + range.start)..(offset + range.end),
+                None => continue,
+            };
+            acc.extend(
+                plain
+                    .find_spans(range)
+                    .into_iter()
+                    .map(|(range, span)| Suggestion {
+                        detector: Detector::NlpRules,
+                        range,
+                        span,
+                        origin: origin.clone(),
+                        replacements: fix.replacements.clone(),
+                        chunk,
+                        description: Some(fix.message.clone()),
+                        rule: Some(fix.rule.clone()),
+                        help: fix.help.clone(),
+                        severity: None,
+                        also: Vec::new(),
+                    }),
+            );
         }
-        let range = start..end;
-        acc.extend(
-            plain
-                .find_spans(range)
-                .into_iter()
-                .map(|(range, span)| Suggestion {
-                    detector: Detector::NlpRules,
-                    range,
-                    span,
-                    origin: origin.clone(),
-                    replacements: replacements.iter().map(|x| x.clone()).collect(),
-                    chunk,
-                    description: Some(message.to_owned()),
-                }),
-        );
     }
 
-    acc
+    Ok(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_by_id_or_prefix() {
+        let entries = vec!["grammar/prp_md_nn".to_owned(), "STYLE".to_owned()];
+        assert!(names(&entries, "GRAMMAR/PRP_MD_NN/2"));
+        assert!(names(&entries, "STYLE/TOO_LONG/1"));
+        assert!(!names(&entries, "GRAMMAR/PRP_VBG/1"));
+        assert!(!names(&entries, "STYLES/TOO_LONG/1"));
+        assert!(!names(&[], "STYLE/TOO_LONG/1"));
+
+        let rules = filtered_rules::<PathBuf>(None).unwrap();
+        let config = crate::config::NlpRulesConfig {
+            disabled_rules: vec!["GRAMMAR".to_owned()],
+            ..Default::default()
+        };
+        let selected = selected_rules(rules.clone(), &config);
+        assert!(selected.rules().len() < rules.rules().len());
+        assert!(selected
+            .rules()
+            .iter()
+            .all(|rule| !rule.id().to_string().starts_with("GRAMMAR/")));
+    }
 }
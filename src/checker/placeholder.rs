@@ -0,0 +1,274 @@
+//! Placeholder documentation.
+//!
+//! Flags doc comments that are empty, only whitespace or only a bare URL,
+//! which are commonly left behind as placeholders and forgotten. Enabled with
+//! `empty-docs` in the `[checker]` table. With `empty-alt`, images without an
+//! alt text are flagged as well, since screen readers have nothing to announce
+//! for them.
+
+use super::{Checker, Selection};
+
+use crate::documentation::CommentVariantCategory;
+use crate::errors::*;
+use crate::{CheckableChunk, Config, ContentOrigin, Detector, Range, Span, Suggestion};
+
+use pulldown_cmark::{Event, Parser, Tag};
+
+/// Rule of empty and whitespace only documentation.
+const EMPTY: &str = "empty-doc";
+/// Rule of documentation that is only a URL.
+const BARE_URL: &str = "bare-url-doc";
+/// Rule of images without alt text.
+const EMPTY_ALT: &str = "empty-alt";
+
+/// Whether findings of `rule` flag placeholder documentation, i.e. empty or
+/// only a URL, which the summary counts separately.
+pub(crate) fn is_placeholder_doc(rule: &str) -> bool {
+    rule == EMPTY || rule == BARE_URL
+}
+
+/// Whether `s` is a single URL, optionally as autolink.
+fn is_bare_url(s: &str) -> bool {
+    let s = s
+        .strip_prefix('<')
+        .and_then(|s| s.strip_suffix('>'))
+        .unwrap_or(s);
+    !s.contains(char::is_whitespace)
+        && ["http://", "https://", "www."]
+            .iter()
+            .any(|scheme| s.starts_with(scheme) && s.len() > scheme.len())
+}
+
+/// The character ranges of the images without alt text within `content`.
+fn empty_alts(content: &str) -> Vec<Range> {
+    let mut acc = Vec::new();
+    let mut image = None;
+    for (event, byte_range) in Parser::new(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Image(..)) => image = Some(true),
+            Event::End(Tag::Image(..)) => {
+                let empty = image.take();
+                if empty == Some(true) {
+                    let start = content[..byte_range.start].chars().count();
+                    acc.push(start..start + content[byte_range].chars().count());
+                }
+            }
+            Event::Text(text) | Event::Code(text) if !text.trim().is_empty() => {
+                if let Some(empty) = image.as_mut() {
+                    *empty = false;
+                }
+            }
+            _ => {}
+        }
+    }
+    acc
+}
+
+pub(crate) struct PlaceholderChecker {
+    /// Flag empty and bare URL documentation.
+    empty_docs: bool,
+    /// Flag images without alt text.
+    empty_alt: bool,
+}
+
+impl PlaceholderChecker {
+    /// Only created if enabled in `config` and not filtered out.
+    pub(crate) fn new(config: &Config, selection: &Selection) -> Option<Self> {
+        if !selection.runs(Self::detector(), &["empty-docs"]) {
+            log::debug!("Checker {} is filtered out.", Self::detector());
+            None
+        } else if config.is_enabled(Self::detector()) {
+            log::debug!("Enabling {} checks.", Self::detector());
+            Some(Self {
+                empty_docs: config.checker.empty_docs,
+                empty_alt: config.checker.empty_alt,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The rules of the enabled checks.
+    pub(crate) fn rules(&self) -> Vec<&'static str> {
+        let mut rules = Vec::new();
+        if self.empty_docs {
+            rules.extend([EMPTY, BARE_URL]);
+        }
+        if self.empty_alt {
+            rules.push(EMPTY_ALT);
+        }
+        rules
+    }
+
+    /// The findings of the images without alt text within `chunk`.
+    fn empty_alt<'s>(origin: &ContentOrigin, chunk: &'s CheckableChunk) -> Vec<Suggestion<'s>> {
+        empty_alts(chunk.as_str())
+            .into_iter()
+            .filter_map(|range| {
+                let spans = chunk.find_spans(range.clone());
+                let first = spans.values().next()?;
+                let last = spans.values().next_back()?;
+                Some(Suggestion {
+                    detector: Self::detector(),
+                    origin: origin.clone(),
+                    chunk,
+                    span: Span {
+                        start: first.start,
+                        end: last.end,
+                    },
+                    range,
+                    replacements: Vec::new(),
+                    description: Some(
+                        "The image has no alt text, describe what it shows.".to_owned(),
+                    ),
+                    rule: Some(EMPTY_ALT.to_owned()),
+                    help: None,
+                    severity: None,
+                    also: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Checker for PlaceholderChecker {
+    type Config = ();
+
+    fn detector() -> Detector {
+        Detector::EmptyDocs
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut suggestions = chunks
+            .iter()
+            .filter(|_| self.empty_docs)
+            .filter(|chunk| chunk.variant().category() == CommentVariantCategory::Doc)
+            .filter_map(|chunk| {
+                let content = chunk.as_str().trim();
+                let (rule, description) = if content.is_empty() {
+                    (EMPTY, "Documentation is empty, a forgotten placeholder?")
+                } else if is_bare_url(content) {
+                    (
+                        BARE_URL,
+                        "Documentation is only a URL, describe what it links to.",
+                    )
+                } else {
+                    return None;
+                };
+                let (_range, first) = chunk.iter().next()?;
+                let (_range, last) = chunk.iter().next_back()?;
+                Some(Suggestion {
+                    detector: Self::detector(),
+                    origin: origin.clone(),
+                    chunk,
+                    span: Span {
+                        start: first.start,
+                        end: last.end,
+                    },
+                    range: 0..chunk.len_in_chars(),
+                    replacements: Vec::new(),
+                    description: Some(description.to_owned()),
+                    rule: Some(rule.to_owned()),
+                    help: None,
+                    severity: None,
+                    also: Vec::new(),
+                })
+            })
+            .collect::<Vec<_>>();
+        if self.empty_alt {
+            for chunk in chunks.iter().filter(|chunk| {
+                matches!(
+                    chunk.variant().category(),
+                    CommentVariantCategory::Doc | CommentVariantCategory::CommonMark
+                )
+            }) {
+                suggestions.extend(Self::empty_alt(origin, chunk));
+            }
+        }
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Documentation;
+
+    #[test]
+    fn placeholders_are_flagged() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            r"///
+struct Empty;
+
+///
+struct Blank;
+
+/// <https://example.com/docs>
+struct Link;
+
+/// See <https://example.com/docs>.
+struct Documented;
+",
+            true,
+            false,
+        );
+        let chunks = docs.get(&origin).unwrap();
+        let checker = PlaceholderChecker {
+            empty_docs: true,
+            empty_alt: false,
+        };
+        let suggestions = checker.check(&origin, chunks).unwrap();
+        let rules = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.span.start.line, suggestion.rule.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![(1, Some(EMPTY)), (4, Some(EMPTY)), (7, Some(BARE_URL))]
+        );
+        assert!(is_bare_url("https://example.com"));
+        assert!(!is_bare_url("https://"));
+        assert!(!is_bare_url("docs at https://example.com"));
+    }
+    #[test]
+    fn images_without_alt_text() {
+        let origin = ContentOrigin::TestEntityCommonMark;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            "A ![logo](logo.png), ![](badge.svg) and\n![ ](a.png \"Title\") but `![](code)`.\n",
+            true,
+            false,
+        );
+        let chunks = docs.get(&origin).unwrap();
+        let checker = PlaceholderChecker {
+            empty_docs: false,
+            empty_alt: true,
+        };
+        let suggestions = checker.check(&origin, chunks).unwrap();
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| {
+                (
+                    crate::util::sub_chars(chunks[0].as_str(), suggestion.range.clone()),
+                    suggestion.span.start.line,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            vec![
+                ("![](badge.svg)".to_owned(), 1),
+                ("![ ](a.png \"Title\")".to_owned(), 2)
+            ]
+        );
+    }
+}
@@ -2,6 +2,223 @@
 
 use crate::Range;
 use fancy_regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Common technical terms mixing letters and digits, compared normalized, see
+/// [`normalize`].
+const DIGIT_WORDS: &[&str] = &[
+    "2FA",
+    "AArch64",
+    "AES128",
+    "AES256",
+    "ARM64",
+    "ARMv7",
+    "ARMv8",
+    "BLAKE2",
+    "BLAKE3",
+    "ChaCha20",
+    "CRC32",
+    "Ed25519",
+    "HTTP2",
+    "HTTP3",
+    "i18n",
+    "IPv4",
+    "IPv6",
+    "k8s",
+    "l10n",
+    "MD5",
+    "md5sum",
+    "OAuth2",
+    "P2P",
+    "Poly1305",
+    "secp256k1",
+    "SHA1",
+    "SHA256",
+    "SHA3",
+    "SHA512",
+    "sha256sum",
+    "UTF-8",
+    "UTF-16",
+    "UTF-32",
+    "wasm32",
+    "Win32",
+    "Win64",
+    "X25519",
+    "x86",
+    "x86_64",
+];
+
+/// Suffixes of ordinal numbers, i.e. `3rd`.
+const ORDINALS: &[&str] = &["st", "nd", "rd", "th"];
+
+/// Lowercase `word` without `-` and `_`, so `UTF-8`, `utf8` and `UTF_8` are
+/// the same word.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Words which mix letters and digits, such as `IPv6`, `SHA256` or `base64`,
+/// which are neither misspelled nor to be split up.
+#[derive(Debug, Clone)]
+pub(crate) struct DigitWords {
+    known: HashSet<String>,
+}
+
+impl DigitWords {
+    /// The builtin table, extended by `extra`.
+    pub(crate) fn new(extra: &[String]) -> Self {
+        let known = DIGIT_WORDS
+            .iter()
+            .copied()
+            .chain(extra.iter().map(String::as_str))
+            .map(normalize)
+            .collect();
+        Self { known }
+    }
+
+    /// Returns `true` iff `word` mixes letters and digits and is either known
+    /// or formed like a technical term. That is letters followed by digits,
+    /// i.e. `RFC3339` or `float32`, or digits followed by letters, i.e. `4K`
+    /// or `64bit`, where the letters are a single one, an acronym or a word
+    /// according to `is_word`. Ordinal numbers such as `3rd` are accepted too.
+    pub(crate) fn accepts(&self, word: &str, is_word: impl Fn(&str) -> bool) -> bool {
+        if !word.contains(|c: char| c.is_ascii_digit())
+            || !word.contains(|c: char| c.is_alphabetic())
+        {
+            return false;
+        }
+        if self.known.contains(&normalize(word)) {
+            return true;
+        }
+        let word = word
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect::<String>();
+        let leading_digits = word.starts_with(|c: char| c.is_ascii_digit());
+        let split = if leading_digits {
+            word.find(|c: char| !c.is_ascii_digit())
+        } else {
+            word.find(|c: char| c.is_ascii_digit())
+        };
+        let (head, tail) = match split {
+            Some(split) => word.split_at(split),
+            None => return false,
+        };
+        let (digits, letters) = if leading_digits {
+            (head, tail)
+        } else {
+            (tail, head)
+        };
+        if !digits.chars().all(|c| c.is_ascii_digit()) || !letters.chars().all(char::is_alphabetic)
+        {
+            return false;
+        }
+        if leading_digits && ORDINALS.contains(&letters) {
+            return true;
+        }
+        letters.chars().count() == 1
+            || (letters.chars().count() <= 5 && letters.chars().all(char::is_uppercase))
+            || is_word(letters)
+    }
+}
+
+/// The capitalization of a word, relative to the known proper nouns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Capitalization<'a> {
+    /// The exact canonical form of a proper noun.
+    Exact,
+    /// A proper noun, but capitalized differently than the canonical form.
+    Variant(&'a str),
+    /// Not a proper noun.
+    Unknown,
+}
+
+/// Proper nouns, such as `GitHub` or `WebAssembly`, which are only accepted
+/// with their exact capitalization, see `[words.proper]`.
+#[derive(Debug, Clone)]
+pub(crate) struct ProperNouns {
+    /// The canonical forms by their lowercase ones.
+    canonical: HashMap<String, String>,
+    /// Flag the variants.
+    pub(crate) flag_variants: bool,
+}
+
+impl ProperNouns {
+    pub(crate) fn new(names: &[String], flag_variants: bool) -> Self {
+        let canonical = names
+            .iter()
+            .map(|name| (name.to_lowercase(), name.clone()))
+            .collect();
+        Self {
+            canonical,
+            flag_variants,
+        }
+    }
+
+    /// How `word` is capitalized, compared to the canonical proper noun.
+    pub(crate) fn capitalization(&self, word: &str) -> Capitalization<'_> {
+        match self.canonical.get(&word.to_lowercase()) {
+            Some(canonical) if canonical == word => Capitalization::Exact,
+            Some(canonical) => Capitalization::Variant(canonical),
+            None => Capitalization::Unknown,
+        }
+    }
+}
+
+/// Suffixes forming the plural of a code identifier, i.e. `` `Vec`s ``.
+const PLURAL_SUFFIXES: &[&str] = &["s", "es"];
+
+/// Returns `true` iff `word`, which starts with `code_len` characters of
+/// inline code, continues with a plural suffix only.
+pub(crate) fn is_code_plural(word: &str, code_len: usize) -> bool {
+    let suffix = word.chars().skip(code_len).collect::<String>();
+    PLURAL_SUFFIXES.contains(&suffix.as_str())
+}
+
+/// The identifier of `identifiers` which `word` is the plural of, if any,
+/// i.e. `Vec` for `Vecs`.
+pub(crate) fn plural_of<'a>(word: &str, identifiers: &'a HashSet<String>) -> Option<&'a str> {
+    PLURAL_SUFFIXES.iter().find_map(|suffix| {
+        let stem = word.strip_suffix(suffix)?;
+        identifiers.get(stem).map(String::as_str)
+    })
+}
+
+/// The character ranges of the sub-words of `word` if it is shaped like a
+/// code identifier, split at `_` and the case changes of `CamelCase`, i.e.
+/// `Token` and `Stream` of `TokenStream` or `HTTP` and `Server` of
+/// `HTTPServer`. None if `word` has a single sub-word.
+pub(crate) fn identifier_parts(word: &str) -> Option<Vec<Range>> {
+    let chars = word.chars().collect::<Vec<_>>();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (idx, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            if start < idx {
+                parts.push(start..idx);
+            }
+            start = idx + 1;
+            continue;
+        }
+        if idx == start || !c.is_uppercase() {
+            continue;
+        }
+        let previous = chars[idx - 1];
+        let acronym_ends =
+            previous.is_uppercase() && chars.get(idx + 1).map_or(false, |c| c.is_lowercase());
+        if previous.is_lowercase() || previous.is_numeric() || acronym_ends {
+            parts.push(start..idx);
+            start = idx;
+        }
+    }
+    if start < chars.len() {
+        parts.push(start..chars.len());
+    }
+    (parts.len() > 1).then(|| parts)
+}
 
 /// Returns `true` iff the replacements contains a variant of `word` without
 /// dashes.
@@ -172,6 +389,83 @@ mod tests {
         assert!(replacements_contain_dashless(WORD, REPLACEMENTS));
     }
 
+    #[test]
+    fn digit_words() {
+        let digit_words = DigitWords::new(&["ARMv9".to_owned()]);
+        let is_word = |word: &str| ["base", "float", "bit"].contains(&word);
+        for word in [
+            "IPv6", "ipv6", "SHA256", "utf8", "UTF-8", "x86_64", "armv9", "base64", "float32",
+            "u8", "RFC3339", "4K", "64bit", "3rd", "21st",
+        ] {
+            assert!(
+                digit_words.accepts(word, is_word),
+                "{} must be accepted",
+                word
+            );
+        }
+        for word in ["wrod5", "IPv", "Word", "42", "3wrods", "ab12cd", "5th1"] {
+            assert!(
+                !digit_words.accepts(word, is_word),
+                "{} must be checked",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn code_plurals() {
+        assert!(is_code_plural("Vecs", 3));
+        assert!(is_code_plural("HashMapes", 7));
+        assert!(!is_code_plural("Vectors", 3));
+        assert!(!is_code_plural("Vec", 3));
+
+        let identifiers = ["Vec".to_owned(), "Box".to_owned()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(plural_of("Vecs", &identifiers), Some("Vec"));
+        assert_eq!(plural_of("Boxes", &identifiers), Some("Box"));
+        assert_eq!(plural_of("Vec", &identifiers), None);
+        assert_eq!(plural_of("vecs", &identifiers), None);
+    }
+
+    #[test]
+    fn identifiers() {
+        let parts = |word: &str| {
+            identifier_parts(word).map(|parts| {
+                parts
+                    .into_iter()
+                    .map(|range| word.chars().skip(range.start).take(range.len()).collect())
+                    .collect::<Vec<String>>()
+            })
+        };
+        assert_eq!(parts("TokenStream").unwrap(), vec!["Token", "Stream"]);
+        assert_eq!(
+            parts("parse_token_tree").unwrap(),
+            vec!["parse", "token", "tree"]
+        );
+        assert_eq!(parts("HTTPServer").unwrap(), vec!["HTTP", "Server"]);
+        assert_eq!(parts("toJSON").unwrap(), vec!["to", "JSON"]);
+        assert_eq!(parts("__init__"), None);
+        assert_eq!(parts("Token"), None);
+        assert_eq!(parts("HTTP"), None);
+        assert_eq!(parts("word"), None);
+    }
+
+    #[test]
+    fn proper_nouns() {
+        let proper = ProperNouns::new(&["GitHub".to_owned(), "WebAssembly".to_owned()], true);
+        assert_eq!(proper.capitalization("GitHub"), Capitalization::Exact);
+        assert_eq!(
+            proper.capitalization("github"),
+            Capitalization::Variant("GitHub")
+        );
+        assert_eq!(
+            proper.capitalization("Webassembly"),
+            Capitalization::Variant("WebAssembly")
+        );
+        assert_eq!(proper.capitalization("GitLab"), Capitalization::Unknown);
+    }
+
     #[test]
     fn transformer() {
         let _ = env_logger::builder()
@@ -0,0 +1,253 @@
+//! Cache of grammar backend responses.
+//!
+//! Boilerplate sentences repeat heavily in generated and templated docs, and
+//! grammar backends are slow or even remote. Their responses are cached per
+//! sentence, keyed by the sentence with its whitespace normalized, in a
+//! bounded LRU which is shared across files. With `[checker] grammar_cache`,
+//! the entries are also kept on disk, so they are shared across runs.
+
+use crate::errors::*;
+use crate::Range;
+
+use hex::ToHex;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::io::Seek;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Number of sentences kept, in memory and on disk.
+const CAPACITY: usize = 16_384;
+
+/// A finding of a grammar backend, relative to the normalized sentence.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Fix {
+    /// Character range within the normalized sentence.
+    pub range: Range,
+    pub message: String,
+    pub replacements: Vec<String>,
+    /// Id of the rule that triggered.
+    pub rule: String,
+    /// Link to documentation about the rule.
+    pub help: Option<String>,
+}
+
+/// `text` with its whitespace runs collapsed into single spaces and trimmed,
+/// and for each of its characters the character offset within `text`.
+pub(crate) fn normalize(text: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    let mut pending = None;
+    for (idx, c) in text.chars().enumerate() {
+        if c.is_whitespace() {
+            pending = pending.or(Some(idx));
+            continue;
+        }
+        if let Some(space) = pending.take() {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+                offsets.push(space);
+            }
+        }
+        normalized.push(c);
+        offsets.push(idx);
+    }
+    (normalized, offsets)
+}
+
+/// Map `range`, within a normalized text, back onto the original text, given
+/// the `offsets` returned by [`normalize`].
+pub(crate) fn denormalize(range: &Range, offsets: &[usize]) -> Option<Range> {
+    let start = *offsets.get(range.start)?;
+    if range.is_empty() {
+        return Some(start..start);
+    }
+    let end = *offsets.get(range.end - 1)? + 1;
+    Some(start..end)
+}
+
+/// Responses of one grammar backend, by normalized sentence.
+pub(crate) struct ResponseCache {
+    /// The most recently used entries last.
+    memory: Mutex<IndexMap<String, Vec<Fix>>>,
+    /// Number of sentences kept.
+    capacity: usize,
+    /// The file the entries are persisted to, if any.
+    file: Option<PathBuf>,
+    /// Whether there are entries which are not persisted yet.
+    dirty: AtomicBool,
+}
+
+impl ResponseCache {
+    /// A cache which is only kept in memory.
+    pub(crate) fn new() -> Self {
+        Self {
+            memory: Mutex::new(IndexMap::new()),
+            capacity: CAPACITY,
+            file: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Keep the entries on disk as well, in a file specific to the `backend`
+    /// and its configuration, given as `fingerprint`.
+    pub(crate) fn with_disk(backend: &str, fingerprint: &str) -> Result<Self> {
+        let cache_dir = super::project_dir()?.cache_dir().join("grammar");
+        fs_err::create_dir_all(&cache_dir)?;
+        let what = format!("{}:{}:{}", backend, env!("CARGO_PKG_VERSION"), fingerprint);
+        let digest = sha2::Sha256::digest(what.as_bytes());
+        let file = cache_dir.join(digest.as_slice().encode_hex::<String>());
+        let entries = load(&file);
+        log::debug!(
+            "Loaded {} cached {} responses from {}",
+            entries.len(),
+            backend,
+            file.display()
+        );
+        Ok(Self {
+            memory: Mutex::new(entries),
+            capacity: CAPACITY,
+            file: Some(file),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// The fixes of `sentence`, which is normalized already, either cached or
+    /// created by `create` and cached.
+    pub(crate) fn fetch_or_insert(
+        &self,
+        sentence: &str,
+        create: impl FnOnce(&str) -> Result<Vec<Fix>>,
+    ) -> Result<Vec<Fix>> {
+        if let Some(fixes) = self.fetch(sentence) {
+            return Ok(fixes);
+        }
+        // the lock is not held while creating, the backend is slow
+        let fixes = create(sentence)?;
+        self.insert(sentence.to_owned(), fixes.clone());
+        Ok(fixes)
+    }
+
+    /// The cached fixes of the normalized `sentence`, if any.
+    pub(crate) fn fetch(&self, sentence: &str) -> Option<Vec<Fix>> {
+        let mut memory = self.memory.lock().unwrap();
+        let (key, fixes) = memory.shift_remove_entry(sentence)?;
+        memory.insert(key, fixes.clone());
+        Some(fixes)
+    }
+
+    /// Cache the `fixes` of the normalized `sentence`.
+    pub(crate) fn insert(&self, sentence: String, fixes: Vec<Fix>) {
+        let mut memory = self.memory.lock().unwrap();
+        memory.insert(sentence, fixes);
+        while memory.len() > self.capacity {
+            memory.shift_remove_index(0);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Merge the entries into the ones on disk, other runs might have stored
+    /// entries in the meantime.
+    fn persist(&self) -> Result<()> {
+        let path = match self.file {
+            Some(ref path) if self.dirty.load(Ordering::Relaxed) => path,
+            _ => return Ok(()),
+        };
+        let file = fs_err::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let mut lock = fd_lock::RwLock::new(file);
+        let mut guard = lock.write()?;
+        let mut merged = load(path);
+        for (sentence, fixes) in self.memory.lock().unwrap().iter() {
+            merged.shift_remove(sentence);
+            merged.insert(sentence.clone(), fixes.clone());
+        }
+        while merged.len() > self.capacity {
+            merged.shift_remove_index(0);
+        }
+        let encoded = bincode::serialize(&merged.into_iter().collect::<Vec<_>>())?;
+        // effectively truncate, but without losing the lock
+        guard.rewind()?;
+        std::io::copy(&mut &encoded[..], &mut *guard)?;
+        let len = guard.stream_position()?;
+        guard.set_len(len)?;
+        Ok(())
+    }
+}
+
+impl Drop for ResponseCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.persist() {
+            log::warn!("Failed to persist the grammar cache: {:?}", e);
+        }
+    }
+}
+
+/// The entries stored at `path`, none if it does not exist or is broken.
+fn load(path: &std::path::Path) -> IndexMap<String, Vec<Fix>> {
+    let content = match fs_err::read(path) {
+        Ok(content) if !content.is_empty() => content,
+        _ => return IndexMap::new(),
+    };
+    match bincode::deserialize::<Vec<(String, Vec<Fix>)>>(&content) {
+        Ok(entries) => entries.into_iter().collect(),
+        Err(e) => {
+            log::warn!("Ignoring broken grammar cache {}: {:?}", path.display(), e);
+            IndexMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_ranges_map_back() {
+        let text = "  A  repeated\n repeated word. ";
+        let (normalized, offsets) = normalize(text);
+        assert_eq!(normalized, "A repeated repeated word.");
+        let range = denormalize(&(2..19), &offsets).unwrap();
+        assert_eq!(
+            text.chars()
+                .skip(range.start)
+                .take(range.len())
+                .collect::<String>(),
+            "repeated\n repeated"
+        );
+        assert_eq!(denormalize(&(24..25), &offsets), Some(28..29));
+        assert_eq!(denormalize(&(25..26), &offsets), None);
+    }
+
+    #[test]
+    fn least_recently_used_are_dropped() {
+        let mut cache = ResponseCache::new();
+        cache.capacity = 2;
+        let fix = |rule: &str| Fix {
+            range: 0..1,
+            message: "Repeated word.".to_owned(),
+            replacements: Vec::new(),
+            rule: rule.to_owned(),
+            help: None,
+        };
+        let fetch = |sentence: &str, rule: &str| {
+            cache
+                .fetch_or_insert(sentence, |_| Ok(vec![fix(rule)]))
+                .unwrap()[0]
+                .rule
+                .clone()
+        };
+        assert_eq!(fetch("One.", "A"), "A");
+        assert_eq!(fetch("Two.", "B"), "B");
+        // a hit makes `One.` the most recently used
+        assert_eq!(fetch("One.", "C"), "A");
+        assert_eq!(fetch("Three.", "D"), "D");
+        assert_eq!(fetch("One.", "E"), "A");
+        assert_eq!(fetch("Two.", "F"), "F");
+    }
+}
@@ -0,0 +1,130 @@
+//! Sentence segmentation for the grammar checker.
+//!
+//! Grammar rules operate on one sentence at a time, a wrong split yields
+//! findings about incomplete sentences. A `.` only ends a sentence if it is
+//! followed by whitespace and the next word does not continue the sentence, so
+//! abbreviations such as `e.g.` or `Dr.`, version numbers like `1.2.3` and
+//! placeholders of inline code never split.
+
+use crate::Range;
+
+/// Abbreviations which are commonly followed by more of the same sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "e.g", "i.e", "etc", "cf", "vs", "viz", "approx", "incl", "resp", "esp", "no", "nr", "fig",
+    "eq", "ch", "sec", "vol", "dr", "mr", "mrs", "ms", "prof", "st", "jr", "sr",
+];
+
+/// Whether the word ending right before the `.` at `dot` is an abbreviation.
+fn is_abbreviation(chars: &[char], dot: usize) -> bool {
+    let start = chars[..dot]
+        .iter()
+        .rposition(|c| c.is_whitespace() || *c == '(')
+        .map_or(0, |idx| idx + 1);
+    let word = chars[start..dot].iter().collect::<String>().to_lowercase();
+    // single letters, i.e. initials or list markers
+    (word.chars().count() == 1 && word.chars().all(char::is_alphabetic))
+        || ABBREVIATIONS.contains(&word.as_str())
+}
+
+/// Split `text` into the char ranges of its sentences, without the separating
+/// whitespace. No sentence ends within any of the `protected` char ranges.
+pub(crate) fn segment(text: &str, protected: &[Range]) -> Vec<Range> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let n = chars.len();
+    let is_protected = |idx: usize| protected.iter().any(|range| range.contains(&idx));
+
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut idx = 0;
+    while idx < n {
+        let c = chars[idx];
+        if !matches!(c, '.' | '!' | '?') || is_protected(idx) {
+            idx += 1;
+            continue;
+        }
+        // include closing quotes and brackets as well as repeated terminators
+        let mut end = idx + 1;
+        while end < n && matches!(chars[end], '.' | '!' | '?' | '"' | '\'' | ')' | ']') {
+            end += 1;
+        }
+        let next = chars[end..].iter().position(|c| !c.is_whitespace());
+        let splits = match next {
+            // `.` followed by more text needs a closer look
+            Some(0) => false,
+            Some(offset) => {
+                let upcoming = chars[end + offset];
+                c != '.'
+                    || !(is_abbreviation(&chars, idx)
+                        || upcoming.is_lowercase()
+                        || upcoming.is_ascii_digit())
+            }
+            None => true,
+        };
+        if splits {
+            sentences.push(start..end);
+            start = end + next.unwrap_or(n - end);
+        }
+        idx = end;
+    }
+    if start < n {
+        let end = n - chars[start..]
+            .iter()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        if start < end {
+            sentences.push(start..end);
+        }
+    }
+    sentences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+
+    fn sentences(text: &str, protected: &[Range]) -> Vec<String> {
+        segment(text, protected)
+            .into_iter()
+            .map(|range| sub_chars(text, range))
+            .collect()
+    }
+
+    #[test]
+    fn plain_sentences() {
+        assert_eq!(
+            sentences("One sentence. Another one!  Why? ", &[]),
+            vec!["One sentence.", "Another one!", "Why?"]
+        );
+        assert_eq!(sentences("No terminator", &[]), vec!["No terminator"]);
+    }
+
+    #[test]
+    fn abbreviations_do_not_split() {
+        assert_eq!(
+            sentences(
+                "Use a buffer, e.g. a Vec. Ask Dr. Ferris, i.e. The crab. Done.",
+                &[]
+            ),
+            vec![
+                "Use a buffer, e.g. a Vec.",
+                "Ask Dr. Ferris, i.e. The crab.",
+                "Done."
+            ]
+        );
+    }
+
+    #[test]
+    fn versions_and_code_do_not_split() {
+        assert_eq!(
+            sentences("Requires version 1.2.3 or later. Fine.", &[]),
+            vec!["Requires version 1.2.3 or later.", "Fine."]
+        );
+        // the placeholder `x. Y` of inline code
+        assert_eq!(
+            sentences("Call x. Y first. Then go.", std::slice::from_ref(&(5..9))),
+            vec!["Call x. Y first.", "Then go."]
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Reusable checkers for embedding.
+//!
+//! Long-running hosts, such as a language server, a daemon or a `mdbook`
+//! preprocessor, check many small inputs. Creating the checkers loads the
+//! dictionaries, which is the expensive part, so a [`CheckerSet`] is created
+//! once and reused for every input.
+//!
+//! Such hosts run for hours, while the extra dictionaries and the config are
+//! edited. [`CheckerSet::reload`] picks the edits up without a restart.
+
+use super::{Checker, Checkers};
+use crate::errors::*;
+use crate::{Suggestion, SuggestionFilter};
+
+use crate::HunspellConfig;
+use crate::{CheckableChunk, Config, ContentOrigin, Detector, Documentation, Range, Span};
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The state of a file on disk, to tell whether it was modified.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The configured checkers, with their dictionaries loaded.
+pub struct CheckerSet {
+    checkers: Checkers,
+    dev_comments: bool,
+    config: Config,
+    /// The file `config` was loaded from, if any.
+    config_path: Option<PathBuf>,
+    /// The files the checkers depend on, as they were when loaded.
+    watched: Vec<(PathBuf, Stamp)>,
+    filter: Option<Arc<dyn SuggestionFilter>>,
+}
+
+impl CheckerSet {
+    /// Create all checkers enabled in `config`, loading their dictionaries.
+    pub fn new(config: &Config) -> Result<Self> {
+        Self::create(config.clone(), None, None)
+    }
+
+    /// Create all checkers enabled in the config at `path`, which is reloaded
+    /// by [`Self::reload`] once it changes.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config = Config::load_from(path)?
+            .ok_or_else(|| eyre!("Config {} does not exist", path.display()))?;
+        Self::create(config, Some(path.to_owned()), None)
+    }
+
+    pub(crate) fn create(
+        config: Config,
+        config_path: Option<PathBuf>,
+        filter: Option<Arc<dyn SuggestionFilter>>,
+    ) -> Result<Self> {
+        let cwd = config.base_dir()?;
+        // the project dictionary is where `learn` and `fix` add words
+        let project_dictionary = config
+            .hunspell
+            .as_ref()
+            .map(|hunspell| hunspell.project_dictionary(&cwd));
+        let watched = config_path
+            .iter()
+            .chain(
+                config
+                    .hunspell
+                    .iter()
+                    .flat_map(HunspellConfig::extra_dictionaries),
+            )
+            .chain(project_dictionary.iter())
+            .map(|path| (path.clone(), stamp(path)))
+            .collect();
+        Ok(Self {
+            checkers: Checkers::new(config.clone())?.with_filter(filter.clone()),
+            dev_comments: config.dev_comments,
+            config,
+            config_path,
+            watched,
+            filter,
+        })
+    }
+
+    /// Pass all suggestions through `filter` before they are returned.
+    pub fn with_filter(self, filter: impl SuggestionFilter + 'static) -> Self {
+        let filter: Arc<dyn SuggestionFilter> = Arc::new(filter);
+        Self {
+            checkers: self.checkers.with_filter(Some(filter.clone())),
+            filter: Some(filter),
+            ..self
+        }
+    }
+
+    /// The checkers as they are currently loaded.
+    pub(crate) fn checkers(&self) -> &Checkers {
+        &self.checkers
+    }
+
+    /// Reload the checkers if the config, one of the extra dictionaries or the
+    /// project dictionary changed on disk since they were loaded.
+    ///
+    /// Returns whether they were reloaded, in which case the host should check
+    /// its open documents again, the findings might differ now. If the
+    /// reload fails, i.e. due to a config which is only partially edited, the
+    /// previous checkers are kept and the error is returned, once per change.
+    pub fn reload(&mut self) -> Result<bool> {
+        let config_path = self.config_path.clone();
+        let config = self.config.clone();
+        self.reload_with(move || match config_path {
+            Some(ref path) => Config::load_from(path)?
+                .ok_or_else(|| eyre!("Config {} does not exist", path.display())),
+            None => Ok(config),
+        })
+    }
+
+    /// Like [`Self::reload`], with the changed config obtained from `load`,
+    /// i.e. with the command line flags applied again.
+    pub(crate) fn reload_with(&mut self, load: impl FnOnce() -> Result<Config>) -> Result<bool> {
+        let changed = self
+            .watched
+            .iter()
+            .filter(|(path, stamp)| self::stamp(path) != *stamp)
+            .map(|(path, _stamp)| path.clone())
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return Ok(false);
+        }
+        for path in &changed {
+            log::info!("Reloading the checkers, {} changed", path.display());
+        }
+        for (path, stamp) in &mut self.watched {
+            *stamp = self::stamp(path);
+        }
+        *self = Self::create(load()?, self.config_path.clone(), self.filter.clone())?;
+        Ok(true)
+    }
+
+    /// Check `text`, which is parsed according to `origin`, i.e. as rust
+    /// source for [`ContentOrigin::RustSourceFile`] and as markdown for
+    /// [`ContentOrigin::CommonMarkFile`]. Nothing is read from disk.
+    pub fn check_str(&self, text: &str, origin: ContentOrigin) -> Result<Checked> {
+        let documentation =
+            Documentation::load_from_str(origin.clone(), text, true, self.dev_comments);
+        let chunks = documentation
+            .into_iter()
+            .next()
+            .map(|(_origin, chunks)| chunks)
+            .unwrap_or_default();
+        self.check_chunks(origin, chunks)
+    }
+
+    /// Check chunks extracted by an alternative front-end, see
+    /// [`Documentation::add_chunks`].
+    pub fn check_chunks(
+        &self,
+        origin: ContentOrigin,
+        chunks: Vec<CheckableChunk>,
+    ) -> Result<Checked> {
+        let findings = self
+            .checkers
+            .check(&origin, &chunks)?
+            .into_iter()
+            .map(|suggestion| {
+                let chunk = chunks
+                    .iter()
+                    .position(|chunk| std::ptr::eq(chunk, suggestion.chunk))
+                    .expect("Suggestions refer to the checked chunks. qed");
+                Finding {
+                    chunk,
+                    detector: suggestion.detector,
+                    span: suggestion.span,
+                    range: suggestion.range,
+                    replacements: suggestion.replacements,
+                    description: suggestion.description,
+                    rule: suggestion.rule,
+                    help: suggestion.help,
+                }
+            })
+            .collect();
+        Ok(Checked {
+            origin,
+            chunks,
+            findings,
+        })
+    }
+}
+
+/// A suggestion, referring to its chunk by index.
+struct Finding {
+    chunk: usize,
+    detector: Detector,
+    span: Span,
+    range: Range,
+    replacements: Vec<String>,
+    description: Option<String>,
+    rule: Option<String>,
+    help: Option<String>,
+}
+
+/// The outcome of [`CheckerSet::check_str`], owning the chunks the suggestions
+/// relate to.
+pub struct Checked {
+    origin: ContentOrigin,
+    chunks: Vec<CheckableChunk>,
+    findings: Vec<Finding>,
+}
+
+impl Checked {
+    /// The checked chunks.
+    pub fn chunks(&self) -> &[CheckableChunk] {
+        &self.chunks
+    }
+
+    /// The number of suggestions.
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Whether no checker had any suggestion.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// All suggestions, in the order of the chunks.
+    pub fn suggestions(&self) -> Vec<Suggestion<'_>> {
+        self.findings
+            .iter()
+            .map(|finding| Suggestion {
+                detector: finding.detector,
+                origin: self.origin.clone(),
+                chunk: &self.chunks[finding.chunk],
+                span: finding.span,
+                range: finding.range.clone(),
+                replacements: finding.replacements.clone(),
+                description: finding.description.clone(),
+                rule: finding.rule.clone(),
+                help: finding.help.clone(),
+                severity: None,
+                also: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "hunspell"))]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, sub_chars, LineColumn};
+
+    #[test]
+    fn reuse_for_several_inputs() {
+        let config = Config {
+            nlprules: None,
+            ..Config::default()
+        };
+        let set = CheckerSet::new(&config).unwrap();
+
+        let checked = set
+            .check_str(
+                chyrp_up!("A speling mistake."),
+                ContentOrigin::TestEntityRust,
+            )
+            .unwrap();
+        assert_eq!(checked.chunks().len(), 1);
+        let words = checked
+            .suggestions()
+            .iter()
+            .map(|suggestion| sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec!["speling"]);
+
+        let checked = set
+            .check_str(
+                "# Title\n\nNo mistake here.\n",
+                ContentOrigin::TestEntityCommonMark,
+            )
+            .unwrap();
+        assert!(checked.is_empty());
+    }
+
+    #[test]
+    fn hyphenated_at_line_end() {
+        let config = Config {
+            nlprules: None,
+            ..Config::default()
+        };
+        let set = CheckerSet::new(&config).unwrap();
+        let checked = set
+            .check_str(
+                "/// A word that is hyphen-\n/// ated across lines, and a speling mis-\n/// take.\nstruct X;\n",
+                ContentOrigin::TestEntityRust,
+            )
+            .unwrap();
+        let words = checked
+            .suggestions()
+            .iter()
+            .map(|suggestion| sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec!["speling"]);
+    }
+
+    #[test]
+    fn chunks_of_other_front_ends() {
+        let config = Config {
+            nlprules: None,
+            ..Config::default()
+        };
+        let set = CheckerSet::new(&config).unwrap();
+        let origin = ContentOrigin::TestEntityRust;
+        let span = Span {
+            start: LineColumn { line: 3, column: 7 },
+            end: LineColumn {
+                line: 3,
+                column: 24,
+            },
+        };
+        let mut documentation = Documentation::new();
+        documentation
+            .add_chunks(
+                origin.clone(),
+                vec![("A speling mistake.".to_owned(), span)],
+            )
+            .unwrap();
+        // the text does not fit the span
+        assert!(documentation
+            .add_chunks(origin.clone(), vec![("Too long.".to_owned(), span)])
+            .is_err());
+
+        let chunks = documentation.into_iter().next().unwrap().1;
+        let checked = set.check_chunks(origin, chunks).unwrap();
+        let suggestions = checked.suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            suggestions[0].span,
+            Span {
+                start: LineColumn { line: 3, column: 9 },
+                end: LineColumn {
+                    line: 3,
+                    column: 15
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn reload_on_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let dictionary = dir.join("custom.dic");
+        fs::write(&dictionary, "1\ncrate\n").unwrap();
+        let path = dir.join("spellcheck.toml");
+        let project = dir.join("project.txt");
+        let config = |extra: &str| {
+            format!(
+                "[Hunspell]\nuse_builtin = true\nsearch_dirs = [\".\"]\nextra_dictionaries = [\"custom.dic\"]\nproject_dictionary = {:?}\n{}",
+                project, extra
+            )
+        };
+        fs::write(&path, config("")).unwrap();
+
+        let mut set = CheckerSet::from_path(&path).unwrap();
+        let text = chyrp_up!("The frobnicator has a speling mistake.");
+        let words = |set: &CheckerSet| {
+            set.check_str(text, ContentOrigin::TestEntityRust)
+                .unwrap()
+                .suggestions()
+                .iter()
+                .map(|suggestion| sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(words(&set), vec!["frobnicator", "speling"]);
+        assert!(!set.reload().unwrap());
+
+        fs::write(&dictionary, "2\ncrate\nfrobnicator\n").unwrap();
+        assert!(set.reload().unwrap());
+        assert_eq!(words(&set), vec!["speling"]);
+
+        // a broken config keeps the previous checkers
+        fs::write(&path, config("[[broken")).unwrap();
+        assert!(set.reload().is_err());
+        assert!(!set.reload().unwrap());
+        assert_eq!(words(&set), vec!["speling"]);
+
+        // learning a word is picked up as well
+        fs::write(&path, config("")).unwrap();
+        assert!(set.reload().unwrap());
+        crate::dict::learn(&project, &["speling".to_owned()]).unwrap();
+        assert!(set.reload().unwrap());
+        assert!(words(&set).is_empty());
+    }
+
+    /// Drops the allowed words, describes all others.
+    struct Allowlist(&'static [&'static str]);
+
+    impl SuggestionFilter for Allowlist {
+        fn filter<'s>(&self, mut suggestion: Suggestion<'s>) -> Option<Suggestion<'s>> {
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            if self.0.contains(&word.as_str()) {
+                return None;
+            }
+            suggestion.description = Some("Not on the allowlist.".to_owned());
+            Some(suggestion)
+        }
+    }
+
+    #[test]
+    fn filter_drops_and_rewrites() {
+        let config = Config {
+            nlprules: None,
+            ..Config::default()
+        };
+        let set = CheckerSet::new(&config)
+            .unwrap()
+            .with_filter(Allowlist(&["Gizmoz"]));
+        let checked = set
+            .check_str(
+                chyrp_up!("Gizmoz has a speling mistake."),
+                ContentOrigin::TestEntityRust,
+            )
+            .unwrap();
+        let suggestions = checked.suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            sub_chars(suggestions[0].chunk.as_str(), suggestions[0].range.clone()),
+            "speling"
+        );
+        assert_eq!(
+            suggestions[0].description.as_deref(),
+            Some("Not on the allowlist.")
+        );
+    }
+}
@@ -145,8 +145,30 @@ pub(crate) fn rules<P: AsRef<Path> + Clone>(override_path: Option<P>) -> Result<
     }
 }
 
+use crate::util::sub_chars;
 use crate::Range;
 
+/// The word hyphenated at the end of a line, if the words at `first` and
+/// `second` are its parts, i.e. `hyphen-` and `ated` on the next line.
+pub(crate) fn soft_wrapped(text: &str, first: &Range, second: &Range) -> Option<String> {
+    if first.start >= second.start {
+        return None;
+    }
+    let leading = sub_chars(text, first.start..second.start);
+    let trimmed = leading.trim_end();
+    let line_breaks = leading[trimmed.len()..].matches('\n').count();
+    let head = trimmed.strip_suffix('-')?;
+    let tail = sub_chars(text, second.clone());
+    if line_breaks != 1
+        || !head.ends_with(char::is_alphanumeric)
+        || head.contains(char::is_whitespace)
+        || !tail.starts_with(char::is_lowercase)
+    {
+        return None;
+    }
+    Some(head.to_owned() + &tail)
+}
+
 pub(crate) fn apply_tokenizer<'t, 'z>(
     tokenizer: &'t Arc<Tokenizer>,
     text: &'z str,
@@ -254,6 +276,22 @@ mod tests {
             });
     }
 
+    #[test]
+    fn soft_wrapped_words() {
+        let text = "hyphen-\nated, well-\n\nformed, semi- colon and dash -\nless";
+        assert_eq!(
+            soft_wrapped(text, &(0..7), &(8..12)).as_deref(),
+            Some("hyphenated")
+        );
+        assert_eq!(
+            soft_wrapped(text, &(0..6), &(8..12)).as_deref(),
+            Some("hyphenated")
+        );
+        assert_eq!(soft_wrapped(text, &(14..19), &(21..27)), None);
+        assert_eq!(soft_wrapped(text, &(29..34), &(35..40)), None);
+        assert_eq!(soft_wrapped(text, &(45..49), &(52..56)), None);
+    }
+
     #[test]
     fn tokenize_for_abbrev_short() {
         let tok = tokenizer::<PathBuf>(None).unwrap();
@@ -0,0 +1,294 @@
+//! WebAssembly checker plugins
+//!
+//! Loads checker modules compiled to WebAssembly and executes them in a
+//! sandbox. The module has no access to anything but the host API below, and
+//! its execution is bounded by a configurable amount of fuel.
+//!
+//! The module must export
+//!
+//! * `memory`
+//! * `alloc(len: i32) -> i32` returning a pointer to `len` writable bytes
+//! * `check(ptr: i32, len: i32)` which is called once per chunk with the UTF-8
+//!   encoded plain content
+//!
+//! and may import from module `spellcheck`
+//!
+//! * `emit(start: i32, end: i32, msg_ptr: i32, msg_len: i32)` to report a
+//!   finding for the character range `start..end` of the current chunk with an
+//!   optional message
+//! * `suggest(ptr: i32, len: i32)` to attach a replacement to the last emitted
+//!   finding
+
+use super::{Checker, Detector, Suggestion};
+
+use crate::config::WasmCheckerConfig;
+use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
+use crate::errors::*;
+use crate::Range;
+
+use fs_err as fs;
+use wasmi::{Caller, Config, Engine, Extern, Linker, Memory, Module, Store};
+
+const HOST_MODULE: &str = "spellcheck";
+
+/// A single finding, as emitted by the module.
+#[derive(Debug, Default)]
+struct Finding {
+    range: Range,
+    message: Option<String>,
+    replacements: Vec<String>,
+}
+
+/// Read a UTF-8 string from the module's memory.
+fn read_str(caller: &Caller<'_, Vec<Finding>>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let mut buf = vec![0_u8; usize::try_from(len).ok()?];
+    memory
+        .read(caller, usize::try_from(ptr).ok()?, &mut buf)
+        .ok()?;
+    String::from_utf8(buf).ok()
+}
+
+pub struct WasmChecker {
+    name: String,
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+}
+
+impl WasmChecker {
+    pub fn new(config: &<Self as Checker>::Config) -> Result<Self> {
+        let name = config.name();
+        let bytes = fs::read(&config.module)?;
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, &bytes[..])
+            .map_err(|e| eyre!("Failed to load wasm checker {}: {}", name, e))?;
+        Ok(Self {
+            name,
+            engine,
+            module,
+            fuel: config.fuel,
+        })
+    }
+
+    fn linker(&self) -> Result<Linker<Vec<Finding>>> {
+        let mut linker = Linker::<Vec<Finding>>::new(&self.engine);
+        linker
+            .func_wrap(
+                HOST_MODULE,
+                "emit",
+                |mut caller: Caller<'_, Vec<Finding>>,
+                 start: i32,
+                 end: i32,
+                 msg_ptr: i32,
+                 msg_len: i32| {
+                    let message = if msg_len > 0 {
+                        read_str(&caller, msg_ptr, msg_len)
+                    } else {
+                        None
+                    };
+                    let range = usize::try_from(start).unwrap_or_default()
+                        ..usize::try_from(end).unwrap_or_default();
+                    caller.data_mut().push(Finding {
+                        range,
+                        message,
+                        replacements: Vec::new(),
+                    });
+                },
+            )
+            .map_err(|e| eyre!("{}", e))?;
+        linker
+            .func_wrap(
+                HOST_MODULE,
+                "suggest",
+                |mut caller: Caller<'_, Vec<Finding>>, ptr: i32, len: i32| {
+                    if let Some(replacement) = read_str(&caller, ptr, len) {
+                        if let Some(finding) = caller.data_mut().last_mut() {
+                            finding.replacements.push(replacement);
+                        }
+                    }
+                },
+            )
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(linker)
+    }
+
+    /// Execute the module for each chunk, returning the findings per chunk.
+    fn execute(&self, contents: &[&str]) -> Result<Vec<Vec<Finding>>> {
+        let mut store = Store::new(&self.engine, Vec::<Finding>::new());
+        store.add_fuel(self.fuel).map_err(|e| eyre!("{}", e))?;
+        let instance = self
+            .linker()?
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| eyre!("Failed to instantiate wasm checker {}: {}", self.name, e))?;
+
+        let memory: Memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| eyre!("Wasm checker {} does not export `memory`", self.name))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| eyre!("Wasm checker {} lacks `alloc`: {}", self.name, e))?;
+        let check = instance
+            .get_typed_func::<(i32, i32), ()>(&store, "check")
+            .map_err(|e| eyre!("Wasm checker {} lacks `check`: {}", self.name, e))?;
+
+        let mut acc = Vec::with_capacity(contents.len());
+        for content in contents {
+            let len = i32::try_from(content.len())?;
+            let ptr = alloc
+                .call(&mut store, len)
+                .map_err(|e| eyre!("Wasm checker {} failed: {}", self.name, e))?;
+            memory
+                .write(&mut store, usize::try_from(ptr)?, content.as_bytes())
+                .map_err(|e| eyre!("Wasm checker {} returned a bad pointer: {}", self.name, e))?;
+            check
+                .call(&mut store, (ptr, len))
+                .map_err(|e| eyre!("Wasm checker {} failed: {}", self.name, e))?;
+            acc.push(std::mem::take(store.data_mut()));
+        }
+        Ok(acc)
+    }
+}
+
+impl Checker for WasmChecker {
+    type Config = WasmCheckerConfig;
+
+    fn detector() -> Detector {
+        Detector::Wasm
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let plains = chunks
+            .iter()
+            .map(CheckableChunk::erase_cmark)
+            .collect::<Vec<_>>();
+        let contents = plains.iter().map(PlainOverlay::as_str).collect::<Vec<_>>();
+        let findings = self.execute(&contents)?;
+
+        let mut acc = Vec::new();
+        for ((chunk, plain), findings) in chunks.iter().zip(plains.iter()).zip(findings) {
+            let char_count = plain.as_str().chars().count();
+            for finding in findings {
+                if finding.range.is_empty() || finding.range.end > char_count {
+                    log::warn!(
+                        "Wasm checker {} reported an invalid range {:?}, ignoring",
+                        self.name,
+                        finding.range
+                    );
+                    continue;
+                }
+                let description = finding
+                    .message
+                    .map(|message| format!("{}: {}", self.name, message));
+                for (range, span) in plain.find_spans(finding.range) {
+                    acc.push(Suggestion {
+                        detector: Detector::Wasm,
+                        origin: origin.clone(),
+                        chunk,
+                        span,
+                        range,
+                        replacements: finding.replacements.clone(),
+                        description: description.clone(),
+                        rule: None,
+                        help: None,
+                        severity: None,
+                        also: Vec::new(),
+                    });
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+    use crate::{Documentation, LineColumn, Span};
+
+    /// Flags the first five characters of every chunk.
+    const FLAG_FIRST_WORD: &str = r#"
+(module
+  (import "spellcheck" "emit" (func $emit (param i32 i32 i32 i32)))
+  (import "spellcheck" "suggest" (func $suggest (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "Bad wordgood")
+  (func (export "alloc") (param i32) (result i32)
+    i32.const 1024)
+  (func (export "check") (param i32 i32)
+    i32.const 0
+    i32.const 5
+    i32.const 0
+    i32.const 8
+    call $emit
+    i32.const 8
+    i32.const 4
+    call $suggest))
+"#;
+
+    const SPIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32)
+    i32.const 1024)
+  (func (export "check") (param i32 i32)
+    (loop $forever (br $forever))))
+"#;
+
+    fn checker(wat: &str, fuel: u64) -> WasmChecker {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("checker.wasm");
+        fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        let checker = WasmChecker::new(&WasmCheckerConfig {
+            name: Some("Terms".to_owned()),
+            module: path.clone(),
+            fuel,
+        })
+        .unwrap();
+        checker
+    }
+
+    #[test]
+    fn findings_are_mapped_back() {
+        const SOURCE: &str = fluff_up!(["Frobs are *great*."]);
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+
+        let suggestions = checker(FLAG_FIRST_WORD, 10_000)
+            .check(&origin, chunks)
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.replacements, vec!["good".to_owned()]);
+        assert_eq!(suggestion.description.as_deref(), Some("Terms: Bad word"));
+        assert_eq!(
+            suggestion.span,
+            Span {
+                start: LineColumn { line: 1, column: 4 },
+                end: LineColumn { line: 1, column: 8 },
+            }
+        );
+    }
+
+    #[test]
+    fn runaway_module_runs_out_of_fuel() {
+        const SOURCE: &str = fluff_up!(["Whatever."]);
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        let chunks = docs.get(&origin).unwrap();
+
+        assert!(checker(SPIN, 10_000).check(&origin, chunks).is_err());
+    }
+}
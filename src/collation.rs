@@ -0,0 +1,132 @@
+//! Locale aware ordering of replacement candidates.
+//!
+//! Approximates the multi level comparison of the unicode collation algorithm:
+//! base letters are compared first, then accents, then case. Languages which
+//! treat some accented letters as letters of their own, i.e. swedish `ä` after
+//! `z`, are tailored.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Letters which sort as separate letters, directly after the given letter.
+type Tailoring = &'static [(char, char)];
+
+const NORDIC_SV: Tailoring = &[('å', 'z'), ('ä', 'z'), ('ö', 'z')];
+const NORDIC_DA: Tailoring = &[('æ', 'z'), ('ø', 'z'), ('å', 'z')];
+const SPANISH: Tailoring = &[('ñ', 'n')];
+const CZECH: Tailoring = &[('č', 'c'), ('ř', 'r'), ('š', 's'), ('ž', 'z')];
+const POLISH: Tailoring = &[
+    ('ą', 'a'),
+    ('ć', 'c'),
+    ('ę', 'e'),
+    ('ł', 'l'),
+    ('ń', 'n'),
+    ('ó', 'o'),
+    ('ś', 's'),
+    ('ź', 'z'),
+    ('ż', 'z'),
+];
+
+/// Sort key of a single string, compared level by level.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SortKey {
+    primary: Vec<u32>,
+    secondary: Vec<u32>,
+    tertiary: Vec<bool>,
+}
+
+/// Compares strings according to the conventions of one language.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Collator {
+    tailoring: Tailoring,
+}
+
+impl Collator {
+    /// Create a collator for an ISO 639-1 language code, i.e. `de`.
+    pub(crate) fn new(lang: &str) -> Self {
+        let tailoring = match lang {
+            "sv" | "fi" => NORDIC_SV,
+            "da" | "nb" | "nn" | "no" => NORDIC_DA,
+            "es" => SPANISH,
+            "cs" | "sk" => CZECH,
+            "pl" => POLISH,
+            _ => &[],
+        };
+        Self { tailoring }
+    }
+
+    fn sort_key(&self, s: &str) -> SortKey {
+        // leave room between two letters for tailored ones
+        const STRIDE: u32 = 16;
+
+        let mut key = SortKey {
+            primary: Vec::with_capacity(s.len()),
+            secondary: Vec::with_capacity(s.len()),
+            tertiary: Vec::with_capacity(s.len()),
+        };
+        for c in s.chars() {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            key.tertiary.push(c != lower);
+            let tailored = self
+                .tailoring
+                .iter()
+                .enumerate()
+                .find(|(_, (letter, _))| *letter == lower);
+            if let Some((idx, (_, after))) = tailored {
+                let offset = u32::try_from(idx).unwrap_or(0) + 1;
+                key.primary.push(u32::from(*after) * STRIDE + offset);
+                key.secondary.push(0);
+                continue;
+            }
+            let mut marks = 0;
+            for decomposed in std::iter::once(lower).nfd() {
+                if is_combining_mark(decomposed) {
+                    marks += u32::from(decomposed);
+                } else {
+                    key.primary.push(u32::from(decomposed) * STRIDE);
+                }
+            }
+            key.secondary.push(marks);
+        }
+        key
+    }
+
+    /// Sort the candidates in place.
+    pub(crate) fn sort(&self, candidates: &mut [String]) {
+        candidates.sort_by_cached_key(|candidate| self.sort_key(candidate));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(lang: &str, words: &[&str]) -> Vec<String> {
+        let mut words = words.iter().map(|w| (*w).to_owned()).collect::<Vec<_>>();
+        Collator::new(lang).sort(&mut words);
+        words
+    }
+
+    #[test]
+    fn german_umlauts_sort_with_base_letter() {
+        assert_eq!(
+            sorted("de", &["Zug", "Äpfel", "apfel", "Bar", "Apfel"]),
+            vec!["apfel", "Apfel", "Äpfel", "Bar", "Zug"]
+        );
+    }
+
+    #[test]
+    fn swedish_umlauts_sort_after_z() {
+        assert_eq!(
+            sorted("sv", &["ödla", "zebra", "äpple", "apa", "åka"]),
+            vec!["apa", "zebra", "åka", "äpple", "ödla"]
+        );
+    }
+
+    #[test]
+    fn spanish_enye() {
+        assert_eq!(
+            sorted("es", &["ñu", "oso", "nube"]),
+            vec!["nube", "ñu", "oso"]
+        );
+    }
+}
@@ -7,7 +7,9 @@ use itertools::Itertools;
 use serde::Deserialize;
 use std::str::FromStr;
 
-use crate::Action;
+use crate::checker::RunFilter;
+use crate::dict::DictFormat;
+use crate::{Action, FailOn, Reporter, Thresholds};
 
 use super::Config;
 
@@ -84,18 +86,87 @@ impl FromStr for MultipleCheckerTypes {
 #[error("Unknown checker type variant: {0}")]
 pub struct UnknownCheckerTypeVariant(String);
 
-#[derive(clap::Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+/// How the findings of `check` are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// The built-in, human readable format.
+    Human,
+    /// Rendered with a user provided template, see `--template`.
+    Template,
+    /// A JSON document with all findings.
+    Json,
+    /// A SARIF 2.1.0 log, for GitHub code scanning.
+    Sarif,
+    /// GitHub Actions workflow commands, one annotation per finding.
+    GitHub,
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownOutputFormat;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        Ok(match s.as_str() {
+            "human" => Self::Human,
+            "template" => Self::Template,
+            "json" => Self::Json,
+            "sarif" => Self::Sarif,
+            "github" => Self::GitHub,
+            _other => return Err(UnknownOutputFormat(s)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Unknown output format: {0}, expected `human`, `template`, `json`, `sarif` or `github`")]
+pub struct UnknownOutputFormat(String);
+
+/// How `fix` picks the fixes without asking.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ApplyStrategy {
+    /// The first replacement of every finding.
+    First,
+    /// The fixes of a decisions file, written with `--decisions`.
+    From(PathBuf),
+}
+
+/// The description of `--help` and the man page.
+const LONG_ABOUT: &str = "Checks the spelling and grammar of the documentation of a crate: doc \
+comments, the `README.md` and other markdown files referenced by the manifest, and with \
+`--dev-comments` regular comments as well.
+
+The findings of `hunspell` and `nlprules` are reported by default. `fix` walks through them \
+interactively, `reflow` rewraps the doc comments to the configured width.
+
+The configuration is looked up in `.config/spellcheck.toml` of the crate or workspace, then the \
+user configuration directory, unless given with `--cfg`. `cargo spellcheck config` prints the \
+defaults.";
+
+/// The examples of `--help` and the man page.
+const EXAMPLES: &str = "Examples:
+  cargo spellcheck                      Check the crate in the current directory
+  cargo spellcheck check -r src/        Check the files beneath src/
+  cargo spellcheck fix                  Fix the findings interactively
+  cargo spellcheck check --code 1       Exit with 1 on findings, i.e. in CI
+  cargo spellcheck config --user        Write the default user configuration";
+
+#[derive(clap::Parser, Debug, Clone)]
+#[clap(author, version, about, long_about = LONG_ABOUT, after_long_help = EXAMPLES)]
 #[clap(rename_all = "kebab-case")]
 #[clap(subcommand_negates_reqs(true))]
 pub struct Args {
-    #[clap(short, long, global(true))]
-    /// Provide a configuration.
+    #[clap(short, long, global(true), visible_alias = "config")]
+    /// Provide a configuration, `-` reads it from the standard input, except
+    /// for `lsp`. Bypasses the discovery of configuration files.
     pub cfg: Option<PathBuf>,
 
     #[clap(flatten)]
     pub verbosity: clap_verbosity_flag::Verbosity,
 
+    #[clap(long, global(true), value_name = "LANG")]
+    /// Language of the prompts and messages, i.e. `de`, defaults to the one
+    /// of the locale.
+    pub ui_lang: Option<String>,
+
     // is required, but we use `subcommand_negates_reqs`, so it's not
     // when a command exists
     #[clap(flatten)]
@@ -106,13 +177,33 @@ pub struct Args {
     /// Alt for `cargo spellcheck fix` [deprecated].
     pub fix: bool,
 
+    #[clap(long, value_name = "WORD")]
+    /// Show how the given word was resolved by earlier `fix` runs.
+    pub show_history: Option<String>,
+
+    #[clap(long)]
+    /// Show how often earlier `fix` runs picked the first replacement
+    /// candidate of each checker, a later one or custom input.
+    pub stats: bool,
+
     #[clap(subcommand)]
     /// Available sub-commands.
     pub command: Option<Sub>,
+
+    #[clap(skip)]
+    /// The configuration read with `--cfg -`, kept for reloading it, since the
+    /// standard input can only be read once. Shared by the clones.
+    stdin_config: std::sync::Arc<std::sync::Mutex<Option<String>>>,
+
+    #[clap(skip)]
+    /// The directory of the `--manifest-path` manifest, see
+    /// [`Args::rebase_on_manifest`].
+    base_dir: Option<PathBuf>,
 }
 
-#[derive(Debug, PartialEq, Eq, clap::Parser)]
+#[derive(Debug, Clone, PartialEq, Eq, clap::Parser)]
 #[clap(rename_all = "kebab-case")]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Common {
     #[clap(short, long)]
     /// Recurse based on the current directory, or all given
@@ -124,6 +215,14 @@ pub struct Common {
     /// Execute the given subset of checkers.
     pub checkers: Option<MultipleCheckerTypes>,
 
+    #[clap(long, value_delimiter = ',')]
+    /// Only run the given checkers or rules, i.e. `hunspell` or `dup-word`.
+    pub only: Vec<String>,
+
+    #[clap(long, value_delimiter = ',')]
+    /// Do not run the given checkers or rules.
+    pub skip: Vec<String>,
+
     #[clap(short, long)]
     /// Do not check the referenced key `readme=` or default `README.md`.
     pub skip_readme: bool,
@@ -140,11 +239,265 @@ pub struct Common {
     /// Return code of the application if spelling mistakes were found.
     pub code: u8,
 
-    /// A list of files and directories to check. See `--recursive`.
+    #[clap(long, value_name = "N")]
+    /// Fail if more than `N` errors, i.e. spelling mistakes, are found.
+    pub max_errors: Option<usize>,
+
+    #[clap(long, value_name = "N")]
+    /// Fail if more than `N` warnings, i.e. grammar findings, are found.
+    pub max_warnings: Option<usize>,
+
+    #[clap(long, value_name = "N")]
+    /// Fail if more than `N` findings of any severity are found.
+    pub max_findings: Option<usize>,
+
+    #[clap(long, value_name = "SEVERITY")]
+    /// Fail on any finding of the given severity or worse, `errors` or
+    /// `warnings`, or never with `none`.
+    pub fail_on: Option<FailOn>,
+
+    #[clap(long)]
+    /// Run as GitHub Action: only check the changed files, unless paths are
+    /// given, and report with annotations, a job summary and step outputs.
+    pub action: bool,
+
+    #[clap(long)]
+    /// Fail on files that could not be parsed, missing targets and unknown
+    /// code fence info strings instead of skipping them with a warning.
+    pub strict: bool,
+
+    #[clap(long)]
+    /// Output format of the findings, `human`, `template`, `json`, `sarif` or
+    /// `github`. Defaults to `github` within GitHub Actions, `human` otherwise.
+    pub format: Option<OutputFormat>,
+
+    #[clap(long)]
+    /// Handlebars template to render the findings with, for `--format
+    /// template`.
+    pub template: Option<PathBuf>,
+
+    #[clap(long, value_name = "DIR")]
+    /// Write the plain text of every chunk, as passed to the checkers, and a
+    /// sidecar file with its spans to `DIR`.
+    pub dump_extracted: Option<PathBuf>,
+
+    #[clap(long, value_name = "PATH")]
+    /// Path to the `Cargo.toml` to check instead of the one of the current
+    /// directory. Discovery, the configuration lookup and relative `[files]`
+    /// patterns are resolved against its directory.
+    pub manifest_path: Option<PathBuf>,
+
+    #[clap(long, value_name = "GLOB")]
+    /// Also check the markdown files matching `GLOB`, relative to the
+    /// current directory, i.e. `docs/**/*.md`. May be repeated.
+    pub markdown: Vec<String>,
+
+    #[clap(long, value_name = "GLOB")]
+    /// Exclude the files and directories matching `GLOB`, relative to the
+    /// current directory, i.e. `src/generated/*.rs`. May be repeated.
+    pub ignore: Vec<String>,
+
+    #[clap(long, value_name = "PATTERN")]
+    /// Only check the items whose path matches `PATTERN`, i.e.
+    /// `my_crate::net::*` for a module and everything in it. May be repeated.
+    pub item_path: Vec<String>,
+
+    #[clap(long, value_name = "REF")]
+    /// Only check the lines changed relative to the git ref `REF`, i.e.
+    /// `origin/main`, uncommitted changes included.
+    pub diff_base: Option<String>,
+
+    #[clap(long)]
+    /// Check every file, rather than taking the findings of the files which
+    /// did not change since the last run from the cache.
+    pub no_cache: bool,
+
+    #[clap(long)]
+    /// Commit the files changed by `fix` afterwards, with a message listing
+    /// the corrected words per file.
+    pub commit: bool,
+
+    #[clap(long, value_name = "SUBJECT")]
+    /// The subject of the commit, implies `--commit`.
+    pub commit_message: Option<String>,
+
+    #[clap(long)]
+    /// Keep running after `check`, and recheck every file whenever it
+    /// changes, printing its findings.
+    pub watch: bool,
+
+    #[clap(long)]
+    /// Let `fix` and `reflow` change all files or none: the fixed files are
+    /// only swapped in once every one of them verifies.
+    pub atomic: bool,
+
+    #[clap(long)]
+    /// Let `fix` ask once per misspelled word, for all of its occurrences
+    /// across the checked files, rather than once per occurrence.
+    pub by_word: bool,
+
+    #[clap(long, value_names = ["STRATEGY", "FILE"], num_args = 1..=2)]
+    /// Let `fix` apply fixes without asking, `first` picks the first
+    /// replacement of every finding, `from <FILE>` the fixes of a decisions
+    /// file.
+    pub apply: Vec<String>,
+
+    #[clap(long, value_name = "FILE")]
+    /// Let `fix` write the picked fixes to a decisions file instead of the
+    /// checked files, so they can be reviewed and applied later with `--apply
+    /// from <FILE>`.
+    pub decisions: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Let `fix` and `reflow` modify files even though the git working tree
+    /// has uncommitted changes.
+    pub allow_dirty: bool,
+
+    #[clap(long)]
+    /// Let `fix` and `reflow` modify files if the git working tree only has
+    /// staged changes.
+    pub allow_staged: bool,
+
+    /// A list of files and directories to check. See `--recursive`. `-` reads
+    /// rust source from the standard input.
     pub paths: Vec<PathBuf>,
 }
 
-#[derive(Debug, PartialEq, Eq, clap::Subcommand)]
+impl Common {
+    /// `--apply` only takes a second value after `from`, the one after `first`
+    /// is the first of the paths.
+    fn reclaim_paths(&mut self) {
+        if self.apply.len() == 2 && self.apply[0].eq_ignore_ascii_case("first") {
+            if let Some(path) = self.apply.pop() {
+                self.paths.insert(0, PathBuf::from(path));
+            }
+        }
+    }
+
+    /// How `fix` picks the fixes without asking, `--apply`, `--decisions` and
+    /// `--by-word` only apply to `fix`, and exclude each other where one of
+    /// them would be ignored.
+    fn apply(&self, action: Action) -> Result<Option<ApplyStrategy>> {
+        if action != Action::Fix {
+            if !self.apply.is_empty() || self.decisions.is_some() || self.by_word {
+                bail!("`--apply`, `--decisions` and `--by-word` only apply to `fix`")
+            }
+            return Ok(None);
+        }
+        let strategy = match self.apply.as_slice() {
+            [] => return Ok(None),
+            [first] if first.eq_ignore_ascii_case("first") => ApplyStrategy::First,
+            [from, file] if from.eq_ignore_ascii_case("from") => {
+                ApplyStrategy::From(PathBuf::from(file))
+            }
+            [from] if from.eq_ignore_ascii_case("from") => {
+                bail!("`--apply from` requires the decisions file to apply")
+            }
+            values => bail!(
+                "Unknown apply strategy: {}, expected `first` or `from <FILE>`",
+                values.join(" ")
+            ),
+        };
+        if self.by_word {
+            bail!("`--by-word` asks for the fixes, which `--apply` picks without asking")
+        }
+        if matches!(strategy, ApplyStrategy::From(_)) && self.decisions.is_some() {
+            bail!("`--apply from` applies the decisions file, which `--decisions` would write")
+        }
+        Ok(Some(strategy))
+    }
+
+    /// The unified arguments to run `action` with.
+    fn operate(
+        &self,
+        action: Action,
+        config_path: Option<PathBuf>,
+        config: &Config,
+        github_actions: bool,
+    ) -> Result<UnifiedArgs> {
+        Ok(UnifiedArgs::Operate {
+            action,
+            config_path,
+            dev_comments: self.dev_comments || config.dev_comments,
+            skip_readme: self.skip_readme || config.skip_readme,
+            recursive: self.recursive,
+            paths: self.paths.clone(),
+            exit_code_override: self.code,
+            strict: self.strict,
+            reporter: self.reporter(action, github_actions)?,
+            thresholds: self.thresholds(),
+            dump_extracted: self.dump_extracted.clone(),
+            allow_dirty: self.allow_dirty,
+            allow_staged: self.allow_staged,
+            watch: self.watch(action)?,
+        })
+    }
+
+    /// Whether to keep rechecking, `--watch` only applies to `check` with the
+    /// human readable output.
+    fn watch(&self, action: Action) -> Result<bool> {
+        if !self.watch {
+            return Ok(false);
+        }
+        if action != Action::Check {
+            bail!("`--watch` only applies to `check`")
+        }
+        if self.action || !matches!(self.format, None | Some(OutputFormat::Human)) {
+            bail!("`--watch` requires the human readable output")
+        }
+        if self
+            .paths
+            .iter()
+            .any(|path| crate::traverse::is_stdin(path))
+        {
+            bail!("`--watch` can not watch the standard input")
+        }
+        Ok(true)
+    }
+
+    /// How the findings of `action` are reported, `--action` only applies to
+    /// `check`.
+    fn reporter(&self, action: Action, github_actions: bool) -> Result<Reporter> {
+        if self.action {
+            if action != Action::Check {
+                bail!("`--action` only applies to `check`")
+            }
+            if self.format.is_some() {
+                bail!("`--action` can not be combined with `--format`")
+            }
+            if self.template.is_some() {
+                bail!("`--template` requires `--format template`")
+            }
+            return Ok(Reporter::GitHub);
+        }
+        let format = self
+            .format
+            .unwrap_or_else(|| default_format(action, github_actions));
+        match (format, &self.template) {
+            (OutputFormat::Human, None) => Ok(Reporter::Human),
+            (OutputFormat::GitHub, None) => Ok(Reporter::Annotations),
+            (OutputFormat::Json, None) => Ok(Reporter::Json),
+            (OutputFormat::Sarif, None) => Ok(Reporter::Sarif),
+            (OutputFormat::Template, Some(template)) => Ok(Reporter::Template(template.clone())),
+            (OutputFormat::Template, None) => {
+                bail!("`--format template` requires `--template <path>`")
+            }
+            (_, Some(_)) => bail!("`--template` requires `--format template`"),
+        }
+    }
+
+    /// The thresholds given with `--max-errors` and `--max-warnings`.
+    fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            max_errors: self.max_errors,
+            max_warnings: self.max_warnings,
+            max_findings: self.max_findings,
+            fail_on: self.fail_on,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, clap::Subcommand)]
 #[clap(rename_all = "kebab-case")]
 pub enum Sub {
     /// Only show check errors, but do not request user input.
@@ -202,12 +555,90 @@ pub enum Sub {
         paths: Vec<PathBuf>,
     },
 
+    /// Maintain dictionaries.
+    Dict {
+        #[clap(subcommand)]
+        command: DictSub,
+    },
+
+    /// Add words to the project dictionary, `.spellcheck-dict.txt` unless
+    /// configured otherwise, which is kept sorted.
+    Learn {
+        #[clap(required = true)]
+        /// The words to accept from now on.
+        words: Vec<String>,
+    },
+
     /// Print completions.
     Completions {
         #[clap(long, env="SHELL", value_parser = load_shell_name)]
         /// Provide the `shell` for which to generate the completion script.
         shell: Shell,
     },
+
+    /// Run a language server on `stdin` and `stdout`, publishing the findings
+    /// of the documents open in an editor as diagnostics, with code actions
+    /// for the replacement candidates.
+    Lsp {
+        #[clap(long)]
+        /// Also check developer comments, see `--dev-comments`.
+        dev_comments: bool,
+    },
+
+    /// Print the man page, generated from the command line definitions.
+    Manpage {
+        #[clap(short, long)]
+        /// Write to the given file instead of `stdout`.
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, clap::Subcommand)]
+#[clap(rename_all = "kebab-case")]
+pub enum DictSub {
+    /// Convert between word lists of other spellcheckers and the hunspell
+    /// format used for `extra_dictionaries`.
+    Convert {
+        #[clap(long)]
+        /// Format of the input, one of `wordlist`, `hunspell`, `vale` or
+        /// `cspell`. Derived from the file extension if omitted.
+        from: Option<DictFormat>,
+
+        #[clap(long, default_value = "hunspell")]
+        /// Format of the output.
+        to: DictFormat,
+
+        #[clap(short, long)]
+        /// Write to the given file instead of `stdout`.
+        output: Option<PathBuf>,
+
+        /// The dictionary to convert.
+        input: PathBuf,
+    },
+
+    /// Propose words that occur frequently across the project, but are
+    /// unknown to the dictionaries, for addition.
+    Review {
+        #[clap(short, long)]
+        /// Recurse down directories and module declaration derived paths.
+        recursive: bool,
+
+        #[clap(long, default_value_t = 3)]
+        /// Only propose words that occur at least this many times.
+        min_count: usize,
+
+        #[clap(long, default_value_t = 50)]
+        /// Maximum number of words to propose.
+        limit: usize,
+
+        #[clap(short, long)]
+        /// Dictionary to add the accepted words to, defaults to the first of
+        /// `extra_dictionaries`.
+        output: Option<PathBuf>,
+
+        /// A list of files and directories to check. See `--recursive`.
+        paths: Vec<PathBuf>,
+    },
 }
 
 #[derive(thiserror::Error, Debug, Clone)]
@@ -232,6 +663,16 @@ fn load_shell_name(shell: &str) -> Result<Shell, ShellErr> {
         .unwrap_or_else(|| Err(ShellErr::MissingArg))
 }
 
+/// The output format of `action` unless given with `--format`, annotations
+/// when running within GitHub Actions.
+fn default_format(action: Action, github_actions: bool) -> OutputFormat {
+    if action == Action::Check && github_actions {
+        OutputFormat::GitHub
+    } else {
+        OutputFormat::Human
+    }
+}
+
 pub fn generate_completions<G: clap_complete::Generator, W: std::io::Write>(
     generator: G,
     sink: &mut W,
@@ -241,6 +682,11 @@ pub fn generate_completions<G: clap_complete::Generator, W: std::io::Write>(
     clap_complete::generate(generator, app, app.get_name().to_string(), sink);
 }
 
+/// Render the man page of the command line, see `manpage`.
+pub fn generate_manpage<W: std::io::Write>(sink: &mut W) -> std::io::Result<()> {
+    super::manpage::render(<Args as clap::CommandFactory>::command(), sink)
+}
+
 impl Args {
     pub fn common(&self) -> Option<&Common> {
         match &self.command {
@@ -248,7 +694,85 @@ impl Args {
             | Some(Sub::Fix { common, .. })
             | Some(Sub::Reflow { common, .. }) => Some(common),
             None => Some(&self.common),
-            Some(Sub::Completions { .. } | Sub::ListFiles { .. } | Sub::Config { .. }) => None,
+            Some(
+                Sub::Completions { .. }
+                | Sub::Manpage { .. }
+                | Sub::Lsp { .. }
+                | Sub::ListFiles { .. }
+                | Sub::Config { .. }
+                | Sub::Dict { .. }
+                | Sub::Learn { .. },
+            ) => None,
+        }
+    }
+
+    fn common_mut(&mut self) -> Option<&mut Common> {
+        match &mut self.command {
+            Some(Sub::Check { common, .. })
+            | Some(Sub::Fix { common, .. })
+            | Some(Sub::Reflow { common, .. }) => Some(common),
+            None => Some(&mut self.common),
+            Some(_) => None,
+        }
+    }
+
+    /// Provide the directory of the `--manifest-path` manifest, if any, which
+    /// the run is based on instead of `cwd`, without changing the working
+    /// directory. The relative paths of the arguments are resolved against
+    /// `cwd` beforehand.
+    pub fn rebase_on_manifest(&mut self, cwd: &Path) -> Result<Option<PathBuf>> {
+        let absolute = |path: &PathBuf| {
+            if path.is_absolute() {
+                path.clone()
+            } else {
+                cwd.join(path)
+            }
+        };
+        let manifest_path = match self
+            .common()
+            .and_then(|common| common.manifest_path.as_ref())
+        {
+            Some(manifest_path) => absolute(manifest_path),
+            None => return Ok(None),
+        };
+        if manifest_path.file_name() != Some("Cargo.toml".as_ref()) || !manifest_path.is_file() {
+            bail!(
+                "`--manifest-path` requires the path of a `Cargo.toml` file, got {}",
+                manifest_path.display()
+            );
+        }
+        let manifest_dir = manifest_path
+            .canonicalize()?
+            .parent()
+            .map(Path::to_owned)
+            .ok_or_else(|| eyre!("Manifest {} has no parent", manifest_path.display()))?;
+
+        self.cfg = self.cfg.as_ref().map(|cfg| {
+            if crate::traverse::is_stdin(cfg) {
+                cfg.clone()
+            } else {
+                absolute(cfg)
+            }
+        });
+        if let Some(common) = self.common_mut() {
+            for path in &mut common.paths {
+                if !crate::traverse::is_stdin(path) {
+                    *path = absolute(path);
+                }
+            }
+            common.template = common.template.as_ref().map(absolute);
+            common.dump_extracted = common.dump_extracted.as_ref().map(absolute);
+        }
+        self.base_dir = Some(manifest_dir.clone());
+        Ok(Some(manifest_dir))
+    }
+
+    /// The directory the run is based on, the working directory unless
+    /// rebased on a manifest.
+    fn base_dir(&self) -> Result<PathBuf> {
+        match self.base_dir {
+            Some(ref base_dir) => Ok(base_dir.clone()),
+            None => crate::traverse::cwd(),
         }
     }
 
@@ -258,6 +782,20 @@ impl Args {
             .flatten()
     }
 
+    /// Checkers required by `--only`, rules are provided by `NlpRules`.
+    fn only_checkers(&self) -> Option<Vec<CheckerType>> {
+        let only = &self.common()?.only;
+        if only.is_empty() {
+            return None;
+        }
+        let checkers = only
+            .iter()
+            .map(|entry| CheckerType::from_str(entry).unwrap_or(CheckerType::NlpRules))
+            .unique()
+            .collect();
+        Some(checkers)
+    }
+
     pub fn job_count(&self) -> usize {
         derive_job_count(self.common().map(|common| common.jobs).flatten())
     }
@@ -277,6 +815,10 @@ impl Args {
             Some(Sub::Config { .. }) => unreachable!(),
             Some(Sub::ListFiles { .. }) => Action::ListFiles,
             Some(Sub::Completions { .. }) => unreachable!(),
+            Some(Sub::Manpage { .. }) => unreachable!(),
+            Some(Sub::Lsp { .. }) => unreachable!(),
+            Some(Sub::Dict { .. }) => unreachable!(),
+            Some(Sub::Learn { .. }) => unreachable!(),
         };
         log::trace!("Derived action {:?} from flags/args/cmds", action);
         action
@@ -333,6 +875,12 @@ impl Args {
                 Vec::new()
             }
         })
+        .map(|mut args| {
+            if let Some(common) = args.common_mut() {
+                common.reclaim_paths();
+            }
+            args
+        })
     }
 
     /// Overrides the enablement status of checkers in the configuration based
@@ -388,15 +936,28 @@ impl Args {
     // TODO split the IO operations and lookup dirs.
     fn load_config_inner(&self) -> Result<(Config, Option<PathBuf>)> {
         log::debug!("Attempting to load configuration by priority.");
-        let cwd = crate::traverse::cwd()?;
+        let base_dir = self.base_dir()?;
         // 1. explicitly specified
+        if self.cfg.as_deref().map_or(false, crate::traverse::is_stdin) {
+            let stdin_paths = self.common().map_or(false, |common| {
+                common
+                    .paths
+                    .iter()
+                    .any(|path| crate::traverse::is_stdin(path))
+            });
+            if stdin_paths {
+                bail!("The config and the checked source can not both be read from stdin");
+            }
+            log::debug!("Using configuration provided by stdin (1)");
+            return Ok((self.stdin_config()?, None));
+        }
         let explicit_cfg = self.cfg.as_ref().map(|config_path| {
             let config_path = if config_path.is_absolute() {
                 config_path.to_owned()
             } else {
                 // TODO make sure this is sane behavior
-                // to use `cwd`.
-                cwd.join(config_path)
+                // to use the base directory.
+                base_dir.join(config_path)
             };
             config_path
         });
@@ -437,15 +998,15 @@ impl Args {
             }
         };
 
-        // 3. manifest meta in current working dir
-        if let Some(manifest_path) = look_for_cargo_manifest(&cwd)? {
+        // 3. manifest meta in the base directory
+        if let Some(manifest_path) = look_for_cargo_manifest(&base_dir)? {
             if let Some((config, config_path)) = load_from_manifest_metadata(&manifest_path)? {
                 return Ok((config, Some(config_path)));
             }
         };
 
-        // 4. load from `.config/spellcheck.toml` from the current working directory.
-        let config_path = cwd.join(".config").join("spellcheck.toml");
+        // 4. load from `.config/spellcheck.toml` from the base directory.
+        let config_path = base_dir.join(".config").join("spellcheck.toml");
         if let Some(cfg) = Config::load_from(&config_path)? {
             log::debug!("Using configuration file (4) {}", config_path.display());
             return Ok((cfg, Some(config_path)));
@@ -468,6 +1029,7 @@ impl Args {
 
     fn load_config(&self) -> Result<(Config, Option<PathBuf>)> {
         let (mut config, config_path) = self.load_config_inner()?;
+        config.base_dir.clone_from(&self.base_dir);
         // mask all disabled checkers, use the default config
         // for those which have one if not enabled already.
 
@@ -477,6 +1039,7 @@ impl Args {
         // ISSUE: https://github.com/drahnr/cargo-spellcheck/issues/242
         let filter_set = self
             .checkers()
+            .or_else(|| self.only_checkers())
             .unwrap_or_else(|| vec![CheckerType::Hunspell]);
         {
             if filter_set.contains(&CheckerType::Hunspell) {
@@ -495,13 +1058,71 @@ impl Args {
             }
             // reflow is a different subcommand, not relevant
         }
+        if let Some(common) = self.common() {
+            config.filter = RunFilter::new(&common.only, &common.skip);
+            config
+                .files
+                .markdown
+                .extend(common.markdown.iter().cloned());
+            config.ignore.extend(common.ignore.iter().cloned());
+            config.item_paths.clone_from(&common.item_path);
+            config.findings_cache = !common.no_cache;
+            config.atomic = common.atomic;
+            config.by_word = common.by_word;
+            config.apply = common.apply(self.action())?;
+            config.decisions.clone_from(&common.decisions);
+            if let Some(ref base) = common.diff_base {
+                config.changes = Some(crate::traverse::Changes::since(base, &self.base_dir()?)?);
+            }
+            if common.commit || common.commit_message.is_some() {
+                config.commit = Some(
+                    common
+                        .commit_message
+                        .clone()
+                        .unwrap_or_else(|| crate::action::commit::DEFAULT_SUBJECT.to_owned()),
+                );
+            }
+        }
 
         Ok((config, config_path))
     }
 
+    /// The configuration of the standard input, only read the first time.
+    fn stdin_config(&self) -> Result<Config> {
+        let mut contents = self
+            .stdin_config
+            .lock()
+            .map_err(|_| eyre!("The configuration of stdin was poisoned"))?;
+        let contents = match *contents {
+            Some(ref contents) => contents,
+            None => contents.insert(Config::read_stdin()?),
+        };
+        Config::parse_stdin(contents, &self.base_dir()?)
+    }
+
+    /// Load the configuration again with the flags applied, once the config
+    /// file changed.
+    pub(crate) fn reload_config(&self) -> Result<Config> {
+        self.load_config().map(|(config, _config_path)| config)
+    }
+
     /// Evaluate the configuration flags, overwrite config values as needed and
     /// provide a new, unified config struct.
     pub fn unified(self) -> Result<(UnifiedArgs, Config)> {
+        let github_actions = std::env::var("GITHUB_ACTIONS").as_deref() == Ok("true");
+        self.unify(github_actions)
+    }
+
+    /// Like [`unified`](Self::unified), with `github_actions` telling whether
+    /// running within GitHub Actions.
+    fn unify(self, github_actions: bool) -> Result<(UnifiedArgs, Config)> {
+        if matches!(self.command, Some(Sub::Lsp { .. }))
+            && self.cfg.as_deref().map_or(false, crate::traverse::is_stdin)
+        {
+            bail!(
+                "The language server talks on stdin, so `--cfg -` can not read the config from it"
+            );
+        }
         let (config, config_path) = self.load_config()?;
         let unified = match self.command {
             Some(Sub::Config {
@@ -536,31 +1157,50 @@ impl Args {
                 recursive,
                 paths: paths.clone(),
                 exit_code_override: 1,
+                strict: false,
+                reporter: Reporter::Human,
+                thresholds: Thresholds::default(),
+                dump_extracted: None,
+                allow_dirty: false,
+                allow_staged: false,
+                watch: false,
             },
-            None => {
-                let common = &self.common;
-                UnifiedArgs::Operate {
-                    action: Action::Check,
-                    config_path,
-                    dev_comments: common.dev_comments || config.dev_comments,
-                    skip_readme: common.skip_readme || config.skip_readme,
-                    recursive: common.recursive,
-                    paths: common.paths.clone(),
-                    exit_code_override: common.code,
-                }
-            }
+            None => self
+                .common
+                .operate(Action::Check, config_path, &config, github_actions)?,
             Some(Sub::Reflow { ref common, .. })
             | Some(Sub::Fix { ref common, .. })
-            | Some(Sub::Check { ref common, .. }) => UnifiedArgs::Operate {
-                action: self.action(),
+            | Some(Sub::Check { ref common, .. }) => {
+                common.operate(self.action(), config_path, &config, github_actions)?
+            }
+            Some(Sub::Completions { .. }) => unreachable!("Was handled earlier. qed"),
+            Some(Sub::Manpage { .. }) => unreachable!("Was handled earlier. qed"),
+            Some(Sub::Dict {
+                command:
+                    DictSub::Review {
+                        recursive,
+                        min_count,
+                        limit,
+                        ref output,
+                        ref paths,
+                    },
+            }) => UnifiedArgs::DictReview {
+                paths: paths.clone(),
+                recursive,
+                skip_readme: config.skip_readme,
+                dev_comments: config.dev_comments,
+                min_count,
+                limit,
+                output: output.clone(),
+            },
+            Some(Sub::Dict { .. }) => unreachable!("Was handled earlier. qed"),
+            Some(Sub::Learn { ref words }) => UnifiedArgs::Learn {
+                words: words.clone(),
+            },
+            Some(Sub::Lsp { dev_comments }) => UnifiedArgs::Lsp {
+                dev_comments: dev_comments || config.dev_comments,
                 config_path,
-                dev_comments: common.dev_comments || config.dev_comments,
-                skip_readme: common.skip_readme || config.skip_readme,
-                recursive: common.recursive,
-                paths: common.paths.clone(),
-                exit_code_override: common.code,
             },
-            Some(Sub::Completions { .. }) => unreachable!("Was handled earlier. qed"),
         };
 
         Ok((unified, config))
@@ -591,6 +1231,29 @@ pub enum UnifiedArgs {
         recursive: bool,
         paths: Vec<PathBuf>,
         exit_code_override: u8,
+        strict: bool,
+        reporter: Reporter,
+        thresholds: Thresholds,
+        dump_extracted: Option<PathBuf>,
+        allow_dirty: bool,
+        allow_staged: bool,
+        watch: bool,
+    },
+    DictReview {
+        paths: Vec<PathBuf>,
+        recursive: bool,
+        skip_readme: bool,
+        dev_comments: bool,
+        min_count: usize,
+        limit: usize,
+        output: Option<PathBuf>,
+    },
+    Learn {
+        words: Vec<String>,
+    },
+    Lsp {
+        dev_comments: bool,
+        config_path: Option<PathBuf>,
     },
 }
 
@@ -768,15 +1431,18 @@ mod tests {
                 "-vvvvv",
                 "check",
                 "--code=77",
+                "--strict",
+                "--max-errors=0",
                 "--dev-comments",
                 "--skip-readme",
+                "--dump-extracted=/tmp/dump",
             ]
             .iter()
             .map(ToOwned::to_owned)
             .map(ToOwned::to_owned),
         )
         .unwrap();
-        let (unified, _config) = args.unified().unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
         assert_matches!(unified,
             UnifiedArgs::Operate {
                 action,
@@ -786,9 +1452,24 @@ mod tests {
                 recursive,
                 paths,
                 exit_code_override,
+                strict,
+                reporter,
+                thresholds,
+                dump_extracted,
+                allow_dirty,
+                allow_staged,
+                watch,
             } => {
+                assert_eq!(dump_extracted, Some(PathBuf::from("/tmp/dump")));
+                assert_eq!(allow_dirty, false);
+                assert_eq!(allow_staged, false);
+                assert_eq!(watch, false);
                 assert_eq!(Action::Check, action);
                 assert_eq!(exit_code_override, 77);
+                assert_eq!(strict, true);
+                assert_eq!(thresholds.max_errors, Some(0));
+                assert_eq!(thresholds.max_warnings, None);
+                assert_eq!(reporter, Reporter::Human);
                 assert_eq!(dev_comments, true);
                 assert_eq!(skip_readme, true);
                 assert_eq!(recursive, false);
@@ -797,6 +1478,227 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_filters() {
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --only hunspell,dup-word --skip STYLE",
+        ))
+        .unwrap();
+        let common = args.common().unwrap();
+        assert_eq!(
+            common.only,
+            vec!["hunspell".to_owned(), "dup-word".to_owned()]
+        );
+        assert_eq!(common.skip, vec!["STYLE".to_owned()]);
+        assert_eq!(
+            args.only_checkers(),
+            Some(vec![CheckerType::Hunspell, CheckerType::NlpRules])
+        );
+    }
+
+    #[test]
+    fn unify_template() {
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --format template --template report.hbs",
+        ))
+        .unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+            assert_eq!(reporter, Reporter::Template(PathBuf::from("report.hbs")));
+        });
+
+        let args =
+            Args::parse(commandline_to_iter("cargo spellcheck check --format json")).unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+            assert_eq!(reporter, Reporter::Json);
+        });
+
+        let args =
+            Args::parse(commandline_to_iter("cargo spellcheck check --format sarif")).unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+            assert_eq!(reporter, Reporter::Sarif);
+        });
+
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --format github",
+        ))
+        .unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+            assert_eq!(reporter, Reporter::Annotations);
+        });
+
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --format template",
+        ))
+        .unwrap();
+        assert!(args.unify(false).is_err());
+    }
+
+    #[test]
+    fn unify_within_github_actions() {
+        for (commandline, expected) in [
+            ("cargo spellcheck check", Reporter::Annotations),
+            ("cargo spellcheck check --format human", Reporter::Human),
+            ("cargo spellcheck fix", Reporter::Human),
+        ] {
+            let args = Args::parse(commandline_to_iter(commandline)).unwrap();
+            let (unified, _config) = args.unify(true).unwrap();
+            assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+                assert_eq!(reporter, expected, "{}", commandline);
+            });
+        }
+    }
+
+    #[test]
+    fn unify_watch() {
+        let args = Args::parse(commandline_to_iter("cargo spellcheck check --watch")).unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { watch, .. } => {
+            assert!(watch);
+        });
+
+        for commandline in [
+            "cargo spellcheck fix --watch",
+            "cargo spellcheck check --watch --format json",
+            "cargo spellcheck check --watch -",
+        ] {
+            let args = Args::parse(commandline_to_iter(commandline)).unwrap();
+            assert!(args.unify(false).is_err(), "{}", commandline);
+        }
+    }
+
+    #[test]
+    fn stdin_config_is_kept() {
+        let args = Args::parse(commandline_to_iter("cargo spellcheck --cfg - check")).unwrap();
+        *args.stdin_config.lock().unwrap() = Some("dev_comments = true\n".to_owned());
+        let reloaded = args.clone();
+        let (_unified, config) = args.unify(false).unwrap();
+        assert!(config.dev_comments);
+        // a reload does not read the exhausted stdin again
+        assert!(reloaded.reload_config().unwrap().dev_comments);
+
+        let args = Args::parse(commandline_to_iter("cargo spellcheck --cfg - lsp")).unwrap();
+        assert!(args.unify(false).is_err());
+    }
+
+    #[test]
+    fn unify_apply() {
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck fix --apply from decisions.toml src/lib.rs",
+        ))
+        .unwrap();
+        let (unified, config) = args.unify(false).unwrap();
+        assert_eq!(
+            config.apply,
+            Some(ApplyStrategy::From(PathBuf::from("decisions.toml")))
+        );
+        assert_matches!(unified, UnifiedArgs::Operate { paths, .. } => {
+            assert_eq!(paths, vec![PathBuf::from("src/lib.rs")]);
+        });
+
+        // the value after `first` is a path
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck fix --apply first src/lib.rs",
+        ))
+        .unwrap();
+        let (unified, config) = args.unify(false).unwrap();
+        assert_eq!(config.apply, Some(ApplyStrategy::First));
+        assert_matches!(unified, UnifiedArgs::Operate { paths, .. } => {
+            assert_eq!(paths, vec![PathBuf::from("src/lib.rs")]);
+        });
+
+        for commandline in [
+            "cargo spellcheck check --apply first",
+            "cargo spellcheck check --decisions decisions.toml",
+            "cargo spellcheck reflow --by-word",
+            "cargo spellcheck --by-word",
+            "cargo spellcheck fix --apply from",
+            "cargo spellcheck fix --apply last",
+            "cargo spellcheck fix --apply first --by-word",
+            "cargo spellcheck fix --apply from a.toml --decisions b.toml",
+        ] {
+            let args = Args::parse(commandline_to_iter(commandline)).unwrap();
+            assert!(args.unify(false).is_err(), "{}", commandline);
+        }
+    }
+
+    #[test]
+    fn manifest_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cwd = tmp.path().to_owned();
+        let krate = cwd.join("krate");
+        fs::create_dir_all(&krate).unwrap();
+        fs::write(krate.join("Cargo.toml"), "[package]\nname = \"krate\"\n").unwrap();
+
+        let mut args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --manifest-path krate/Cargo.toml --cfg spellcheck.toml src/lib.rs -",
+        ))
+        .unwrap();
+        assert_eq!(
+            args.rebase_on_manifest(&cwd).unwrap(),
+            Some(krate.canonicalize().unwrap())
+        );
+        assert_eq!(args.cfg, Some(cwd.join("spellcheck.toml")));
+        assert_eq!(
+            args.common().unwrap().paths,
+            vec![cwd.join("src/lib.rs"), PathBuf::from("-")]
+        );
+
+        let mut args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --manifest-path krate/Cargo.toml --config -",
+        ))
+        .unwrap();
+        args.rebase_on_manifest(&cwd).unwrap();
+        assert_eq!(args.cfg, Some(PathBuf::from("-")));
+
+        let mut args = Args::parse(commandline_to_iter(
+            "cargo spellcheck --manifest-path krate",
+        ))
+        .unwrap();
+        assert!(args.rebase_on_manifest(&cwd).is_err());
+        let mut args = Args::parse(commandline_to_iter("cargo spellcheck")).unwrap();
+        assert_eq!(args.rebase_on_manifest(&cwd).unwrap(), None);
+
+        // the config of the manifest directory is used, the working directory
+        // is left alone
+        fs::create_dir_all(krate.join(".config")).unwrap();
+        fs::write(
+            krate.join(".config").join("spellcheck.toml"),
+            "dev_comments = true\n",
+        )
+        .unwrap();
+        fs::write(
+            krate.join("Cargo.toml"),
+            "[package]\nname = \"krate\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let before = crate::traverse::cwd().unwrap();
+        let mut args = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --manifest-path krate/Cargo.toml",
+        ))
+        .unwrap();
+        args.rebase_on_manifest(&cwd).unwrap();
+        let (config, _config_path) = args.load_config().unwrap();
+        assert!(config.dev_comments);
+        assert_eq!(config.base_dir().unwrap(), krate.canonicalize().unwrap());
+        assert_eq!(crate::traverse::cwd().unwrap(), before);
+    }
+
+    #[test]
+    fn unify_action() {
+        let args = Args::parse(commandline_to_iter("cargo spellcheck --action")).unwrap();
+        let (unified, _config) = args.unify(false).unwrap();
+        assert_matches!(unified, UnifiedArgs::Operate { reporter, .. } => {
+            assert_eq!(reporter, Reporter::GitHub);
+        });
+
+        let args = Args::parse(commandline_to_iter("cargo spellcheck fix --action")).unwrap();
+        assert!(args.unify(false).is_err());
+    }
+
     // FIXME checkers interpretation seems to have changed XXX
     #[test]
     fn unify_config() {
@@ -813,7 +1715,7 @@ mod tests {
             .map(ToOwned::to_owned),
         )
         .unwrap();
-        let (unified, _config) = dbg!(args).unified().unwrap();
+        let (unified, _config) = dbg!(args).unify(false).unwrap();
         assert_matches!(dbg!(unified),
             UnifiedArgs::Config {
                 dest_config: ConfigWriteDestination::File { overwrite, path },
@@ -853,4 +1755,33 @@ mod tests {
             assert_eq!(shell.to_string(), "fish")
         });
     }
+
+    #[test]
+    fn dict_review() {
+        static C: &str = "cargo spellcheck dict review -r --min-count 5 src";
+        assert_matches!(Args::parse(commandline_to_iter(C)), Ok(Args {
+            command: Some(Sub::Dict { command: DictSub::Review { recursive, min_count, limit, output, paths } }),
+            ..
+        }) => {
+            assert!(recursive);
+            assert_eq!(min_count, 5);
+            assert_eq!(limit, 50);
+            assert_eq!(output, None);
+            assert_eq!(paths, vec![PathBuf::from("src")]);
+        });
+    }
+
+    #[test]
+    fn dict_convert() {
+        static C: &str = "cargo spellcheck dict convert --from vale -o lingo.dic accept.txt";
+        assert_matches!(Args::parse(commandline_to_iter(C)), Ok(Args {
+            command: Some(Sub::Dict { command: DictSub::Convert { from, to, output, input } }),
+            ..
+        }) => {
+            assert_eq!(from, Some(DictFormat::Vale));
+            assert_eq!(to, DictFormat::Hunspell);
+            assert_eq!(output, Some(PathBuf::from("lingo.dic")));
+            assert_eq!(input, PathBuf::from("accept.txt"));
+        });
+    }
 }
@@ -0,0 +1,160 @@
+//! External checker configuration.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Checkers that are not built in, collected under the `[checker]` table.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CheckerTableConfig {
+    /// External subprocess checkers, declared as `[[checker.external]]`.
+    #[serde(default)]
+    pub external: Vec<ExternalCheckerConfig>,
+    /// WebAssembly checker modules, declared as `[[checker.wasm]]`.
+    #[serde(default)]
+    pub wasm: Vec<WasmCheckerConfig>,
+    /// Do not pass chunks that the cheap checkers already flagged to the
+    /// expensive ones, `nlprules` and external checkers not marked `cheap`.
+    #[serde(default)]
+    #[serde(alias = "skip-flagged")]
+    pub skip_flagged: bool,
+    /// Flag items whose documentation is empty, only whitespace or only a bare
+    /// URL.
+    #[serde(default)]
+    #[serde(alias = "empty-docs")]
+    pub empty_docs: bool,
+    /// Flag images without alt text, an accessibility lint.
+    #[serde(default)]
+    #[serde(alias = "empty-alt")]
+    pub empty_alt: bool,
+    /// Keep the findings of the grammar backends, `nlprules` and
+    /// `LanguageTool`, per sentence on disk, so unchanged sentences are not
+    /// checked again in later runs.
+    #[serde(default)]
+    #[serde(alias = "grammar-cache")]
+    pub grammar_cache: bool,
+    /// Chunks with fewer words are not passed to the grammar backends,
+    /// `nlprules` and `LanguageTool`, fragments like `See above.` only trigger
+    /// pointless rules. The spell check covers all chunks.
+    #[serde(default = "default_grammar_min_words")]
+    #[serde(alias = "grammar-min-words")]
+    pub grammar_min_words: usize,
+}
+
+fn default_grammar_min_words() -> usize {
+    4
+}
+
+impl Default for CheckerTableConfig {
+    fn default() -> Self {
+        Self {
+            external: Vec::new(),
+            wasm: Vec::new(),
+            skip_flagged: false,
+            empty_docs: false,
+            empty_alt: false,
+            grammar_cache: false,
+            grammar_min_words: default_grammar_min_words(),
+        }
+    }
+}
+
+impl CheckerTableConfig {
+    pub fn is_empty(&self) -> bool {
+        self.external.is_empty()
+            && self.wasm.is_empty()
+            && !self.skip_flagged
+            && !self.empty_docs
+            && !self.empty_alt
+            && !self.grammar_cache
+            && self.grammar_min_words == default_grammar_min_words()
+    }
+
+    pub(crate) fn sanitize_paths(&mut self, base: &Path) {
+        for external in &mut self.external {
+            external.sanitize_paths(base);
+        }
+        for wasm in &mut self.wasm {
+            wasm.sanitize_paths(base);
+        }
+    }
+}
+
+/// A checker that is executed as a separate process.
+///
+/// The chunks are passed as JSON on `stdin`, the findings are read as JSON
+/// from `stdout`. See `docs/checkers.md` for the protocol.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalCheckerConfig {
+    /// Name to display with each finding, defaults to the command.
+    pub name: Option<String>,
+    /// The executable, relative paths are resolved against the config file
+    /// location, plain names are looked up in `$PATH`.
+    pub command: PathBuf,
+    /// Additional arguments passed to the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Runs in the first pass, along with `hunspell` and wasm checkers, i.e.
+    /// for a lookup in a replacement map.
+    #[serde(default)]
+    pub cheap: bool,
+}
+
+impl ExternalCheckerConfig {
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.command.display().to_string())
+    }
+
+    /// Only commands that are given as a path, i.e. `./my-checker`, are
+    /// resolved relative to `base`.
+    fn sanitize_paths(&mut self, base: &Path) {
+        if self.command.is_relative() && self.command.components().count() > 1 {
+            let abspath = base.join(&self.command);
+            log::trace!(
+                "Sanitized ({} + {}) -> {}",
+                base.display(),
+                self.command.display(),
+                abspath.display()
+            );
+            self.command = abspath;
+        }
+    }
+}
+
+/// A checker compiled to WebAssembly, executed in a sandbox.
+///
+/// Requires the `wasm` feature. See `docs/checkers.md` for the host API.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WasmCheckerConfig {
+    /// Name to display with each finding, defaults to the module path.
+    pub name: Option<String>,
+    /// Path to the `.wasm` module, relative paths are resolved against the
+    /// config file location.
+    pub module: PathBuf,
+    /// Upper bound of executed instructions per file, to bail on runaway
+    /// modules.
+    #[serde(default = "default_fuel")]
+    pub fuel: u64,
+}
+
+fn default_fuel() -> u64 {
+    1_000_000_000
+}
+
+impl WasmCheckerConfig {
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.module.display().to_string())
+    }
+
+    fn sanitize_paths(&mut self, base: &Path) {
+        if self.module.is_relative() {
+            self.module = base.join(&self.module);
+        }
+    }
+}
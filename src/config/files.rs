@@ -0,0 +1,145 @@
+//! File selection configuration.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Settings which affect which files are checked, collected under `[files]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FilesConfig {
+    /// Files containing one of these markers within the first
+    /// `generated_header_lines` lines are considered generated and skipped.
+    #[serde(default = "default_generated_markers")]
+    #[serde(alias = "generated-markers")]
+    pub generated_markers: Vec<String>,
+    /// Number of leading lines searched for `generated_markers`.
+    #[serde(default = "default_generated_header_lines")]
+    #[serde(alias = "generated-header-lines")]
+    pub generated_header_lines: usize,
+    /// Check generated FFI bindings instead of skipping them, limited to their
+    /// `#[doc = ".."]` attributes.
+    #[serde(default)]
+    pub bindings: bool,
+    /// Files larger than this many bytes are skipped, `0` disables the limit.
+    #[serde(default = "default_max_file_size")]
+    #[serde(alias = "max-file-size")]
+    pub max_file_size: u64,
+    /// Once the literals extracted from all files exceed this many bytes, the
+    /// remaining files are skipped, `0` disables the limit.
+    #[serde(default = "default_max_total_size")]
+    #[serde(alias = "max-total-size")]
+    pub max_total_size: u64,
+    /// Recover the doc and developer comments of rust sources that fail to
+    /// parse, with an error tolerant lexer, instead of skipping them.
+    #[serde(default)]
+    #[serde(alias = "recover-unparseable")]
+    pub recover_unparseable: bool,
+    /// Check the comments of the rust code examples in fenced code blocks,
+    /// which are rendered along with the documentation. The code itself is
+    /// never checked.
+    #[serde(default)]
+    #[serde(alias = "example-comments")]
+    pub example_comments: bool,
+    /// Glob patterns, relative to the working directory, of additional
+    /// markdown files to check, i.e. `docs/**/*.md`.
+    #[serde(default)]
+    pub markdown: Vec<String>,
+    /// Glob patterns, relative to the working directory, of configuration
+    /// files whose comments are checked, i.e. `Cargo.toml` or
+    /// `.github/**/*.yml`. `.toml`, `.yaml`, `.yml` and `.json5` files are
+    /// supported, as well as all extensions of `extra`, none are checked by
+    /// default.
+    #[serde(default)]
+    #[serde(alias = "config-comments")]
+    pub config_comments: Vec<String>,
+    /// How the workspace members with `publish = false` are treated.
+    #[serde(default)]
+    pub unpublished: Unpublished,
+    /// The line comment prefix by file extension, i.e. `sh = "#"` or
+    /// `sql = "--"`, for the scripts matched by `config_comments`.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+}
+
+/// Treatment of the packages which are never published, i.e. examples, test
+/// utilities and other internal crates of a workspace.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Unpublished {
+    /// Check them like all the others.
+    Check,
+    /// Skip the workspace members, the package in the working directory is
+    /// checked regardless.
+    Skip,
+    /// Report all of their findings as notes, unless a severity override
+    /// applies.
+    Note,
+}
+
+impl Default for Unpublished {
+    fn default() -> Self {
+        Self::Check
+    }
+}
+
+fn default_generated_markers() -> Vec<String> {
+    [
+        "@generated",
+        "DO NOT EDIT",
+        "Autogenerated by",
+        "automatically generated by",
+    ]
+    .iter()
+    .map(|marker| (*marker).to_owned())
+    .collect()
+}
+
+fn default_generated_header_lines() -> usize {
+    5
+}
+
+fn default_max_file_size() -> u64 {
+    8 << 20
+}
+
+fn default_max_total_size() -> u64 {
+    256 << 20
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            generated_markers: default_generated_markers(),
+            generated_header_lines: default_generated_header_lines(),
+            bindings: false,
+            max_file_size: default_max_file_size(),
+            max_total_size: default_max_total_size(),
+            recover_unparseable: false,
+            example_comments: false,
+            markdown: Vec::new(),
+            config_comments: Vec::new(),
+            unpublished: Unpublished::default(),
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl FilesConfig {
+    /// The line comment prefix of `path` configured in `extra`.
+    pub fn comment_prefix(&self, path: &Path) -> Option<&str> {
+        let extension = path.extension()?.to_str()?;
+        self.extra.get(extension).map(String::as_str)
+    }
+
+    /// Check if the header of `content` contains one of the generated markers.
+    pub fn is_generated(&self, content: &str) -> bool {
+        content
+            .lines()
+            .take(self.generated_header_lines)
+            .any(|line| {
+                self.generated_markers
+                    .iter()
+                    .any(|marker| line.contains(marker.as_str()))
+            })
+    }
+}
@@ -0,0 +1,103 @@
+//! The stages of the filter pipeline, collected under `[filters]`.
+use super::WrappedRegex;
+use serde::{Deserialize, Serialize};
+
+/// Built in stages of the filter pipeline.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BuiltinStage {
+    /// URLs with a scheme, i.e. `https://example.com`, or starting with
+    /// `www.`.
+    Urls,
+    /// Email addresses, i.e. `someone@example.com`.
+    Emails,
+    /// Absolute and relative file paths, i.e. `/etc/hosts`, `./run.sh` or
+    /// `src/lib.rs`.
+    Paths,
+    /// Inline code, i.e. `` `Vec` ``, including its plurals.
+    Code,
+    /// Words shaped like code identifiers, i.e. `snake_case`, `CamelCase` or
+    /// `std::mem`.
+    Identifiers,
+    /// Citation keys in brackets, i.e. `[Knuth1974]`, `[KR88]` or
+    /// `[@knuth1974; @lamport1994]`.
+    Citations,
+    /// Digital object identifiers, i.e. `10.1145/361604.361612` or
+    /// `doi:10.1000/182`.
+    Dois,
+    /// arXiv identifiers, i.e. `arXiv:2101.00001v2` or `arXiv:hep-th/9901001`.
+    Arxiv,
+}
+
+/// A stage of the filter pipeline, either built in or a custom regular
+/// expression, i.e. `{ regex = "v\\d+" }`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum StageConfig {
+    Builtin(BuiltinStage),
+    Regex { regex: WrappedRegex },
+}
+
+fn default_stages() -> Vec<StageConfig> {
+    vec![
+        StageConfig::Builtin(BuiltinStage::Urls),
+        StageConfig::Builtin(BuiltinStage::Emails),
+        StageConfig::Builtin(BuiltinStage::Paths),
+    ]
+}
+
+/// The filter pipeline, collected under `[filters]`.
+///
+/// The stages run in order on the markdown erased content and mask parts of
+/// it, which then never produce findings of any checker.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FiltersConfig {
+    /// Defaults to masking URLs, email addresses and paths.
+    #[serde(default = "default_stages")]
+    pub stages: Vec<StageConfig>,
+}
+
+impl Default for FiltersConfig {
+    fn default() -> Self {
+        Self {
+            stages: default_stages(),
+        }
+    }
+}
+
+impl FiltersConfig {
+    pub fn is_default(&self) -> bool {
+        matches!(
+            self.stages.as_slice(),
+            [
+                StageConfig::Builtin(BuiltinStage::Urls),
+                StageConfig::Builtin(BuiltinStage::Emails),
+                StageConfig::Builtin(BuiltinStage::Paths),
+            ]
+        )
+    }
+}
+
+/// Replace the `[mask]` table of former versions in the config `table` by the
+/// `[filters]` stages it corresponds to, unless `[filters]` is given too.
+pub(crate) fn migrate_mask(table: &mut toml::value::Table) {
+    let mask = match table.remove("mask").or_else(|| table.remove("Mask")) {
+        Some(mask) => mask,
+        None => return,
+    };
+    if table.contains_key("filters") || table.contains_key("Filters") {
+        log::warn!("Ignoring the deprecated `[mask]` table, `[filters]` is given");
+        return;
+    }
+    log::warn!("The `[mask]` table is deprecated, use `[filters]` instead");
+    let enabled = |key: &str| mask.get(key).and_then(toml::Value::as_bool) != Some(false);
+    let stages = [("urls", "urls"), ("emails", "emails"), ("paths", "paths")]
+        .iter()
+        .filter(|(key, _stage)| enabled(key))
+        .map(|(_key, stage)| toml::Value::from(*stage))
+        .collect::<Vec<_>>();
+    let mut filters = toml::value::Table::new();
+    filters.insert("stages".to_owned(), toml::Value::Array(stages));
+    table.insert("filters".to_owned(), toml::Value::Table(filters));
+}
@@ -12,6 +12,7 @@ const fn yes() -> bool {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Quirks {
     /// A regular expression, whose capture groups will be checked, instead of
     /// the initial token. Only the first one that matches will be used to split
@@ -30,6 +31,60 @@ pub struct Quirks {
     /// Treats sequences of emojis as OK.
     #[serde(default = "yes")]
     pub allow_emojis: bool,
+    /// Accept words mixing letters and digits, such as `IPv6`, `SHA256` or
+    /// `base64`, if they are common technical terms or formed like them.
+    #[serde(default = "yes")]
+    #[serde(alias = "allow-digit-words")]
+    pub allow_digit_words: bool,
+    /// Additional words mixing letters and digits to accept, i.e. `ARMv8`,
+    /// matched ignoring case, `-` and `_`.
+    #[serde(default)]
+    #[serde(alias = "digit-words")]
+    pub digit_words: Vec<String>,
+    /// How plurals of code identifiers, i.e. `` `Vec`s `` or `Vecs` if `Vec`
+    /// is inline code elsewhere in the file, are treated.
+    #[serde(default)]
+    #[serde(alias = "code-plurals")]
+    pub code_plurals: CodePlurals,
+    /// How words shaped like code identifiers, i.e. `TokenStream` or
+    /// `parse_token_tree`, are checked.
+    #[serde(default)]
+    #[serde(alias = "check-identifiers")]
+    pub check_identifiers: CheckIdentifiers,
+}
+
+/// The treatment of plurals of code identifiers.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodePlurals {
+    /// Accept them.
+    Accept,
+    /// Flag them, suggesting `` `Vec` instances ``.
+    Instances,
+}
+
+impl Default for CodePlurals {
+    fn default() -> Self {
+        Self::Accept
+    }
+}
+
+/// The treatment of words shaped like code identifiers.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckIdentifiers {
+    /// Check them as a whole, like any other word.
+    Whole,
+    /// Check each of their sub-words, split at `_` and case changes.
+    Split,
+    /// Accept them.
+    Skip,
+}
+
+impl Default for CheckIdentifiers {
+    fn default() -> Self {
+        Self::Whole
+    }
 }
 
 impl Default for Quirks {
@@ -39,6 +94,10 @@ impl Default for Quirks {
             allow_concatenation: false,
             allow_dashes: false,
             allow_emojis: true,
+            allow_digit_words: true,
+            digit_words: Vec::new(),
+            code_plurals: CodePlurals::default(),
+            check_identifiers: CheckIdentifiers::default(),
         }
     }
 }
@@ -56,6 +115,12 @@ impl Quirks {
         self.allow_emojis
     }
 
+    /// The additional words mixing letters and digits, if those are accepted
+    /// at all.
+    pub(crate) fn digit_words(&self) -> Option<&[String]> {
+        self.allow_digit_words.then(|| self.digit_words.as_slice())
+    }
+
     pub(crate) fn transform_regex(&self) -> &[WrappedRegex] {
         &self.transform_regex
     }
@@ -65,8 +130,14 @@ fn default_tokenization_splitchars() -> String {
     "\",;:.!?#(){}[]|/_-‒'`&@§¶…".to_owned()
 }
 
+fn default_project_dictionary() -> PathBuf {
+    PathBuf::from(crate::dict::PROJECT_DICTIONARY)
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
 pub struct HunspellConfig {
     /// The language we want to check against, used as the dictionary and
     /// affixes file name.
@@ -94,6 +165,26 @@ pub struct HunspellConfig {
     /// Additional dictionaries for topic specific lingo.
     #[serde(default)]
     pub extra_dictionaries: Vec<PathBuf>,
+
+    /// Merge the project dictionaries of VS Code (`cSpell.words` in
+    /// `.vscode/settings.json`) and IDEA (`.idea/dictionaries/*.xml`)
+    /// found in the current working directory.
+    #[serde(default)]
+    #[serde(alias = "import-editor-dictionaries")]
+    pub import_editor_dictionaries: bool,
+
+    /// Word list of project specific terms, relative to the project root,
+    /// which `learn` and `fix` add accepted words to. Used if it exists.
+    #[serde(default = "default_project_dictionary")]
+    #[serde(alias = "project-dictionary")]
+    pub project_dictionary: PathBuf,
+
+    /// Sort the replacement candidates alphabetically, following the
+    /// conventions of `lang`, instead of by likelihood.
+    #[serde(default)]
+    #[serde(alias = "sort-candidates")]
+    pub sort_candidates: bool,
+
     /// Additional quirks besides dictionary lookups.
     #[serde(default)]
     pub quirks: Quirks,
@@ -105,6 +196,9 @@ impl Default for HunspellConfig {
             lang: Lang5::en_US,
             search_dirs: SearchDirs::default(),
             extra_dictionaries: Vec::default(),
+            import_editor_dictionaries: false,
+            project_dictionary: default_project_dictionary(),
+            sort_candidates: false,
             quirks: Quirks::default(),
             tokenization_splitchars: default_tokenization_splitchars(),
             skip_os_lookups: false,
@@ -126,6 +220,11 @@ impl HunspellConfig {
         self.extra_dictionaries.iter()
     }
 
+    /// The project dictionary, relative paths are resolved against `root`.
+    pub(crate) fn project_dictionary(&self, root: &Path) -> PathBuf {
+        root.join(&self.project_dictionary)
+    }
+
     pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
         self.search_dirs = self
             .search_dirs
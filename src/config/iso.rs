@@ -82,7 +82,7 @@ struct Lang5SpacerError(char);
 #[derive(Debug, Clone, Copy, Default)]
 struct Lang5Visitor;
 
-impl<'de> de::Visitor<'de> for Lang5Visitor {
+impl de::Visitor<'_> for Lang5Visitor {
     type Value = Lang5;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -92,7 +92,7 @@ impl<'de> de::Visitor<'de> for Lang5Visitor {
         )
     }
 
-    fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E>
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
@@ -0,0 +1,38 @@
+//! `LanguageTool` checker configuration.
+use serde::{Deserialize, Serialize};
+
+fn default_lang() -> String {
+    "en-US".to_owned()
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LanguageToolConfig {
+    /// The server to query, i.e. `http://localhost:8081` for a local one. The
+    /// `/v2/check` endpoint is appended.
+    pub url: url::Url,
+    /// The language code of the text, i.e. `en-US`, or `auto` to let the
+    /// server detect it.
+    #[serde(default = "default_lang")]
+    pub lang: String,
+    /// Ids of the rules the server must not apply, i.e. `WHITESPACE_RULE`.
+    #[serde(default)]
+    #[serde(alias = "disabled-rules")]
+    pub disabled_rules: Vec<String>,
+}
+
+impl LanguageToolConfig {
+    /// Query the server at `url` for `en-US` text with all its rules.
+    pub fn new(url: url::Url) -> Self {
+        Self {
+            url,
+            lang: default_lang(),
+            disabled_rules: Vec::new(),
+        }
+    }
+
+    pub fn url(&self) -> &url::Url {
+        &self.url
+    }
+}
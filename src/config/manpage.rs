@@ -0,0 +1,177 @@
+//! Render the man page from the command line definitions, see `manpage`.
+//!
+//! Emits `roff` with the `man` macros, one section for the top level options
+//! and one for each subcommand, so the page never gets out of sync with
+//! `--help`.
+
+use clap::{Arg, Command};
+use std::io::Write;
+
+/// Escape `text` for roff, a leading `.` or `'` would start a request.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Write `text` as paragraphs, blank lines separate them.
+fn paragraphs<W: Write>(sink: &mut W, text: &str) -> std::io::Result<()> {
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        writeln!(sink, ".PP\n{}", escape(paragraph.trim()))?;
+    }
+    Ok(())
+}
+
+/// The flags of `arg` and its value, i.e. `-c, --cfg <CFG>`.
+fn signature(arg: &Arg) -> String {
+    let value = arg
+        .get_value_names()
+        .map(|names| {
+            names
+                .iter()
+                .map(|name| format!("\\fI<{}>\\fR", escape(name)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|_| arg.get_num_args().map_or(false, |n| n.takes_values()))
+        .unwrap_or_else(|| {
+            format!(
+                "\\fI<{}>\\fR",
+                escape(&arg.get_id().as_str().to_uppercase())
+            )
+        });
+    if arg.is_positional() {
+        return value;
+    }
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut signature = flags.join(", ");
+    if arg.get_num_args().map_or(false, |n| n.takes_values()) {
+        signature.push(' ');
+        signature.push_str(&value);
+    }
+    signature
+}
+
+/// Write the arguments of `command`.
+fn arguments<W: Write>(sink: &mut W, command: &Command) -> std::io::Result<()> {
+    for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        writeln!(sink, ".TP\n{}", signature(arg))?;
+        if let Some(help) = arg.get_long_help().or_else(|| arg.get_help()) {
+            writeln!(sink, "{}", escape(help.to_string().trim()))?;
+        }
+        let defaults = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect::<Vec<_>>();
+        if !defaults.is_empty() && arg.get_num_args().map_or(false, |n| n.takes_values()) {
+            writeln!(sink, ".br\n[default: {}]", escape(&defaults.join(", ")))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the subcommands of `command`, nested ones prefixed with the names of
+/// their parents.
+fn subcommands<W: Write>(sink: &mut W, command: &Command, prefix: &str) -> std::io::Result<()> {
+    for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        let name = format!("{}{}", prefix, sub.get_name());
+        if name.ends_with("help") {
+            continue;
+        }
+        writeln!(sink, ".SS {}", escape(&name))?;
+        if let Some(about) = sub.get_long_about().or_else(|| sub.get_about()) {
+            paragraphs(sink, &about.to_string())?;
+        }
+        arguments(sink, sub)?;
+        subcommands(sink, sub, &format!("{} ", name))?;
+    }
+    Ok(())
+}
+
+/// Render the man page of `command` to `sink`.
+pub(crate) fn render<W: Write>(mut command: Command, sink: &mut W) -> std::io::Result<()> {
+    command.build();
+    let name = command.get_name().to_owned();
+    let version = command.get_version().unwrap_or_default();
+    writeln!(
+        sink,
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+        escape(&name.to_uppercase()),
+        escape(&name),
+        escape(version)
+    )?;
+    writeln!(sink, ".SH NAME")?;
+    let about = command
+        .get_about()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    writeln!(sink, "{} \\- {}", escape(&name), escape(about.trim()))?;
+    writeln!(sink, ".SH SYNOPSIS")?;
+    writeln!(
+        sink,
+        "\\fBcargo spellcheck\\fR [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR] [\\fIPATHS\\fR]..."
+    )?;
+    writeln!(sink, ".SH DESCRIPTION")?;
+    let description = command.get_long_about().map(ToString::to_string);
+    paragraphs(sink, description.as_deref().unwrap_or(&about))?;
+    writeln!(sink, ".SH OPTIONS")?;
+    arguments(sink, &command)?;
+    writeln!(sink, ".SH COMMANDS")?;
+    subcommands(sink, &command, "")?;
+    if let Some(after) = command.get_after_long_help() {
+        writeln!(sink, ".SH EXAMPLES")?;
+        writeln!(sink, ".nf\n{}\n.fi", escape(after.to_string().trim()))?;
+    }
+    if let Some(author) = command.get_author() {
+        writeln!(sink, ".SH AUTHORS\n{}", escape(author))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape(".spellcheck-dict"), "\\&.spellcheck\\-dict");
+        assert_eq!(escape("a\\b"), "a\\eb");
+    }
+
+    #[test]
+    fn all_commands() {
+        let mut page = Vec::new();
+        render(
+            <crate::config::args::Args as clap::CommandFactory>::command(),
+            &mut page,
+        )
+        .unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".TH CARGO\\-SPELLCHECK 1"));
+        for section in [
+            ".SS check",
+            ".SS dict convert",
+            ".SS manpage",
+            ".SH EXAMPLES",
+        ] {
+            assert!(page.contains(section), "{} is missing", section);
+        }
+        assert!(page.contains("\\fB\\-m\\fR, \\fB\\-\\-code\\fR \\fI<CODE>\\fR"));
+        assert!(!page.contains(".SS help"));
+    }
+}
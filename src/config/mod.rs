@@ -9,6 +9,7 @@
 #![allow(missing_docs)]
 
 pub mod args;
+mod manpage;
 
 mod regex;
 pub use self::regex::*;
@@ -22,12 +23,33 @@ pub use self::hunspell::*;
 mod nlprules;
 pub use self::nlprules::*;
 
+mod languagetool;
+pub use self::languagetool::*;
+
+mod external;
+pub use self::external::*;
+
+mod files;
+pub use self::files::*;
+
 mod search_dirs;
 pub use search_dirs::*;
 
 mod iso;
 pub use iso::*;
 
+mod severity;
+pub(crate) use self::severity::Overrides;
+pub use self::severity::SeverityOverrides;
+
+mod words;
+pub use self::words::*;
+
+mod filters;
+pub use self::filters::*;
+
+use self::args::ApplyStrategy;
+use crate::checker::RunFilter;
 use crate::errors::*;
 use crate::Detector;
 use fancy_regex::Regex;
@@ -40,6 +62,7 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 // TODO figure out which ISO spec this actually is
+#[allow(dead_code)]
 pub struct CommonLang(String);
 
 impl std::str::FromStr for CommonLang {
@@ -50,13 +73,17 @@ impl std::str::FromStr for CommonLang {
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
+#[non_exhaustive]
 pub struct Config {
     // Options that modify the inputs being picked up.
     #[serde(default)]
     #[serde(alias = "dev-comments")]
     #[serde(alias = "devcomments")]
+    #[serde(alias = "check_comments")]
+    #[serde(alias = "check-comments")]
     pub dev_comments: bool,
 
     #[serde(default)]
@@ -64,6 +91,26 @@ pub struct Config {
     #[serde(alias = "skipreadme")]
     pub skip_readme: bool,
 
+    /// Glob patterns, relative to the working directory, of the files and
+    /// directories to exclude, i.e. `target/**`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+
+    /// Skip the files ignored by `.gitignore`, `.ignore` and the global git
+    /// excludes while walking directories.
+    #[serde(default = "default_respect_gitignore")]
+    #[serde(alias = "respect-gitignore")]
+    pub respect_gitignore: bool,
+
+    /// The checkers to run, by the names `--only` accepts, all configured
+    /// ones if unset. The order does not matter, the checkers always run in
+    /// the same order.
+    #[serde(default)]
+    #[serde(alias = "Checkers")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkers: Option<Vec<String>>,
+
     #[serde(alias = "Hunspell")]
     #[serde(default = "default_hunspell")]
     pub hunspell: Option<HunspellConfig>,
@@ -73,11 +120,90 @@ pub struct Config {
     #[serde(alias = "nlp")]
     #[serde(alias = "NLP")]
     #[serde(alias = "NlpRules")]
+    #[serde(alias = "nlprule")]
     #[serde(default = "default_nlprules")]
     pub nlprules: Option<NlpRulesConfig>,
+
+    #[serde(alias = "LanguageTool")]
+    #[serde(alias = "Languagetool")]
+    pub languagetool: Option<LanguageToolConfig>,
+
     #[serde(alias = "ReFlow")]
     #[serde(alias = "Reflow")]
     pub reflow: Option<ReflowConfig>,
+
+    #[serde(default)]
+    #[serde(alias = "Files")]
+    pub files: FilesConfig,
+
+    #[serde(default)]
+    #[serde(alias = "severity-overrides")]
+    #[serde(skip_serializing_if = "SeverityOverrides::is_empty")]
+    pub severity_overrides: SeverityOverrides,
+
+    #[serde(default)]
+    #[serde(alias = "Words")]
+    #[serde(skip_serializing_if = "WordsConfig::is_empty")]
+    pub words: WordsConfig,
+
+    /// Regular expressions whose matches are masked, i.e. version strings,
+    /// appended to the stages of `[filters]`.
+    #[serde(default)]
+    #[serde(alias = "ignore-patterns")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore_patterns: Vec<WrappedRegex>,
+
+    #[serde(default)]
+    #[serde(alias = "Filters")]
+    #[serde(skip_serializing_if = "FiltersConfig::is_default")]
+    pub filters: FiltersConfig,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "CheckerTableConfig::is_empty")]
+    pub checker: CheckerTableConfig,
+
+    /// Checkers and rules selected with `--only` and `--skip`.
+    #[serde(skip)]
+    pub(crate) filter: RunFilter,
+
+    /// The subject of the commit of the applied fixes, see `--commit`.
+    #[serde(skip)]
+    pub(crate) commit: Option<String>,
+
+    /// Patterns of the items to check, see `--item-path`.
+    #[serde(skip)]
+    pub(crate) item_paths: Vec<String>,
+
+    /// The changed lines to check, see `--diff-base`.
+    #[serde(skip)]
+    pub(crate) changes: Option<crate::traverse::Changes>,
+
+    /// Keep the findings of every file on disk, so unchanged files are not
+    /// checked again, unless `--no-cache` is given.
+    #[serde(skip)]
+    pub(crate) findings_cache: bool,
+
+    /// Only change the files if all of them can be fixed, see `--atomic`.
+    #[serde(skip)]
+    pub(crate) atomic: bool,
+
+    /// Resolve all occurrences of a word at once during `fix`, see
+    /// `--by-word`.
+    #[serde(skip)]
+    pub(crate) by_word: bool,
+
+    /// Pick the fixes without asking, see `--apply`.
+    #[serde(skip)]
+    pub(crate) apply: Option<ApplyStrategy>,
+
+    /// Read or write the picked fixes, see `--decisions`.
+    #[serde(skip)]
+    pub(crate) decisions: Option<PathBuf>,
+
+    /// The directory the run is based on, the one of the `--manifest-path`
+    /// manifest, the working directory if unset.
+    #[serde(skip)]
+    pub(crate) base_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -90,14 +216,25 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        self.checker.sanitize_paths(base);
         Ok(())
     }
 
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
-        Ok(toml::from_str(s.as_ref())?)
+        let mut value = toml::from_str::<toml::Value>(s.as_ref())?;
+        if let Some(table) = value.as_table_mut() {
+            filters::migrate_mask(table);
+        }
+        Ok(value.try_into()?)
     }
 
     pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
+        Self::load_extending(path.as_ref(), &mut Vec::new())
+    }
+
+    /// Load the config at `path`, merged onto the config it `extends`.
+    /// `chain` holds the configs extended so far, to detect cycles.
+    fn load_extending(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Option<Self>> {
         let (contents, path) = match Self::load_content(path) {
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 return Ok(None);
@@ -105,19 +242,97 @@ impl Config {
             Err(e) => bail!(e),
             Ok(contents) => contents,
         };
-        Self::parse(&contents)
-            .wrap_err_with(|| {
+        Self::from_content(&contents, &path, chain).map(Some)
+    }
+
+    /// Load the config read from the standard input. Relative paths are
+    /// resolved against the working directory, discovery is bypassed.
+    pub fn load_from_stdin() -> Result<Self> {
+        Self::parse_stdin(&Self::read_stdin()?, &crate::traverse::cwd()?)
+    }
+
+    /// The raw config of the standard input.
+    pub(crate) fn read_stdin() -> Result<String> {
+        let mut contents = String::with_capacity(1024);
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .wrap_err("Failed to read the config from stdin")?;
+        Ok(contents)
+    }
+
+    /// Parse the `contents` read from the standard input, relative `extends`
+    /// are resolved against `base`.
+    pub(crate) fn parse_stdin(contents: &str, base: &Path) -> Result<Self> {
+        Self::from_content(contents, &base.join("<stdin>"), &mut Vec::new())
+    }
+
+    /// Parse the `contents` of the config at `path`, merged onto the config
+    /// it `extends`.
+    fn from_content(contents: &str, path: &Path, chain: &mut Vec<PathBuf>) -> Result<Self> {
+        let broken = || {
+            eyre!(
+                "Syntax of a given config file({}) is broken",
+                path.display()
+            )
+        };
+        let mut value = toml::from_str::<toml::Value>(contents).wrap_err_with(broken)?;
+        if let Some(table) = value.as_table_mut() {
+            filters::migrate_mask(table);
+        }
+        let extends = value
+            .as_table_mut()
+            .and_then(|table| table.remove("extends"));
+        if let Some(extends) = extends {
+            let extends = extends
+                .as_str()
+                .ok_or_else(|| eyre!("`extends` of {} must be a string", path.display()))?;
+            let base_path = Self::resolve_extends(extends, path)?;
+            chain.push(path.to_owned());
+            if chain.contains(&base_path) {
+                bail!(
+                    "Config {} extends itself via {}",
+                    base_path.display(),
+                    path.display()
+                );
+            }
+            let base = Self::load_extending(&base_path, chain)?.ok_or_else(|| {
                 eyre!(
-                    "Syntax of a given config file({}) is broken",
+                    "Config {} extended by {} does not exist",
+                    base_path.display(),
                     path.display()
                 )
-            })
-            .and_then(|mut cfg| {
-                if let Some(base) = path.parent() {
-                    cfg.sanitize_paths(base)?;
-                }
-                Ok(Some(cfg))
-            })
+            })?;
+            chain.pop();
+            let mut merged = toml::Value::try_from(base)?;
+            merge(&mut merged, value);
+            value = merged;
+        }
+        let mut cfg = value.try_into::<Self>().wrap_err_with(broken)?;
+        if let Some(base) = path.parent() {
+            cfg.sanitize_paths(base)?;
+        }
+        Ok(cfg)
+    }
+
+    /// The path of the config named by `extends` in the config at `path`.
+    ///
+    /// A file name without extension refers to a preset installed in the
+    /// `presets` directory next to the per user configuration, anything else
+    /// is a path relative to the extending config.
+    fn resolve_extends(extends: &str, path: &Path) -> Result<PathBuf> {
+        let extends = Path::new(extends);
+        let is_preset = extends.components().count() == 1 && extends.extension().is_none();
+        let base_path = if is_preset {
+            Self::default_path()?
+                .with_file_name("presets")
+                .join(extends)
+                .with_extension("toml")
+        } else {
+            path.parent().unwrap_or(path).join(extends)
+        };
+        base_path
+            .canonicalize()
+            .wrap_err_with(|| eyre!("Failed to find config {}", base_path.display()))
     }
 
     pub fn load_content<P: AsRef<Path>>(path: P) -> std::io::Result<(String, PathBuf)> {
@@ -217,11 +432,24 @@ impl Config {
         Self::write_default_values_to(d.join("config.toml"))
     }
 
+    /// The directory relative paths of the run are resolved against, see
+    /// [`Config::base_dir`](#structfield.base_dir).
+    pub(crate) fn base_dir(&self) -> Result<PathBuf> {
+        match self.base_dir {
+            Some(ref base_dir) => Ok(base_dir.clone()),
+            None => crate::traverse::cwd(),
+        }
+    }
+
     pub fn is_enabled(&self, detector: Detector) -> bool {
         match detector {
             Detector::Hunspell => self.hunspell.is_some(),
             Detector::NlpRules => self.nlprules.is_some(),
+            Detector::LanguageTool => self.languagetool.is_some(),
             Detector::Reflow => self.reflow.is_some(),
+            Detector::External => !self.checker.external.is_empty(),
+            Detector::Wasm => !self.checker.wasm.is_empty(),
+            Detector::EmptyDocs => self.checker.empty_docs || self.checker.empty_alt,
             #[cfg(test)]
             Detector::Dummy => true,
         }
@@ -241,6 +469,10 @@ fn default_nlprules() -> Option<NlpRulesConfig> {
     }
 }
 
+fn default_respect_gitignore() -> bool {
+    true
+}
+
 fn default_hunspell() -> Option<HunspellConfig> {
     Some(HunspellConfig::default())
 }
@@ -250,13 +482,70 @@ impl Default for Config {
         Self {
             dev_comments: false,
             skip_readme: false,
+            ignore: Vec::new(),
+            respect_gitignore: default_respect_gitignore(),
+            checkers: None,
             hunspell: default_hunspell(),
             nlprules: default_nlprules(),
+            languagetool: None,
             reflow: Some(ReflowConfig::default()),
+            files: FilesConfig::default(),
+            severity_overrides: SeverityOverrides::default(),
+            words: WordsConfig::default(),
+            filters: FiltersConfig::default(),
+            ignore_patterns: Vec::new(),
+            checker: CheckerTableConfig::default(),
+            filter: RunFilter::default(),
+            commit: None,
+            item_paths: Vec::new(),
+            changes: None,
+            findings_cache: false,
+            atomic: false,
+            by_word: false,
+            apply: None,
+            decisions: None,
+            base_dir: None,
         }
     }
 }
 
+/// The spelling of a key all of its aliases share, i.e. `dev-comments` and
+/// `dev_comments` are the same key.
+fn canonical_key(key: &str) -> String {
+    let key = key.to_lowercase().replace(|c| c == '-' || c == '_', "");
+    match key.as_str() {
+        "nlp" => "nlprules".to_owned(),
+        "checkcomments" => "devcomments".to_owned(),
+        _ => key,
+    }
+}
+
+/// Merge `overlay` onto `base`, tables are merged recursively, all other values
+/// including arrays are replaced.
+fn merge(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let canonical = canonical_key(&key);
+                let existing = base
+                    .keys()
+                    .find(|existing| canonical_key(existing) == canonical)
+                    .cloned();
+                match existing.and_then(|existing| base.remove(&existing)) {
+                    Some(mut existing) => {
+                        merge(&mut existing, value);
+                        base.insert(key, existing);
+                    }
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -408,7 +697,302 @@ max_line_length = 42
         .unwrap();
         assert_eq!(
             cfg.reflow.expect("Must contain reflow cfg").max_line_length,
-            42
+            Some(42)
         );
     }
+
+    #[test]
+    fn partial_10() {
+        let cfg = Config::parse(
+            r#"
+[[checker.external]]
+command = "./my-checker"
+args = ["--strict"]
+
+[[checker.external]]
+name = "Terminology"
+command = "terms"
+"#,
+        )
+        .unwrap();
+        let external = cfg.checker.external;
+        assert_eq!(external.len(), 2);
+        assert_eq!(external[0].name(), "./my-checker");
+        assert_eq!(external[0].args, vec!["--strict".to_owned()]);
+        assert_eq!(external[1].name(), "Terminology");
+    }
+
+    #[test]
+    fn partial_11() {
+        let cfg = Config::parse(
+            r#"
+[[checker.wasm]]
+module = "plugins/terms.wasm"
+"#,
+        )
+        .unwrap();
+        let wasm = &cfg.checker.wasm[0];
+        assert_eq!(wasm.name(), "plugins/terms.wasm");
+        assert_eq!(wasm.fuel, 1_000_000_000);
+    }
+
+    #[test]
+    fn partial_12() {
+        let cfg = Config::parse(
+            r#"
+[Hunspell]
+import_editor_dictionaries = true
+"#,
+        )
+        .unwrap();
+        assert!(cfg.hunspell.unwrap().import_editor_dictionaries);
+    }
+
+    #[test]
+    fn partial_13() {
+        let cfg = Config::parse(
+            r#"
+[files]
+generated_markers = ["// Code generated"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.files.generated_markers, vec!["// Code generated"]);
+        assert_eq!(cfg.files.generated_header_lines, 5);
+        assert!(cfg
+            .files
+            .is_generated("// Code generated by bindgen.\nfn foo() {}"));
+        assert!(!Config::default().files.is_generated("//! Hand written."));
+    }
+
+    #[test]
+    fn partial_14() {
+        let cfg = Config::parse(
+            r#"
+[files]
+max_file_size = 1024
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.files.max_file_size, 1024);
+        assert_eq!(cfg.files.max_total_size, 256 << 20);
+    }
+
+    #[test]
+    fn partial_15() {
+        let cfg = Config::parse(
+            r#"
+[checker]
+skip-flagged = true
+
+[[checker.external]]
+command = "replacements"
+cheap = true
+"#,
+        )
+        .unwrap();
+        assert!(cfg.checker.skip_flagged);
+        assert!(cfg.checker.external[0].cheap);
+    }
+
+    #[test]
+    fn partial_16() {
+        let cfg = Config::parse(
+            r#"
+[checker]
+empty-docs = true
+"#,
+        )
+        .unwrap();
+        assert!(cfg.checker.empty_docs);
+        assert!(cfg.is_enabled(Detector::EmptyDocs));
+    }
+
+    #[test]
+    fn partial_17() {
+        let cfg = Config::parse(
+            r#"
+[files]
+recover-unparseable = true
+"#,
+        )
+        .unwrap();
+        assert!(cfg.files.recover_unparseable);
+        assert!(!Config::default().files.recover_unparseable);
+    }
+
+    #[test]
+    fn partial_18() {
+        let cfg = Config::parse(
+            r#"
+[files]
+config-comments = ["Cargo.toml", ".github/**/*.yml"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.files.config_comments,
+            vec!["Cargo.toml", ".github/**/*.yml"]
+        );
+        assert!(Config::default().files.config_comments.is_empty());
+    }
+
+    #[test]
+    fn partial_19() {
+        let cfg = Config::parse(
+            r##"
+[files]
+config-comments = ["scripts/*"]
+
+[files.extra]
+sh = "#"
+lua = "--"
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.files
+                .comment_prefix(std::path::Path::new("scripts/release.sh")),
+            Some("#")
+        );
+        assert_eq!(
+            cfg.files.comment_prefix(std::path::Path::new("init.lua")),
+            Some("--")
+        );
+        assert_eq!(
+            cfg.files.comment_prefix(std::path::Path::new("Cargo.toml")),
+            None
+        );
+    }
+
+    #[test]
+    fn partial_20() {
+        let cfg = Config::parse(
+            r#"
+[files]
+markdown = ["docs/**/*.md", "CHANGELOG.md"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.files.markdown, vec!["docs/**/*.md", "CHANGELOG.md"]);
+    }
+
+    #[test]
+    fn partial_21() {
+        let cfg = Config::parse(
+            r#"
+checkers = ["hunspell", "nlprules"]
+
+[nlprule]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.checkers,
+            Some(vec!["hunspell".to_owned(), "nlprules".to_owned()])
+        );
+        assert!(cfg.nlprules.is_some());
+        assert!(Config::default().checkers.is_none());
+    }
+
+    #[test]
+    fn former_mask_table() {
+        let stages = |content: &str| {
+            Config::parse(content)
+                .unwrap()
+                .filters
+                .stages
+                .into_iter()
+                .map(|stage| format!("{:?}", stage))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            stages("[mask]\npaths = false\n"),
+            vec!["Builtin(Urls)", "Builtin(Emails)"]
+        );
+        assert_eq!(stages("[mask]\n").len(), 3);
+        assert_eq!(
+            stages("[mask]\nurls = false\n\n[filters]\nstages = [\"urls\", \"code\"]\n"),
+            vec!["Builtin(Urls)", "Builtin(Code)"]
+        );
+    }
+
+    #[test]
+    fn extends_base_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("repo")).unwrap();
+        fs::write(
+            dir.join("base.toml"),
+            r#"
+dev_comments = true
+
+[hunspell]
+lang = "en_GB"
+
+[files]
+recover-unparseable = true
+config-comments = ["*.toml", "*.yml"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("repo").join("spellcheck.toml"),
+            r#"
+extends = "../base.toml"
+skip_readme = true
+
+[files]
+config-comments = ["*.json5"]
+"#,
+        )
+        .unwrap();
+
+        let cfg = Config::load_from(dir.join("repo").join("spellcheck.toml"))
+            .unwrap()
+            .unwrap();
+        assert!(cfg.dev_comments);
+        assert!(cfg.skip_readme);
+        assert_eq!(cfg.hunspell.unwrap().lang.to_string(), "en_GB");
+        // tables are merged, arrays replaced
+        assert!(cfg.files.recover_unparseable);
+        assert_eq!(cfg.files.config_comments, vec!["*.json5".to_owned()]);
+
+        fs::write(
+            dir.join("base.toml"),
+            "extends = \"repo/spellcheck.toml\"\n",
+        )
+        .unwrap();
+        assert!(Config::load_from(dir.join("repo").join("spellcheck.toml")).is_err());
+    }
+
+    #[test]
+    fn checker_table_and_checkers_list() {
+        const CONTENT: &str = "checkers = [\"hunspell\"]\n\n[checker]\nskip_flagged = true\n";
+        let cfg = Config::parse(CONTENT).unwrap();
+        assert_eq!(cfg.checkers, Some(vec!["hunspell".to_owned()]));
+        assert!(cfg.checker.skip_flagged);
+
+        // neither replaces the other when merged
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("base.toml"), CONTENT).unwrap();
+        fs::write(
+            dir.join("spellcheck.toml"),
+            "extends = \"base.toml\"\nCheckers = [\"nlprules\"]\n\n[checker]\nempty_docs = true\n",
+        )
+        .unwrap();
+        let cfg = Config::load_from(dir.join("spellcheck.toml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cfg.checkers, Some(vec!["nlprules".to_owned()]));
+        assert!(cfg.checker.skip_flagged);
+        assert!(cfg.checker.empty_docs);
+    }
+
+    #[test]
+    fn check_comments() {
+        let cfg = Config::parse("check_comments = true\n").unwrap();
+        assert!(cfg.dev_comments);
+    }
 }
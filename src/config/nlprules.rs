@@ -2,17 +2,6 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct LanguageToolConfig {
-    pub url: url::Url,
-}
-
-impl LanguageToolConfig {
-    pub fn url(&self) -> &url::Url {
-        &self.url
-    }
-}
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct NlpRulesConfig {
@@ -20,4 +9,13 @@ pub struct NlpRulesConfig {
     /// data.
     pub override_rules: Option<PathBuf>,
     pub override_tokenizer: Option<PathBuf>,
+    /// Only apply the rules with these ids, or id prefixes ending at a `/`,
+    /// i.e. `GRAMMAR` or `GRAMMAR/PRP_MD_NN`. All rules if empty.
+    #[serde(default)]
+    #[serde(alias = "enabled-rules")]
+    pub enabled_rules: Vec<String>,
+    /// Never apply the rules with these ids, or id prefixes ending at a `/`.
+    #[serde(default)]
+    #[serde(alias = "disabled-rules")]
+    pub disabled_rules: Vec<String>,
 }
@@ -1,19 +1,101 @@
 //! Reflow configuration.
+use crate::errors::*;
+
+use fs_err as fs;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// Line width used if neither configured nor given by `rustfmt.toml`.
+const DEFAULT_MAX_LINE_LENGTH: usize = 80;
 
 /// Parameters for wrapping doc comments
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReflowConfig {
-    /// Hard limit for absolute length of lines.
+    /// Hard limit for absolute length of lines. If unset, the `comment_width`
+    /// or `max_width` of the project's `rustfmt.toml` is used.
     #[serde(default)]
     #[serde(alias = "max_line_width")]
-    pub(crate) max_line_length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_line_length: Option<usize>,
+}
+
+impl ReflowConfig {
+    /// The effective limit for the length of lines.
+    pub(crate) fn max_line_length(&self) -> usize {
+        self.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH)
+    }
+
+    /// Unless configured explicitly, use the line width of the `rustfmt.toml`
+    /// or `.rustfmt.toml` in `dir` or the closest of its parents.
+    pub(crate) fn with_rustfmt_width(self, dir: &Path) -> Result<Self> {
+        if self.max_line_length.is_some() {
+            return Ok(self);
+        }
+        Ok(Self {
+            max_line_length: rustfmt_width(dir)?,
+        })
+    }
 }
 
-impl Default for ReflowConfig {
-    fn default() -> Self {
-        Self {
-            max_line_length: 80,
+/// The `comment_width`, or the `max_width` if there is none, of the closest
+/// rustfmt configuration, like `rustfmt` itself looks them up.
+fn rustfmt_width(dir: &Path) -> Result<Option<usize>> {
+    for dir in dir.ancestors() {
+        for name in &["rustfmt.toml", ".rustfmt.toml"] {
+            let path = dir.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(&path)?;
+            let table = toml::from_str::<toml::Value>(&content)
+                .wrap_err_with(|| eyre!("Failed to parse {}", path.display()))?;
+            let width = |key: &str| {
+                table
+                    .get(key)
+                    .and_then(toml::Value::as_integer)
+                    .and_then(|width| usize::try_from(width).ok())
+            };
+            let width = width("comment_width").or_else(|| width("max_width"));
+            log::debug!("Using line width {:?} of {}", width, path.display());
+            return Ok(width);
         }
     }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_from_rustfmt() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let nested = dir.join("src");
+        fs::create_dir_all(&nested).unwrap();
+
+        fs::write(dir.join("rustfmt.toml"), "max_width = 120\n").unwrap();
+        let cfg = ReflowConfig::default().with_rustfmt_width(&nested).unwrap();
+        assert_eq!(cfg.max_line_length(), 120);
+
+        fs::write(
+            dir.join("rustfmt.toml"),
+            "max_width = 120\ncomment_width = 90\n",
+        )
+        .unwrap();
+        let cfg = ReflowConfig::default().with_rustfmt_width(&nested).unwrap();
+        assert_eq!(cfg.max_line_length(), 90);
+
+        let explicit = ReflowConfig {
+            max_line_length: Some(42),
+        };
+        assert_eq!(
+            explicit
+                .with_rustfmt_width(&nested)
+                .unwrap()
+                .max_line_length(),
+            42
+        );
+    }
 }
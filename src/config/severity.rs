@@ -0,0 +1,137 @@
+//! Severities of specific words and paths, collected under
+//! `[severity_overrides]`.
+//!
+//! Keys containing a `/` or a `*` are glob patterns of paths, relative to the
+//! working directory, i.e. `"src/lib.rs" = "error"` for the crate front page,
+//! all others are words, compared case insensitively, i.e.
+//! `"recieve" = "error"`. An override of the word wins over the one of the
+//! path.
+
+use crate::errors::*;
+use crate::{sub_chars, Severity, Suggestion};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// The severity by word or path pattern.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SeverityOverrides(pub BTreeMap<String, Severity>);
+
+impl SeverityOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Compile the path patterns, relative to `base_dir`.
+    pub(crate) fn compile(&self, base_dir: &Path) -> Result<Overrides> {
+        let mut overrides = Overrides::default();
+        if self.is_empty() {
+            return Ok(overrides);
+        }
+        let cwd = base_dir
+            .canonicalize()
+            .unwrap_or_else(|_| base_dir.to_owned());
+        for (key, severity) in &self.0 {
+            if !key.contains(|c| matches!(c, '/' | '*')) {
+                overrides.words.insert(key.to_lowercase(), *severity);
+                continue;
+            }
+            let pattern = cwd.join(key);
+            let pattern = pattern.to_str().ok_or_else(|| {
+                eyre!(
+                    "Failed to convert path to str for severity override {}",
+                    pattern.display()
+                )
+            })?;
+            let pattern = glob::Pattern::new(pattern)
+                .wrap_err_with(|| eyre!("Invalid severity override pattern {}", key))?;
+            overrides.paths.push((pattern, *severity));
+        }
+        Ok(overrides)
+    }
+}
+
+/// The compiled [`SeverityOverrides`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overrides {
+    words: HashMap<String, Severity>,
+    paths: Vec<(glob::Pattern, Severity)>,
+}
+
+impl Overrides {
+    /// The severity `suggestion` is overridden with, if any.
+    pub(crate) fn resolve(&self, suggestion: &Suggestion<'_>) -> Option<Severity> {
+        if !self.words.is_empty() {
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            if let Some(severity) = self.words.get(&word.to_lowercase()) {
+                return Some(*severity);
+            }
+        }
+        let path = suggestion.origin.as_path();
+        self.paths
+            .iter()
+            .find(|(pattern, _)| pattern.matches_path(path))
+            .map(|(_, severity)| *severity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, ContentOrigin, Detector, Documentation};
+
+    #[test]
+    fn words_and_paths() {
+        let overrides = toml::from_str::<BTreeMap<String, SeverityOverrides>>(
+            r#"
+[severity_overrides]
+"Recieve" = "error"
+"teh" = "note"
+"/tmp/test/*.rs" = "warning"
+"#,
+        )
+        .unwrap()
+        .remove("severity_overrides")
+        .unwrap()
+        .compile(&crate::traverse::cwd().unwrap())
+        .unwrap();
+
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(
+            origin.clone(),
+            chyrp_up!("We recieve teh data."),
+            true,
+            false,
+        );
+        let chunk = &docs.get(&origin).unwrap()[0];
+        let suggestion = |word: &str| {
+            let start = chunk.as_str().find(word).unwrap();
+            let range = start..start + word.len();
+            Suggestion {
+                detector: Detector::NlpRules,
+                origin: origin.clone(),
+                chunk,
+                span: chunk.find_spans(range.clone())[0],
+                range,
+                replacements: Vec::new(),
+                description: None,
+                rule: None,
+                help: None,
+                severity: None,
+                also: Vec::new(),
+            }
+        };
+        assert_eq!(
+            overrides.resolve(&suggestion("recieve")),
+            Some(Severity::Error)
+        );
+        assert_eq!(overrides.resolve(&suggestion("teh")), Some(Severity::Note));
+        assert_eq!(
+            overrides.resolve(&suggestion("data")),
+            Some(Severity::Warning)
+        );
+        assert_eq!(Overrides::default().resolve(&suggestion("recieve")), None);
+    }
+}
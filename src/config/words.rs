@@ -0,0 +1,40 @@
+//! Word lists, collected under `[words]`.
+use serde::{Deserialize, Serialize};
+
+/// Settings of specific words, collected under `[words]`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WordsConfig {
+    /// Proper nouns, see [`ProperNounsConfig`].
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ProperNounsConfig::is_empty")]
+    pub proper: ProperNounsConfig,
+}
+
+impl WordsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.proper.is_empty()
+    }
+}
+
+/// Proper nouns, collected under `[words.proper]`, which are only accepted
+/// with their exact capitalization, i.e. `GitHub` or `WebAssembly`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ProperNounsConfig {
+    /// The canonical forms.
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Flag other capitalizations, i.e. `github`, as style findings with the
+    /// canonical form as replacement, rather than leaving them to the
+    /// dictionary.
+    #[serde(default)]
+    #[serde(alias = "flag-variants")]
+    pub flag_variants: bool,
+}
+
+impl ProperNounsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && !self.flag_variants
+    }
+}
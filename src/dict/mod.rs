@@ -0,0 +1,515 @@
+//! Dictionary maintenance
+//!
+//! Conversion between the word list formats of other spellcheckers and the
+//! hunspell format used for `extra_dictionaries`, import of editor specific
+//! project dictionaries, the project dictionary `learn` appends to and review
+//! of project jargon.
+
+use crate::errors::*;
+
+use fs_err as fs;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+mod review;
+pub(crate) use self::review::review;
+
+/// The default file name of the project dictionary, in the project root.
+pub(crate) const PROJECT_DICTIONARY: &str = ".spellcheck-dict.txt";
+
+/// Supported word list formats.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DictFormat {
+    /// One word per line, `#` starts a comment.
+    Wordlist,
+    /// The hunspell `.dic` format, as used for `extra_dictionaries`.
+    Hunspell,
+    /// Vale vocabulary files, i.e. `accept.txt`.
+    Vale,
+    /// VS Code settings or `cspell.json`, containing `cSpell.words` or `words`.
+    CSpell,
+    /// IDEA project dictionaries, i.e. `.idea/dictionaries/alice.xml`.
+    IntelliJ,
+}
+
+impl FromStr for DictFormat {
+    type Err = UnknownDictFormat;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        Ok(match s.as_str() {
+            "wordlist" | "txt" | "plain" => Self::Wordlist,
+            "hunspell" | "dic" => Self::Hunspell,
+            "vale" => Self::Vale,
+            "cspell" | "vscode" => Self::CSpell,
+            "intellij" | "idea" => Self::IntelliJ,
+            _other => return Err(UnknownDictFormat(s)),
+        })
+    }
+}
+
+/// The provided string does not name a known dictionary format.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "Unknown dictionary format `{0}`, expected one of wordlist, hunspell, vale, cspell, intellij"
+)]
+pub struct UnknownDictFormat(String);
+
+impl DictFormat {
+    /// Derive the format from the file name of vale vocabularies, or else the
+    /// file extension.
+    fn from_path(path: &Path) -> Option<Self> {
+        if path.file_name()? == "accept.txt" {
+            return Some(Self::Vale);
+        }
+        match path.extension()?.to_str()? {
+            "dic" => Some(Self::Hunspell),
+            "json" => Some(Self::CSpell),
+            "xml" => Some(Self::IntelliJ),
+            "txt" => Some(Self::Wordlist),
+            _ => None,
+        }
+    }
+}
+
+/// Characters that indicate a regular expression in a vale vocabulary entry.
+const REGEX_META: &[char] = &[
+    '[', ']', '(', ')', '?', '*', '+', '|', '\\', '{', '}', '^', '$',
+];
+
+/// Remove `//` and `/* */` comments outside of string literals, VS Code
+/// settings are JSON with comments.
+fn strip_json_comments(s: &str) -> String {
+    let mut acc = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            acc.push(c);
+            match c {
+                '\\' => acc.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                acc.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        acc.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                let _ = chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => acc.push(c),
+        }
+    }
+    acc
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn parse_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Extract the words from `content`, given in `format`.
+pub fn read_words(format: DictFormat, content: &str) -> Result<Vec<String>> {
+    let words = match format {
+        DictFormat::Wordlist => parse_lines(content).map(ToOwned::to_owned).collect(),
+        DictFormat::Hunspell => {
+            let mut lines = content.lines();
+            if let Some(first) = lines.next() {
+                let _ = first.trim().parse::<u64>().wrap_err_with(|| {
+                    eyre!(
+                        "First line of a hunspell dictionary must be a number, but is: >{}<",
+                        first
+                    )
+                })?;
+            }
+            lines
+                .map(|line| line.split('/').next().unwrap_or_default().trim())
+                .filter(|word| !word.is_empty())
+                .map(ToOwned::to_owned)
+                .collect()
+        }
+        DictFormat::Vale => parse_lines(content)
+            .filter(|entry| {
+                let is_regex = entry.contains(REGEX_META);
+                if is_regex {
+                    log::warn!("Skipping vale regex entry >{}<", entry);
+                }
+                !is_regex
+            })
+            .map(ToOwned::to_owned)
+            .collect(),
+        DictFormat::CSpell => {
+            let value: serde_json::Value = serde_json::from_str(&strip_json_comments(content))
+                .wrap_err_with(|| eyre!("Failed to parse cSpell settings"))?;
+            let words = value
+                .get("cSpell.words")
+                .or_else(|| value.get("words"))
+                .and_then(serde_json::Value::as_array)
+                .ok_or_else(|| eyre!("Neither `cSpell.words` nor `words` is present"))?;
+            words
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        }
+        DictFormat::IntelliJ => {
+            lazy_static::lazy_static! {
+                static ref WORD: regex::Regex = regex::Regex::new(r"<w>([^<]*)</w>")
+                    .expect("REGEX grammar is human checked. qed");
+            };
+            WORD.captures_iter(content)
+                .map(|captures| xml_unescape(captures[1].trim()))
+                .filter(|word| !word.is_empty())
+                .collect()
+        }
+    };
+    Ok(words)
+}
+
+/// Write `words` in `format` to `sink`.
+///
+/// The words are sorted and deduplicated.
+pub fn write_words(format: DictFormat, words: &[String], mut sink: impl Write) -> Result<()> {
+    let mut words = words.to_vec();
+    words.sort();
+    words.dedup();
+    match format {
+        DictFormat::Wordlist | DictFormat::Vale => {
+            for word in words {
+                writeln!(sink, "{}", word)?;
+            }
+        }
+        DictFormat::Hunspell => {
+            writeln!(sink, "{}", words.len())?;
+            for word in words {
+                writeln!(sink, "{}", word)?;
+            }
+        }
+        DictFormat::CSpell => {
+            let value = serde_json::json!({ "cSpell.words": words });
+            serde_json::to_writer_pretty(&mut sink, &value)?;
+            writeln!(sink)?;
+        }
+        DictFormat::IntelliJ => {
+            writeln!(sink, r#"<component name="ProjectDictionaryState">"#)?;
+            writeln!(sink, r#"  <dictionary name="cargo-spellcheck">"#)?;
+            writeln!(sink, "    <words>")?;
+            for word in words {
+                writeln!(sink, "      <w>{}</w>", xml_escape(&word))?;
+            }
+            writeln!(sink, "    </words>")?;
+            writeln!(sink, "  </dictionary>")?;
+            writeln!(sink, "</component>")?;
+        }
+    }
+    Ok(())
+}
+
+/// Collect the words of editor specific project dictionaries below `root`.
+///
+/// Considers `cSpell.words` of `.vscode/settings.json` and all IDEA
+/// dictionaries in `.idea/dictionaries/*.xml`. Missing and malformed files are
+/// skipped.
+pub(crate) fn editor_words(root: &Path) -> Result<Vec<String>> {
+    let mut acc = Vec::new();
+
+    let vscode = root.join(".vscode").join("settings.json");
+    if vscode.is_file() {
+        let content = fs::read_to_string(&vscode)?;
+        match read_words(DictFormat::CSpell, &content) {
+            Ok(words) => {
+                log::debug!("Importing {} words from {}", words.len(), vscode.display());
+                acc.extend(words);
+            }
+            Err(e) => log::debug!("No words imported from {}: {}", vscode.display(), e),
+        }
+    }
+
+    let idea = root.join(".idea").join("dictionaries");
+    if idea.is_dir() {
+        for entry in fs::read_dir(&idea)? {
+            let path = entry?.path();
+            if path.extension().filter(|ext| *ext == "xml").is_none() {
+                continue;
+            }
+            let words = fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|content| read_words(DictFormat::IntelliJ, &content));
+            match words {
+                Ok(words) => {
+                    log::debug!("Importing {} words from {}", words.len(), path.display());
+                    acc.extend(words);
+                }
+                Err(e) => log::debug!("No words imported from {}: {}", path.display(), e),
+            }
+        }
+    }
+    Ok(acc)
+}
+
+/// The words of the project dictionary at `path`, a word list, none if it
+/// does not exist.
+pub(crate) fn project_words(path: &Path) -> Result<Vec<String>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    read_words(DictFormat::Wordlist, &fs::read_to_string(path)?)
+}
+
+/// The first occurrence of each of `words` which is not `known` yet.
+pub(crate) fn unknown_words(words: &[String], known: impl Fn(&str) -> bool) -> Vec<&str> {
+    let mut acc = Vec::<&str>::new();
+    for word in words {
+        if !known(word) && !acc.contains(&word.as_str()) {
+            acc.push(word);
+        }
+    }
+    acc
+}
+
+/// Add `words` to the project dictionary at `path`, which is kept sorted and
+/// free of duplicates.
+///
+/// The project dictionary is a plain word list, which is rewritten as a whole.
+/// The `extra_dictionaries` are hunspell dictionaries instead, which carry
+/// affix flags and are maintained by hand, so
+/// [`add_words`](crate::action::dictionary::add_words) only appends to them.
+///
+/// Returns the number of words which were not present before.
+pub(crate) fn learn(path: &Path, words: &[String]) -> Result<usize> {
+    let mut known = project_words(path)?;
+    let added = unknown_words(words, |word| known.iter().any(|known| known == word));
+    if added.is_empty() {
+        return Ok(0);
+    }
+    let n = added.len();
+    known.extend(added.into_iter().map(ToOwned::to_owned));
+    let mut acc = Vec::new();
+    write_words(DictFormat::Wordlist, &known, &mut acc)?;
+    fs::write(path, acc)
+        .wrap_err_with(|| eyre!("Failed to update project dictionary {}", path.display()))?;
+    Ok(n)
+}
+
+/// Convert the dictionary at `input` to `to`, and write it to `output` or
+/// `stdout`.
+pub fn convert(
+    input: &Path,
+    from: Option<DictFormat>,
+    to: DictFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let from = from
+        .or_else(|| DictFormat::from_path(input))
+        .ok_or_else(|| {
+            eyre!(
+                "Cannot derive the format of {}, use `--from`",
+                input.display()
+            )
+        })?;
+    let content = fs::read_to_string(input)?;
+    let words = read_words(from, &content)?;
+    log::info!(
+        "Converting {} words from {:?} to {:?}",
+        words.len(),
+        from,
+        to
+    );
+    if let Some(output) = output {
+        let file = fs::File::create(output)?;
+        write_words(to, &words, std::io::BufWriter::new(file))
+    } else {
+        write_words(to, &words, std::io::stdout().lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(from: DictFormat, to: DictFormat, content: &str) -> String {
+        let words = read_words(from, content).unwrap();
+        let mut sink = Vec::new();
+        write_words(to, &words, &mut sink).unwrap();
+        String::from_utf8(sink).unwrap()
+    }
+
+    #[test]
+    fn wordlist_to_hunspell() {
+        assert_eq!(
+            roundtrip(
+                DictFormat::Wordlist,
+                DictFormat::Hunspell,
+                "# lingo\nfoo\n\nbar\nfoo\n"
+            ),
+            "2\nbar\nfoo\n"
+        );
+    }
+
+    #[test]
+    fn hunspell_to_wordlist() {
+        assert_eq!(
+            roundtrip(
+                DictFormat::Hunspell,
+                DictFormat::Wordlist,
+                "3\nfoo/S\nbar\nbaz/MS\n"
+            ),
+            "bar\nbaz\nfoo\n"
+        );
+        assert!(read_words(DictFormat::Hunspell, "foo\nbar\n").is_err());
+    }
+
+    #[test]
+    fn vale_skips_regex() {
+        assert_eq!(
+            roundtrip(
+                DictFormat::Vale,
+                DictFormat::Wordlist,
+                "Rust\n[Cc]argo\nclippy\n"
+            ),
+            "Rust\nclippy\n"
+        );
+    }
+
+    #[test]
+    fn cspell_vscode_settings() {
+        const SETTINGS: &str = r#"{
+    // editor things
+    "editor.tabSize": 4,
+    /* the
+       words */
+    "cSpell.words": ["serde", "tokio", "http://x"]
+}"#;
+        assert_eq!(
+            roundtrip(DictFormat::CSpell, DictFormat::Wordlist, SETTINGS),
+            "http://x\nserde\ntokio\n"
+        );
+        assert_eq!(
+            read_words(DictFormat::CSpell, r#"{ "words": ["a"] }"#).unwrap(),
+            vec!["a".to_owned()]
+        );
+    }
+
+    #[test]
+    fn format_from_path() {
+        let format = |path: &str| DictFormat::from_path(Path::new(path));
+        assert_eq!(
+            format("styles/Vocab/Rust/accept.txt"),
+            Some(DictFormat::Vale)
+        );
+        assert_eq!(format("words.txt"), Some(DictFormat::Wordlist));
+        assert_eq!(format(".config/lingo.dic"), Some(DictFormat::Hunspell));
+        assert_eq!(format("README"), None);
+    }
+
+    #[test]
+    fn intellij() {
+        const XML: &str = r#"<component name="ProjectDictionaryState">
+  <dictionary name="alice">
+    <words>
+      <w>rustc</w>
+      <w>AT&amp;T</w>
+    </words>
+  </dictionary>
+</component>"#;
+        let words = read_words(DictFormat::IntelliJ, XML).unwrap();
+        assert_eq!(words, vec!["rustc".to_owned(), "AT&T".to_owned()]);
+        let xml = roundtrip(DictFormat::IntelliJ, DictFormat::IntelliJ, XML);
+        assert!(xml.contains("<w>AT&amp;T</w>"));
+        assert_eq!(
+            read_words(DictFormat::IntelliJ, &xml).unwrap(),
+            vec!["AT&T".to_owned(), "rustc".to_owned()]
+        );
+    }
+
+    #[test]
+    fn editor_words_are_collected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        fs::create_dir_all(root.join(".vscode")).unwrap();
+        fs::create_dir_all(root.join(".idea").join("dictionaries")).unwrap();
+        fs::write(
+            root.join(".vscode").join("settings.json"),
+            r#"{ "cSpell.words": ["tokio"] }"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join(".idea").join("dictionaries").join("bob.xml"),
+            "<words><w>serde</w></words>",
+        )
+        .unwrap();
+        // not utf-8, as written by some other tool
+        fs::write(
+            root.join(".idea").join("dictionaries").join("eve.xml"),
+            b"<words><w>caf\xe9</w></words>",
+        )
+        .unwrap();
+        let mut words = editor_words(&root).unwrap();
+        words.sort();
+        assert_eq!(words, vec!["serde".to_owned(), "tokio".to_owned()]);
+    }
+
+    #[test]
+    fn to_cspell() {
+        let json = roundtrip(DictFormat::Wordlist, DictFormat::CSpell, "b\na\n");
+        assert_eq!(
+            read_words(DictFormat::CSpell, &json).unwrap(),
+            vec!["a".to_owned(), "b".to_owned()]
+        );
+    }
+
+    #[test]
+    fn learn_sorts_and_dedups() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let path = dir.join(".spellcheck-dict.txt");
+        assert!(project_words(&path).unwrap().is_empty());
+
+        let words = ["tokio", "serde", "tokio"].map(ToOwned::to_owned);
+        assert_eq!(learn(&path, &words).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "serde\ntokio\n");
+        let words = ["axum", "serde"].map(ToOwned::to_owned);
+        assert_eq!(learn(&path, &words).unwrap(), 1);
+        assert_eq!(
+            project_words(&path).unwrap(),
+            vec!["axum", "serde", "tokio"]
+        );
+    }
+}
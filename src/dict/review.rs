@@ -0,0 +1,257 @@
+//! Project jargon detection.
+//!
+//! Words unknown to the hunspell dictionaries are counted per file and ranked
+//! by tf-idf, with each file being one document. Words that occur often across
+//! the project are more likely crate specific jargon than typos, and are
+//! proposed for bulk addition with `cargo spellcheck dict review`.
+
+use super::{read_words, write_words, DictFormat};
+use crate::checker::{Checker, Checkers};
+use crate::config::{CheckerTableConfig, Config};
+use crate::errors::*;
+use crate::util::sub_chars;
+use crate::{Detector, Documentation};
+
+use fs_err as fs;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// A word proposed for addition.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Candidate {
+    pub(crate) word: String,
+    /// Total number of occurrences.
+    pub(crate) count: usize,
+    /// Number of files the word occurs in.
+    pub(crate) files: usize,
+    pub(crate) score: f64,
+}
+
+/// Rank `occurrences`, the per file counts of each word, out of `documents`
+/// files.
+#[allow(clippy::cast_precision_loss)]
+fn rank(
+    occurrences: HashMap<String, HashMap<usize, usize>>,
+    documents: usize,
+    min_count: usize,
+) -> Vec<Candidate> {
+    // smoothed, so words present in all files retain a weight
+    let idf = |files: usize| ((1. + documents as f64) / (1. + files as f64)).ln() + 1.;
+    let mut candidates = occurrences
+        .into_iter()
+        .map(|(word, per_file)| {
+            let count = per_file.values().sum::<usize>();
+            let files = per_file.len();
+            Candidate {
+                word,
+                count,
+                files,
+                score: count as f64 * idf(files),
+            }
+        })
+        .filter(|candidate| candidate.count >= min_count)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.word.cmp(&b.word))
+    });
+    candidates
+}
+
+/// Collect the words the hunspell checker does not know, ranked.
+pub(crate) fn candidates(
+    documents: &Documentation,
+    config: &Config,
+    min_count: usize,
+) -> Result<Vec<Candidate>> {
+    if config.hunspell.is_none() {
+        bail!("Reviewing jargon requires the hunspell checker to be configured");
+    }
+    let config = Config {
+        nlprules: None,
+        reflow: None,
+        checker: CheckerTableConfig::default(),
+        ..config.clone()
+    };
+    let checkers = Checkers::new(config)?;
+
+    let mut occurrences = HashMap::<String, HashMap<usize, usize>>::new();
+    for (idx, (origin, chunks)) in documents.iter().enumerate() {
+        for suggestion in checkers.check(origin, chunks)? {
+            if suggestion.detector != Detector::Hunspell {
+                continue;
+            }
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            *occurrences.entry(word).or_default().entry(idx).or_default() += 1;
+        }
+    }
+    Ok(rank(occurrences, documents.entry_count(), min_count))
+}
+
+/// Ask for each of the candidates whether to add it, reading the answers line
+/// by line from `input`.
+pub(crate) fn select(
+    candidates: &[Candidate],
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> Result<Vec<String>> {
+    let mut accepted = Vec::new();
+    let n = candidates.len();
+    for (idx, candidate) in candidates.iter().enumerate() {
+        write!(
+            output,
+            "[{}/{}] {} ({}x in {} files) - add? [y]es/[n]o/[a]ll/[q]uit: ",
+            idx + 1,
+            n,
+            candidate.word,
+            candidate.count,
+            candidate.files
+        )?;
+        output.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            break;
+        }
+        match answer.trim() {
+            "y" | "yes" => accepted.push(candidate.word.clone()),
+            "a" | "all" => {
+                accepted.extend(candidates[idx..].iter().map(|c| c.word.clone()));
+                break;
+            }
+            "q" | "quit" => break,
+            _ => {}
+        }
+    }
+    Ok(accepted)
+}
+
+/// Propose frequent unknown words of `documents` and add the accepted ones to
+/// the dictionary at `output`, by default the first of `extra_dictionaries`.
+pub(crate) fn review(
+    documents: &Documentation,
+    config: &Config,
+    min_count: usize,
+    limit: usize,
+    output: Option<&Path>,
+) -> Result<()> {
+    let output = output
+        .or_else(|| {
+            config
+                .hunspell
+                .as_ref()
+                .and_then(|hunspell| hunspell.extra_dictionaries().next())
+                .map(PathBuf::as_path)
+        })
+        .ok_or_else(|| eyre!("No extra dictionary configured, pass `--output`"))?;
+
+    let mut candidates = candidates(documents, config, min_count)?;
+    candidates.truncate(limit);
+    if candidates.is_empty() {
+        println!("No unknown words occur {} times or more.", min_count);
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    let accepted = select(&candidates, stdin.lock(), std::io::stdout())?;
+    if accepted.is_empty() {
+        return Ok(());
+    }
+
+    let added = add_accepted(output, &accepted)?;
+    println!("Added {} words to {}", added, output.display());
+    Ok(())
+}
+
+/// Add the `accepted` words to the dictionary at `output`, returns the number
+/// of words which were not in there before.
+fn add_accepted(output: &Path, accepted: &[String]) -> Result<usize> {
+    let format = DictFormat::from_path(output).unwrap_or(DictFormat::Hunspell);
+    if format == DictFormat::Hunspell {
+        // keep the affix flags of the existing entries
+        return crate::action::dictionary::add_words(output, accepted);
+    }
+    let mut words = if output.is_file() {
+        read_words(format, &fs::read_to_string(output)?)?
+    } else {
+        Vec::new()
+    };
+    let before = words.len();
+    for word in accepted {
+        if !words.contains(word) {
+            words.push(word.clone());
+        }
+    }
+    let file = fs::File::create(output)?;
+    write_words(format, &words, std::io::BufWriter::new(file))?;
+    Ok(words.len() - before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences(entries: &[(&str, &[usize])]) -> HashMap<String, HashMap<usize, usize>> {
+        entries
+            .iter()
+            .map(|(word, counts)| {
+                let per_file = counts.iter().copied().enumerate().collect();
+                ((*word).to_owned(), per_file)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frequent_words_rank_first() {
+        let ranked = rank(
+            occurrences(&[("tokio", &[4, 3, 5]), ("teh", &[1]), ("frob", &[2, 1])]),
+            3,
+            2,
+        );
+        let words = ranked.iter().map(|c| c.word.as_str()).collect::<Vec<_>>();
+        assert_eq!(words, vec!["tokio", "frob"]);
+        assert_eq!(ranked[0].count, 12);
+        assert_eq!(ranked[0].files, 3);
+    }
+
+    #[test]
+    fn select_answers() {
+        let candidates = ["tokio", "frob", "teh", "serde"]
+            .iter()
+            .map(|word| Candidate {
+                word: (*word).to_owned(),
+                count: 3,
+                files: 1,
+                score: 1.,
+            })
+            .collect::<Vec<_>>();
+        let accepted = select(&candidates, "y\nn\na\n".as_bytes(), std::io::sink()).unwrap();
+        assert_eq!(accepted, vec!["tokio", "teh", "serde"]);
+    }
+
+    #[test]
+    fn flags_are_kept() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let dic = dir.join("project.dic");
+        fs::write(&dic, "2\nfoo/S\nbar\n").unwrap();
+        let accepted = ["tokio", "foo"].map(ToOwned::to_owned);
+        assert_eq!(add_accepted(&dic, &accepted).unwrap(), 1);
+        assert_eq!(fs::read_to_string(&dic).unwrap(), "3\nfoo/S\nbar\ntokio\n");
+
+        let wordlist = dir.join("words.txt");
+        fs::write(&wordlist, "foo\n").unwrap();
+        assert_eq!(add_accepted(&wordlist, &accepted).unwrap(), 1);
+        assert_eq!(
+            read_words(
+                DictFormat::Wordlist,
+                &fs::read_to_string(&wordlist).unwrap()
+            )
+            .unwrap(),
+            vec!["foo", "tokio"]
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Filter pipeline, run on the content of each chunk before it is checked,
+//! see `[filters]`.
+//!
+//! The first stage is always the markdown erasure, which yields the plain
+//! content and its [`SourceMap`](crate::documentation::SourceMap) to the
+//! chunk. The configured stages follow in order, each masks parts of the text
+//! left by the previous ones. Masked characters are replaced by spaces, so the
+//! length of the text and with it the source map of the erasure hold
+//! throughout the pipeline. The checkers are given the masked chunks, see
+//! [`CheckableChunk::with_masked`], so new checkers benefit without further
+//! ado, and the findings apply to the original chunks as they are.
+
+use crate::checker::identifier_parts;
+use crate::config::{BuiltinStage, Config, StageConfig};
+use crate::documentation::PlainOverlay;
+use crate::util::sub_chars;
+use crate::{CheckableChunk, Range};
+
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref URL: Regex =
+        Regex::new(r#"\b(?:[a-zA-Z][a-zA-Z0-9+.-]*://|www\.)[^\s<>()\[\]{}"'`]+"#).unwrap();
+    static ref EMAIL: Regex =
+        Regex::new(r"\b[a-zA-Z0-9._%+-]+@[a-zA-Z0-9-]+(?:\.[a-zA-Z0-9-]+)*\.[a-zA-Z]{2,}\b").unwrap();
+    // a separator has to precede a path, so `and/or` is not one
+    static ref PATH: Regex = Regex::new(
+        r"(?:^|[\s(\[])((?:[a-zA-Z]:\\|~/|\.{1,2}/|/)[\w.-]+(?:[/\\][\w.-]+)*/?|[\w.-]+(?:/[\w.-]+)+\.[a-zA-Z0-9]+)"
+    )
+    .unwrap();
+    // author or abbreviation followed by a year, optionally several of them
+    static ref CITATION: Regex = Regex::new(
+        r"\[@?[A-Za-z][A-Za-z+-]*(?:\d{4}|\d{2})[a-z]?(?:\s*[,;]\s*@?[A-Za-z][A-Za-z+-]*(?:\d{4}|\d{2})[a-z]?)*\]"
+    )
+    .unwrap();
+    static ref DOI: Regex =
+        Regex::new(r#"(?i)\b(?:doi:\s*)?10\.\d{4,9}/[^\s"<>]+"#).unwrap();
+    static ref ARXIV: Regex = Regex::new(
+        r"(?i)\barxiv:\s*(?:\d{4}\.\d{4,5}|[a-z-]+(?:\.[a-z]{2})?/\d{7})(?:v\d+)?\b"
+    )
+    .unwrap();
+}
+
+/// Punctuation which ends a sentence rather than a URL or path.
+const TRAILING: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// A stage of the pipeline.
+trait Stage: std::fmt::Debug + Send + Sync {
+    /// The character ranges of `text` to mask, `text` is the plain content of
+    /// `plain` with the parts masked by the previous stages blanked.
+    fn mask(&self, text: &str, plain: &PlainOverlay<'_>) -> Vec<Range>;
+}
+
+/// The byte range `range` of `text` as character range.
+fn char_range(text: &str, range: std::ops::Range<usize>) -> Range {
+    let char_idx = |byte: usize| text[..byte].chars().count();
+    char_idx(range.start)..char_idx(range.end)
+}
+
+/// Masks the matches of a built in pattern, without trailing punctuation.
+#[derive(Debug)]
+struct Pattern(&'static Regex);
+
+impl Stage for Pattern {
+    fn mask(&self, text: &str, _plain: &PlainOverlay<'_>) -> Vec<Range> {
+        self.0
+            .captures_iter(text)
+            .filter_map(|captures| {
+                let found = captures.get(1).or_else(|| captures.get(0))?;
+                let trimmed = found.as_str().trim_end_matches(TRAILING);
+                (!trimmed.is_empty())
+                    .then(|| char_range(text, found.start()..found.start() + trimmed.len()))
+            })
+            .collect()
+    }
+}
+
+/// Masks the matches of a built in pattern within the raw content, i.e. for
+/// markup the erasure drops, like the brackets of a citation key.
+#[derive(Debug)]
+struct Raw(&'static Regex);
+
+impl Stage for Raw {
+    fn mask(&self, text: &str, plain: &PlainOverlay<'_>) -> Vec<Range> {
+        // blank what the previous stages masked within the raw content too
+        let masked = text
+            .chars()
+            .zip(plain.as_str().chars())
+            .enumerate()
+            .filter(|(_idx, (masked, c))| masked != c)
+            .flat_map(|(idx, _)| {
+                plain
+                    .source_map()
+                    .to_source(idx..idx + 1)
+                    .map(|(_plain, raw)| raw.start)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<std::collections::HashSet<_>>();
+        let raw = plain
+            .raw()
+            .as_str()
+            .chars()
+            .enumerate()
+            .map(|(idx, c)| if masked.contains(&idx) { ' ' } else { c })
+            .collect::<String>();
+        let raw = raw.as_str();
+        self.0
+            .find_iter(raw)
+            .flat_map(|found| {
+                plain
+                    .source_map()
+                    .to_plain(char_range(raw, found.start()..found.end()))
+                    .map(|(_raw, plain)| plain)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Masks inline code, along with a plural suffix.
+#[derive(Debug)]
+struct Code;
+
+impl Stage for Code {
+    fn mask(&self, text: &str, plain: &PlainOverlay<'_>) -> Vec<Range> {
+        let chars = text.chars().collect::<Vec<_>>();
+        plain
+            .placeholders()
+            .map(|mut code| {
+                let suffix = chars[code.end..]
+                    .iter()
+                    .take_while(|c| c.is_alphabetic())
+                    .count();
+                if suffix <= 2 {
+                    code.end += suffix;
+                }
+                code
+            })
+            .collect()
+    }
+}
+
+/// Masks words shaped like code identifiers.
+#[derive(Debug)]
+struct Identifiers;
+
+impl Stage for Identifiers {
+    fn mask(&self, text: &str, _plain: &PlainOverlay<'_>) -> Vec<Range> {
+        let mut acc = Vec::new();
+        let mut cursor = 0;
+        for word in text.split(|c: char| c.is_whitespace()) {
+            let len = word.chars().count();
+            let trimmed = word.trim_matches(|c: char| !(c.is_alphanumeric() || c == '_'));
+            let identifier = word.contains("::")
+                || (trimmed.starts_with(char::is_alphabetic)
+                    && identifier_parts(trimmed).is_some());
+            if identifier {
+                acc.push(cursor..cursor + len);
+            }
+            // the separator is a single char
+            cursor += len + 1;
+        }
+        acc
+    }
+}
+
+/// Masks the matches of a custom regular expression.
+#[derive(Debug)]
+struct Custom(fancy_regex::Regex);
+
+impl Stage for Custom {
+    fn mask(&self, text: &str, _plain: &PlainOverlay<'_>) -> Vec<Range> {
+        self.0
+            .find_iter(text)
+            .filter_map(|found| match found {
+                Ok(found) => Some(char_range(text, found.start()..found.end())),
+                Err(e) => {
+                    log::warn!("Failed to match {}: {:?}", self.0.as_str(), e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// The configured stages.
+#[derive(Debug)]
+pub(crate) struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Pipeline {
+    /// The pipeline of `config`, the configured stages followed by the
+    /// `ignore_patterns`, none if it has no stages.
+    pub(crate) fn new(config: &Config) -> Option<Self> {
+        let patterns = config
+            .ignore_patterns
+            .iter()
+            .map(|regex| -> Box<dyn Stage> { Box::new(Custom(regex.0.clone())) });
+        let stages = config
+            .filters
+            .stages
+            .iter()
+            .map(|stage| -> Box<dyn Stage> {
+                match stage {
+                    StageConfig::Builtin(BuiltinStage::Urls) => Box::new(Pattern(&URL)),
+                    StageConfig::Builtin(BuiltinStage::Emails) => Box::new(Pattern(&EMAIL)),
+                    StageConfig::Builtin(BuiltinStage::Paths) => Box::new(Pattern(&PATH)),
+                    StageConfig::Builtin(BuiltinStage::Code) => Box::new(Code),
+                    StageConfig::Builtin(BuiltinStage::Identifiers) => Box::new(Identifiers),
+                    StageConfig::Builtin(BuiltinStage::Citations) => Box::new(Raw(&CITATION)),
+                    StageConfig::Builtin(BuiltinStage::Dois) => Box::new(Pattern(&DOI)),
+                    StageConfig::Builtin(BuiltinStage::Arxiv) => Box::new(Pattern(&ARXIV)),
+                    StageConfig::Regex { regex } => Box::new(Custom(regex.0.clone())),
+                }
+            })
+            .chain(patterns)
+            .collect::<Vec<_>>();
+        (!stages.is_empty()).then(|| Self { stages })
+    }
+
+    /// Run all stages on `plain`, returning the text with the masked parts
+    /// blanked and the masked character ranges, sorted.
+    fn run(&self, plain: &PlainOverlay<'_>) -> (String, Vec<Range>) {
+        let mut text = plain.as_str().to_owned();
+        let mut masked = Vec::<Range>::new();
+        for stage in &self.stages {
+            let found = stage.mask(&text, plain);
+            if found.is_empty() {
+                continue;
+            }
+            text = text
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| {
+                    if found.iter().any(|range| range.contains(&idx)) {
+                        ' '
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+            masked.extend(found);
+        }
+        masked.sort_by_key(|range| range.start);
+        (text, masked)
+    }
+
+    /// The copies of `chunks` with the masked parts blanked, in the same
+    /// order.
+    pub(crate) fn mask(&self, chunks: &[CheckableChunk]) -> Vec<CheckableChunk> {
+        chunks
+            .iter()
+            .map(|chunk| {
+                let plain = chunk.erase_cmark();
+                let (_text, masked) = self.run(&plain);
+                log::trace!(
+                    "Masked {:?}",
+                    masked
+                        .iter()
+                        .map(|range| sub_chars(plain.as_str(), range.clone()))
+                        .collect::<Vec<_>>()
+                );
+                chunk.with_masked(masked)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommentVariant, LineColumn, Span};
+
+    fn masked(stages: &str, markdown: &str) -> (String, Vec<String>) {
+        masked_by(&format!("[filters]\nstages = {}", stages), markdown)
+    }
+
+    fn masked_by(config: &str, markdown: &str) -> (String, Vec<String>) {
+        let config = Config::parse(config).unwrap();
+        let pipeline = Pipeline::new(&config).unwrap();
+        let span = Span {
+            start: LineColumn { line: 1, column: 0 },
+            end: LineColumn {
+                line: 1,
+                column: markdown.chars().count() - 1,
+            },
+        };
+        let chunk = CheckableChunk::from_str(
+            markdown,
+            indexmap::indexmap! { 0..markdown.chars().count() => span },
+            CommentVariant::CommonMark,
+        );
+        let plain = chunk.erase_cmark();
+        let (text, masked) = pipeline.run(&plain);
+        assert_eq!(text.chars().count(), plain.as_str().chars().count());
+        (
+            text,
+            masked
+                .into_iter()
+                .map(|range| sub_chars(plain.as_str(), range))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn checkers_see_the_masked_text() {
+        use crate::checker::{dummy::DummyChecker, Checker};
+
+        let pipeline = Pipeline::new(&Config::default()).unwrap();
+        let markdown = "See https://example.com/wrod now.";
+        let span = Span {
+            start: LineColumn { line: 1, column: 0 },
+            end: LineColumn {
+                line: 1,
+                column: markdown.chars().count() - 1,
+            },
+        };
+        let chunks = vec![CheckableChunk::from_str(
+            markdown,
+            indexmap::indexmap! { 0..markdown.chars().count() => span },
+            CommentVariant::CommonMark,
+        )];
+        let masked = pipeline.mask(&chunks);
+        assert_eq!(masked[0].as_str(), markdown);
+        assert_eq!(
+            masked[0].erase_cmark().as_str(),
+            "See                          now."
+        );
+
+        let origin = crate::ContentOrigin::TestEntityCommonMark;
+        let flagged = DummyChecker
+            .check(&origin, &masked)
+            .unwrap()
+            .into_iter()
+            .map(|suggestion| suggestion.rebind(&chunks[0]))
+            .map(|suggestion| sub_chars(suggestion.chunk.as_str(), suggestion.range))
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, vec!["See", "now", "."]);
+    }
+
+    #[test]
+    fn urls_paths_and_emails() {
+        let stages = r#"["urls", "emails", "paths"]"#;
+        assert_eq!(
+            masked(
+                stages,
+                "Sée https://exämple.com/a?b=c, or www.rust-lang.org."
+            )
+            .1,
+            vec!["https://exämple.com/a?b=c", "www.rust-lang.org"]
+        );
+        assert_eq!(
+            masked(stages, "Mail someone@example.co.uk for (docs/README.md).").1,
+            vec!["someone@example.co.uk", "docs/README.md"]
+        );
+        assert_eq!(
+            masked(
+                stages,
+                "Run ./run.sh, read /etc/hosts and ~/.cargo/config or C:\\\\Users."
+            )
+            .1,
+            vec!["./run.sh", "/etc/hosts", "~/.cargo/config", "C:\\Users"]
+        );
+        assert!(masked(stages, "Either and/or, but not 1/2 of it.")
+            .1
+            .is_empty());
+        assert!(Pipeline::new(&Config::parse("[filters]\nstages = []").unwrap()).is_none());
+    }
+
+    #[test]
+    fn stages_run_in_order() {
+        let (text, masked) = masked(
+            r#"["code", "identifiers", { regex = "v\\d+" }]"#,
+            "The `Vec`s of __HashMap__ in std::mem are v2 now.",
+        );
+        assert_eq!(masked, vec!["Vecs", "HashMap", "std::mem", "v2"]);
+        assert_eq!(text, "The      of         in          are    now.");
+    }
+
+    #[test]
+    fn citations_dois_and_arxiv() {
+        let stages = r#"["citations", "dois", "arxiv"]"#;
+        assert_eq!(
+            masked(
+                stages,
+                "As shown in [Knuth1974], [KR88] and [@knuth1974; @lamport1994a], not [see below]."
+            )
+            .1,
+            // the brackets are erased as link markup
+            vec!["Knuth1974", "KR88", "@knuth1974; @lamport1994a"]
+        );
+        assert_eq!(
+            masked(
+                stages,
+                "See doi:10.1145/361604.361612, 10.1000/182 and arXiv:2101.00001v2 or arXiv:hep-th/9901001."
+            )
+            .1,
+            vec![
+                "doi:10.1145/361604.361612",
+                "10.1000/182",
+                "arXiv:2101.00001v2",
+                "arXiv:hep-th/9901001"
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let (_text, masked) = masked_by(
+            r#"ignore_patterns = ["\\bv\\d+\\.\\d+\\.\\d+\\b", "0x[0-9a-fA-F]+", "JIRA-\\d+"]"#,
+            "Fixed in v1.2.3 at 0xdeadBEEF, see JIRA-1234 or https://x.org.",
+        );
+        assert_eq!(
+            masked,
+            vec!["v1.2.3", "0xdeadBEEF", "JIRA-1234", "https://x.org"]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+# Meldungen des interaktiven `fix` Modus.
+interactive-question = ({ $nth }/{ $total }) Diesen Vorschlag übernehmen [y,n,q,a,d,j,e,m,?]?
+interactive-question-word = ({ $nth }/{ $total }) Diesen Vorschlag für alle { $count } Vorkommen von `{ $word }` übernehmen [y,n,q,a,e,m,?]?
+interactive-more = ... { $hidden } weitere, m zeigt alle an
+interactive-unknown = ⚠ nicht im Wörterbuch: { $words }
+interactive-help =
+    y - diesen Vorschlag übernehmen
+    n - die vorgeschlagene Korrektur nicht übernehmen
+    q - beenden; weder diesen noch einen der verbleibenden Vorschläge übernehmen
+    d - diesen Vorschlag nicht übernehmen und den Rest der Datei überspringen
+    g - zu einem Vorschlag springen
+    j - diesen Vorschlag offen lassen, zum nächsten offenen Vorschlag
+    J - diesen Vorschlag offen lassen, zum nächsten Vorschlag
+    e - den Vorschlag manuell bearbeiten
+    a - das Wort zum eigenen Wörterbuch hinzufügen
+    m - alle Ersetzungen anzeigen
+    ? - diese Hilfe anzeigen
+
+# Zusammenfassungen von `check`.
+legend = Legende:
+count-error-one = { $count } Fehler
+count-error-other = { $count } Fehler
+count-warning-one = { $count } Warnung
+count-warning-other = { $count } Warnungen
+count-note-one = { $count } Hinweis
+count-note-other = { $count } Hinweise
+count-file-one = { $count } Datei
+count-file-other = { $count } Dateien
+count-placeholder-doc-one = { $count } Platzhalter-Dokumentation
+count-placeholder-doc-other = { $count } Platzhalter-Dokumentationen
@@ -0,0 +1,30 @@
+# Messages of the interactive `fix` mode.
+interactive-question = ({ $nth }/{ $total }) Apply this suggestion [y,n,q,a,d,j,e,m,?]?
+interactive-question-word = ({ $nth }/{ $total }) Apply this suggestion to all { $count } occurrences of `{ $word }` [y,n,q,a,e,m,?]?
+interactive-more = ... { $hidden } more, press m to show all
+interactive-unknown = ⚠ not in the dictionary: { $words }
+interactive-help =
+    y - apply this suggestion
+    n - do not apply the suggested correction
+    q - quit; do not stage this hunk or any of the remaining ones
+    d - do not apply this suggestion and skip the rest of the file
+    g - select a suggestion to go to
+    j - leave this hunk undecided, see next undecided hunk
+    J - leave this hunk undecided, see next hunk
+    e - manually edit the current hunk
+    a - add the word to the custom dictionary
+    m - show all replacement candidates
+    ? - print help
+
+# Summaries of `check`, counts choose the `-one` or `-other` variant.
+legend = Legend:
+count-error-one = { $count } error
+count-error-other = { $count } errors
+count-warning-one = { $count } warning
+count-warning-other = { $count } warnings
+count-note-one = { $count } note
+count-note-other = { $count } notes
+count-file-one = { $count } file
+count-file-other = { $count } files
+count-placeholder-doc-one = { $count } placeholder doc
+count-placeholder-doc-other = { $count } placeholder docs
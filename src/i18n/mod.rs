@@ -0,0 +1,188 @@
+//! Localization of the tool's own prompts and messages.
+//!
+//! Messages are kept in per language catalogs, `en.ftl` and `de.ftl`, written
+//! in the subset of the [Fluent](https://projectfluent.org) syntax consisting
+//! of messages, multiline values and `{ $var }` placeables. Plurals use
+//! separate `-one` and `-other` messages, see [`tr_count`].
+//!
+//! The language is selected once with `--ui-lang`, or the `LC_ALL`,
+//! `LC_MESSAGES` and `LANG` environment variables, and falls back to English,
+//! for the whole catalog and for every message missing in a catalog. Until
+//! then, i.e. in unit tests, all messages are English.
+//!
+//! Only the human facing output is localized, the descriptions of findings
+//! stay English since they are also part of the machine readable formats.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+/// The default language, has to contain every message.
+const FALLBACK: &str = "en";
+
+/// All available catalogs, by language.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("en.ftl")),
+    ("de", include_str!("de.ftl")),
+];
+
+/// The messages of one language.
+type Catalog = HashMap<&'static str, String>;
+
+lazy_static::lazy_static! {
+    static ref PARSED: HashMap<&'static str, Catalog> = CATALOGS
+        .iter()
+        .map(|(lang, source)| (*lang, parse(source)))
+        .collect();
+    static ref SELECTED: RwLock<&'static str> = RwLock::new(FALLBACK);
+}
+
+/// Parse the messages of a catalog, unknown syntax is skipped.
+fn parse(source: &'static str) -> Catalog {
+    let mut catalog = Catalog::new();
+    let mut current: Option<(&'static str, Vec<&'static str>)> = None;
+    let mut flush = |current: &mut Option<(&'static str, Vec<&'static str>)>| {
+        if let Some((id, lines)) = current.take() {
+            catalog.insert(id, lines.join("\n"));
+        }
+    };
+    for line in source.lines() {
+        if line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            if let Some((_id, ref mut lines)) = current {
+                lines.push(line.trim());
+                continue;
+            }
+        }
+        flush(&mut current);
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((id, value)) = line.split_once('=') {
+            let id = id.trim();
+            let value = value.trim();
+            let lines = if value.is_empty() {
+                Vec::new()
+            } else {
+                vec![value]
+            };
+            current = Some((id, lines));
+        }
+    }
+    flush(&mut current);
+    catalog
+}
+
+/// The language part of a locale, i.e. `de` of `de_DE.UTF-8`.
+fn language_of(locale: &str) -> Option<String> {
+    let lang = locale
+        .split(|c| matches!(c, '_' | '-' | '.' | '@'))
+        .next()?
+        .to_lowercase();
+    if lang.is_empty() || lang == "c" || lang == "posix" {
+        None
+    } else {
+        Some(lang)
+    }
+}
+
+/// Select the language of all messages, either the given `ui_lang` or the one
+/// of the locale environment variables.
+pub(crate) fn select(ui_lang: Option<&str>) {
+    let from_env = || {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+    };
+    let explicit = ui_lang.is_some();
+    let lang = match ui_lang.map(ToOwned::to_owned).or_else(from_env) {
+        Some(locale) => language_of(&locale),
+        None => None,
+    };
+    let lang = match lang {
+        Some(lang) => lang,
+        None => return,
+    };
+    match CATALOGS.iter().find(|(available, _)| *available == lang) {
+        Some((available, _)) => {
+            log::debug!("Using the {} user interface", available);
+            *SELECTED.write().expect("Lock is never poisoned. qed") = available;
+        }
+        None if explicit => log::warn!(
+            "User interface language {} is not available, using English",
+            lang
+        ),
+        None => log::debug!("No user interface in {}, using English", lang),
+    }
+}
+
+/// Look up the message `id`, with the `{ $var }` placeables replaced by `args`.
+pub(crate) fn tr_args(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let lang = *SELECTED.read().expect("Lock is never poisoned. qed");
+    let message = PARSED
+        .get(lang)
+        .and_then(|catalog| catalog.get(id))
+        .or_else(|| PARSED.get(FALLBACK).and_then(|catalog| catalog.get(id)));
+    let mut message = match message {
+        Some(message) => message.clone(),
+        None => {
+            log::warn!("BUG: Message {} is missing in the catalogs", id);
+            return id.to_owned();
+        }
+    };
+    for (name, value) in args {
+        message = message.replace(&format!("{{ ${} }}", name), &value.to_string());
+    }
+    message
+}
+
+/// Look up the message `id`, which has no placeables.
+pub(crate) fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// Look up the `{id}-one` or `{id}-other` message, depending on `count`.
+pub(crate) fn tr_count(id: &str, count: usize) -> String {
+    let variant = if count == 1 { "one" } else { "other" };
+    tr_args(&format!("{}-{}", id, variant), &[("count", &count)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_catalog() {
+        let catalog =
+            parse("# comment\nsingle = A { $var }.\nmulti =\n    first\n    second\n\nnext = x\n");
+        assert_eq!(catalog["single"], "A { $var }.");
+        assert_eq!(catalog["multi"], "first\nsecond");
+        assert_eq!(catalog["next"], "x");
+        assert_eq!(catalog.len(), 3);
+    }
+
+    #[test]
+    fn catalogs_are_complete() {
+        let fallback = &PARSED[FALLBACK];
+        for (lang, catalog) in PARSED.iter() {
+            for id in fallback.keys() {
+                assert!(catalog.contains_key(id), "{} lacks {}", lang, id);
+            }
+            for id in catalog.keys() {
+                assert!(fallback.contains_key(id), "{} has unknown {}", lang, id);
+            }
+        }
+    }
+
+    #[test]
+    fn placeables_and_counts() {
+        assert_eq!(
+            tr_args("interactive-question", &[("nth", &3), ("total", &7)]),
+            "(3/7) Apply this suggestion [y,n,q,a,d,j,e,m,?]?"
+        );
+        assert_eq!(tr_count("count-error", 1), "1 error");
+        assert_eq!(tr_count("count-file", 2), "2 files");
+        assert_eq!(language_of("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(language_of("C.UTF-8"), None);
+    }
+}
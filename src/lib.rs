@@ -14,6 +14,33 @@
 //! cargo-spellcheck
 //!
 //! A syntax tree based doc comment and common mark spell checker.
+//!
+//! ## Library
+//!
+//! Besides the binary, the checks are available as a library, i.e. to check
+//! the documentation within an `xtask`:
+//!
+//! ```
+//! use cargo_spellcheck::{Checker, Checkers, Config, Documentation};
+//!
+//! # fn main() -> cargo_spellcheck::errors::Result<()> {
+//! let docs = Documentation::from_path("src/lib.rs", false)?;
+//! let checkers = Checkers::new(Config::default())?;
+//! for (origin, chunks) in docs.iter() {
+//!     for suggestion in checkers.check(origin, chunks)? {
+//!         println!("{}", suggestion);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Documentation`], [`Checker`] with [`Checkers`], [`Suggestion`], [`Span`]
+//! and [`Config`] are the public API and follow semantic versioning. The
+//! configuration types, [`Suggestion`] and [`Detector`] are non exhaustive, so
+//! fields and variants can be added within a minor release, create them with
+//! their constructors or `Default`. The other items serve the binary and may
+//! change with any release.
 
 pub use doc_chunks as documentation;
 #[cfg(test)]
@@ -21,16 +48,25 @@ pub(crate) use doc_chunks::{chyrp_up, fluff_up};
 
 pub mod action;
 mod checker;
+#[cfg(feature = "hunspell")]
+mod collation;
 mod config;
+mod dict;
 pub mod errors;
+mod filters;
+mod i18n;
 mod reflow;
+#[cfg(any(test, feature = "proptest"))]
+pub mod roundtrip;
 mod suggestion;
 mod tinhat;
 mod traverse;
 
 pub use self::action::*;
+pub use self::checker::{Checked, Checker, CheckerSet, Checkers};
 pub use self::config::args::*;
 pub use self::config::{Config, HunspellConfig, LanguageToolConfig};
+pub use self::dict::DictFormat;
 pub use self::documentation::span::*;
 pub use self::documentation::util::*;
 pub use self::documentation::*;
@@ -44,8 +80,6 @@ use std::io::Write;
 #[cfg(target_os = "windows")]
 use signal_hook as _;
 
-use checker::Checker;
-
 /// A simple exit code representation.
 ///
 /// `Custom` can be specified by the user, others map to their UNIX equivalents
@@ -59,6 +93,10 @@ pub enum ExitCode {
     Signal,
     /// A custom exit code, as specified with `--code=<code>`.
     Custom(u8),
+    /// Content had to be skipped with `--strict`, the code depends on the kind
+    /// of the first issue: `3` for unparseable files, `4` for missing files,
+    /// included ones too, and `5` for unknown code fence info strings.
+    Strict(u8),
     // Failure is already default for `Err(_)`
 }
 
@@ -68,13 +106,32 @@ impl ExitCode {
         match *self {
             Self::Success => 0u8,
             Self::Signal => 130u8,
-            Self::Custom(code) => code,
+            Self::Custom(code) | Self::Strict(code) => code,
         }
     }
 }
 
+/// Fail with `--strict` if any content had to be skipped.
+fn strict_exit_code(
+    documents: &Documentation,
+    mut issues: Vec<traverse::Issue>,
+) -> Option<ExitCode> {
+    let fences = traverse::issues::unknown_fences(documents);
+    for issue in &fences {
+        log::warn!("{}", issue);
+    }
+    issues.extend(fences);
+    let code = issues.first()?.exit_code();
+    log::error!(
+        "Encountered {} extraction issue(s) in strict mode",
+        issues.len()
+    );
+    Some(ExitCode::Strict(code))
+}
+
 /// The inner main.
-pub fn run(args: Args) -> Result<ExitCode> {
+#[allow(clippy::too_many_lines)]
+pub fn run(mut args: Args) -> Result<ExitCode> {
     let _ = ::rayon::ThreadPoolBuilder::new()
         .num_threads(args.job_count())
         .build_global();
@@ -85,6 +142,8 @@ pub fn run(args: Args) -> Result<ExitCode> {
         .filter_module("mio", log::LevelFilter::Error)
         .init();
 
+    i18n::select(args.ui_lang.as_deref());
+
     #[cfg(not(target_os = "windows"))]
     signal_handler(move || {
         if let Err(e) = action::interactive::ScopedRaw::restore_terminal() {
@@ -92,6 +151,22 @@ pub fn run(args: Args) -> Result<ExitCode> {
         }
     });
 
+    if let Some(manifest_dir) = args.rebase_on_manifest(&traverse::cwd()?)? {
+        log::debug!("Running in manifest directory {}", manifest_dir.display());
+    }
+
+    if let Some(ref word) = args.show_history {
+        action::history::show(word)?;
+        return Ok(ExitCode::Success);
+    }
+
+    if args.stats {
+        action::history::show_stats()?;
+        return Ok(ExitCode::Success);
+    }
+
+    // `--watch` and the language server reload the config once it changes
+    let reloaded = args.clone();
     let (unified, config) = match &args.command {
         Some(Sub::Completions { shell }) => {
             let sink = &mut std::io::stdout();
@@ -99,6 +174,25 @@ pub fn run(args: Args) -> Result<ExitCode> {
             let _ = sink.flush();
             return Ok(ExitCode::Success);
         }
+        Some(Sub::Manpage { output }) => {
+            match output {
+                Some(output) => generate_manpage(&mut fs_err::File::create(output)?)?,
+                None => generate_manpage(&mut std::io::stdout().lock())?,
+            }
+            return Ok(ExitCode::Success);
+        }
+        Some(Sub::Dict {
+            command:
+                DictSub::Convert {
+                    from,
+                    to,
+                    output,
+                    input,
+                },
+        }) => {
+            dict::convert(input, *from, *to, output.as_deref())?;
+            return Ok(ExitCode::Success);
+        }
         _ => args.unified()?,
     };
 
@@ -134,6 +228,39 @@ pub fn run(args: Args) -> Result<ExitCode> {
             }
             return Ok(ExitCode::Success);
         }
+        UnifiedArgs::DictReview {
+            paths,
+            recursive,
+            skip_readme,
+            dev_comments,
+            min_count,
+            limit,
+            output,
+        } => {
+            let (documents, _issues) =
+                traverse::extract(paths, recursive, skip_readme, dev_comments, &config)?;
+            dict::review(&documents, &config, min_count, limit, output.as_deref())?;
+            Ok(ExitCode::Success)
+        }
+        UnifiedArgs::Learn { words } => {
+            let base_dir = config.base_dir()?;
+            let path = config
+                .hunspell
+                .unwrap_or_default()
+                .project_dictionary(&base_dir);
+            let added = dict::learn(&path, &words)?;
+            println!("Added {} words to {}", added, path.display());
+            Ok(ExitCode::Success)
+        }
+        UnifiedArgs::Lsp {
+            dev_comments,
+            config_path,
+        } => {
+            action::lsp::serve(config, config_path, dev_comments, move || {
+                reloaded.reload_config()
+            })?;
+            Ok(ExitCode::Success)
+        }
         UnifiedArgs::Operate {
             action,
             paths,
@@ -142,6 +269,13 @@ pub fn run(args: Args) -> Result<ExitCode> {
             config_path,
             dev_comments,
             exit_code_override,
+            strict,
+            reporter,
+            thresholds,
+            dump_extracted,
+            allow_dirty,
+            allow_staged,
+            watch,
         } => {
             log::debug!(
                 "Executing: {:?} with {:?} from {:?}",
@@ -150,15 +284,105 @@ pub fn run(args: Args) -> Result<ExitCode> {
                 config_path
             );
 
-            let documents =
-                traverse::extract(paths, recursive, skip_readme, dev_comments, &config)?;
+            if action.modifies_files() && paths.iter().any(|path| traverse::is_stdin(path)) {
+                bail!("Changes to the standard input can not be written back, use `check`");
+            }
+
+            // refuse to modify a tree another instance is modifying
+            let mut tree_lock = if action.modifies_files() {
+                // without paths, the tree of the base directory is modified
+                let roots = if paths.is_empty() {
+                    vec![config.base_dir()?]
+                } else {
+                    paths.clone()
+                };
+                ensure_committed(&roots, allow_dirty, allow_staged)?;
+                Some(TreeLock::new(&roots)?)
+            } else {
+                None
+            };
+            let _guard = tree_lock.as_mut().map(TreeLock::acquire).transpose()?;
+
+            // only check what the pull request or push changed
+            let changed = if reporter == Reporter::GitHub && paths.is_empty() {
+                Workflow::from_env(&config.base_dir()?)?.changed_files()?
+            } else {
+                None
+            };
+            let (documents, issues) = match changed {
+                Some(changed) if changed.is_empty() => {
+                    log::info!("No changed files to check");
+                    (Documentation::new(), Vec::new())
+                }
+                Some(changed) => {
+                    // files matching the `[files]` patterns only if changed too
+                    let mut config = config.clone();
+                    let base_dir = config.base_dir()?;
+                    traverse::restrict_patterns(&mut config.files, &base_dir, &changed)?;
+                    traverse::extract(changed, false, skip_readme, dev_comments, &config)?
+                }
+                None => traverse::extract(paths, recursive, skip_readme, dev_comments, &config)?,
+            };
 
+            if strict {
+                if let Some(exit_code) = strict_exit_code(&documents, issues) {
+                    return Ok(exit_code);
+                }
+            }
+
+            if let Some(ref dir) = dump_extracted {
+                traverse::dump::dump(&documents, &config, dir)?;
+            }
+
+            if watch {
+                let extract_config = config.clone();
+                action::watch(
+                    documents,
+                    config,
+                    config_path,
+                    || reloaded.reload_config(),
+                    |path| {
+                        let (documents, _issues) = traverse::extract(
+                            vec![path.to_owned()],
+                            false,
+                            skip_readme,
+                            dev_comments,
+                            &extract_config,
+                        )?;
+                        Ok(documents)
+                    },
+                )?;
+                return Ok(ExitCode::Success);
+            }
+
+            let templated = matches!(
+                reporter,
+                Reporter::Template(_) | Reporter::Json | Reporter::Sarif
+            );
             let rt = tokio::runtime::Runtime::new()?;
-            let finish = rt.block_on(async move { action.run(documents, config).await })?;
+            let finish =
+                rt.block_on(async move { action.run(documents, config, reporter).await })?;
 
             match finish {
+                Finish::Checked(tally) if !thresholds.is_empty() => {
+                    let verdict = thresholds.verdict(&tally);
+                    // keep the rendered template free of anything else
+                    if templated {
+                        eprintln!("{}", verdict);
+                    } else {
+                        println!("{}", verdict);
+                    }
+                    if verdict.passed() {
+                        Ok(ExitCode::Success)
+                    } else {
+                        Ok(ExitCode::Custom(exit_code_override.max(1)))
+                    }
+                }
+                Finish::Checked(tally) if tally.total() == 0 => Ok(ExitCode::Success),
                 Finish::Success | Finish::MistakeCount(0) => Ok(ExitCode::Success),
-                Finish::MistakeCount(_n) => Ok(ExitCode::Custom(exit_code_override)),
+                Finish::Checked(_) | Finish::MistakeCount(_) => {
+                    Ok(ExitCode::Custom(exit_code_override))
+                }
                 Finish::Abort => Ok(ExitCode::Signal),
             }
         }
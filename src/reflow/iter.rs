@@ -6,6 +6,7 @@
 //! line length.
 
 use super::{Indentation, Range};
+use crate::checker::HunspellChecker;
 
 use std::borrow::Cow;
 use std::collections::VecDeque;
@@ -29,6 +30,11 @@ pub struct Tokeneer<'s> {
     previous_byte_offset: usize,
     /// Offset from previous word in chars
     previous_char_offset: usize,
+    /// Drop the block quote markers `>` at the start of continued lines.
+    quote_markers: bool,
+    /// Decides whether a word hyphenated at a line end is glued back together
+    /// without the hyphen. Without one, the hyphen is always kept.
+    dictionary: Option<&'s HunspellChecker>,
 }
 
 impl<'s> Tokeneer<'s> {
@@ -42,9 +48,17 @@ impl<'s> Tokeneer<'s> {
             inner,
             previous_byte_offset: 0usize,
             previous_char_offset: 0usize,
+            quote_markers: false,
+            dictionary: None,
         }
     }
 
+    /// Keep the hyphen of soft wrapped words, if the joined word is not known
+    /// to `dictionary`.
+    pub(crate) fn set_dictionary(&mut self, dictionary: &'s HunspellChecker) {
+        self.dictionary = Some(dictionary);
+    }
+
     #[inline(always)]
     pub fn add_unbreakables(&mut self, unbreakable_ranges: impl IntoIterator<Item = Range>) {
         self.unbreakable_ranges.extend(unbreakable_ranges);
@@ -86,11 +100,65 @@ impl<'s> Tokeneer<'s> {
     }
 }
 
-impl<'s> Iterator for Tokeneer<'s> {
-    /// `(char range, byte range, )`
-    type Item = (Range, Range, Cow<'s, str>);
-    /// Yields individual tokens with associated ranges in chars and bytes.
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'s> Tokeneer<'s> {
+    /// Whether `token` is the first part of a word hyphenated at the end of a
+    /// line, i.e. `hyphen-` followed by `ated` on the next line.
+    fn is_soft_wrapped(&self, token: &(Range, Range, Cow<'s, str>)) -> bool {
+        let (_char_range, byte_range, s) = token;
+        let mut rev = s.chars().rev();
+        if rev.next() != Some('-') || !rev.next().map_or(false, char::is_alphanumeric) {
+            return false;
+        }
+        let rest = &self.s[byte_range.end..];
+        let continued = rest.trim_start();
+        let whitespace = &rest[..rest.len() - continued.len()];
+        let continued = if self.quote_markers {
+            continued.trim_start_matches(|c: char| c == '>' || c.is_whitespace())
+        } else {
+            continued
+        };
+        whitespace.matches('\n').count() == 1 && continued.starts_with(char::is_lowercase)
+    }
+
+    /// Strips the block quote markers off `token`, if it starts a continued
+    /// line. Yields `None` if nothing but the markers is left.
+    fn strip_quote_markers(
+        &self,
+        token: (Range, Range, Cow<'s, str>),
+    ) -> Option<(Range, Range, Cow<'s, str>)> {
+        let (char_range, byte_range, s) = token;
+        let line_start = match self.s[..byte_range.start].rfind('\n') {
+            Some(idx) if self.quote_markers => idx + 1,
+            _ => return Some((char_range, byte_range, s)),
+        };
+        let leading = &self.s[line_start..byte_range.start];
+        if !leading.chars().all(|c| c == '>' || c.is_whitespace()) {
+            return Some((char_range, byte_range, s));
+        }
+        // markers are ASCII, so chars and bytes are in sync
+        let n = s.len() - s.trim_start_matches('>').len();
+        if n == s.len() {
+            return None;
+        }
+        Some((
+            char_range.start + n..char_range.end,
+            byte_range.start + n..byte_range.end,
+            Cow::Borrowed(&self.s[byte_range.start + n..byte_range.end]),
+        ))
+    }
+
+    /// Yields the next token, without the block quote markers.
+    fn next_word(&mut self) -> Option<(Range, Range, Cow<'s, str>)> {
+        while let Some(token) = self.next_token() {
+            if let Some(token) = self.strip_quote_markers(token) {
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    /// Yields the next whitespace delimited token.
+    fn next_token(&mut self) -> Option<(Range, Range, Cow<'s, str>)> {
         'outer: while let Some((char_idx, (byte_offset, c))) = self.inner.next() {
             'unbreakable: while let Some(unbreakable) =
                 self.unbreakable_ranges.get(self.unbreakable_idx)
@@ -150,6 +218,38 @@ impl<'s> Iterator for Tokeneer<'s> {
     }
 }
 
+impl<'s> Iterator for Tokeneer<'s> {
+    /// `(char range, byte range, )`
+    type Item = (Range, Range, Cow<'s, str>);
+    /// Yields individual tokens with associated ranges in chars and bytes. The
+    /// parts of a word hyphenated at a line end are rejoined into one token,
+    /// dropping the hyphen only if the dictionary knows the result.
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.next_word()?;
+        if !self.is_soft_wrapped(&first) {
+            return Some(first);
+        }
+        let (char_range, byte_range, s) = first;
+        match self.next_word() {
+            Some((next_char_range, next_byte_range, next)) => {
+                let dehyphenated = format!("{}{}", s.trim_end_matches('-'), next);
+                let joined = match self.dictionary {
+                    Some(dictionary) if dictionary.unknown_words(&dehyphenated).is_empty() => {
+                        dehyphenated
+                    }
+                    _ => format!("{}{}", s, next),
+                };
+                Some((
+                    char_range.start..next_char_range.end,
+                    byte_range.start..next_byte_range.end,
+                    Cow::Owned(joined),
+                ))
+            }
+            None => Some((char_range, byte_range, s)),
+        }
+    }
+}
+
 /// Re-glue all tokenized items under the constrained of a maximum line width
 #[derive(Debug, Clone)]
 pub struct Gluon<'s> {
@@ -167,6 +267,9 @@ pub struct Gluon<'s> {
     /// The inner iterator which first tokenizes the string into undividable
     /// items.
     inner: Tokeneer<'s>,
+    /// Width of the prefix of all but the first line, if it is not given by
+    /// the indentations.
+    continuation_width: Option<usize>,
 }
 
 impl<'s> Gluon<'s> {
@@ -181,9 +284,27 @@ impl<'s> Gluon<'s> {
             indentations,
             line_counter: 0usize,
             inner: Tokeneer::<'s>::new(s, vec![]),
+            continuation_width: None,
         }
     }
 
+    /// Lines within a block quote repeat the `>` markers, which are not part of
+    /// the content to be wrapped.
+    pub(crate) fn strip_quote_markers(&mut self) {
+        self.inner.quote_markers = true;
+    }
+
+    /// Words hyphenated at a line end keep the hyphen, if the joined word is
+    /// not known to `dictionary`.
+    pub(crate) fn set_dictionary(&mut self, dictionary: &'s HunspellChecker) {
+        self.inner.set_dictionary(dictionary);
+    }
+
+    /// All lines but the first are prefixed with `width` characters.
+    pub(crate) fn set_continuation_width(&mut self, width: usize) {
+        self.continuation_width = Some(width);
+    }
+
     #[inline]
     #[allow(unused)]
     pub(crate) fn add_unbreakables(&mut self, unbreakable_ranges: impl IntoIterator<Item = Range>) {
@@ -245,7 +366,10 @@ impl<'s> Iterator for Gluon<'s> {
                 0usize
             };
 
-            let offset = indentation.offset();
+            let offset = match self.continuation_width {
+                Some(width) if self.line_counter > 0 => width,
+                _ => indentation.offset(),
+            };
             let item_len = char_range.len();
             let item = (char_range.clone(), cow_str);
             let ret = if offset + acc_len <= self.max_line_width {
@@ -354,6 +478,39 @@ mod tests {
             verify(CONTENT, EXPECTED, vec![0..2]);
         }
 
+        #[test]
+        fn soft_wrapped_hyphenation() {
+            const CONTENT: &str = "some hyphen-\n ated words, a dash -\n and a Proper-\n Noun";
+            const EXPECTED: &[&'static str] = &[
+                "some",
+                "hyphen-ated",
+                "words,",
+                "a",
+                "dash",
+                "-",
+                "and",
+                "a",
+                "Proper-",
+                "Noun",
+            ];
+            verify(CONTENT, EXPECTED, vec![]);
+        }
+
+        #[test]
+        fn soft_wrapped_hyphenation_with_dictionary() {
+            let config = crate::config::HunspellConfig {
+                use_builtin: true,
+                ..Default::default()
+            };
+            let dictionary =
+                HunspellChecker::new(&config, &crate::traverse::cwd().unwrap()).unwrap();
+            const CONTENT: &str = "some hyphen-\n ated, well-\n xyzzy words";
+            let mut tokeneer = Tokeneer::new(CONTENT, vec![]);
+            tokeneer.set_dictionary(&dictionary);
+            let tokens = tokeneer.map(|(_, _, s)| s.into_owned()).collect::<Vec<_>>();
+            assert_eq!(tokens, vec!["some", "hyphenated,", "well-xyzzy", "words"]);
+        }
+
         #[test]
         fn multi_char() {
             const CONTENT: &str = "abc xyz qwert";
@@ -3,7 +3,7 @@
 //! Note that for commonmark this might not be possible with links. The reflow
 //! is done based on the comments no matter the content.
 
-use crate::checker::Checker;
+use crate::checker::{Checker, HunspellChecker};
 use crate::documentation::CheckableChunk;
 use crate::errors::{eyre, Result};
 use crate::util::extract_delimiter;
@@ -13,6 +13,7 @@ use crate::util::{byte_range_to_char_range, byte_range_to_char_range_many, sub_c
 
 use crate::{CommentVariant, ContentOrigin, Detector, Range, Span, Suggestion};
 
+use indexmap::IndexMap;
 use pulldown_cmark::{Event, Options, Parser, Tag};
 
 pub use crate::config::ReflowConfig;
@@ -23,14 +24,34 @@ pub use iter::{Gluon, Tokeneer};
 #[derive(Debug)]
 pub struct Reflow {
     config: ReflowConfig,
+    dictionary: Option<HunspellChecker>,
 }
 
 impl Reflow {
     pub fn new(config: ReflowConfig) -> Result<Self> {
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            dictionary: None,
+        })
+    }
+
+    /// Words hyphenated at a line end are only rejoined without the hyphen, if
+    /// `dictionary` knows the joined word.
+    pub fn with_dictionary(mut self, dictionary: HunspellChecker) -> Self {
+        self.dictionary = Some(dictionary);
+        self
     }
 }
 
+/// How the lines of a paragraph are glued back together.
+#[derive(Debug, Clone, Copy)]
+struct Wrapping<'d> {
+    /// Maximum width in columns of a line.
+    max_line_width: usize,
+    /// Decides whether soft wrapped hyphenated words keep their hyphen.
+    dictionary: Option<&'d HunspellChecker>,
+}
+
 impl Checker for Reflow {
     type Config = ReflowConfig;
 
@@ -49,12 +70,23 @@ impl Checker for Reflow {
         let mut acc = Vec::with_capacity(chunks.len());
         for chunk in chunks {
             match chunk.variant() {
+                // leading `*` of continued lines are not understood
                 CommentVariant::SlashAsterisk
                 | CommentVariant::SlashAsteriskAsterisk
-                | CommentVariant::SlashAsteriskEM => continue,
+                | CommentVariant::SlashAsteriskEM
+                    if chunk
+                        .as_str()
+                        .lines()
+                        .skip(1)
+                        .any(|line| line.trim_start().starts_with('*')) =>
+                {
+                    continue
+                }
+                // the reflown content would have to be escaped again
+                CommentVariant::MacroDocEqStr(..) if chunk.is_unescaped() => continue,
                 _ => {}
             }
-            let suggestions = reflow(&origin, chunk, &self.config)?;
+            let suggestions = reflow(&origin, chunk, &self.config, self.dictionary.as_ref())?;
             acc.extend(suggestions);
         }
         Ok(acc)
@@ -71,13 +103,20 @@ impl Checker for Reflow {
 /// words/sequences which must not be split during the reflow. They are relative
 /// to the top-level `CheckableChunk` similar to `range`. The indentation vector
 /// contains the indentation for each line in `s`.
+///
+/// If the paragraph continues within the same literal or block comment,
+/// `continuation` is the indentation of the continued lines, which then do not
+/// repeat the comment prefix and suffix of `variant`.
+/// Within list items and block quotes, `continuation` is the whole prefix of
+/// the continued lines, including the markdown indentation and quote markers.
 fn reflow_inner<'s>(
     s: &'s str,
     range: Range,
     unbreakable_ranges: &[Range],
     indentations: &[Indentation<'s>],
-    max_line_width: usize,
+    wrapping: Wrapping<'_>,
     variant: &CommentVariant,
+    continuation: Option<&str>,
 ) -> Result<Option<String>> {
     // Get type of newline from current chunk, either plain \n or \r\n
     let line_delimiter = extract_delimiter(s).unwrap_or_else(|| {
@@ -101,8 +140,17 @@ fn reflow_inner<'s>(
         .iter()
         .map(|r| (r.start.saturating_sub(range.start))..(r.end.saturating_sub(range.start)));
 
-    let mut gluon = Gluon::new(s_absolute, max_line_width, &indentations);
+    let mut gluon = Gluon::new(s_absolute, wrapping.max_line_width, &indentations);
     gluon.add_unbreakables(unbreakables);
+    if let Some(dictionary) = wrapping.dictionary {
+        gluon.set_dictionary(dictionary);
+    }
+    if let Some(prefix) = continuation {
+        gluon.set_continuation_width(prefix.chars().count());
+        if prefix.contains('>') {
+            gluon.strip_quote_markers();
+        }
+    }
 
     let mut reflow_applied = false;
     let mut lines = s_absolute.lines();
@@ -122,7 +170,12 @@ fn reflow_inner<'s>(
         reflow_applied = true;
     }
 
-    let mut acc = content.to_owned() + &variant.suffix_string();
+    let suffix = if continuation.is_some() {
+        String::new()
+    } else {
+        variant.suffix_string()
+    };
+    let mut acc = content.to_owned() + &suffix;
     if !acc.is_empty() {
         acc.push_str(line_delimiter);
     }
@@ -133,6 +186,13 @@ fn reflow_inner<'s>(
             reflow_applied = true;
         }
 
+        if let Some(indentation) = continuation {
+            acc.push_str(indentation);
+            acc.push_str(&content);
+            acc.push_str(line_delimiter);
+            return acc;
+        }
+
         // avoid stray spaces after newlines due to a truely required indentation
         // of 3 for `///` but practically, it's `/// ` (added space), which should be accounted for,
         // since that is used for accounting for the skip covered by `///`,
@@ -180,7 +240,8 @@ fn reflow_inner<'s>(
     Ok(if reflow_applied {
         // for MacroDocEq comments, we also have to remove the last closing delimiter
         let mut content = content
-            .strip_suffix(&variant.suffix_string())
+            .strip_suffix(&suffix)
+            .filter(|_| !suffix.is_empty())
             .map(|content| content.to_owned())
             .unwrap_or_else(|| content);
         if &CommentVariant::CommonMark == variant && last_char_is_newline && !content.is_empty() {
@@ -244,6 +305,103 @@ impl<'s> Indentation<'s> {
     }
 }
 
+/// Determine the indentation of continued lines, if the paragraph `range` is
+/// not made of one comment per line but continues within a block comment or a
+/// `#[doc = ".."]` literal spanning multiple lines. These are reflown in their
+/// own style, without repeating the comment prefix on each line.
+fn continuation(
+    chunk: &CheckableChunk,
+    range: &Range,
+    range2span: &IndexMap<Range, Span>,
+    span: Span,
+) -> Option<String> {
+    let s = chunk.as_str();
+    let multiline = match chunk.variant() {
+        CommentVariant::SlashAsterisk
+        | CommentVariant::SlashAsteriskAsterisk
+        | CommentVariant::SlashAsteriskEM => true,
+        CommentVariant::MacroDocEqStr(..) => range2span
+            .keys()
+            .any(|fragment| sub_char_range(s, fragment.clone()).contains('\n')),
+        _ => false,
+    };
+    if !multiline {
+        return None;
+    }
+    // a block comment with a star gutter is split into one fragment per line,
+    // the gutter is recreated from the column the content starts at
+    if !matches!(chunk.variant(), CommentVariant::MacroDocEqStr(..)) {
+        let fragments = chunk.find_spans(0..chunk.len_in_chars());
+        if let Some(column) = fragments
+            .values()
+            .skip(1)
+            .find(|fragment| fragment.start.line != span.start.line)
+            .map(|fragment| fragment.start.column)
+        {
+            return Some(match column {
+                0 | 1 => "*".to_owned(),
+                column => " ".repeat(column - 2) + "* ",
+            });
+        }
+    }
+    let leading_whitespace = |line: &str| {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .collect::<String>()
+    };
+    // keep the indentation of the continued lines as is, tabs included, or
+    // the one of other lines if the paragraph is a single line
+    let indentation = sub_char_range(s, range.clone())
+        .lines()
+        .nth(1)
+        .or_else(|| s.lines().nth(1))
+        .map(leading_whitespace)
+        .unwrap_or_else(|| match chunk.variant() {
+            CommentVariant::MacroDocEqStr(..) => String::new(),
+            _ => " ".repeat(span.start.column),
+        });
+    Some(indentation)
+}
+
+/// The prefix of the continued lines of a paragraph within list items or block
+/// quotes, the `container` prefix included. Only line comments and markdown
+/// files are supported, since the prefix of the other variants can not be
+/// repeated.
+fn container_prefix(chunk: &CheckableChunk, range: &Range, container: &str) -> Option<String> {
+    let variant = chunk.variant();
+    let extra_space = match variant {
+        CommentVariant::TripleSlash | CommentVariant::DoubleSlashEM => " ",
+        CommentVariant::CommonMark => "",
+        _ => return None,
+    };
+    let s = chunk.as_str();
+    let before = sub_char_range(s, 0..range.start);
+    let line_start = before
+        .rfind('\n')
+        .map_or(0, |idx| before[..=idx].chars().count());
+    let column = chunk
+        .find_spans(line_start..line_start + 1)
+        .values()
+        .next()?
+        .start
+        .column;
+    Some(
+        " ".repeat(column.saturating_sub(variant.prefix_len()))
+            + &variant.prefix_string()
+            + extra_space
+            + container,
+    )
+}
+
+/// The width of the list item marker at the start of `item`, including the
+/// following spaces, which is the indentation of its continued lines.
+fn item_marker_width(item: &str) -> usize {
+    let item = item.trim_start();
+    let marker = item.chars().take_while(|c| !c.is_whitespace()).count();
+    let spaces = item.chars().skip(marker).take_while(|&c| c == ' ').count();
+    marker + if (1..=4).contains(&spaces) { spaces } else { 1 }
+}
+
 /// Collect reflown Paragraphs in a `Vec` of `Suggestions`.
 ///
 /// Note: Leading spaces are skipped by the CommonMark parser, which implies for
@@ -258,7 +416,8 @@ fn store_suggestion<'s>(
     bytes_paragraph: usize,
     bytes_end: usize,
     bytes_unbreakable_ranges: &[Range],
-    max_line_width: usize,
+    wrapping: Wrapping<'_>,
+    container: &str,
 ) -> Result<(usize, Option<Suggestion<'s>>)> {
     let bytes_range = Range {
         start: bytes_paragraph,
@@ -346,6 +505,12 @@ fn store_suggestion<'s>(
         })
         .collect::<Vec<Indentation>>();
 
+    let continuation = match continuation(chunk, &range, &range2span, span) {
+        Some(indentation) => Some(indentation + container),
+        None if container.is_empty() => None,
+        None => container_prefix(chunk, &range, container),
+    };
+
     Ok((
         bytes_end,
         reflow_inner(
@@ -353,8 +518,9 @@ fn store_suggestion<'s>(
             range.clone(),
             unbreakable_ranges,
             &indentations,
-            max_line_width,
+            wrapping,
             &chunk.variant(),
+            continuation.as_deref(),
         )?
         .map(|replacement| {
             let suggestion = Suggestion {
@@ -362,9 +528,13 @@ fn store_suggestion<'s>(
                 detector: Detector::Reflow,
                 origin: origin.clone(),
                 description: None,
+                rule: None,
+                help: None,
+                severity: None,
                 range,
                 replacements: vec![replacement],
                 span,
+                also: Vec::new(),
             };
             suggestion
         }),
@@ -377,8 +547,13 @@ fn reflow<'s>(
     origin: &ContentOrigin,
     chunk: &'s CheckableChunk,
     cfg: &ReflowConfig,
+    dictionary: Option<&HunspellChecker>,
 ) -> Result<Vec<Suggestion<'s>>> {
     log::debug!("Reflowing {:?}", origin);
+    let wrapping = Wrapping {
+        max_line_width: cfg.max_line_length(),
+        dictionary,
+    };
     let parser = Parser::new_ext(chunk.as_str(), Options::all());
 
     let mut paragraph = 0_usize;
@@ -387,6 +562,8 @@ fn reflow<'s>(
                                                                     // the true unbreakables (without inner nested items)
                                                                     // to be used for reflowing
     let mut unbreakables = Vec::with_capacity(256);
+    // the prefixes of the enclosing list items and block quotes
+    let mut containers = Vec::<String>::new();
 
     let mut acc = Vec::with_capacity(128);
 
@@ -421,13 +598,21 @@ fn reflow<'s>(
                             paragraph,
                             paragraph,
                             unbreakables.as_slice(),
-                            cfg.max_line_length,
+                            wrapping,
+                            &containers.concat(),
                         )?;
                         paragraph = p;
                         if let Some(suggestion) = suggestion {
                             acc.push(suggestion);
                         }
                         unbreakable_stack.clear();
+                        match tag {
+                            Tag::BlockQuote => containers.push("> ".to_owned()),
+                            Tag::Item => containers.push(" ".repeat(item_marker_width(
+                                &chunk.as_str()[cover.start..cover.end],
+                            ))),
+                            _ => {}
+                        }
                     }
                 }
             }
@@ -456,7 +641,8 @@ fn reflow<'s>(
                             paragraph,
                             cover.end,
                             unbreakables.as_slice(),
-                            cfg.max_line_length,
+                            wrapping,
+                            &containers.concat(),
                         )?;
                         paragraph = p;
                         if let Some(suggestion) = suggestion {
@@ -464,6 +650,10 @@ fn reflow<'s>(
                         }
                         unbreakable_stack.clear();
                     }
+                    Tag::BlockQuote | Tag::Item => {
+                        let _ = containers.pop();
+                        paragraph = cover.end;
+                    }
                     _ => {
                         paragraph = cover.end;
                     }
@@ -485,15 +675,24 @@ fn reflow<'s>(
                 // ignored
             }
             Event::HardBreak => {
-                let (p, suggestion) = store_suggestion(
+                // the trailing spaces or backslash of the break are kept as is
+                let (_p, suggestion) = store_suggestion(
                     chunk,
                     origin,
                     paragraph,
-                    cover.end,
+                    cover.start,
                     unbreakables.as_slice(),
-                    cfg.max_line_length,
+                    wrapping,
+                    &containers.concat(),
                 )?;
-                paragraph = p;
+                // the next line starts after its indentation and quote markers
+                let quoted = containers
+                    .iter()
+                    .any(|container| container.starts_with('>'));
+                let rest = &chunk.as_str()[cover.end..];
+                let content = rest
+                    .trim_start_matches(|c: char| c == ' ' || c == '\t' || (quoted && c == '>'));
+                paragraph = cover.end + rest.len() - content.len();
                 if let Some(suggestion) = suggestion {
                     acc.push(suggestion);
                 }
@@ -29,8 +29,12 @@ macro_rules! verify_reflow_inner {
             range,
             &unbreakables,
             &indentation,
-            $n,
-            &chunk.variant()
+            Wrapping {
+                max_line_width: $n,
+                dictionary: None,
+            },
+            &chunk.variant(),
+            None
         );
 
         if let Ok(Some(repl)) = replacement {
@@ -78,7 +82,7 @@ fn reflow_inner_not_required() {
 macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => applied $expected:literal) => {
         const CFG: ReflowConfig = ReflowConfig {
-            max_line_length: $max_line_width,
+            max_line_length: Some($max_line_width),
         };
 
         let _ = env_logger::Builder::new()
@@ -93,7 +97,7 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
 
         let patches = suggestions
             .into_iter()
@@ -120,7 +124,7 @@ macro_rules! reflow_content {
     };
     ($max_line_width:literal break $content_type:expr, $content:expr => ok) => {
         const CFG: ReflowConfig = ReflowConfig {
-            max_line_length: $max_line_width,
+            max_line_length: Some($max_line_width),
         };
 
         let _ = env_logger::Builder::new()
@@ -135,7 +139,7 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
 
         assert_eq!(
             dbg!(suggestions).len(),
@@ -145,7 +149,7 @@ macro_rules! reflow_content {
     };
     ($max_line_width:literal break $content_type:expr, $content:expr => patches [ $( $expected:literal ),+ $(,)?]) => {
         const CFG: ReflowConfig = ReflowConfig {
-            max_line_length: $max_line_width,
+            max_line_length: Some($max_line_width),
         };
 
         let _ = env_logger::Builder::new()
@@ -161,7 +165,7 @@ macro_rules! reflow_content {
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
         println!("reflow content:\n {:?}", $content);
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
         let patches = suggestions
             .into_iter()
             .filter_map(|suggestion| {
@@ -283,7 +287,7 @@ fn reflow_indentations() {
     /// 🤔"#;
 
     const CONFIG: ReflowConfig = ReflowConfig {
-        max_line_length: 10,
+        max_line_length: Some(10),
     };
 
     let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true, false);
@@ -294,8 +298,8 @@ fn reflow_indentations() {
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = &chunks[0];
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &CONFIG).expect("Reflow is wokring. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &CONFIG, None)
+        .expect("Reflow is wokring. qed");
 
     let suggestion = suggestion_set
         .first()
@@ -318,9 +322,9 @@ fn reflow_doc_indentations() {
             "#]
     struct Fluffy {};"##;
 
-    const EXPECTED: &str = r##"A comment with indentation"#]
-    #[doc = r#"that spans over two lines and"#]
-    #[doc = r#"should be rewrapped."##;
+    const EXPECTED: &str = r##"A comment with indentation
+                that spans over two lines and
+                should be rewrapped."##;
 
     let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true, false);
     assert_eq!(dbg!(&docs).entry_count(), 1);
@@ -331,10 +335,10 @@ fn reflow_doc_indentations() {
     let chunk = &chunks[0];
 
     let cfg = ReflowConfig {
-        max_line_length: 45,
+        max_line_length: Some(45),
     };
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg, None).expect("Reflow is working. qed");
 
     let suggestions = suggestion_set
         .first()
@@ -401,11 +405,11 @@ fn reflow_markdown_two_paragraphs() {
     let chunk = &chunks[0];
 
     let cfg = ReflowConfig {
-        max_line_length: 60,
+        max_line_length: Some(60),
     };
 
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg, None).expect("Reflow is working. qed");
 
     for (sug, expected) in suggestion_set.iter().zip(expected) {
         assert_eq!(sug.replacements.len(), 1);
@@ -428,8 +432,7 @@ With a second part that is fine"#
     println!("{}", chyrped);
 
     let expected = vec![
-        r##"A long comment that spans over two"#]
-#[doc=r#"lines."##,
+        "A long comment that spans over two\nlines.",
         r#"With a second part that is fine"#,
     ];
 
@@ -440,12 +443,12 @@ With a second part that is fine"#
         .expect("Contains test data. qed");
 
     let cfg = ReflowConfig {
-        max_line_length: 45,
+        max_line_length: Some(45),
     };
 
     for (chunk, expect) in chunks.iter().zip(expected) {
-        let suggestion_set =
-            reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &cfg, None)
+            .expect("Reflow is working. qed");
         let sug = suggestion_set.first().expect("Contains a suggestion. qed");
         let replacement = sug
             .replacements
@@ -463,8 +466,7 @@ fn reflow_doc_short() {
 #[test]
 fn reflow_doc_indent_middle() {
     reflow_chyrp!(28 break ["First line", "     Second line", "         third line"]
-        => r##"First line Second"#]
-#[doc=r#"line third line"##);
+        => "First line Second\n     line third line");
 }
 
 #[test]
@@ -478,7 +480,7 @@ fn reflow_doc_long() {
 #[test]
 fn reflow_sole_markdown() {
     const CONFIG: ReflowConfig = ReflowConfig {
-        max_line_length: 60,
+        max_line_length: Some(60),
     };
 
     const CONTENT: &str = "# Possible __ways__ to run __rustc__ and request various parts of LTO.
@@ -536,7 +538,7 @@ multiline. Fullstop."#,
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &CONFIG)
+    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &CONFIG, None)
         .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 2);
 
@@ -580,7 +582,7 @@ fn reflow_line_delimiters() {
 #[test]
 fn reflow_check_span() {
     const CONFIG: ReflowConfig = ReflowConfig {
-        max_line_length: 27,
+        max_line_length: Some(27),
     };
 
     const CONTENT: &str = "/// A comment as we have many here and we will always
@@ -604,8 +606,8 @@ struct Fff;
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, &chunk, &CONFIG).expect("Reflow is working. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, &chunk, &CONFIG, None)
+        .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 1);
     let suggestion = suggestion_set
         .first()
@@ -718,3 +720,116 @@ fn reflow_crlf() {
         "cargo spellcheck can be\r\n        /// configured with `-m <code>`\r\n        /// to return a non-zero return\r\n        /// code."
     ]);
 }
+
+#[test]
+fn reflow_block_comment_style() {
+    const CONTENT: &str = r#"
+    /** A block comment which is long enough
+        that it must be reflown into more lines. */
+    struct Fluffy {};"#;
+
+    const EXPECTED: &str = "A block comment which is\n        long enough that it must\n        be reflown into more\n        lines.";
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(dbg!(chunks).len(), 1);
+
+    let cfg = ReflowConfig {
+        max_line_length: Some(32),
+    };
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, &chunks[0], &cfg, None)
+        .expect("Reflow is working. qed");
+    let replacement = suggestion_set
+        .first()
+        .and_then(|suggestion| suggestion.replacements.first())
+        .expect("There is a replacement. qed");
+    assert_eq!(replacement.as_str(), EXPECTED);
+}
+
+#[test]
+fn reflow_star_gutter() {
+    const CONTENT: &str = r#"
+    /**
+     * A block comment with a star gutter which is long enough
+     * that it must be reflown.
+     */
+    struct Fluffy {};"#;
+
+    const EXPECTED: &str = "A block comment with a star\n     * gutter which is long enough that\n     * it must be reflown.";
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(dbg!(chunks).len(), 1);
+
+    let cfg = ReflowConfig {
+        max_line_length: Some(40),
+    };
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, &chunks[0], &cfg, None)
+        .expect("Reflow is working. qed");
+    let replacement = suggestion_set
+        .first()
+        .and_then(|suggestion| suggestion.replacements.first())
+        .expect("There is a replacement. qed");
+    assert_eq!(replacement.as_str(), EXPECTED);
+}
+
+#[test]
+fn reflow_nested_list_items() {
+    reflow_content!(36usize break ContentOrigin::TestEntityRust,
+        r#"/// - A first list item which is too long for one line.
+///
+///   - A nested item, which also is too long for a line.
+///
+///   - A short one.
+///
+/// - A second item.
+struct Fluffy {};"#
+        => applied
+        r#"/// - A first list item which is too
+///   long for one line.
+///
+///   - A nested item, which also is
+///     too long for a line.
+///
+///   - A short one.
+///
+/// - A second item.
+struct Fluffy {};"#
+    );
+}
+
+#[test]
+fn reflow_block_quote() {
+    reflow_content!(32usize break ContentOrigin::TestEntityRust,
+        r#"/// > A quote which is too long for a single line,
+/// > and spans two.
+///
+/// > - A quoted item which needs reflow as well.
+/// >
+/// > - A short one.
+struct Fluffy {};"#
+        => applied
+        r#"/// > A quote which is too long
+/// > for a single line, and
+/// > spans two.
+///
+/// > - A quoted item which
+/// >   needs reflow as well.
+/// >
+/// > - A short one.
+struct Fluffy {};"#
+    );
+}
+
+#[test]
+fn reflow_keeps_hard_breaks() {
+    reflow_content!(32usize break ContentOrigin::TestEntityRust,
+        "/// The first line which ends with a break  \n/// the last line.\nstruct Fluffy {};"
+        => applied
+        "/// The first line which ends\n/// with a break  \n/// the last line.\nstruct Fluffy {};"
+    );
+}
@@ -0,0 +1,324 @@
+//! Property based span round trip harness.
+//!
+//! Generates random doc comments, made of markdown fragments with unicode
+//! content, and verifies that the chain `erase -> check -> map back -> apply
+//! fix` never touches bytes that are unrelated to the fixed words.
+//!
+//! Available to unit tests, and to downstream contributors with the `proptest`
+//! feature enabled, so additional generators can be plugged into
+//! [`check_round_trip`]. The fuzz targets in `fuzz/` feed arbitrary input to
+//! [`check_partial_replacement`].
+
+use crate::action::{apply_patches, Patch};
+use crate::documentation::{ContentOrigin, Documentation, PlainOverlay};
+use crate::util::{iter_with_line_column, load_span_from, sub_chars};
+use crate::{Range, Span};
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use std::fmt::Write;
+
+/// A single word, mixing ASCII and multi-byte characters.
+pub fn word() -> impl Strategy<Value = String> {
+    proptest::string::string_regex("[a-zA-Zäöüßéøλж漢字🐠]{1,8}")
+        .expect("Regex of the word generator is human checked. qed")
+}
+
+/// A word, optionally wrapped in inline markdown.
+pub fn markdown_fragment() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => word(),
+        1 => word().prop_map(|w| format!("*{}*", w)),
+        1 => word().prop_map(|w| format!("_{}_", w)),
+        1 => word().prop_map(|w| format!("**{}**", w)),
+        1 => word().prop_map(|w| format!("~~{}~~", w)),
+        1 => word().prop_map(|w| format!("`{}`", w)),
+    ]
+}
+
+/// A single line of documentation content, without the comment prefix.
+pub fn doc_line() -> impl Strategy<Value = String> {
+    proptest::collection::vec(markdown_fragment(), 1..6).prop_map(|fragments| fragments.join(" "))
+}
+
+/// A rust source snippet with a doc comment attached to an item.
+///
+/// Covers `///`, `//!` and `#[doc = ".."]` comment variants.
+pub fn doc_comment() -> impl Strategy<Value = String> {
+    let lines = proptest::collection::vec(doc_line(), 1..5);
+    (lines, 0..3_u8).prop_map(|(lines, variant)| {
+        let mut source = String::with_capacity(256);
+        for line in lines {
+            match variant {
+                0 => {
+                    let _ = writeln!(source, "/// {}", line);
+                }
+                1 => {
+                    let _ = writeln!(source, "//! {}", line);
+                }
+                _ => {
+                    let _ = writeln!(source, "#[doc = \" {}\"]", line);
+                }
+            }
+        }
+        source.push_str("struct Fluff;\n");
+        source
+    })
+}
+
+/// Split the plain, cmark erased content into whitespace separated tokens.
+fn tokenize(plain: &str) -> Vec<Range> {
+    let mut acc = Vec::new();
+    let mut start = None;
+    for (idx, c) in plain.chars().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                acc.push(s..idx);
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        acc.push(s..plain.chars().count());
+    }
+    acc
+}
+
+/// Replace all `spans` in `source` with the associated replacement, without
+/// using any of the patch machinery.
+///
+/// `spans` must be sorted and must not overlap.
+fn splice(source: &str, spans: &[(Span, String)]) -> String {
+    let mut acc = String::with_capacity(source.len());
+    let mut spans = spans.iter().peekable();
+    for (c, _byte_offset, _idx, linecol) in iter_with_line_column(source) {
+        while let Some((span, _)) = spans.peek() {
+            if linecol > span.end {
+                let _ = spans.next();
+            } else {
+                break;
+            }
+        }
+        match spans.peek() {
+            Some((span, replacement)) if linecol == span.start => acc.push_str(replacement),
+            Some((span, _)) if linecol > span.start => {}
+            _ => acc.push(c),
+        }
+    }
+    acc
+}
+
+fn apply(source: &str, spans: &[(Span, String)]) -> Result<String, TestCaseError> {
+    let patches = spans.iter().map(|(span, replacement)| Patch::Replace {
+        replace_span: *span,
+        replacement: replacement.clone(),
+    });
+    let mut sink = Vec::with_capacity(source.len());
+    apply_patches(patches, source, &mut sink).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    String::from_utf8(sink).map_err(|e| TestCaseError::fail(e.to_string()))
+}
+
+/// Assert that mapping every token of every chunk of `source` back to its
+/// `Span`s and applying a fix there only ever modifies the token itself.
+pub fn check_round_trip(source: &str) -> Result<(), TestCaseError> {
+    let origin = ContentOrigin::TestEntityRust;
+    let docs = Documentation::load_from_str(origin.clone(), source, true, false);
+
+    let mut identity = Vec::new();
+    let mut fixes = Vec::new();
+    for chunk in docs.get(&origin).unwrap_or_default() {
+        let plain = chunk.erase_cmark();
+        for range in tokenize(plain.as_str()) {
+            let token = sub_chars(plain.as_str(), range.clone());
+            let spans = plain.find_spans(range);
+            if spans.is_empty() {
+                // i.e. inline code, which is not mapped back
+                continue;
+            }
+            let mut extracted = String::new();
+            for span in spans.values() {
+                let fragment = load_span_from(source.as_bytes(), *span)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                identity.push((*span, fragment.clone()));
+                fixes.push((*span, format!("<{}>", fixes.len())));
+                extracted.push_str(&fragment);
+            }
+            prop_assert_eq!(extracted, token);
+        }
+    }
+    identity.sort_by_key(|(span, _)| span.start);
+    fixes.sort_by_key(|(span, _)| span.start);
+
+    prop_assert_eq!(apply(source, &identity)?, source);
+    prop_assert_eq!(apply(source, &fixes)?, splice(source, &fixes));
+    Ok(())
+}
+
+/// The number of chars of each line of `source`, including the line break.
+fn line_lengths(source: &str) -> Vec<usize> {
+    source
+        .split_inclusive('\n')
+        .map(|line| line.chars().count())
+        .collect()
+}
+
+fn check_in_bounds(lines: &[usize], span: &Span) -> Result<(), TestCaseError> {
+    prop_assert!(span.start <= span.end, "Span {:?} is reversed", span);
+    let within = |line: usize, column: usize| {
+        line >= 1 && lines.get(line - 1).map_or(false, |len| column < *len)
+    };
+    prop_assert!(
+        within(span.start.line, span.start.column) && within(span.end.line, span.end.column),
+        "Span {:?} is out of bounds",
+        span
+    );
+    Ok(())
+}
+
+/// Assert that arbitrary char ranges of the erased content of all chunks of
+/// `source`, with the given `origin`, map to in bounds spans, and that
+/// replacing some of them only modifies those.
+///
+/// The ranges and replacements are derived from `pick`, so fuzzers can steer
+/// them. Other than [`check_round_trip`] this does not require the mapped
+/// spans to reproduce the erased content, which does not hold for all input,
+/// i.e. escaped characters.
+pub fn check_partial_replacement(
+    origin: ContentOrigin,
+    source: &str,
+    pick: &[u8],
+) -> Result<(), TestCaseError> {
+    let docs = Documentation::load_from_str(origin.clone(), source, true, true);
+    let lines = line_lengths(source);
+
+    let mut picks = pick.chunks_exact(2).cycle().take(pick.len().max(32) / 2);
+    let mut fixes = Vec::new();
+    for chunk in docs.get(&origin).unwrap_or_default() {
+        // the chunk itself must be in bounds, before and after erasure
+        for span in chunk.find_spans(0..chunk.len_in_chars()).values() {
+            check_in_bounds(&lines, span)?;
+        }
+        let plain = chunk.erase_cmark();
+        let n = plain.as_str().chars().count();
+        for pair in picks.by_ref().take(8) {
+            let (a, b) = (
+                usize::from(pair[0]) % (n + 1),
+                usize::from(pair[1]) % (n + 1),
+            );
+            let range = a.min(b)..a.max(b);
+            for span in plain.find_spans(range).values() {
+                check_in_bounds(&lines, span)?;
+                load_span_from(source.as_bytes(), *span)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                if pair[0] % 2 == 0 {
+                    fixes.push((*span, format!("<{}>", fixes.len())));
+                }
+            }
+        }
+    }
+    fixes.sort_by_key(|(span, _)| span.start);
+    // patches must not overlap, keep the first of each overlapping group
+    let mut end = None;
+    fixes.retain(|(span, _)| {
+        if end.map_or(false, |end| span.start <= end) {
+            return false;
+        }
+        end = Some(span.end);
+        true
+    });
+
+    prop_assert_eq!(apply(source, &fixes)?, splice(source, &fixes));
+    Ok(())
+}
+
+/// Assert that the source map of the erased `markdown` is consistent, the
+/// plain ranges derived from `pick` map to the source and back again.
+///
+/// Requires `markdown` to have no escaped characters, as generated by
+/// [`doc_line`], otherwise direct entries do not reproduce the source.
+pub fn check_source_map(markdown: &str, pick: &[u8]) -> Result<(), TestCaseError> {
+    let (plain, map) = PlainOverlay::extract_plain_with_mapping(markdown);
+    for (plain_range, raw_range) in map.direct() {
+        prop_assert_eq!(
+            sub_chars(&plain, plain_range.clone()),
+            sub_chars(markdown, raw_range.clone())
+        );
+    }
+    let n = plain.chars().count();
+    for pair in pick.chunks_exact(2) {
+        let (a, b) = (
+            usize::from(pair[0]) % (n + 1),
+            usize::from(pair[1]) % (n + 1),
+        );
+        let range = a.min(b)..a.max(b);
+        let mut end = range.start;
+        for (covered, raw) in map.to_source(range.clone()) {
+            // ordered, disjoint and within the queried range
+            prop_assert!(end <= covered.start && covered.end <= range.end);
+            end = covered.end;
+            prop_assert_eq!(
+                map.to_plain(raw.clone()).collect::<Vec<_>>(),
+                vec![(raw, covered)]
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splice_works() {
+        let span = Span {
+            start: crate::LineColumn { line: 1, column: 4 },
+            end: crate::LineColumn { line: 1, column: 6 },
+        };
+        assert_eq!(
+            splice("/// fäx\n", &[(span, "fox".to_owned())]),
+            "/// fox\n"
+        );
+    }
+
+    #[test]
+    fn partial_replacement() {
+        let source = "/// A *fäx* jumped\n/// over `it`.\nstruct Fluff;\n";
+        check_partial_replacement(ContentOrigin::TestEntityRust, source, &[0, 5, 2, 9, 13, 40])
+            .unwrap();
+        check_partial_replacement(ContentOrigin::TestEntityCommonMark, "# T\\*t\n\n- a\n", &[])
+            .unwrap();
+    }
+
+    proptest! {
+        #[test]
+        fn span_round_trip(source in doc_comment()) {
+            check_round_trip(&source)?;
+        }
+
+        #[test]
+        fn source_map_round_trip(
+            line in doc_line(),
+            pick in proptest::collection::vec(any::<u8>(), 0..64),
+        ) {
+            check_source_map(&line, &pick)?;
+        }
+
+        #[test]
+        fn arbitrary_partial_replacement(
+            lines in proptest::collection::vec("(?s:.{0,40})", 1..4),
+            pick in proptest::collection::vec(any::<u8>(), 0..64),
+        ) {
+            let markdown = lines.join("\n");
+            check_partial_replacement(ContentOrigin::TestEntityCommonMark, &markdown, &pick)?;
+            let mut rust = lines.iter().fold(String::new(), |mut acc, line| {
+                let _ = writeln!(acc, "/// {}", line);
+                acc
+            });
+            rust.push_str("struct X;\n");
+            check_partial_replacement(ContentOrigin::TestEntityRust, &rust, &pick)?;
+        }
+    }
+}
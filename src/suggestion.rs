@@ -11,7 +11,9 @@
 //!     |     - you can add it to your personal dictionary to prevent future alerts.
 //! ```
 
-use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::checker::is_placeholder_doc;
+use crate::documentation::{CheckableChunk, CommentVariantCategory, ContentOrigin};
+use crate::i18n::{tr, tr_count};
 
 use std::cmp;
 use std::convert::TryFrom;
@@ -21,14 +23,23 @@ use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use crate::{Range, Span};
 
 /// Bitflag of available checkers by compilation / configuration.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum Detector {
     /// Hunspell lib based detector.
     Hunspell,
     /// Language server rules based on NLP detector.
     NlpRules,
+    /// Grammar checks of a `LanguageTool` server.
+    LanguageTool,
     /// Reflow according to a given max column.
     Reflow,
+    /// An external subprocess checker.
+    External,
+    /// A checker compiled to WebAssembly.
+    Wasm,
+    /// Placeholder documentation, empty or only a URL.
+    EmptyDocs,
     /// Detection of nothing, a test helper.
     #[cfg(test)]
     Dummy,
@@ -40,11 +51,339 @@ impl Detector {
         match self {
             Self::Hunspell => "Hunspell",
             Self::NlpRules => "NlpRules",
+            Self::LanguageTool => "LanguageTool",
             Self::Reflow => "Reflow",
+            Self::External => "External",
+            Self::Wasm => "Wasm",
+            Self::EmptyDocs => "EmptyDocs",
             #[cfg(test)]
             Self::Dummy => "Dummy",
         }
     }
+
+    /// The severity of all findings of this detector.
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::Hunspell => Severity::Error,
+            Self::NlpRules | Self::LanguageTool | Self::External | Self::Wasm | Self::EmptyDocs => {
+                Severity::Warning
+            }
+            Self::Reflow => Severity::Note,
+            #[cfg(test)]
+            Self::Dummy => Severity::Error,
+        }
+    }
+}
+
+/// Severity of a suggestion, determines the color and the legend entry.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Spelling mistakes.
+    Error,
+    /// Grammar and style findings.
+    Warning,
+    /// Purely cosmetic, i.e. reflow.
+    Note,
+}
+
+impl Severity {
+    /// All severities, from most to least severe.
+    pub const ALL: [Self; 3] = [Self::Error, Self::Warning, Self::Note];
+
+    /// Converts the severity to its static str representation.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+
+    fn style(self) -> console::Style {
+        let style = console::Style::new().bold();
+        match self {
+            Self::Error => style.red(),
+            Self::Warning => style.yellow(),
+            Self::Note => style.cyan(),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.style().apply_to(self.as_str()).fmt(formatter)
+    }
+}
+
+/// Per severity counts of the suggestions of one or more files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    counts: [usize; 3],
+    files: usize,
+    placeholder_docs: usize,
+}
+
+impl Tally {
+    /// Count the suggestions of a single file.
+    pub fn from_file(suggestions: &[Suggestion<'_>]) -> Self {
+        let mut tally = Self::default();
+        for suggestion in suggestions {
+            tally.counts[suggestion.severity() as usize] += 1;
+            if suggestion.rule.as_deref().map_or(false, is_placeholder_doc) {
+                tally.placeholder_docs += 1;
+            }
+        }
+        tally.files = usize::from(!suggestions.is_empty());
+        tally
+    }
+
+    /// Number of suggestions with the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.counts[severity as usize]
+    }
+
+    /// Number of suggestions of all severities.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Number of files with at least one suggestion.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    /// Number of empty or bare URL documentation comments, a subset of the
+    /// suggestions.
+    pub fn placeholder_docs(&self) -> usize {
+        self.placeholder_docs
+    }
+
+    /// A file header, printed before the suggestions of one file.
+    pub fn header<'a>(&'a self, origin: &'a ContentOrigin) -> impl fmt::Display + 'a {
+        self.package_header(origin, None)
+    }
+
+    /// A file header prefixed with the name of the package of the file, to
+    /// tell apart the members of a workspace.
+    pub fn package_header<'a>(
+        &'a self,
+        origin: &'a ContentOrigin,
+        package: Option<&'a str>,
+    ) -> impl fmt::Display + 'a {
+        FileHeader {
+            origin,
+            package,
+            tally: self,
+        }
+    }
+
+    /// The per severity counts, colored, i.e. `2 errors, 1 warning`.
+    fn fmt_counts(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for severity in Severity::ALL {
+            let n = self.count(severity);
+            if n == 0 {
+                continue;
+            }
+            if !first {
+                formatter.write_str(", ")?;
+            }
+            first = false;
+            let counted = tr_count(&format!("count-{}", severity.as_str()), n);
+            write!(formatter, "{}", severity.style().apply_to(counted))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::ops::Add for Tally {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (acc, n) in self.counts.iter_mut().zip(other.counts) {
+            *acc += n;
+        }
+        self.files += other.files;
+        self.placeholder_docs += other.placeholder_docs;
+        self
+    }
+}
+
+/// Legend printed after all files, summing up all findings.
+impl fmt::Display for Tally {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        console::Style::new()
+            .bold()
+            .apply_to(tr("legend"))
+            .fmt(formatter)?;
+        for severity in Severity::ALL {
+            write!(formatter, " {} {}", severity, self.count(severity))?;
+        }
+        write!(formatter, " ({}", tr_count("count-file", self.files))?;
+        if self.placeholder_docs > 0 {
+            let counted = tr_count("count-placeholder-doc", self.placeholder_docs);
+            write!(formatter, ", {}", counted)?;
+        }
+        writeln!(formatter, ")")
+    }
+}
+
+/// The least severe findings that fail a run, given with `--fail-on`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    /// Any error, i.e. a spelling mistake.
+    Errors,
+    /// Any error or warning.
+    Warnings,
+    /// Nothing, the run always passes.
+    None,
+}
+
+impl std::str::FromStr for FailOn {
+    type Err = UnknownFailOn;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        Ok(match s.as_str() {
+            "errors" => Self::Errors,
+            "warnings" => Self::Warnings,
+            "none" => Self::None,
+            _other => return Err(UnknownFailOn(s)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Unknown `--fail-on` value: {0}, expected `errors`, `warnings` or `none`")]
+/// A `--fail-on` value other than `errors`, `warnings` or `none`.
+pub struct UnknownFailOn(String);
+
+/// Upper bounds of the number of findings per severity, given with
+/// `--max-errors`, `--max-warnings` and `--max-findings`, and `--fail-on`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Thresholds {
+    /// Maximum number of errors, i.e. spelling mistakes.
+    pub max_errors: Option<usize>,
+    /// Maximum number of warnings, i.e. grammar findings.
+    pub max_warnings: Option<usize>,
+    /// Maximum number of findings of any severity.
+    pub max_findings: Option<usize>,
+    /// The least severe findings that fail the run.
+    pub fail_on: Option<FailOn>,
+}
+
+impl Thresholds {
+    /// No threshold is given, mistakes are handled with `--code`.
+    pub fn is_empty(&self) -> bool {
+        self.max_errors.is_none()
+            && self.max_warnings.is_none()
+            && self.max_findings.is_none()
+            && self.fail_on.is_none()
+    }
+
+    /// Evaluate the final counts of a run.
+    pub fn verdict<'a>(&'a self, tally: &'a Tally) -> Verdict<'a> {
+        Verdict {
+            thresholds: self,
+            tally,
+        }
+    }
+}
+
+/// The one line summary of a run with [`Thresholds`], i.e.
+/// `spellcheck: 3 errors, 12 warnings — FAILED (max-errors=0)`.
+pub struct Verdict<'a> {
+    thresholds: &'a Thresholds,
+    tally: &'a Tally,
+}
+
+impl Verdict<'_> {
+    /// The thresholds that were exceeded, i.e. `max-errors=0`.
+    fn exceeded(&self) -> Vec<String> {
+        let errors = self.tally.count(Severity::Error);
+        let warnings = self.tally.count(Severity::Warning);
+        let mut acc = [
+            ("max-errors", errors, self.thresholds.max_errors),
+            ("max-warnings", warnings, self.thresholds.max_warnings),
+            (
+                "max-findings",
+                self.tally.total(),
+                self.thresholds.max_findings,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, n, max)| {
+            max.filter(|max| n > *max)
+                .map(|max| format!("{}={}", name, max))
+        })
+        .collect::<Vec<_>>();
+        match self.thresholds.fail_on {
+            Some(FailOn::Errors) if errors > 0 => acc.push("fail-on=errors".to_owned()),
+            Some(FailOn::Warnings) if errors + warnings > 0 => {
+                acc.push("fail-on=warnings".to_owned());
+            }
+            _ => {}
+        }
+        acc
+    }
+
+    /// Whether all counts are within their thresholds.
+    pub fn passed(&self) -> bool {
+        self.thresholds.fail_on == Some(FailOn::None) || self.exceeded().is_empty()
+    }
+}
+
+impl fmt::Display for Verdict<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("spellcheck: ")?;
+        // notes count towards `max-findings`, so they are shown with it
+        let notes = self.thresholds.max_findings.is_some() || self.tally.count(Severity::Note) > 0;
+        let shown = if notes {
+            &Severity::ALL[..]
+        } else {
+            &Severity::ALL[..2]
+        };
+        for (idx, &severity) in shown.iter().enumerate() {
+            let n = self.tally.count(severity);
+            let plural = if n == 1 { "" } else { "s" };
+            let sep = if idx == 0 { "" } else { ", " };
+            write!(formatter, "{}{} {}{}", sep, n, severity.as_str(), plural)?;
+        }
+        if self.passed() {
+            return write!(formatter, " — {}", console::style("PASSED").green().bold());
+        }
+        let limits = self.exceeded().join(", ");
+        write!(
+            formatter,
+            " — {} ({})",
+            console::style("FAILED").red().bold(),
+            limits
+        )
+    }
+}
+
+struct FileHeader<'a> {
+    origin: &'a ContentOrigin,
+    package: Option<&'a str>,
+    tally: &'a Tally,
+}
+
+impl fmt::Display for FileHeader<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(package) = self.package {
+            write!(formatter, "[{}] ", package)?;
+        }
+        console::Style::new()
+            .bold()
+            .underlined()
+            .apply_to(self.origin.as_path().display())
+            .fmt(formatter)?;
+        formatter.write_str(" (")?;
+        self.tally.fmt_counts(formatter)?;
+        writeln!(formatter, ")")
+    }
 }
 
 /// Terminal size in characters.
@@ -295,8 +634,52 @@ pub fn condition_display_content(
     (conditioned_line, offset, marker_size)
 }
 
+/// Lines longer than this many multiples of the available width are soft
+/// wrapped rather than ellipsized, too much would be cut.
+const WRAP_THRESHOLD: usize = 2;
+
+/// Mistakes covering more rows are ellipsized nevertheless.
+const MAX_WRAPPED_ROWS: usize = 3;
+
+/// Soft wrap `line` into rows of at most `width` characters, preferably after
+/// whitespace. Returns the character ranges of the rows.
+fn soft_wrap(line: &str, width: usize) -> Vec<Range> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let width = cmp::max(width, 1);
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while chars.len() - start > width {
+        let hard = start + width;
+        // do not break so early that the row becomes tiny
+        let end = (start + width / 2..hard)
+            .rev()
+            .find(|&idx| chars[idx].is_whitespace())
+            .map_or(hard, |idx| idx + 1);
+        rows.push(start..end);
+        start = end;
+    }
+    rows.push(start..chars.len());
+    rows
+}
+
+/// A column ruler for a row of `len` characters, which starts at the one based
+/// `column`. The first and every tenth column are labeled.
+fn ruler(column: usize, len: usize) -> String {
+    let mut acc = String::with_capacity(len);
+    for pos in 0..len {
+        let label = (column + pos).to_string();
+        let free = pos == 0 || acc.len() < pos;
+        if free && (pos == 0 || (column + pos) % 10 == 0) && pos + label.len() <= len {
+            acc.extend(std::iter::repeat(' ').take(pos - acc.len()));
+            acc.push_str(&label);
+        }
+    }
+    acc
+}
+
 /// A suggestion for certain offending span.
 #[derive(Clone, Hash, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Suggestion<'s> {
     /// Which checker suggested the change.
     pub detector: Detector,
@@ -313,6 +696,148 @@ pub struct Suggestion<'s> {
     pub replacements: Vec<String>,
     /// Descriptive reason for the suggestion.
     pub description: Option<String>,
+    /// Identifier of the rule that triggered, if the detector has a notion of
+    /// rules.
+    pub rule: Option<String>,
+    /// Link to documentation about the rule.
+    pub help: Option<String>,
+    /// Overrides the severity of the detector, see `[severity_overrides]`.
+    pub severity: Option<Severity>,
+    /// The other checkers which flagged the same span, their findings are
+    /// merged into this one.
+    pub also: Vec<Detector>,
+}
+
+/// Hook for embedders, applied to every suggestion after checking and before
+/// any output, i.e. to consult an allowlist service.
+pub trait SuggestionFilter: Send + Sync {
+    /// Return the suggestion to keep it, possibly rewritten, or `None` to
+    /// drop it.
+    fn filter<'s>(&self, suggestion: Suggestion<'s>) -> Option<Suggestion<'s>>;
+}
+
+impl<'s> Suggestion<'s> {
+    /// A finding of `detector` at `span` of the content from `origin`,
+    /// located at `range` within `chunk`, without any replacements or
+    /// description yet.
+    pub fn new(
+        detector: Detector,
+        origin: ContentOrigin,
+        chunk: &'s CheckableChunk,
+        span: Span,
+        range: Range,
+    ) -> Self {
+        Self {
+            detector,
+            origin,
+            chunk,
+            span,
+            range,
+            replacements: Vec::new(),
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        }
+    }
+
+    /// The severity of the finding, the one of its detector unless overridden.
+    pub fn severity(&self) -> Severity {
+        self.severity.unwrap_or_else(|| self.detector.severity())
+    }
+
+    /// The same finding within `chunk`, which must have the same content as
+    /// the chunk of `self`, only the masked parts may differ.
+    pub(crate) fn rebind(self, chunk: &CheckableChunk) -> Suggestion<'_> {
+        let Suggestion {
+            detector,
+            origin,
+            chunk: _,
+            span,
+            range,
+            replacements,
+            description,
+            rule,
+            help,
+            severity,
+            also,
+        } = self;
+        Suggestion {
+            detector,
+            origin,
+            chunk,
+            span,
+            range,
+            replacements,
+            description,
+            rule,
+            help,
+            severity,
+            also,
+        }
+    }
+
+    /// Print the `rows` of the soft wrapped `line` which cover the `mistake`,
+    /// each with a column ruler above, so the columns of very long lines can
+    /// still be told apart.
+    fn fmt_wrapped(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+        line: &str,
+        rows: &[Range],
+        mistake: Range,
+        indent: usize,
+    ) -> fmt::Result {
+        use fmt::Display;
+
+        let context_marker = console::Style::new().bold().blue();
+        let help = console::Style::new().yellow().bold();
+        let ruler_style = console::Style::new().dim();
+        for (idx, row) in rows.iter().enumerate() {
+            // one based column of the row start within the file
+            let column = (self.span.start.column + 1 + row.start).saturating_sub(mistake.start);
+            context_marker
+                .apply_to(format!("{:>width$}", "|", width = indent))
+                .fmt(formatter)?;
+            writeln!(
+                formatter,
+                " {}",
+                ruler_style.apply_to(ruler(column, row.len()))
+            )?;
+            if idx == 0 {
+                context_marker
+                    .apply_to(format!(
+                        "{:>width$} |",
+                        self.span.start.line,
+                        width = indent - 2,
+                    ))
+                    .fmt(formatter)?;
+            } else {
+                context_marker
+                    .apply_to(format!("{:>width$}", "|", width = indent))
+                    .fmt(formatter)?;
+            }
+            let content = line
+                .chars()
+                .skip(row.start)
+                .take(row.len())
+                .collect::<String>();
+            writeln!(formatter, " {}", content.trim_end())?;
+
+            let start = cmp::max(row.start, mistake.start);
+            let end = cmp::min(row.end, mistake.end);
+            context_marker
+                .apply_to(format!("{:>width$}", "|", width = indent))
+                .fmt(formatter)?;
+            help.apply_to(format!(" {:>offset$}", "", offset = start - row.start))
+                .fmt(formatter)?;
+            help.apply_to(format!("{:^>size$}", "", size = end - start))
+                .fmt(formatter)?;
+            formatter.write_str("\n")?;
+        }
+        Ok(())
+    }
 }
 
 impl<'s> fmt::Display for Suggestion<'s> {
@@ -329,10 +854,19 @@ impl<'s> fmt::Display for Suggestion<'s> {
         let line_number_digit_count = self.span.start.line.to_string().len();
         let indent = 3 + line_number_digit_count;
 
-        error.apply_to("error").fmt(formatter)?;
+        self.severity().fmt(formatter)?;
+        let detectors = std::iter::once(&self.detector)
+            .chain(&self.also)
+            .map(Detector::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
         highlight
-            .apply_to(format!(": spellcheck({})", &self.detector))
+            .apply_to(format!(": spellcheck({})", detectors))
             .fmt(formatter)?;
+        // doc comments are the default, call out the others
+        if self.chunk.variant().category() == CommentVariantCategory::Dev {
+            formatter.write_str(" in a dev comment")?;
+        }
         formatter.write_str("\n")?;
 
         arrow_marker
@@ -344,21 +878,13 @@ impl<'s> fmt::Display for Suggestion<'s> {
             ContentOrigin::RustDocTest(ref path, ref span) => {
                 (path.display().to_string(), x + span.start.line)
             }
-            ref origin => (origin.as_path().display().to_string(), x),
+            ref origin => (origin.to_string(), x),
         };
         writeln!(formatter, " {path}:{line}", path = path, line = line)?;
         context_marker
             .apply_to(format!("{:>width$}", "|", width = indent))
             .fmt(formatter)?;
         formatter.write_str("\n")?;
-        context_marker
-            .apply_to(format!(
-                "{:>width$} |",
-                self.span.start.line,
-                width = indent - 2,
-            ))
-            .fmt(formatter)?;
-
         // underline the relevant part with ^^^^^
 
         // TODO this needs some more thought once multiline comments pop up
@@ -401,41 +927,68 @@ impl<'s> fmt::Display for Suggestion<'s> {
         // the line being analysed can affect how the indentation is done.
         let padding_till_excerpt_start = indent + 2;
 
-        let (formatted, offset, marker_size) = condition_display_content(
-            terminal_size,
-            indent,
-            relevant_line.as_str(),
-            intra_line_mistake_range,
-            padding_till_excerpt_start,
-            marker_size,
-        );
-
-        writeln!(formatter, " {}", formatted.as_str())?;
-
-        if marker_size > 0 {
-            context_marker
-                .apply_to(format!("{:>width$}", "|", width = indent))
-                .fmt(formatter)?;
-            help.apply_to(format!(" {:>offset$}", "", offset = offset))
-                .fmt(formatter)?;
-            help.apply_to(format!("{:^>size$}", "", size = marker_size))
-                .fmt(formatter)?;
-            formatter.write_str("\n")?;
-            log::trace!(
-                "marker_size={} span {{ {:?} .. {:?} }} >> {:?} <<",
-                marker_size,
-                self.span.start,
-                self.span.end,
-                self,
-            );
+        let width = terminal_size.saturating_sub(padding_till_excerpt_start + 1);
+        let rows = soft_wrap(&relevant_line, width)
+            .into_iter()
+            .filter(|row| {
+                row.start < intra_line_mistake_range.end && intra_line_mistake_range.start < row.end
+            })
+            .collect::<Vec<_>>();
+        if relevant_line.chars().count() > WRAP_THRESHOLD * width
+            && !rows.is_empty()
+            && rows.len() <= MAX_WRAPPED_ROWS
+        {
+            self.fmt_wrapped(
+                formatter,
+                &relevant_line,
+                &rows,
+                intra_line_mistake_range,
+                indent,
+            )?;
         } else {
-            log::warn!(
-                "marker_size={} span {{ {:?} .. {:?} }} >> {:?} <<",
+            let (formatted, offset, marker_size) = condition_display_content(
+                terminal_size,
+                indent,
+                relevant_line.as_str(),
+                intra_line_mistake_range,
+                padding_till_excerpt_start,
                 marker_size,
-                self.span.start,
-                self.span.end,
-                self,
             );
+
+            context_marker
+                .apply_to(format!(
+                    "{:>width$} |",
+                    self.span.start.line,
+                    width = indent - 2,
+                ))
+                .fmt(formatter)?;
+            writeln!(formatter, " {}", formatted.as_str())?;
+
+            if marker_size > 0 {
+                context_marker
+                    .apply_to(format!("{:>width$}", "|", width = indent))
+                    .fmt(formatter)?;
+                help.apply_to(format!(" {:>offset$}", "", offset = offset))
+                    .fmt(formatter)?;
+                help.apply_to(format!("{:^>size$}", "", size = marker_size))
+                    .fmt(formatter)?;
+                formatter.write_str("\n")?;
+                log::trace!(
+                    "marker_size={} span {{ {:?} .. {:?} }} >> {:?} <<",
+                    marker_size,
+                    self.span.start,
+                    self.span.end,
+                    self,
+                );
+            } else {
+                log::warn!(
+                    "marker_size={} span {{ {:?} .. {:?} }} >> {:?} <<",
+                    marker_size,
+                    self.span.start,
+                    self.span.end,
+                    self,
+                );
+            }
         }
 
         context_marker
@@ -489,6 +1042,15 @@ impl<'s> fmt::Display for Suggestion<'s> {
         if let Some(ref description) = self.description {
             writeln!(formatter, "   {}", description)?;
         }
+        if let Some(ref help) = self.help {
+            if self.description.is_none() {
+                formatter.write_str("\n")?;
+            }
+            context_marker
+                .apply_to(format!("{:>width$}", "=", width = indent))
+                .fmt(formatter)?;
+            writeln!(formatter, " {} {}", highlight.apply_to("help:"), help)?;
+        }
         Ok(())
     }
 }
@@ -741,6 +1303,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
         };
 
         const EXPECTED: &str = r#"error: spellcheck(Dummy)
@@ -755,6 +1321,40 @@ mod tests {
         assert_display_eq(suggestion, EXPECTED);
     }
 
+    #[test]
+    fn fmt_dev_comment() {
+        let chunk = CheckableChunk::from_str(
+            " Is it dyrck again?",
+            indexmap::indexmap! { 0..18 => Span {
+                    start: LineColumn { line: 1, column: 2 },
+                    end: LineColumn { line: 1, column: 19 },
+                }
+            },
+            CommentVariant::DoubleSlash,
+        );
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            range: 7..12,
+            span: Span {
+                start: LineColumn { line: 1, column: 8 },
+                end: LineColumn {
+                    line: 1,
+                    column: 12,
+                },
+            },
+            replacements: Vec::new(),
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        };
+        assert!(console::strip_ansi_codes(&suggestion.to_string())
+            .starts_with("error: spellcheck(Dummy) in a dev comment\n"));
+    }
+
     #[test]
     fn fmt_0_no_suggestion() {
         const CONTENT: &str = " Is it dyrck again?";
@@ -788,6 +1388,10 @@ mod tests {
             },
             replacements: vec![],
             description: Some("Possible spelling mistake found.".to_owned()),
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
         };
 
         const EXPECTED: &str = r#"error: spellcheck(Dummy)
@@ -800,6 +1404,56 @@ mod tests {
         assert_display_eq(suggestion, EXPECTED);
     }
 
+    #[test]
+    fn fmt_0_help() {
+        const CONTENT: &str = " Is it dyrck again?";
+        let chunk = CheckableChunk::from_str(
+            CONTENT,
+            indexmap::indexmap! { 0..18 => Span {
+                    start: LineColumn {
+                        line: 1,
+                        column: 0,
+                    },
+                    end: LineColumn {
+                        line: 1,
+                        column: 17,
+                    }
+                }
+            },
+            CommentVariant::TripleSlash,
+        );
+
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            range: 7..12,
+            span: Span {
+                start: LineColumn { line: 1, column: 6 },
+                end: LineColumn {
+                    line: 1,
+                    column: 10,
+                },
+            },
+            replacements: vec![],
+            description: Some("Bad style.".to_owned()),
+            rule: Some("STYLE/1".to_owned()),
+            help: Some("https://example.com/style".to_owned()),
+            severity: None,
+            also: Vec::new(),
+        };
+
+        const EXPECTED: &str = r#"error: spellcheck(Dummy)
+  --> /tmp/test/entity.rs:1
+   |
+ 1 |  Is it dyrck again?
+   |        ^^^^^
+   |   Bad style.
+   = help: https://example.com/style
+"#;
+        assert_display_eq(suggestion, EXPECTED);
+    }
+
     #[test]
     fn fmt_1_multi() {
         const CONTENT: &str = r#" Line mitake 1
@@ -864,6 +1518,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
         };
 
         const EXPECTED: &str = r#"error: spellcheck(Dummy)
@@ -930,6 +1588,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
         };
 
         const EXPECTED: &str = r#"error: spellcheck(Dummy)
@@ -945,6 +1607,182 @@ mod tests {
         assert_display_eq(suggestion, EXPECTED);
     }
 
+    #[test]
+    fn fmt_3_wrapped_long_line() {
+        let content = format!(
+            " {} The mistaek is here. {}",
+            "First sentence of many. ".repeat(6).trim_end(),
+            "Another sentence follows. ".repeat(4).trim_end()
+        );
+        let len = content.chars().count();
+        let chunk = CheckableChunk::from_str(
+            &content,
+            indexmap::indexmap! { 0..len => Span {
+                    start: LineColumn { line: 1, column: 9 },
+                    end: LineColumn {
+                        line: 1,
+                        column: 9 + len - 1,
+                    }
+                }
+            },
+            CommentVariant::MacroDocEqStr("#[doc=".to_owned(), 0),
+        );
+        let start = content.find("mistaek").unwrap();
+
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            range: start..start + 7,
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 9 + start,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: 9 + start + 6,
+                },
+            },
+            replacements: vec!["mistake".to_owned()],
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        };
+
+        const EXPECTED: &str = r#"error: spellcheck(Dummy)
+  --> /tmp/test/entity.rs:1
+   |
+   | 155  160       170       180       190       200       210       220
+ 1 | The mistaek is here. Another sentence follows. Another sentence follows.
+   |     ^^^^^^^
+   | - mistake
+   |
+   |"#;
+        assert_display_eq(suggestion, EXPECTED);
+    }
+
+    #[test]
+    fn tally_header_and_legend() {
+        const CONTENT: &str = " Is it dyrck again?";
+        let chunk = CheckableChunk::from_str(
+            CONTENT,
+            indexmap::indexmap! { 0..18 => Span {
+                    start: LineColumn { line: 1, column: 0 },
+                    end: LineColumn { line: 1, column: 17 },
+                }
+            },
+            CommentVariant::TripleSlash,
+        );
+        let suggestion = |detector| Suggestion {
+            detector,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            range: 7..12,
+            span: Span {
+                start: LineColumn { line: 1, column: 6 },
+                end: LineColumn {
+                    line: 1,
+                    column: 10,
+                },
+            },
+            replacements: vec![],
+            description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
+        };
+        let suggestions = vec![
+            suggestion(Detector::Hunspell),
+            suggestion(Detector::Hunspell),
+            suggestion(Detector::NlpRules),
+        ];
+        let tally = Tally::from_file(&suggestions);
+        assert_eq!(tally.count(Severity::Error), 2);
+        assert_eq!(tally.count(Severity::Warning), 1);
+        assert_display_eq(
+            tally.header(&ContentOrigin::TestEntityRust),
+            "/tmp/test/entity.rs (2 errors, 1 warning)\n",
+        );
+        assert_display_eq(
+            tally.package_header(&ContentOrigin::TestEntityRust, Some("entity")),
+            "[entity] /tmp/test/entity.rs (2 errors, 1 warning)\n",
+        );
+
+        let tally = tally + Tally::from_file(&[suggestion(Detector::Reflow)]) + Tally::default();
+        assert_eq!(tally.total(), 4);
+        assert_eq!(tally.files(), 2);
+        assert_display_eq(tally, "Legend: error 2 warning 1 note 1 (2 files)\n");
+        let placeholder = Suggestion {
+            rule: Some("empty-doc".to_owned()),
+            ..suggestion(Detector::EmptyDocs)
+        };
+        let with_placeholder = tally + Tally::from_file(&[placeholder]);
+        assert_eq!(with_placeholder.placeholder_docs(), 1);
+        assert_display_eq(
+            with_placeholder,
+            "Legend: error 2 warning 2 note 1 (3 files, 1 placeholder doc)\n",
+        );
+
+        let thresholds = Thresholds {
+            max_errors: Some(0),
+            max_warnings: Some(1),
+            ..Thresholds::default()
+        };
+        let verdict = thresholds.verdict(&tally);
+        assert!(!verdict.passed());
+        assert_display_eq(
+            verdict,
+            "spellcheck: 2 errors, 1 warning, 1 note — FAILED (max-errors=0)",
+        );
+        let thresholds = Thresholds {
+            max_errors: Some(2),
+            max_warnings: None,
+            ..Thresholds::default()
+        };
+        let verdict = thresholds.verdict(&tally);
+        assert!(verdict.passed());
+        assert_display_eq(verdict, "spellcheck: 2 errors, 1 warning, 1 note — PASSED");
+
+        let thresholds = Thresholds {
+            max_findings: Some(3),
+            fail_on: Some(FailOn::Warnings),
+            ..Thresholds::default()
+        };
+        assert_display_eq(
+            thresholds.verdict(&tally),
+            "spellcheck: 2 errors, 1 warning, 1 note — FAILED (max-findings=3, fail-on=warnings)",
+        );
+        let thresholds = Thresholds {
+            max_errors: Some(0),
+            fail_on: Some(FailOn::None),
+            ..Thresholds::default()
+        };
+        assert!(thresholds.verdict(&tally).passed());
+        let notes = Tally::from_file(&[suggestion(Detector::Reflow)]);
+        let thresholds = Thresholds {
+            fail_on: Some(FailOn::Errors),
+            ..Thresholds::default()
+        };
+        assert!(thresholds.verdict(&notes).passed());
+        let errors = Tally::from_file(&[suggestion(Detector::Hunspell)]);
+        assert_display_eq(
+            thresholds.verdict(&errors),
+            "spellcheck: 1 error, 0 warnings — FAILED (fail-on=errors)",
+        );
+        let thresholds = Thresholds {
+            max_findings: Some(1),
+            ..Thresholds::default()
+        };
+        assert_display_eq(
+            thresholds.verdict(&errors),
+            "spellcheck: 1 error, 0 warnings, 0 notes — PASSED",
+        );
+    }
+
     #[test]
     fn multiline_is_dbg_printable() {
         let _ = env_logger::builder()
@@ -987,6 +1825,10 @@ mod tests {
             range: 2..6,
             replacements: vec!["whocares".to_owned()],
             description: None,
+            rule: None,
+            help: None,
+            severity: None,
+            also: Vec::new(),
         };
 
         let suggestion = dbg!(suggestion);
@@ -1,6 +1,6 @@
-use super::literalset::tests::gen_literal_set;
 use super::*;
 use crate::checker::Checker;
+use crate::documentation::tests::gen_literal_set;
 use crate::util::{load_span_from, sub_char_range, sub_chars};
 use crate::{chyrp_up, fluff_up};
 
@@ -84,7 +84,7 @@ macro_rules! end2end {
         end2end!($test, $origin, $n, $checker, cfg);
     }};
 
-    ($test:expr, $origin:expr, $n:expr, $checker:ty, $cfg:expr) => {{
+    ($test:expr, $origin:expr, $n:expr, $checker:ty, $cfg:expr $(, $arg:expr)*) => {{
         let _ = ::env_logger::builder()
             .is_test(true)
             .filter_level(::log::LevelFilter::Trace)
@@ -100,7 +100,7 @@ macro_rules! end2end {
         let _plain = chunk.erase_cmark();
         let cfg = $cfg;
         dbg!(std::any::type_name::<$checker>());
-        let checker = <$checker>::new(&cfg).expect("Checker construction works");
+        let checker = <$checker>::new(&cfg $(, $arg)*).expect("Checker construction works");
         let suggestions = checker
             .check(&origin, &chunks[..])
             .expect("Must not fail to extract suggestions");
@@ -193,7 +193,8 @@ struct X;
             ContentOrigin::TestEntityRust,
             0,
             HunspellChecker,
-            cfg
+            cfg,
+            &crate::traverse::cwd().unwrap()
         );
     }
 
@@ -281,7 +282,8 @@ struct CAPI;
             HunspellConfig {
                 extra_dictionaries: vec![dict_path],
                 ..Default::default()
-            }
+            },
+            &crate::traverse::cwd().unwrap()
         );
     }
 
@@ -1778,3 +1780,77 @@ fn variant_consistency() {
         );
     }
 }
+
+#[test]
+fn reduce_w_badges() {
+    cmark_reduction_test(
+        r#"[![Crates.io](https://img.shields.io/crates/v/foo.svg)](https://crates.io/crates/foo)[![Build Stauts](https://ci.example.com/badge.svg "CI stauts")](https://ci.example.com)"#,
+        r#"Crates.io Build Stauts CI stauts"#,
+        3,
+    );
+}
+
+#[test]
+fn reduce_w_link_titles() {
+    cmark_reduction_test(
+        r#"See [the dcos](https://docs.rs/foo "API documentaton") and ![A lgoo](logo.png "The lgoo")."#,
+        r#"See the dcos API documentaton and A lgoo The lgoo."#,
+        7,
+    );
+}
+
+#[test]
+fn crate_level_badges() {
+    const TEST_SOURCE: &str = r#"#![doc(html_root_url = "https://docs.rs/foo/0.1.0")]
+//! [![docs.rs](https://docs.rs/foo/badge.svg)](https://docs.rs/foo)
+//!
+//! Some crate.
+"#;
+
+    let origin = ContentOrigin::TestEntityRust;
+    let docs = Documentation::load_from_str(origin.clone(), TEST_SOURCE, true, false);
+    let chunks = docs.get(&origin).expect("Must contain dummy path");
+    assert_eq!(chunks.len(), 1);
+    let plain = chunks[0].erase_cmark();
+    assert_eq!(plain.as_str().trim(), "docs.rs\n\nSome crate.");
+}
+
+#[test]
+fn example_comments() {
+    const TEST_SOURCE: &str = r#"/// Adds two numbers.
+///
+/// ```
+/// // the sum of bth
+/// let x = add(1, 2); // a coment
+/// # fn add(a: u8, b: u8) -> u8 { a + b }
+/// ```
+///
+/// ```text
+/// // not rust
+/// ```
+struct Foo;
+"#;
+
+    let origin = ContentOrigin::TestEntityRust;
+    let mut docs = Documentation::load_from_str(origin.clone(), TEST_SOURCE, true, false);
+    docs.add_example_comments();
+    let chunks = docs.get(&origin).expect("Must contain dummy path");
+    let comments = &chunks[1..];
+    assert_eq!(
+        comments
+            .iter()
+            .map(CheckableChunk::as_str)
+            .collect::<Vec<_>>(),
+        vec![" the sum of bth\n a coment"]
+    );
+    for chunk in comments {
+        for (range, span) in chunk.iter() {
+            assert_eq!(
+                load_span_from(TEST_SOURCE.as_bytes(), *span).unwrap(),
+                sub_chars(chunk.as_str(), range.clone())
+            );
+        }
+    }
+    let span = comments[0].iter().nth(1).unwrap().1;
+    assert_eq!((span.start.line, span.start.column), (5, 25));
+}
@@ -0,0 +1,109 @@
+//! FFI bindings passthrough.
+//!
+//! Generated bindings, i.e. by `bindgen`, carry the documentation of the
+//! upstream C headers as `#[doc = ".."]` attributes. With `[files] bindings`
+//! enabled, only those are checked. The findings are still located in the
+//! bindings, `check` merely names the header of the closest preceding
+//! `// from: <header>[:<line>]` comment in their description.
+
+use crate::documentation::{CheckableChunk, CommentVariant, ContentOrigin};
+use crate::Suggestion;
+
+use fs_err as fs;
+
+const PROVENANCE_PREFIX: &str = "// from:";
+
+/// Only retain the chunks passed through from upstream headers.
+pub(crate) fn retain_doc_attributes(chunks: Vec<CheckableChunk>) -> Vec<CheckableChunk> {
+    chunks
+        .into_iter()
+        .filter(|chunk| matches!(chunk.variant(), CommentVariant::MacroDocEqStr(..)))
+        .collect()
+}
+
+/// The `// from:` provenance comments of one file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Provenance {
+    /// The 1-based line of the comment and the referenced header location.
+    entries: Vec<(usize, String)>,
+}
+
+impl Provenance {
+    pub(crate) fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                let header = line.trim_start().strip_prefix(PROVENANCE_PREFIX)?.trim();
+                (!header.is_empty()).then(|| (idx + 1, header.to_owned()))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Load the provenance comments of a rust source file.
+    pub(crate) fn load(origin: &ContentOrigin) -> Option<Self> {
+        match origin {
+            ContentOrigin::RustSourceFile(path) => fs::read_to_string(path)
+                .ok()
+                .map(|content| Self::parse(&content)),
+            _ => None,
+        }
+    }
+
+    /// Header location the given line originates from.
+    pub(crate) fn lookup(&self, line: usize) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(comment_line, _)| *comment_line <= line)
+            .map(|(_, header)| header.as_str())
+    }
+
+    /// Mention the originating header in the description of the suggestion,
+    /// the span is left as is.
+    pub(crate) fn annotate(&self, suggestion: &mut Suggestion<'_>) {
+        if let Some(header) = self.lookup(suggestion.span.start.line) {
+            let description = match suggestion.description.take() {
+                Some(description) => format!("{} (from {})", description, header),
+                None => format!("from {}", header),
+            };
+            suggestion.description = Some(description);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documentation::Documentation;
+
+    const BINDINGS: &str = r#"/* automatically generated by rust-bindgen */
+// from: include/frob.h:12
+#[doc = " Frobnicate the widgte."]
+pub fn frob();
+/// Hand written wrapper.
+pub fn wrap() {}
+// from: include/knob.h
+#[doc = " Turn the knob."]
+pub fn knob();
+"#;
+
+    #[test]
+    fn only_doc_attributes_are_retained() {
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), BINDINGS, true, false);
+        let chunks = retain_doc_attributes(docs.get(&origin).unwrap().to_vec());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_str(), " Frobnicate the widgte.");
+        assert_eq!(chunks[1].as_str(), " Turn the knob.");
+    }
+
+    #[test]
+    fn provenance_lookup() {
+        let provenance = Provenance::parse(BINDINGS);
+        assert_eq!(provenance.lookup(1), None);
+        assert_eq!(provenance.lookup(3), Some("include/frob.h:12"));
+        assert_eq!(provenance.lookup(9), Some("include/knob.h"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Size limits of the extracted content.
+//!
+//! Huge generated inputs would otherwise be read and kept in memory as a whole,
+//! see the `max_file_size` and `max_total_size` settings of `[files]`.
+
+use super::Issue;
+use crate::config::FilesConfig;
+use crate::documentation::CheckableChunk;
+
+use fs_err as fs;
+use std::path::Path;
+
+/// Check the size of the file at `path` before reading it, a `limit` of `0`
+/// admits any size.
+pub(crate) fn admit(path: &Path, limit: u64) -> Result<(), Issue> {
+    if limit == 0 {
+        return Ok(());
+    }
+    // unreadable files are reported once they are read
+    let size = fs::metadata(path).map_or(0, |meta| meta.len());
+    if size > limit {
+        return Err(Issue::Oversized {
+            path: path.to_owned(),
+            size,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Accounts the literal bytes extracted over all files.
+#[derive(Debug, Clone)]
+pub(crate) struct Budget {
+    max_file_size: u64,
+    max_total_size: u64,
+    used: u64,
+    /// Whether a file did not fit the total limit anymore.
+    spent: bool,
+}
+
+impl Budget {
+    pub(crate) fn new(files: &FilesConfig) -> Self {
+        Self {
+            max_file_size: files.max_file_size,
+            max_total_size: files.max_total_size,
+            used: 0,
+            spent: false,
+        }
+    }
+
+    /// See [`admit`].
+    pub(crate) fn admit(&self, path: &Path) -> Result<(), Issue> {
+        admit(path, self.max_file_size)
+    }
+
+    pub(crate) fn max_file_size(&self) -> u64 {
+        self.max_file_size
+    }
+
+    /// Account the `chunks` extracted from the file at `path`. Fails without
+    /// accounting anything if they would exceed the total limit.
+    pub(crate) fn consume(&mut self, path: &Path, chunks: &[CheckableChunk]) -> Result<(), Issue> {
+        let size = chunks
+            .iter()
+            .map(|chunk| chunk.as_str().len() as u64)
+            .sum::<u64>();
+        if self.max_total_size != 0 && self.used + size > self.max_total_size {
+            self.spent = true;
+            return Err(Issue::LimitReached {
+                path: path.to_owned(),
+                limit: self.max_total_size,
+            });
+        }
+        self.used += size;
+        Ok(())
+    }
+
+    /// Whether the total limit was reached, no further files need to be read.
+    pub(crate) fn is_spent(&self) -> bool {
+        self.spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommentVariant;
+
+    #[test]
+    fn limits() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        assert!(admit(&path, 0).is_ok());
+        assert!(matches!(
+            admit(&path, 16),
+            Err(Issue::Oversized { limit: 16, .. })
+        ));
+
+        let mut budget = Budget::new(&FilesConfig {
+            max_total_size: 10,
+            ..FilesConfig::default()
+        });
+        let chunk = CheckableChunk::from_str(
+            "Six b.",
+            indexmap::IndexMap::default(),
+            CommentVariant::Unknown,
+        );
+        let chunks = vec![chunk];
+        assert!(budget.consume(&path, &chunks).is_ok());
+        assert!(!budget.is_spent());
+        assert!(matches!(
+            budget.consume(&path, &chunks),
+            Err(Issue::LimitReached { limit: 10, .. })
+        ));
+        assert!(budget.is_spent());
+    }
+}
@@ -0,0 +1,216 @@
+//! Restrict the check to the changes relative to a git ref, see `--diff-base`.
+//!
+//! The changes are the ones of the working tree relative to the merge base of
+//! the ref and `HEAD`, so uncommitted changes count as well, while changes
+//! merged into the ref in the meantime do not. Only the added and modified
+//! lines of tracked files are considered. Unchanged files are only read to
+//! follow their module declarations, the chunks and findings of unchanged
+//! lines are dropped.
+//!
+//! Renames are always detected, regardless of `diff.renames`, so a moved file
+//! only counts with the lines changed along with the move, instead of as a
+//! whole new file.
+
+use super::identity::canonical;
+use crate::errors::*;
+use crate::Documentation;
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The changed lines by file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Changes {
+    /// Canonical paths, with the 1-based line ranges that were added or
+    /// modified.
+    files: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+/// Run `git` with `args` within `cwd`, returning its trimmed output.
+fn git(cwd: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(cwd)
+        .args(args)
+        .output()
+        .wrap_err("Failed to run `git`")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_owned())
+}
+
+/// The added lines of a hunk header, i.e. `@@ -10,2 +12,3 @@ fn main()`, none
+/// if the hunk only removes lines.
+fn hunk_lines(header: &str) -> Option<RangeInclusive<usize>> {
+    let added = header
+        .strip_prefix("@@ ")?
+        .split(' ')
+        .find_map(|range| range.strip_prefix('+'))?;
+    let (start, count) = match added.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (added.parse::<usize>().ok()?, 1),
+    };
+    (count > 0).then(|| start..=start + count - 1)
+}
+
+impl Changes {
+    /// The changes of the working tree of the repository containing `cwd`,
+    /// relative to the merge base of `base` and `HEAD`.
+    pub(crate) fn since(base: &str, cwd: &Path) -> Result<Self> {
+        let toplevel = canonical(PathBuf::from(git(cwd, &["rev-parse", "--show-toplevel"])?));
+        let merge_base = git(cwd, &["merge-base", base, "HEAD"])
+            .wrap_err_with(|| eyre!("Failed to find the merge base of {} and HEAD", base))?;
+        let diff = git(
+            cwd,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "--unified=0",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-prefix",
+                "--find-renames",
+                "--diff-filter=ACMR",
+                &merge_base,
+                "--",
+            ],
+        )?;
+        let changes = Self::parse(&toplevel, &diff);
+        log::debug!(
+            "{} files changed relative to {} ({})",
+            changes.files.len(),
+            base,
+            merge_base
+        );
+        Ok(changes)
+    }
+
+    /// Parse the output of `git diff --unified=0 --no-prefix`, with paths
+    /// relative to `toplevel`.
+    fn parse(toplevel: &Path, diff: &str) -> Self {
+        let mut files = HashMap::<PathBuf, Vec<RangeInclusive<usize>>>::new();
+        let mut current = None;
+        // added lines may start with `++ ` as well
+        let mut header = false;
+        for line in diff.lines() {
+            if line.starts_with("diff ") {
+                header = true;
+            } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| header) {
+                let path = toplevel.join(path);
+                files.entry(path.clone()).or_default();
+                current = Some(path);
+            } else if let Some(lines) = hunk_lines(line) {
+                header = false;
+                if let Some(lines_of_file) = current.as_ref().and_then(|path| files.get_mut(path)) {
+                    lines_of_file.push(lines);
+                }
+            }
+        }
+        Self { files }
+    }
+
+    /// Whether the file at the canonical `path` is changed.
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Whether one of `lines` of the file at `path` is changed.
+    pub(crate) fn touches(&self, path: &Path, lines: RangeInclusive<usize>) -> bool {
+        self.files.get(path).map_or(false, |changed| {
+            changed
+                .iter()
+                .any(|changed| changed.start() <= lines.end() && lines.start() <= changed.end())
+        })
+    }
+
+    /// Retain only the chunks which span a changed line.
+    pub(crate) fn retain(&self, docs: &mut Documentation) {
+        docs.retain(|origin, chunk| {
+            chunk.iter().any(|(_range, span)| {
+                self.touches(origin.as_path(), span.start.line..=span.end.line)
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_lines() {
+        let toplevel = Path::new("/tmp/repo");
+        let changes = Changes::parse(
+            toplevel,
+            r"diff --git src/lib.rs src/lib.rs
+index 1111111..2222222 100644
+--- src/lib.rs
++++ src/lib.rs
+@@ -3 +3 @@
+-//! Old.
++//! New.
+@@ -10,2 +10,0 @@ fn removed()
+-/// Gone.
+-fn removed() {}
+@@ -20,0 +19,3 @@ fn added()
++/// Added.
++fn added() {}
++++ not a header
+diff --git README.md README.md
+new file mode 100644
+--- /dev/null
++++ README.md
+@@ -0,0 +1 @@
++# Readme
+",
+        );
+        let lib = toplevel.join("src/lib.rs");
+        assert!(changes.contains(&lib));
+        assert!(changes.contains(&toplevel.join("README.md")));
+        assert!(!changes.contains(&toplevel.join("src/main.rs")));
+        assert!(changes.touches(&lib, 3..=3));
+        assert!(changes.touches(&lib, 1..=4));
+        assert!(!changes.touches(&lib, 10..=11));
+        assert!(changes.touches(&lib, 21..=30));
+        assert!(!changes.touches(&lib, 22..=30));
+        assert!(changes.touches(&toplevel.join("README.md"), 1..=1));
+        assert_eq!(hunk_lines("@@ -1 +0,0 @@"), None);
+    }
+
+    #[test]
+    fn renamed_files() {
+        let toplevel = Path::new("/tmp/repo");
+        let changes = Changes::parse(
+            toplevel,
+            r"diff --git src/old.rs src/moved.rs
+similarity index 100%
+rename from src/old.rs
+rename to src/moved.rs
+diff --git src/a.rs src/b.rs
+similarity index 90%
+rename from src/a.rs
+rename to src/b.rs
+index 1111111..2222222 100644
+--- src/a.rs
++++ src/b.rs
+@@ -2 +2 @@
+-/// Old.
++/// New.
+",
+        );
+        assert!(!changes.contains(&toplevel.join("src/moved.rs")));
+        assert!(!changes.contains(&toplevel.join("src/a.rs")));
+        let renamed = toplevel.join("src/b.rs");
+        assert!(changes.touches(&renamed, 2..=2));
+        assert!(!changes.touches(&renamed, 1..=1));
+    }
+}
@@ -0,0 +1,118 @@
+//! Dump of the extracted content, see `--dump-extracted`.
+//!
+//! For every chunk, the plain text the checkers receive, with the parts masked
+//! by `[filters]` blanked, is written to a `.txt` file, next to a `.json`
+//! sidecar file with the spans within the origin each part of the plain text
+//! maps to and the masked ranges. Missed or bogus findings can be traced back
+//! to the extraction or the markdown erasure this way.
+
+use crate::errors::*;
+use crate::filters::Pipeline;
+use crate::{CheckableChunk, Config, Documentation, Span};
+
+use fs_err as fs;
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn span_to_json(span: &Span) -> Value {
+    json!({
+        "start": { "line": span.start.line, "column": span.start.column },
+        "end": { "line": span.end.line, "column": span.end.column },
+    })
+}
+
+/// A file name friendly excerpt of the origin.
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Write the plain text and spans of all chunks of `documents` to `dir`,
+/// masked by the filters of `config` like the checkers do.
+pub(crate) fn dump(documents: &Documentation, config: &Config, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).wrap_err("Failed to create the dump directory")?;
+    let filters = Pipeline::new(config);
+    let mut count = 0_usize;
+    for (file_idx, (origin, chunks)) in documents.iter().enumerate() {
+        let stem = stem(origin.as_path());
+        let masked = filters.as_ref().map(|filters| filters.mask(chunks));
+        let chunks: &[CheckableChunk] = masked.as_deref().unwrap_or(chunks);
+        for (chunk_idx, chunk) in chunks.iter().enumerate() {
+            let plain = chunk.erase_cmark();
+            let segments = plain
+                .direct()
+                .map(|range| {
+                    let spans = plain
+                        .find_spans(range.clone())
+                        .values()
+                        .map(span_to_json)
+                        .collect::<Vec<_>>();
+                    json!({ "range": [range.start, range.end], "spans": spans })
+                })
+                .collect::<Vec<_>>();
+            let placeholders = plain
+                .placeholders()
+                .map(|range| json!([range.start, range.end]))
+                .collect::<Vec<_>>();
+            let masked = chunk
+                .masked()
+                .iter()
+                .map(|range| json!([range.start, range.end]))
+                .collect::<Vec<_>>();
+            let sidecar = json!({
+                "origin": origin.to_string(),
+                "chunk": chunk_idx,
+                "variant": format!("{:?}", chunk.variant()),
+                "segments": segments,
+                "placeholders": placeholders,
+                "masked": masked,
+            });
+
+            let name = format!("{:04}-{:03}-{}", file_idx, chunk_idx, stem);
+            fs::write(dir.join(format!("{}.txt", name)), plain.as_str())?;
+            fs::write(
+                dir.join(format!("{}.json", name)),
+                serde_json::to_string_pretty(&sidecar)?,
+            )?;
+            count += 1;
+        }
+    }
+    log::info!("Dumped {} chunks to {}", count, dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chyrp_up, ContentOrigin};
+
+    #[test]
+    fn plain_text_and_sidecar() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityRust,
+            chyrp_up!("Some `code` and *emphasis* at https://example.com."),
+            true,
+            false,
+        );
+        dump(&docs, &Config::default(), &dir).unwrap();
+
+        // the link is masked, as for the checkers
+        let plain = fs::read_to_string(dir.join("0000-000-entity.txt")).unwrap();
+        assert_eq!(plain, "Some code and emphasis at                    .");
+        let sidecar = fs::read_to_string(dir.join("0000-000-entity.json")).unwrap();
+        let sidecar = serde_json::from_str::<Value>(&sidecar).unwrap();
+        assert_eq!(sidecar["origin"], "/tmp/test/entity.rs");
+        assert_eq!(sidecar["placeholders"].as_array().unwrap().len(), 1);
+        assert_eq!(sidecar["masked"], json!([[26, 45]]));
+        assert_eq!(
+            sidecar["segments"][0]["spans"][0]["start"],
+            json!({ "line": 1, "column": 9 })
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Identity of discovered files.
+//!
+//! The same file can be reached under several paths, via symlinks or, on case
+//! insensitive file systems such as the defaults of macOS and Windows, by
+//! spelling `lib.rs` as `Lib.rs`. Each file must only be checked once, so
+//! every path is reduced to a key before it is added.
+
+use same_file::is_same_file;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Swap the case of the first cased character of the last path component
+/// which has one.
+fn case_swapped(path: &Path) -> Option<PathBuf> {
+    let components = path.components().collect::<Vec<_>>();
+    let (idx, swapped) = components
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(idx, component)| {
+            let s = component.as_os_str().to_str()?;
+            let (byte, c) = s
+                .char_indices()
+                .find(|(_, c)| c.is_lowercase() || c.is_uppercase())?;
+            let flipped = if c.is_lowercase() {
+                c.to_uppercase().collect::<String>()
+            } else {
+                c.to_lowercase().collect::<String>()
+            };
+            Some((
+                idx,
+                format!("{}{}{}", &s[..byte], flipped, &s[byte + c.len_utf8()..]),
+            ))
+        })?;
+    let mut swapped_path = PathBuf::new();
+    for (i, c) in components.iter().enumerate() {
+        if i == idx {
+            swapped_path.push(&swapped);
+        } else {
+            swapped_path.push(c.as_os_str());
+        }
+    }
+    Some(swapped_path)
+}
+
+/// Whether the file system `path` resides on ignores the case of file names.
+fn is_case_insensitive(path: &Path) -> bool {
+    case_swapped(path).map_or(false, |swapped| {
+        is_same_file(path, swapped).unwrap_or(false)
+    })
+}
+
+/// Keeps track of the files discovered so far.
+#[derive(Debug, Clone)]
+pub(crate) struct Seen {
+    case_insensitive: bool,
+    keys: HashSet<PathBuf>,
+}
+
+impl Seen {
+    /// Track files below `root`, which must exist.
+    pub(crate) fn new(root: &Path) -> Self {
+        let case_insensitive = is_case_insensitive(root);
+        if case_insensitive {
+            log::debug!("File system at {} ignores case", root.display());
+        }
+        Self {
+            case_insensitive,
+            keys: HashSet::with_capacity(64),
+        }
+    }
+
+    fn key(&self, path: &Path) -> PathBuf {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        if self.case_insensitive {
+            PathBuf::from(path.to_string_lossy().to_lowercase())
+        } else {
+            path
+        }
+    }
+
+    /// Returns `true` if the file was not seen before.
+    pub(crate) fn insert(&mut self, path: &Path) -> bool {
+        let key = self.key(path);
+        self.keys.insert(key)
+    }
+}
+
+/// Resolve symlinks, keeping the path as is if that fails.
+pub(crate) fn canonical(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs_err as fs;
+
+    #[test]
+    fn swap_last_cased_component() {
+        assert_eq!(
+            case_swapped(Path::new("/tmp/src/lib.rs")),
+            Some(PathBuf::from("/tmp/src/Lib.rs"))
+        );
+        assert_eq!(
+            case_swapped(Path::new("/tmp/Src/1.0")),
+            Some(PathBuf::from("/tmp/src/1.0"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_seen_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let file = dir.join("lib.rs");
+        fs::write(&file, "//! Docs.\n").unwrap();
+        let link = dir.join("link.rs");
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let mut seen = Seen::new(&dir);
+        assert!(seen.insert(&file));
+        assert!(!seen.insert(&link));
+        assert!(!seen.insert(&dir.join(".").join("lib.rs")));
+        if seen.case_insensitive {
+            assert!(!seen.insert(&dir.join("Lib.rs")));
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Paths excluded from the check, see `ignore` and `--ignore`.
+//!
+//! Generated or vendored code is excluded while discovering files, before it
+//! is read, let alone parsed. Patterns are resolved relative to the working
+//! directory, `*` does not match across `/`, `**` does. A directory is skipped
+//! as a whole if it matches, or if a pattern covers everything below it, i.e.
+//! `target/**`.
+//!
+//! Unless `respect_gitignore` is disabled, directories are listed honoring
+//! `.gitignore`, `.ignore` and the global git excludes, like `ripgrep` does,
+//! also outside of a git repository.
+
+use super::identity::canonical;
+use crate::errors::*;
+
+use std::path::{Path, PathBuf};
+
+/// Options to match paths, a single `*` stays within one component.
+const OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The compiled `ignore` patterns.
+#[derive(Debug, Clone)]
+pub(crate) struct Ignored {
+    patterns: Vec<glob::Pattern>,
+    respect_gitignore: bool,
+}
+
+impl Default for Ignored {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            respect_gitignore: true,
+        }
+    }
+}
+
+impl Ignored {
+    /// Compile `patterns`, relative to `cwd`.
+    pub(crate) fn new(cwd: &Path, patterns: &[String], respect_gitignore: bool) -> Result<Self> {
+        let cwd = canonical(cwd.to_owned());
+        let mut acc = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let absolute = cwd.join(pattern);
+            let absolute = absolute.to_str().ok_or_else(|| {
+                eyre!(
+                    "Failed to convert path to str for ignore pattern {}",
+                    absolute.display()
+                )
+            })?;
+            // the directory itself, so it is not even listed
+            if let Some(dir) = absolute.strip_suffix("/**") {
+                acc.push(
+                    glob::Pattern::new(dir)
+                        .wrap_err_with(|| eyre!("Invalid ignore pattern {}", pattern))?,
+                );
+            }
+            acc.push(
+                glob::Pattern::new(absolute)
+                    .wrap_err_with(|| eyre!("Invalid ignore pattern {}", pattern))?,
+            );
+        }
+        Ok(Self {
+            patterns: acc,
+            respect_gitignore,
+        })
+    }
+
+    /// The entries of the directory `dir` which are neither ignored by git
+    /// nor by the patterns.
+    pub(crate) fn list(&self, dir: &Path) -> Vec<PathBuf> {
+        ignore::WalkBuilder::new(dir)
+            .standard_filters(self.respect_gitignore)
+            .hidden(false)
+            .require_git(false)
+            .max_depth(Some(1))
+            .build()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    log::warn!("Listing directory contents {} failed", err);
+                    None
+                }
+            })
+            .filter(|entry| entry.depth() > 0)
+            .map(|entry| canonical(entry.into_path()))
+            .filter(|path| !self.contains(path))
+            .collect()
+    }
+
+    /// Whether the canonical `path`, a file or a directory, is ignored.
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        let ignored = self
+            .patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, OPTIONS));
+        if ignored {
+            log::debug!("Ignoring {}", path.display());
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        let cwd = canonical(std::env::temp_dir());
+        let ignored = Ignored::new(
+            &cwd,
+            &[
+                "target/**".to_owned(),
+                "src/generated/*.rs".to_owned(),
+                "**/vendor".to_owned(),
+            ],
+            true,
+        )
+        .unwrap();
+        assert!(ignored.contains(&cwd.join("target")));
+        assert!(ignored.contains(&cwd.join("target/debug/build/out.rs")));
+        assert!(ignored.contains(&cwd.join("src/generated/bindings.rs")));
+        assert!(!ignored.contains(&cwd.join("src/generated/nested/bindings.rs")));
+        assert!(!ignored.contains(&cwd.join("src/lib.rs")));
+        assert!(ignored.contains(&cwd.join("crates/alpha/vendor")));
+        assert!(!ignored.contains(&cwd.join("vendor.rs")));
+        assert!(Ignored::new(&cwd, &["src/[".to_owned()], true).is_err());
+    }
+
+    #[test]
+    fn gitignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs_err::create_dir_all(dir.join("vendor")).unwrap();
+        let dir = canonical(dir);
+        fs_err::write(dir.join(".gitignore"), "vendor/\n*.bak\n").unwrap();
+        fs_err::write(dir.join("lib.rs"), "").unwrap();
+        fs_err::write(dir.join("lib.rs.bak"), "").unwrap();
+        let list = |respect_gitignore| {
+            let mut paths = Ignored::new(&dir, &[], respect_gitignore)
+                .unwrap()
+                .list(&dir);
+            paths.sort();
+            paths
+        };
+        assert_eq!(list(true), vec![dir.join(".gitignore"), dir.join("lib.rs")]);
+        assert_eq!(
+            list(false),
+            vec![
+                dir.join(".gitignore"),
+                dir.join("lib.rs"),
+                dir.join("lib.rs.bak"),
+                dir.join("vendor"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Recoverable conditions encountered while extracting.
+//!
+//! By default these are logged and the affected content is skipped. With
+//! `--strict` any of them fails the run, with an exit code per kind.
+
+use crate::documentation::{CheckableChunk, ContentOrigin, Documentation};
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Info string tokens understood by `rustdoc`.
+const RUSTDOC_ATTRIBUTES: &[&str] = &[
+    "rust",
+    "ignore",
+    "should_panic",
+    "no_run",
+    "compile_fail",
+    "test_harness",
+    "standalone_crate",
+];
+
+/// Languages commonly used for non-rust code blocks.
+const LANGUAGES: &[&str] = &[
+    "text",
+    "txt",
+    "plain",
+    "plaintext",
+    "console",
+    "sh",
+    "bash",
+    "shell",
+    "zsh",
+    "fish",
+    "powershell",
+    "ps1",
+    "bat",
+    "cmd",
+    "toml",
+    "yaml",
+    "yml",
+    "json",
+    "json5",
+    "ron",
+    "xml",
+    "html",
+    "css",
+    "js",
+    "javascript",
+    "ts",
+    "typescript",
+    "c",
+    "h",
+    "cpp",
+    "c++",
+    "python",
+    "py",
+    "ruby",
+    "go",
+    "java",
+    "kotlin",
+    "swift",
+    "sql",
+    "diff",
+    "patch",
+    "markdown",
+    "md",
+    "ini",
+    "dockerfile",
+    "makefile",
+    "make",
+    "cmake",
+    "nix",
+    "lua",
+    "wat",
+    "asm",
+    "llvm",
+    "mermaid",
+    "dot",
+    "graphql",
+    "proto",
+];
+
+/// A recoverable condition, which causes content to be skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Issue {
+    /// A rust source file has syntax errors.
+    Unparseable { path: PathBuf, reason: String },
+    /// A file that was passed as argument or declared in a manifest does not
+    /// exist.
+    MissingTarget(PathBuf),
+    /// A file included as documentation with `include_str!` does not exist.
+    MissingInclude(PathBuf),
+    /// A fenced code block with an info string that is neither understood by
+    /// `rustdoc` nor a known language.
+    UnknownFence { origin: ContentOrigin, info: String },
+    /// A file exceeds `max_file_size`.
+    Oversized {
+        path: PathBuf,
+        size: u64,
+        limit: u64,
+    },
+    /// The literals extracted so far exceed `max_total_size`.
+    LimitReached { path: PathBuf, limit: u64 },
+}
+
+impl Issue {
+    /// The exit code used in strict mode.
+    pub(crate) fn exit_code(&self) -> u8 {
+        match self {
+            Self::Unparseable { .. } => 3,
+            Self::MissingTarget(_) | Self::MissingInclude(_) => 4,
+            Self::UnknownFence { .. } => 5,
+            Self::Oversized { .. } | Self::LimitReached { .. } => 6,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unparseable { path, reason } => {
+                write!(f, "Failed to parse {}: {}", path.display(), reason)
+            }
+            Self::MissingTarget(path) => write!(
+                f,
+                "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
+                path.display()
+            ),
+            Self::MissingInclude(path) => {
+                write!(
+                    f,
+                    "Included documentation does not exist: {}",
+                    path.display()
+                )
+            }
+            Self::UnknownFence { origin, info } => {
+                write!(f, "Unknown code fence info string `{info}` in {origin}")
+            }
+            Self::Oversized { path, size, limit } => write!(
+                f,
+                "Skipping {} with {} bytes, exceeding the limit of {} bytes",
+                path.display(),
+                size,
+                limit
+            ),
+            Self::LimitReached { path, limit } => write!(
+                f,
+                "Skipping {}, the literals extracted so far exceed the limit of {} bytes",
+                path.display(),
+                limit
+            ),
+        }
+    }
+}
+
+fn is_known_info_token(token: &str) -> bool {
+    RUSTDOC_ATTRIBUTES.contains(&token)
+        || LANGUAGES.contains(&token)
+        || token.starts_with("edition")
+        || token.starts_with("ignore-")
+}
+
+fn unknown_fences_in_chunk<'a>(
+    origin: &'a ContentOrigin,
+    chunk: &'a CheckableChunk,
+) -> impl Iterator<Item = Issue> + 'a {
+    Parser::new(chunk.as_str()).filter_map(move |event| match event {
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+            let known = info
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|token| !token.is_empty())
+                .all(|token| is_known_info_token(&token.to_lowercase()));
+            (!known).then(|| Issue::UnknownFence {
+                origin: origin.clone(),
+                info: info.to_string(),
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Find all fenced code blocks with unknown info strings.
+pub(crate) fn unknown_fences(docs: &Documentation) -> Vec<Issue> {
+    docs.iter()
+        .flat_map(|(origin, chunks)| {
+            chunks
+                .iter()
+                .flat_map(move |chunk| unknown_fences_in_chunk(origin, chunk))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+
+    #[test]
+    fn unknown_fences_are_found() {
+        const SOURCE: &str = fluff_up!([
+            "Some code.",
+            "",
+            "```rust,no_run",
+            "let x = 1;",
+            "```",
+            "",
+            "```sh",
+            "cargo spellcheck",
+            "```",
+            "",
+            "```rsut",
+            "let y = 2;",
+            "```"
+        ]);
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), SOURCE, true, false);
+        assert_eq!(
+            unknown_fences(&docs),
+            vec![Issue::UnknownFence {
+                origin,
+                info: "rsut".to_owned()
+            }]
+        );
+    }
+}
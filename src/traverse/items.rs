@@ -0,0 +1,298 @@
+//! Restrict the check to items matching a path pattern, see `--item-path`.
+//!
+//! Every chunk of a rust source belongs to the innermost item it is attached
+//! to or contained in, i.e. `my_crate::net::Socket::connect`. The module path
+//! of a file is derived from its location relative to the `src` directory of
+//! its package, the first segment is the name of the package, with `-`
+//! replaced by `_`.
+
+use super::packages::package_name;
+use crate::{ContentOrigin, Documentation};
+
+use fs_err as fs;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+
+/// An item path, relative to some module, and the lines it spans.
+type Item = (Vec<String>, RangeInclusive<usize>);
+
+/// The module path of the rust source file at `path`.
+fn module_path(path: &Path) -> Vec<String> {
+    let manifest_dir = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file());
+    let name = manifest_dir
+        .and_then(package_name)
+        .map_or_else(|| "crate".to_owned(), |name| name.replace('-', "_"));
+    let mut acc = vec![name];
+    let relative = manifest_dir.and_then(|dir| {
+        path.strip_prefix(dir.join("src"))
+            .or_else(|_| path.strip_prefix(dir))
+            .ok()
+    });
+    if let Some(relative) = relative {
+        let mut segments = relative
+            .with_extension("")
+            .components()
+            .filter_map(|component| component.as_os_str().to_str().map(ToOwned::to_owned))
+            .collect::<Vec<_>>();
+        // `mod.rs` and the crate roots do not add a module of their own
+        if segments.last().map(String::as_str) == Some("mod")
+            || (segments.len() == 1 && matches!(segments[0].as_str(), "lib" | "main"))
+        {
+            segments.pop();
+        }
+        acc.extend(segments);
+    }
+    acc
+}
+
+/// Record the item `name` below `parent`, spanning the lines of `span`.
+fn push(
+    acc: &mut Vec<Item>,
+    parent: &[String],
+    name: String,
+    span: proc_macro2::Span,
+) -> Vec<String> {
+    let mut path = parent.to_vec();
+    path.push(name);
+    acc.push((path.clone(), span.start().line..=span.end().line));
+    path
+}
+
+/// Collect the paths of `items` and their nested items below `parent`.
+fn walk(items: &[syn::Item], parent: &[String], acc: &mut Vec<Item>) {
+    for item in items {
+        let name = match item {
+            syn::Item::Const(item) => &item.ident,
+            syn::Item::Enum(item) => &item.ident,
+            syn::Item::Fn(item) => &item.sig.ident,
+            syn::Item::Macro(syn::ItemMacro {
+                ident: Some(ident), ..
+            }) => ident,
+            syn::Item::Mod(item) => &item.ident,
+            syn::Item::Static(item) => &item.ident,
+            syn::Item::Struct(item) => &item.ident,
+            syn::Item::Trait(item) => &item.ident,
+            syn::Item::Type(item) => &item.ident,
+            syn::Item::Union(item) => &item.ident,
+            syn::Item::Impl(item) => match &*item.self_ty {
+                syn::Type::Path(ty) => match ty.path.segments.last() {
+                    Some(segment) => &segment.ident,
+                    None => continue,
+                },
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let path = push(acc, parent, name.to_string(), item.span());
+        match item {
+            syn::Item::Mod(syn::ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => walk(items, &path, acc),
+            syn::Item::Enum(item) => {
+                for variant in &item.variants {
+                    push(acc, &path, variant.ident.to_string(), variant.span());
+                }
+            }
+            syn::Item::Struct(item) => {
+                for field in &item.fields {
+                    if let Some(ref ident) = field.ident {
+                        push(acc, &path, ident.to_string(), field.span());
+                    }
+                }
+            }
+            syn::Item::Impl(item) => {
+                for impl_item in &item.items {
+                    let name = match impl_item {
+                        syn::ImplItem::Const(item) => &item.ident,
+                        syn::ImplItem::Method(item) => &item.sig.ident,
+                        syn::ImplItem::Type(item) => &item.ident,
+                        _ => continue,
+                    };
+                    push(acc, &path, name.to_string(), impl_item.span());
+                }
+            }
+            syn::Item::Trait(item) => {
+                for trait_item in &item.items {
+                    let name = match trait_item {
+                        syn::TraitItem::Const(item) => &item.ident,
+                        syn::TraitItem::Method(item) => &item.sig.ident,
+                        syn::TraitItem::Type(item) => &item.ident,
+                        _ => continue,
+                    };
+                    push(acc, &path, name.to_string(), trait_item.span());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The items of the rust source `content`, none if it fails to parse.
+fn items(content: &str) -> Vec<Item> {
+    let mut acc = Vec::new();
+    match syn::parse_file(content) {
+        Ok(file) => walk(&file.items, &[], &mut acc),
+        Err(e) => log::debug!("Failed to parse items, only the module path applies: {}", e),
+    }
+    acc
+}
+
+/// Whether the item `path` matches the segments of `pattern`. Segments may
+/// contain `*` wildcards, a trailing `*` segment matches the item itself and
+/// everything below, a leading `crate` any package.
+fn matches(pattern: &[String], path: &[String]) -> bool {
+    fn segment(pattern: &str, segment: &str) -> bool {
+        glob::Pattern::new(pattern).map_or(pattern == segment, |pattern| pattern.matches(segment))
+    }
+    fn inner(pattern: &[String], path: &[String]) -> bool {
+        match (pattern.split_first(), path.split_first()) {
+            (Some((wildcard, rest)), _) if wildcard == "*" && rest.is_empty() => true,
+            (Some((first, rest)), Some((head, tail))) => segment(first, head) && inner(rest, tail),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+    match (pattern.split_first(), path.split_first()) {
+        (Some((first, rest)), Some((_, tail))) if first == "crate" => inner(rest, tail),
+        _ => inner(pattern, path),
+    }
+}
+
+/// Retain only the chunks of the items matching one of `patterns`, i.e.
+/// `my_crate::net::*`. Content other than rust source files belongs to no
+/// item and is dropped.
+pub(crate) fn retain(docs: &mut Documentation, patterns: &[String]) {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            pattern
+                .split("::")
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut files = HashMap::<PathBuf, (Vec<String>, Vec<Item>)>::new();
+    docs.retain(|origin, chunk| {
+        let path = match origin {
+            ContentOrigin::RustSourceFile(path) => path,
+            _ => return false,
+        };
+        let (module, items) = files.entry(path.clone()).or_insert_with(|| {
+            let content = fs::read_to_string(path).unwrap_or_default();
+            (module_path(path), items(&content))
+        });
+        let line = match chunk.iter().next() {
+            Some((_range, span)) => span.start.line,
+            None => return false,
+        };
+        let mut item_path = module.clone();
+        if let Some((path, _lines)) = items
+            .iter()
+            .filter(|(_path, lines)| lines.contains(&line))
+            .max_by_key(|(path, _lines)| path.len())
+        {
+            item_path.extend(path.iter().cloned());
+        }
+        let keep = patterns.iter().any(|pattern| matches(pattern, &item_path));
+        log::trace!("Item {} is kept: {}", item_path.join("::"), keep);
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_items() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        fs::create_dir_all(root.join("src/net")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"my-crate\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let lib = root.join("src/lib.rs");
+        fs::write(
+            &lib,
+            "//! The crate.\n\nmod net;\n\n/// A config.\npub struct Config;\n",
+        )
+        .unwrap();
+        let net = root.join("src/net/mod.rs");
+        fs::write(
+            &net,
+            r"//! Networking.
+
+/// A socket.
+pub struct Socket;
+
+impl Socket {
+    /// Connect the socket.
+    pub fn connect(&self) {}
+}
+
+mod inner {
+    /// Deeply nested.
+    fn deep() {}
+}
+",
+        )
+        .unwrap();
+        assert_eq!(module_path(&lib), vec!["my_crate"]);
+        assert_eq!(module_path(&net), vec!["my_crate", "net"]);
+
+        let load = || {
+            let mut docs = Documentation::new();
+            for path in [&lib, &net] {
+                let content = fs::read_to_string(path).unwrap();
+                docs.extend(Documentation::load_from_str(
+                    ContentOrigin::RustSourceFile(path.clone()),
+                    &content,
+                    true,
+                    false,
+                ));
+            }
+            docs
+        };
+        let contents = |patterns: &[&str]| {
+            let mut docs = load();
+            retain(
+                &mut docs,
+                &patterns.iter().map(|s| (*s).to_owned()).collect::<Vec<_>>(),
+            );
+            let mut contents = docs
+                .iter()
+                .flat_map(|(_origin, chunks)| {
+                    chunks.iter().map(|chunk| chunk.as_str().trim().to_owned())
+                })
+                .collect::<Vec<_>>();
+            contents.sort();
+            contents
+        };
+        assert_eq!(
+            contents(&["my_crate::net::*"]),
+            vec![
+                "A socket.",
+                "Connect the socket.",
+                "Deeply nested.",
+                "Networking."
+            ]
+        );
+        assert_eq!(
+            contents(&["crate::net::Socket::*"]),
+            vec!["A socket.", "Connect the socket."]
+        );
+        assert_eq!(
+            contents(&["my_crate::Config", "my_crate::net::inner::d*"]),
+            vec!["A config.", "Deeply nested."]
+        );
+        assert_eq!(contents(&["my_crate"]), vec!["The crate."]);
+    }
+}
@@ -13,6 +13,8 @@ pub struct TraverseModulesIter {
     /// zero limits to the provided path, if it is a directory, all children are
     /// collected
     max_depth: usize,
+    /// neither yielded nor searched for further modules
+    ignored: Ignored,
 }
 
 impl Default for TraverseModulesIter {
@@ -20,6 +22,7 @@ impl Default for TraverseModulesIter {
         Self {
             max_depth: usize::MAX,
             queue: VecDeque::with_capacity(128),
+            ignored: Ignored::default(),
         }
     }
 }
@@ -35,29 +38,12 @@ impl TraverseModulesIter {
         if meta.is_file() {
             self.queue.push_front((path, level));
         } else if meta.is_dir() {
-            ignore::WalkBuilder::new(dbg!(path))
-                .git_ignore(true)
-                .max_depth(1.into())
-                .same_file_system(true)
-                .skip_stdout(true)
-                .build()
-                .filter_map(|entry| {
-                    entry
-                        .ok()
-                        .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-                        .map(|x| x.path().to_owned())
-                })
-                .filter(|path: &PathBuf| {
-                    path.to_str()
-                        .map(|x| x.to_owned())
-                        .filter(|path| path.ends_with(".rs"))
-                        .is_some()
-                })
-                .try_for_each::<_, Result<()>>(|path| {
+            for path in self.ignored.list(&path) {
+                if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
                     log::trace!("🌱 using path {} as seed recursion dir", path.display());
                     self.queue.push_front((path, level));
-                    Ok(())
-                })?;
+                }
+            }
         }
         Ok(())
     }
@@ -77,8 +63,18 @@ impl TraverseModulesIter {
     }
 
     pub fn with_depth_limit<P: AsRef<Path>>(path: P, max_depth: usize) -> Result<Self> {
+        Self::with_ignored(path, max_depth, Ignored::default())
+    }
+
+    /// Skip the `ignored` paths, and the modules declared within them.
+    pub(crate) fn with_ignored<P: AsRef<Path>>(
+        path: P,
+        max_depth: usize,
+        ignored: Ignored,
+    ) -> Result<Self> {
         let mut me = Self {
             max_depth,
+            ignored,
             ..Default::default()
         };
         me.add_initial_path(path, 0)?;
@@ -109,16 +105,18 @@ impl TraverseModulesIter {
 impl Iterator for TraverseModulesIter {
     type Item = PathBuf;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((path, level)) = self.queue.pop_front() {
+        while let Some((path, level)) = self.queue.pop_front() {
+            if self.ignored.contains(&path) {
+                continue;
+            }
             if level < self.max_depth {
                 // ignore the error here, there is nothing we can do really
                 // TODO potentially consider returning a result covering this
                 let _ = self.collect_modules(path.as_path(), level + 1);
             }
-            Some(path)
-        } else {
-            None
+            return Some(path);
         }
+        None
     }
 }
 
@@ -128,32 +126,62 @@ impl Iterator for TraverseModulesIter {
 // TODO should not have knowledge of `dev_comments`.
 pub(crate) fn traverse(
     path: &Path,
+    ignored: &Ignored,
     doc_comments: bool,
     dev_comments: bool,
-) -> Result<impl Iterator<Item = Documentation>> {
-    traverse_with_depth_limit(path, usize::MAX, doc_comments, dev_comments)
+    max_file_size: u64,
+    recover: bool,
+) -> Result<impl Iterator<Item = std::result::Result<Documentation, Issue>>> {
+    traverse_with_depth_limit(
+        path,
+        ignored,
+        usize::MAX,
+        doc_comments,
+        dev_comments,
+        max_file_size,
+        recover,
+    )
 }
 
 /// traverse path with a depth limit, if the path is a directory all its
 /// children will be collected as depth 0 instead
+///
+/// Files that can not be read or parsed or exceed `max_file_size` are yielded
+/// as `Issue`, unparseable ones only unless their comments are to be
+/// `recover`ed.
 pub(crate) fn traverse_with_depth_limit(
     path: &Path,
+    ignored: &Ignored,
     max_depth: usize,
     doc_comments: bool,
     dev_comments: bool,
-) -> Result<impl Iterator<Item = Documentation>> {
-    let it = TraverseModulesIter::with_depth_limit(path, max_depth)?
-        .filter_map(move |path: PathBuf| -> Option<Documentation> {
-            fs::read_to_string(&path).ok().map(|content| {
-                Documentation::load_from_str(
-                    ContentOrigin::RustSourceFile(path),
-                    content.as_str(),
-                    doc_comments,
-                    dev_comments,
-                )
+    max_file_size: u64,
+    recover: bool,
+) -> Result<impl Iterator<Item = std::result::Result<Documentation, Issue>>> {
+    let it = TraverseModulesIter::with_ignored(path, max_depth, ignored.clone())?
+        .map(move |path: PathBuf| {
+            budget::admit(&path, max_file_size)?;
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => return Err(Issue::MissingTarget(path)),
+            };
+            super::extract_rust(
+                ContentOrigin::RustSourceFile(path.clone()),
+                content.as_str(),
+                doc_comments,
+                dev_comments,
+                recover,
+            )
+            .map_err(|e| Issue::Unparseable {
+                path,
+                reason: e.to_string(),
             })
         })
-        .filter(|documentation| !documentation.is_empty());
+        .filter(|documentation| {
+            documentation
+                .as_ref()
+                .map_or(true, |documentation| !documentation.is_empty())
+        });
     Ok(it)
 }
 
@@ -4,6 +4,7 @@
 //! `Origin`.
 
 use super::*;
+use crate::config::{FilesConfig, Unpublished};
 use crate::Documentation;
 
 use crate::errors::*;
@@ -26,6 +27,23 @@ use std::collections::VecDeque;
 mod iter;
 pub use iter::*;
 
+pub(crate) mod bindings;
+mod budget;
+mod diff;
+pub(crate) use diff::Changes;
+pub(crate) mod dump;
+use budget::Budget;
+mod identity;
+use identity::{canonical, Seen};
+mod ignored;
+use ignored::Ignored;
+pub(crate) mod issues;
+mod items;
+pub(crate) mod packages;
+mod skip;
+mod workspace;
+pub(crate) use issues::Issue;
+
 use proc_macro2::Spacing;
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
@@ -57,13 +75,13 @@ fn extract_modules_recurse_collect<P: AsRef<Path>>(
     }
     match (path1.is_file(), path2.is_file(), path3.is_file()) {
         (true, false, false) => {
-            let _ = acc.insert(path1);
+            let _ = acc.insert(canonical(path1));
         }
         (false, true, false) => {
-            let _ = acc.insert(path2);
+            let _ = acc.insert(canonical(path2));
         }
         (false, false, true) => {
-            let _ = acc.insert(path3);
+            let _ = acc.insert(canonical(path3));
         }
         (true, true, _) | (true, _, true) | (_, true, true) => {
             return Err(eyre!(
@@ -185,15 +203,16 @@ pub enum CheckEntity {
     Markdown(PathBuf),
     Source(PathBuf, bool), // recurse is the bool
     ManifestDescription(PathBuf, String),
+    ConfigFile(PathBuf),
 }
 
 impl CheckEntity {
-    #[allow(dead_code)]
     pub fn as_path(&self) -> &Path {
         match self {
             Self::Markdown(ref path) => path,
             Self::Source(ref path, _) => path,
             Self::ManifestDescription(ref path, _) => path,
+            Self::ConfigFile(ref path) => path,
         }
         .as_path()
     }
@@ -277,7 +296,7 @@ fn extract_products(
             }
             is_file
         })
-        .map(|path_str| CheckEntity::Source(manifest_dir.join(path_str), true))
+        .map(|path_str| CheckEntity::Source(canonical(manifest_dir.join(path_str)), true))
         .collect::<HashSet<CheckEntity>>();
 
     log::trace!("📜 explicit manifest products {:?}", &items);
@@ -287,20 +306,22 @@ fn extract_products(
 fn extract_readme(
     manifest: &cargo_toml::Manifest,
     manifest_dir: &Path,
+    issues: &mut Vec<Issue>,
 ) -> Result<Option<CheckEntity>> {
     Ok(manifest
         .package
         .as_ref()
         .and_then(|package| package.readme.get().ok().and_then(|x| x.as_path()))
         .and_then(|readme| {
-            let readme = PathBuf::from(readme);
+            let readme = manifest_dir.join(readme);
             if readme.is_file() {
-                Some(CheckEntity::Markdown(manifest_dir.join(readme)))
+                Some(CheckEntity::Markdown(canonical(readme)))
             } else {
                 log::warn!(
                     "📜 read-me file declared in Cargo.toml {} is not a file",
                     readme.display()
                 );
+                issues.push(Issue::MissingTarget(readme));
                 None
             }
         }))
@@ -326,6 +347,8 @@ fn extract_description(
 fn handle_manifest<P: AsRef<Path>>(
     manifest_dir: P,
     skip_readme: bool,
+    unpublished: Unpublished,
+    issues: &mut Vec<Issue>,
 ) -> Result<HashSet<CheckEntity>> {
     let manifest_dir = to_manifest_dir(manifest_dir)?;
     log::trace!("📜 Handle manifest in dir: {}", manifest_dir.display());
@@ -346,7 +369,7 @@ fn handle_manifest<P: AsRef<Path>>(
     })?;
 
     if !skip_readme {
-        let v = extract_readme(&manifest, &manifest_dir).wrap_err_with(|| {
+        let v = extract_readme(&manifest, &manifest_dir, issues).wrap_err_with(|| {
             eyre!(
                 "Failed to extract description from manifest {}",
                 manifest_dir.display()
@@ -370,66 +393,244 @@ fn handle_manifest<P: AsRef<Path>>(
 
     if let Some(workspace) = manifest.workspace {
         log::trace!("🪆 Handling manifest workspace");
-        workspace
-            .members
-            .into_iter()
-            .try_for_each::<_, Result<()>>(|member_entry_glob| {
-                let member_dir_glob = manifest_dir.join(&member_entry_glob);
-
-                let back_to_glob = member_dir_glob.as_os_str().to_str().ok_or_else(|| {
-                    eyre!(
-                        "Failed to convert path to str for member directory {}",
-                        member_dir_glob.display()
-                    )
-                })?;
-                let member_dirs = glob::glob(back_to_glob)?;
-                log::debug!("🪆 Handing manifest member: {}", &member_entry_glob);
-                for member_dir in member_dirs {
-                    let member_dir = member_dir?;
-                    log::trace!(
-                        "🪆 Handling manifest member glob resolved: {}",
-                        member_dir.display()
-                    );
-                    if let Ok((member_manifest, _member_manifest_content)) =
-                        load_manifest(&member_dir).wrap_err_with(|| {
-                            eyre!(
-                                "Failed to load manifest from member directory {}",
-                                member_dir.display()
-                            )
-                        })
-                    {
-                        if let Ok(member) = extract_products(&member_manifest, &member_dir) {
-                            acc.extend(member.into_iter());
-                        } else {
-                            bail!(
-                                "Workspace member {} product extraction failed",
-                                member_dir.display()
-                            );
-                        }
-                    } else {
-                        log::warn!(
-                            "🪆 Opening manifest from member failed {}",
+        for member_dir in workspace::members(manifest_dir, &workspace)? {
+            if member_dir == manifest_dir {
+                continue;
+            }
+            if unpublished == Unpublished::Skip && packages::is_unpublished(&member_dir) {
+                log::debug!("🪆 Skipping unpublished member {}", member_dir.display());
+                continue;
+            }
+            log::debug!("🪆 Handling manifest member: {}", member_dir.display());
+            if let Ok((member_manifest, _member_manifest_content)) = load_manifest(&member_dir) {
+                let member =
+                    extract_products(&member_manifest, &member_dir).wrap_err_with(|| {
+                        eyre!(
+                            "Workspace member {} product extraction failed",
                             member_dir.display()
-                        );
-                    }
+                        )
+                    })?;
+                acc.extend(member);
+                if !skip_readme {
+                    acc.extend(extract_readme(&member_manifest, &member_dir, issues)?);
                 }
-                Ok(())
-            })?;
+            } else {
+                log::warn!(
+                    "🪆 Opening manifest from member failed {}",
+                    member_dir.display()
+                );
+                issues.push(Issue::MissingTarget(member_dir.join("Cargo.toml")));
+            }
+        }
     }
     Ok(acc)
 }
 
-/// Extract all chunks from
+/// Check the leading lines of the file at `path` for generated markers.
+fn is_generated_file(path: &Path, files: &FilesConfig) -> bool {
+    use std::io::{BufRead, BufReader};
+
+    let header = fs::File::open(path).map(|file| {
+        BufReader::new(file)
+            .lines()
+            .take(files.generated_header_lines)
+            .map_while(std::result::Result::ok)
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    header.map_or(false, |header| files.is_generated(&header))
+}
+
+/// The files included as documentation by `#[doc = include_str!("..")]` in
+/// the rust sources of `docs`, unless ignored or, with `skip_readme`, a
+/// readme. Missing ones are reported.
+fn included_files(
+    docs: &Documentation,
+    skip_readme: bool,
+    ignored: &Ignored,
+    issues: &mut Vec<Issue>,
+) -> Vec<PathBuf> {
+    let is_readme = |path: &Path| {
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .map_or(false, |stem| stem.eq_ignore_ascii_case("readme"))
+    };
+    docs.iter()
+        .filter_map(|(origin, _chunks)| match origin {
+            ContentOrigin::RustSourceFile(path) => Some(path),
+            _ => None,
+        })
+        .flat_map(|path| {
+            let content = fs::read_to_string(path).unwrap_or_default();
+            let includes = if content.contains("include_str!") {
+                crate::documentation::included_docs(&content)
+            } else {
+                Vec::new()
+            };
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            includes.into_iter().map(move |include| dir.join(include))
+        })
+        .filter_map(|path| {
+            let canonical = path.canonicalize().ok();
+            if canonical.is_none() {
+                report(issues, Issue::MissingInclude(path));
+            }
+            canonical
+        })
+        .filter(|path| !(ignored.contains(path) || skip_readme && is_readme(path)))
+        .collect()
+}
+
+/// Log the issue and keep it, for `--strict` to fail on.
+fn report(issues: &mut Vec<Issue>, issue: Issue) {
+    log::warn!("{}", issue);
+    issues.push(issue);
+}
+
+/// Add the `extracted` chunks to `docs`, as long as they fit the budget.
+fn add_within_budget(
+    docs: &mut Documentation,
+    extracted: impl IntoIterator<Item = (ContentOrigin, Vec<CheckableChunk>)>,
+    budget: &mut Budget,
+    issues: &mut Vec<Issue>,
+) {
+    for (origin, chunks) in extracted {
+        match budget.consume(origin.as_path(), &chunks) {
+            Ok(()) => docs.extend(std::iter::once((origin, chunks))),
+            Err(issue) => report(issues, issue),
+        }
+    }
+}
+
+/// The files matching `patterns`, relative to `cwd`.
+fn glob_files(cwd: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut acc = Vec::new();
+    for pattern in patterns {
+        let pattern = cwd.join(pattern);
+        let pattern = pattern.to_str().ok_or_else(|| {
+            eyre!(
+                "Failed to convert path to str for pattern {}",
+                pattern.display()
+            )
+        })?;
+        let paths =
+            glob::glob(pattern).wrap_err_with(|| eyre!("Invalid file pattern {}", pattern))?;
+        for path in paths {
+            let path = path?;
+            if path.is_file() {
+                acc.push(path.canonicalize()?);
+            }
+        }
+    }
+    Ok(acc)
+}
+
+/// The markdown files matching the `markdown` patterns and the configuration
+/// files and scripts matching the `config_comments` patterns, relative to
+/// `cwd`.
+fn pattern_files(cwd: &Path, files: &FilesConfig) -> Result<Vec<CheckEntity>> {
+    let markdown = glob_files(cwd, &files.markdown)?
+        .into_iter()
+        .map(CheckEntity::Markdown);
+    let config = glob_files(cwd, &files.config_comments)?
+        .into_iter()
+        .map(CheckEntity::ConfigFile);
+    Ok(markdown.chain(config).collect())
+}
+
+/// Restrict the `markdown` and `config_comments` patterns of `files` to the
+/// `changed` files matching them, which are relative to `cwd`, so only those
+/// are checked of all the files matching the patterns.
+pub(crate) fn restrict_patterns(
+    files: &mut FilesConfig,
+    cwd: &Path,
+    changed: &[PathBuf],
+) -> Result<()> {
+    let changed = changed
+        .iter()
+        .filter_map(|path| cwd.join(path).canonicalize().ok())
+        .collect::<HashSet<_>>();
+    let restrict = |patterns: &[String]| -> Result<Vec<String>> {
+        Ok(glob_files(cwd, patterns)?
+            .into_iter()
+            .filter(|path| changed.contains(path))
+            .filter_map(|path| path.to_str().map(glob::Pattern::escape))
+            .collect())
+    };
+    files.markdown = restrict(&files.markdown)?;
+    files.config_comments = restrict(&files.config_comments)?;
+    Ok(())
+}
+
+/// Whether `path` is `-`, which requests to read rust source from the standard
+/// input.
+pub(crate) fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Extract the chunks of the rust source `content`. If it fails to parse and
+/// `recover` is set, the comments are recovered with an error tolerant lexer.
+fn extract_rust(
+    origin: ContentOrigin,
+    content: &str,
+    doc_comments: bool,
+    dev_comments: bool,
+    recover: bool,
+) -> Result<Documentation> {
+    let mut extracted = Documentation::new();
+    match extracted.add_rust(origin.clone(), content, doc_comments, dev_comments) {
+        Ok(()) => {}
+        Err(e) if recover => {
+            log::info!("Failed to parse {}, recovering its comments: {}", origin, e);
+            extracted.add_rust_tolerant(origin, content, doc_comments, dev_comments);
+        }
+        Err(e) => return Err(e.into()),
+    }
+    skip::retain(&mut extracted, content);
+    Ok(extracted)
+}
+
+/// Extract the chunks of the rust source read from `input`.
+fn extract_stdin(
+    mut input: impl std::io::Read,
+    dev_comments: bool,
+    recover: bool,
+    docs: &mut Documentation,
+    budget: &mut Budget,
+    issues: &mut Vec<Issue>,
+) -> Result<()> {
+    let mut content = String::new();
+    input
+        .read_to_string(&mut content)
+        .wrap_err("Failed to read the standard input")?;
+    let extracted = extract_rust(
+        ContentOrigin::Stdin,
+        content.as_str(),
+        true,
+        dev_comments,
+        recover,
+    )?;
+    add_within_budget(docs, extracted, budget, issues);
+    Ok(())
+}
+
+/// Extract all chunks from the given paths, `-` reads rust source from the
+/// standard input.
+///
+/// Content that had to be skipped is returned as list of issues.
 pub(crate) fn extract(
     mut paths: Vec<PathBuf>,
     mut recurse: bool,
     skip_readme: bool,
     dev_comments: bool,
-    _config: &Config,
-) -> Result<Documentation> {
-    let cwd = cwd()?;
+    config: &Config,
+) -> Result<(Documentation, Vec<Issue>)> {
+    let mut issues = Vec::new();
+    let cwd = config.base_dir()?;
+    let stdin = paths.iter().any(|path| is_stdin(path));
+    paths.retain(|path| !is_stdin(path));
     // if there are no arguments, pretend to be told to check the whole project
-    if paths.is_empty() {
+    if paths.is_empty() && !stdin {
         paths.push(cwd.clone());
         recurse = true;
     }
@@ -446,21 +647,28 @@ pub(crate) fn extract(
 
     // stage 1 - obtain canonical paths
     let mut flow = VecDeque::<PathBuf>::with_capacity(32);
-    flow.extend(paths.into_iter().filter_map(|path_in| {
+    for path_in in paths {
         let path = if path_in.is_absolute() {
             path_in.to_owned()
         } else {
             cwd.join(&path_in)
         };
         log::debug!("Processing {} -> {}", path_in.display(), path.display());
-        path.canonicalize().ok()
-    }));
+        match path.canonicalize() {
+            Ok(path) => flow.push_back(path),
+            Err(_) => report(&mut issues, Issue::MissingTarget(path)),
+        }
+    }
 
     log::debug!("Running on absolute dirs {:?} ", &flow);
 
     // stage 2 - check for manifest, .rs , .md files and directories
+    let ignored = Ignored::new(&cwd, &config.ignore, config.respect_gitignore)?;
     let mut files_to_check = Vec::with_capacity(64);
     while let Some(path) = flow.pop_front() {
+        if ignored.contains(&path) {
+            continue;
+        }
         let x = if let Ok(meta) = path.metadata() {
             if meta.is_file() {
                 match path.file_name().map(|x| x.to_str()).flatten() {
@@ -487,31 +695,18 @@ pub(crate) fn extract(
                     // keep walking directories and feed the path back
                     // if recursing is wanted
                     // and if it doesn't contain a manifest file
-                    match fs::read_dir(path) {
-                        Err(err) => log::warn!("Listing directory contents {} failed", err),
-                        Ok(entries) => {
-                            for entry in entries.flatten() {
-                                let path = entry.path();
-                                // let's try with that path again
-                                flow.push_back(path);
-                            }
-                        }
-                    }
+                    // let's try with these paths again
+                    flow.extend(ignored.list(&path));
                     continue;
                 } else {
-                    match fs::read_dir(path) {
-                        Err(err) => log::warn!("Listing directory contents {} failed", err),
-                        Ok(entries) => {
-                            for entry in entries.flatten() {
-                                let path = entry.path();
-                                // let's try attempt with that .rs file
-                                // if we end up here, recursion is off already
-                                if path.is_file() {
-                                    flow.push_back(path);
-                                }
-                            }
-                        }
-                    }
+                    // let's try attempt with that .rs file
+                    // if we end up here, recursion is off already
+                    flow.extend(
+                        ignored
+                            .list(&path)
+                            .into_iter()
+                            .filter(|path| path.is_file()),
+                    );
                     continue;
                 }
             } else {
@@ -526,61 +721,187 @@ pub(crate) fn extract(
     log::debug!("Found a total of {} files to check ", files_to_check.len());
 
     // stage 3 - resolve the manifest products and workspaces, warn about missing
-    let files_to_check = files_to_check
+    let mut files_to_check = files_to_check
         .into_iter()
         .try_fold::<Vec<_>, _, Result<_>>(Vec::with_capacity(64), |mut acc, tagged_path| {
             match tagged_path {
                 Extraction::Manifest(ref cargo_toml_path) => {
-                    let manifest_list = handle_manifest(cargo_toml_path, skip_readme)?;
+                    let manifest_list = handle_manifest(
+                        cargo_toml_path,
+                        skip_readme,
+                        config.files.unpublished,
+                        &mut issues,
+                    )?;
                     acc.extend(manifest_list);
                 }
-                Extraction::Missing(ref missing_path) => log::warn!(
-                    "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
-                    missing_path.display()
-                ),
+                Extraction::Missing(missing_path) => {
+                    report(&mut issues, Issue::MissingTarget(missing_path));
+                }
                 Extraction::Source(path) => acc.push(CheckEntity::Source(path, recurse)),
                 Extraction::Markdown(path) => acc.push(CheckEntity::Markdown(path)),
             }
             Ok(acc)
         })?;
+    files_to_check.extend(pattern_files(&cwd, &config.files)?);
+    files_to_check.retain(|check_entity| !ignored.contains(check_entity.as_path()));
+    if let Some(ref changes) = config.changes {
+        // the modules below a changed file might be changed
+        files_to_check.retain(|check_entity| {
+            matches!(check_entity, CheckEntity::Source(_, true))
+                || changes.contains(check_entity.as_path())
+        });
+    }
 
     // stage 4 - expand from the passed source files, if recursive, recurse down the module train
-    let docs =
+    let mut seen = Seen::new(&cwd);
+    let mut budget = Budget::new(&config.files);
+    let mut docs =
         files_to_check
             .into_iter()
             .try_fold(Documentation::new(), |mut docs, check_entity| {
                 match check_entity {
                     CheckEntity::Source(path, recurse) => {
+                        if !seen.insert(&path) {
+                            log::debug!("Skipping already seen file {}", path.display());
+                            return Ok(docs);
+                        }
+                        if let Err(issue) = budget.admit(&path) {
+                            report(&mut issues, issue);
+                            return Ok(docs);
+                        }
                         let content: String = fs::read_to_string(&path)?;
-                        docs.add_rust(
-                            ContentOrigin::RustSourceFile(path.clone()),
-                            content.as_str(),
-                            true,
-                            dev_comments,
-                        )?;
+                        let origin = ContentOrigin::RustSourceFile(path.clone());
+                        let extracted = if !config.files.is_generated(&content) {
+                            match extract_rust(
+                                origin,
+                                content.as_str(),
+                                true,
+                                dev_comments,
+                                config.files.recover_unparseable,
+                            ) {
+                                Ok(extracted) => extracted.into_iter().collect::<Vec<_>>(),
+                                Err(e) => {
+                                    // the modules of the file are unknown as well
+                                    let reason = e.to_string();
+                                    report(&mut issues, Issue::Unparseable { path, reason });
+                                    return Ok(docs);
+                                }
+                            }
+                        } else if config.files.bindings {
+                            log::debug!("Checking generated bindings {}", path.display());
+                            let bindings =
+                                Documentation::load_from_str(origin, &content, true, false);
+                            bindings
+                                .into_iter()
+                                .map(|(origin, chunks)| {
+                                    (origin, bindings::retain_doc_attributes(chunks))
+                                })
+                                .collect::<Vec<_>>()
+                        } else {
+                            log::info!("Skipping generated file {}", path.display());
+                            return Ok(docs);
+                        };
+                        add_within_budget(&mut docs, extracted, &mut budget, &mut issues);
 
                         if recurse {
-                            let iter = traverse(path.as_path(), true, dev_comments)?
-                                .map(|documentation| {
-                                    // Filter out duplicate _chunks_
-                                    // that `extend` would happily duplicate.
-                                    documentation
-                                        .into_iter()
-                                        .filter(|(origin, _chunks)| !docs.contains_key(origin))
-                                })
-                                .flatten()
-                                .collect::<Vec<_>>();
-                            docs.extend(iter);
+                            let documents = traverse(
+                                path.as_path(),
+                                &ignored,
+                                true,
+                                dev_comments,
+                                budget.max_file_size(),
+                                config.files.recover_unparseable,
+                            )?;
+                            // read lazily, so nothing is read once the budget
+                            // is spent
+                            for documentation in documents {
+                                if budget.is_spent() {
+                                    log::info!("Not traversing further below {}", path.display());
+                                    break;
+                                }
+                                let documentation = match documentation {
+                                    Ok(documentation) => documentation,
+                                    Err(issue) => {
+                                        report(&mut issues, issue);
+                                        continue;
+                                    }
+                                };
+                                for (origin, chunks) in documentation {
+                                    // Skip the files added before, also when
+                                    // reached under a different path.
+                                    if docs.contains_key(&origin) || !seen.insert(origin.as_path())
+                                    {
+                                        continue;
+                                    }
+                                    let chunks =
+                                        if !is_generated_file(origin.as_path(), &config.files) {
+                                            chunks
+                                        } else if config.files.bindings {
+                                            bindings::retain_doc_attributes(chunks)
+                                        } else {
+                                            log::info!(
+                                                "Skipping generated file {}",
+                                                origin.as_path().display()
+                                            );
+                                            continue;
+                                        };
+                                    add_within_budget(
+                                        &mut docs,
+                                        std::iter::once((origin, chunks)),
+                                        &mut budget,
+                                        &mut issues,
+                                    );
+                                }
+                            }
                         }
                     }
                     CheckEntity::Markdown(path) => {
+                        if !seen.insert(&path) {
+                            log::debug!("Skipping already seen file {}", path.display());
+                            return Ok(docs);
+                        }
+                        if let Err(issue) = budget.admit(&path) {
+                            report(&mut issues, issue);
+                            return Ok(docs);
+                        }
                         let content = fs::read_to_string(&path).wrap_err_with(|| {
                             eyre!("Common mark / markdown file does not exist")
                         })?;
                         if content.is_empty() {
                             bail!("Common mark / markdown file is empty")
                         }
-                        docs.add_commonmark(ContentOrigin::CommonMarkFile(path), content.as_str())?;
+                        if config.files.is_generated(&content) {
+                            log::info!("Skipping generated file {}", path.display());
+                            return Ok(docs);
+                        }
+                        let mut extracted = Documentation::new();
+                        extracted.add_commonmark(
+                            ContentOrigin::CommonMarkFile(path),
+                            content.as_str(),
+                        )?;
+                        add_within_budget(&mut docs, extracted, &mut budget, &mut issues);
+                    }
+                    CheckEntity::ConfigFile(path) => {
+                        if !seen.insert(&path) {
+                            log::debug!("Skipping already seen file {}", path.display());
+                            return Ok(docs);
+                        }
+                        if let Err(issue) = budget.admit(&path) {
+                            report(&mut issues, issue);
+                            return Ok(docs);
+                        }
+                        let content = fs::read_to_string(&path)?;
+                        let mut extracted = Documentation::new();
+                        if let Some(prefix) = config.files.comment_prefix(&path) {
+                            let origin = ContentOrigin::ConfigFile(path.clone());
+                            extracted.add_line_comments(origin, &content, prefix);
+                        } else if let Err(e) =
+                            extracted.add_config_file(ContentOrigin::ConfigFile(path), &content)
+                        {
+                            log::warn!("Skipping configuration file: {}", e);
+                            return Ok(docs);
+                        }
+                        add_within_budget(&mut docs, extracted, &mut budget, &mut issues);
                     }
                     CheckEntity::ManifestDescription(path, content) => {
                         if content.is_empty() {
@@ -592,7 +913,49 @@ pub(crate) fn extract(
                 Ok(docs)
             })?;
 
-    Ok(docs)
+    // stage 5 - the files included as documentation, checked as common mark
+    // files of their own, so the findings refer to them
+    for path in included_files(&docs, skip_readme, &ignored, &mut issues) {
+        if !seen.insert(&path) {
+            log::debug!("Skipping already seen file {}", path.display());
+            continue;
+        }
+        if let Err(issue) = budget.admit(&path) {
+            report(&mut issues, issue);
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        if content.is_empty() || config.files.is_generated(&content) {
+            log::debug!("Skipping included documentation {}", path.display());
+            continue;
+        }
+        let mut extracted = Documentation::new();
+        extracted.add_commonmark(ContentOrigin::CommonMarkFile(path), &content)?;
+        add_within_budget(&mut docs, extracted, &mut budget, &mut issues);
+    }
+
+    if stdin {
+        extract_stdin(
+            std::io::stdin().lock(),
+            dev_comments,
+            config.files.recover_unparseable,
+            &mut docs,
+            &mut budget,
+            &mut issues,
+        )?;
+    }
+
+    if config.files.example_comments {
+        docs.add_example_comments();
+    }
+    if !config.item_paths.is_empty() {
+        items::retain(&mut docs, &config.item_paths);
+    }
+    if let Some(ref changes) = config.changes {
+        changes.retain(&mut docs);
+    }
+
+    Ok((docs, issues))
 }
 
 #[cfg(test)]
@@ -638,7 +1001,7 @@ mod tests {
             ]
         );
         assert_eq!(
-            extract_readme(&manifest, &dir).expect("Must succeed"),
+            extract_readme(&manifest, &dir, &mut Vec::new()).expect("Must succeed"),
             Some(CheckEntity::Markdown(demo_dir().join("README.md")),)
         );
 
@@ -724,7 +1087,7 @@ mod tests {
                 .filter(None, log::LevelFilter::Trace)
                 .try_init();
 
-            let docs = extract(
+            let (docs, _issues) = extract(
                 vec![
                     $(
                         demo_dir().join($path)
@@ -756,6 +1119,413 @@ mod tests {
         };
     }
 
+    #[test]
+    fn missing_paths_are_issues() {
+        let missing = demo_dir().join("src/does_not_exist.rs");
+        let (docs, issues) = extract(
+            vec![missing.clone()],
+            false,
+            true,
+            false,
+            &Config::default(),
+        )
+        .unwrap();
+        assert!(docs.is_empty());
+        assert_eq!(issues, vec![Issue::MissingTarget(missing)]);
+    }
+
+    #[test]
+    fn stdin_is_an_origin() {
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+
+        let mut docs = Documentation::new();
+        let mut issues = Vec::new();
+        let mut budget = Budget::new(&FilesConfig::default());
+        extract_stdin(
+            "/// Some wrod.\nstruct X;\n".as_bytes(),
+            false,
+            false,
+            &mut docs,
+            &mut budget,
+            &mut issues,
+        )
+        .unwrap();
+        let chunks = docs.get(&ContentOrigin::Stdin).unwrap();
+        assert_eq!(chunks[0].as_str(), " Some wrod.");
+        assert_eq!(ContentOrigin::Stdin.to_string(), "<stdin>");
+        assert_eq!(ContentOrigin::Stdin.file(), None);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn unparseable_files_are_recovered() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let broken = dir.join("lib.rs");
+        fs::write(&broken, "/// Some wrod.\nfn broken( {\n").unwrap();
+
+        let mut config = Config::default();
+        let (docs, issues) = extract(vec![broken.clone()], true, true, false, &config).unwrap();
+        assert!(docs.is_empty());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].exit_code(), 3);
+
+        config.files.recover_unparseable = true;
+        let (docs, issues) = extract(vec![broken], true, true, false, &config).unwrap();
+        let chunks = docs.iter().next().unwrap().1;
+        assert_eq!(chunks[0].as_str(), " Some wrod.");
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn overlapping_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(
+            dir.join("lib.rs"),
+            "/// Some wrod.\nmod nested;\n#[path = \"nested/mod.rs\"]\nmod again;\n",
+        )
+        .unwrap();
+        fs::write(dir.join("nested/mod.rs"), "/// Another wrod.\nstruct X;\n").unwrap();
+        fs::write(dir.join("README.md"), "A wrod.\n").unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        for paths in [
+            vec![dir.clone(), dir.join("lib.rs")],
+            vec![dir.join("lib.rs"), dir.clone()],
+            vec![
+                dir.join("nested/mod.rs"),
+                dir.join("nested/../lib.rs"),
+                dir.join("README.md"),
+                dir.join("./README.md"),
+            ],
+        ] {
+            let (mut docs, _issues) =
+                extract(paths, true, true, false, &Config::default()).unwrap();
+            // adding a file again does not duplicate its chunks either
+            docs.extend(docs.clone());
+            assert_eq!(docs.entry_count(), 3);
+            for (origin, chunks) in docs.iter() {
+                assert_eq!(chunks.len(), 1, "{} is duplicated", origin);
+            }
+        }
+    }
+
+    #[test]
+    fn included_docs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "#![doc = include_str!(\"../README.md\")]\n#![doc = include_str!(\"gone.md\")]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "# X\n\nA wrod.\n").unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let (docs, issues) = extract(
+            vec![dir.join("src/lib.rs"), dir.join("README.md")],
+            false,
+            true,
+            false,
+            &Config::default(),
+        )
+        .unwrap();
+        // `--strict` fails on the missing one
+        assert_eq!(issues, vec![Issue::MissingInclude(dir.join("src/gone.md"))]);
+        assert_eq!(issues[0].exit_code(), 4);
+        let origin = ContentOrigin::CommonMarkFile(dir.join("README.md"));
+        let chunks = docs.get(&origin).unwrap();
+        // also listed explicitly, the readme is not added twice
+        assert_eq!(chunks.len(), 1);
+        let span = chunks[0].find_spans(7..11).into_values().next().unwrap();
+        assert_eq!(span.start, crate::LineColumn { line: 3, column: 2 });
+
+        let lib = vec![dir.join("src/lib.rs")];
+        let (docs, _issues) =
+            extract(lib.clone(), false, false, false, &Config::default()).unwrap();
+        assert!(docs.contains_key(&origin));
+        let (docs, _issues) = extract(lib.clone(), false, true, false, &Config::default()).unwrap();
+        assert!(!docs.contains_key(&origin));
+        let config = Config {
+            ignore: vec![dir.join("*.md").display().to_string()],
+            ..Config::default()
+        };
+        let (docs, _issues) = extract(lib, false, false, false, &config).unwrap();
+        assert!(!docs.contains_key(&origin));
+    }
+
+    #[test]
+    fn recursion_stops_once_the_budget_is_spent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::write(dir.join("lib.rs"), "/// Root.\nmod a;\nmod b;\nmod c;\n").unwrap();
+        for module in ["a", "b", "c"] {
+            fs::write(
+                dir.join(module).with_extension("rs"),
+                "/// Some lengthy module documentation.\nstruct X;\n",
+            )
+            .unwrap();
+        }
+        let dir = dir.canonicalize().unwrap();
+
+        let config = Config {
+            files: FilesConfig {
+                max_total_size: 48,
+                ..FilesConfig::default()
+            },
+            ..Config::default()
+        };
+        let (docs, issues) = extract(vec![dir.join("lib.rs")], true, true, false, &config).unwrap();
+        assert_eq!(docs.entry_count(), 2);
+        let reached = issues
+            .iter()
+            .filter(|issue| matches!(issue, Issue::LimitReached { .. }))
+            .count();
+        assert_eq!(reached, 1);
+    }
+
+    #[test]
+    fn config_file_comments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("ci")).unwrap();
+        let workflow = dir.join("ci").join("check.yml");
+        fs::write(
+            &workflow,
+            "# Runs the chekcs.\nname: check # of pull requests\n",
+        )
+        .unwrap();
+        fs::write(dir.join("ci").join("data.json"), "{}").unwrap();
+        let lib = dir.join("lib.rs");
+        fs::write(&lib, "/// Some word.\nstruct Y;\n").unwrap();
+
+        let mut config = Config::default();
+        config.files.config_comments = vec![format!("{}/ci/*", dir.display())];
+        let (docs, _issues) = extract(vec![lib], false, true, false, &config).unwrap();
+        let origin = ContentOrigin::ConfigFile(workflow.canonicalize().unwrap());
+        let chunks = docs.get(&origin).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_str(), " Runs the chekcs.");
+        assert_eq!(chunks[1].as_str(), " of pull requests");
+        assert_eq!(docs.entry_count(), 2);
+    }
+
+    #[test]
+    fn patterns_restricted_to_changed_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        fs::write(dir.join("docs").join("changed.md"), "# Changed\n").unwrap();
+        fs::write(dir.join("docs").join("other.md"), "# Other\n").unwrap();
+        fs::write(dir.join("docs").join("check.yml"), "# A comment\n").unwrap();
+
+        let mut files = FilesConfig {
+            markdown: vec!["docs/*.md".to_owned()],
+            config_comments: vec!["docs/*.yml".to_owned()],
+            ..FilesConfig::default()
+        };
+        let changed = vec![
+            PathBuf::from("docs/changed.md"),
+            PathBuf::from("src/lib.rs"),
+        ];
+        restrict_patterns(&mut files, &dir, &changed).unwrap();
+        assert_eq!(
+            glob_files(&dir, &files.markdown).unwrap(),
+            vec![dir.join("docs").join("changed.md")]
+        );
+        assert!(glob_files(&dir, &files.config_comments).unwrap().is_empty());
+    }
+
+    #[test]
+    fn markdown_patterns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        let guide = dir.join("docs").join("guide.md");
+        fs::write(&guide, "# Guide\n\nA wrod and `some_code`.\n").unwrap();
+        fs::write(dir.join("docs").join("notes.txt"), "Not markdown.").unwrap();
+        let lib = dir.join("lib.rs");
+        fs::write(&lib, "/// Some word.\nstruct Y;\n").unwrap();
+
+        let mut config = Config::default();
+        config.files.markdown = vec![format!("{}/docs/*.md", dir.display())];
+        let (docs, _issues) = extract(vec![lib], false, true, false, &config).unwrap();
+        let origin = ContentOrigin::CommonMarkFile(guide.canonicalize().unwrap());
+        assert!(docs.get(&origin).is_some());
+        assert_eq!(docs.entry_count(), 2);
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("generated")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(
+            dir.join("lib.rs"),
+            "/// Some word.\nmod generated;\nmod kept;\n",
+        )
+        .unwrap();
+        fs::write(dir.join("kept.rs"), "/// Kept.\nstruct X;\n").unwrap();
+        fs::write(dir.join("generated/mod.rs"), "/// Wrod.\nstruct Y;\n").unwrap();
+        fs::write(dir.join("target/out.rs"), "/// Wrod.\nstruct Z;\n").unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let config = Config {
+            ignore: vec![
+                format!("{}/generated/*.rs", dir.display()),
+                format!("{}/target/**", dir.display()),
+            ],
+            ..Config::default()
+        };
+        let (docs, _issues) = extract(vec![dir.clone()], true, true, false, &config).unwrap();
+        let mut paths = docs
+            .iter()
+            .map(|(origin, _chunks)| origin.as_path().to_owned())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec![dir.join("kept.rs"), dir.join("lib.rs")]);
+    }
+
+    #[test]
+    fn workspace_members() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        for (dir, manifest) in [
+            (
+                "",
+                "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+            ),
+            (
+                "crates/alpha",
+                "[package]\nname = \"alpha\"\nversion = \"0.1.0\"\nreadme = \"README.md\"\n",
+            ),
+            (
+                "crates/beta",
+                "[package]\nname = \"beta\"\nversion = \"0.1.0\"\n",
+            ),
+            (
+                "crates/old",
+                "[package]\nname = \"old\"\nversion = \"0.1.0\"\n",
+            ),
+        ] {
+            fs::create_dir_all(root.join(dir).join("src")).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+            fs::write(root.join(dir).join("src/lib.rs"), "//! Some crate.\n").unwrap();
+        }
+        fs::write(root.join("crates/alpha/README.md"), "# Alpha\n").unwrap();
+
+        let (docs, issues) =
+            extract(vec![root.clone()], false, false, false, &Config::default()).unwrap();
+        assert!(issues.is_empty());
+        let root = root.canonicalize().unwrap();
+        let mut origins = docs
+            .iter()
+            .map(|(origin, _)| origin.as_path().strip_prefix(&root).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        origins.sort();
+        assert_eq!(
+            origins,
+            vec![
+                PathBuf::from("crates/alpha/README.md"),
+                PathBuf::from("crates/alpha/src/lib.rs"),
+                PathBuf::from("crates/beta/src/lib.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unpublished_members() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        for (dir, manifest) in [
+            ("", "[workspace]\nmembers = [\"shipped\", \"internal\"]\n"),
+            (
+                "shipped",
+                "[package]\nname = \"shipped\"\nversion = \"0.1.0\"\n",
+            ),
+            (
+                "internal",
+                "[package]\nname = \"internal\"\nversion = \"0.1.0\"\npublish = false\n",
+            ),
+        ] {
+            fs::create_dir_all(root.join(dir).join("src")).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+            fs::write(root.join(dir).join("src/lib.rs"), "//! Some crate.\n").unwrap();
+        }
+
+        let mut config = Config::default();
+        let (docs, _issues) = extract(vec![root.clone()], false, true, false, &config).unwrap();
+        assert_eq!(docs.entry_count(), 2);
+
+        config.files.unpublished = Unpublished::Skip;
+        let (docs, _issues) = extract(vec![root.clone()], false, true, false, &config).unwrap();
+        let origins = docs
+            .iter()
+            .map(|(origin, _)| origin.as_path().to_owned())
+            .collect::<Vec<_>>();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(origins, vec![root.join("shipped/src/lib.rs")]);
+    }
+
+    #[test]
+    fn script_comments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        let shell = dir.join("scripts").join("release.sh");
+        fs::write(&shell, "#!/bin/sh\n# Tags the relaese.\necho \"# not\"\n").unwrap();
+        let sql = dir.join("scripts").join("schema.sql");
+        fs::write(&sql, "-- The tabels.\nCREATE TABLE x (y INT);\n").unwrap();
+        let lib = dir.join("lib.rs");
+        fs::write(&lib, "/// Some word.\nstruct Y;\n").unwrap();
+
+        let mut config = Config::default();
+        config.files.config_comments = vec![format!("{}/scripts/*", dir.display())];
+        config.files.extra.insert("sh".to_owned(), "#".to_owned());
+        config.files.extra.insert("sql".to_owned(), "--".to_owned());
+        let (docs, _issues) = extract(vec![lib], false, true, false, &config).unwrap();
+        let text = |path: &Path| {
+            let origin = ContentOrigin::ConfigFile(path.canonicalize().unwrap());
+            docs.get(&origin).unwrap()[0].as_str().to_owned()
+        };
+        assert_eq!(text(&shell), " Tags the relaese.");
+        assert_eq!(text(&sql), " The tabels.");
+    }
+
+    #[test]
+    fn generated_files_are_skipped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_owned();
+        let generated = dir.join("bindings.rs");
+        let hand_written = dir.join("lib.rs");
+        fs::write(
+            &generated,
+            "// @generated by bindgen, DO NOT EDIT\n/// Some wrod.\nstruct X;\n",
+        )
+        .unwrap();
+        fs::write(&hand_written, "/// Some word.\nstruct Y;\n").unwrap();
+
+        let (docs, _issues) = extract(
+            vec![generated.clone(), hand_written.clone()],
+            false,
+            true,
+            false,
+            &Config::default(),
+        )
+        .unwrap();
+        let origins = docs
+            .iter()
+            .map(|(origin, _)| origin.as_path().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(origins, vec![hand_written.canonicalize().unwrap()]);
+    }
+
     #[test]
     fn traverse_manifest_1() {
         extract_test!(["Cargo.toml"] + false => [
@@ -772,6 +1542,7 @@ mod tests {
             "src/nested/justtwo.rs",
             "src/nested/mod.rs",
             "member/true/lib.rs",
+            "member/true/README.md",
             "member/procmacro/src/lib.rs",
         ]);
     }
@@ -807,6 +1578,7 @@ mod tests {
         "src/nested/justtwo.rs",
         "src/nested/mod.rs",
         "member/true/lib.rs",
+        "member/true/README.md",
         "member/procmacro/src/lib.rs",
     ]);
 
@@ -824,6 +1596,7 @@ mod tests {
         "src/nested/justtwo.rs",
         "src/nested/mod.rs",
         "member/true/lib.rs",
+        "member/true/README.md",
         "member/procmacro/src/lib.rs",
     ]);
 
@@ -0,0 +1,132 @@
+//! Package names of checked files, to namespace the output of workspaces.
+//!
+//! Members of a workspace commonly share relative paths such as `src/lib.rs`,
+//! so findings are prefixed with the name of the package a file belongs to,
+//! which is the `[package]` of the closest `Cargo.toml` among its ancestors.
+//! The same manifest tells whether the package is ever published, see
+//! `files.unpublished`.
+
+use fs_err as fs;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// The name of the package defined by the manifest in `dir`, if any.
+pub(super) fn package_name(dir: &Path) -> Option<String> {
+    // a virtual manifest has no package, files next to it belong to none
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+        .and_then(|manifest| {
+            manifest
+                .get("package")?
+                .get("name")?
+                .as_str()
+                .map(ToOwned::to_owned)
+        })
+}
+
+/// Whether the package defined by the manifest in `dir` is never published,
+/// with `publish = false` or an empty list of registries.
+pub(crate) fn is_unpublished(dir: &Path) -> bool {
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+        .and_then(|manifest| {
+            let publish = manifest.get("package")?.get("publish")?;
+            Some(
+                publish.as_bool() == Some(false) || publish.as_array().map_or(false, Vec::is_empty),
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Whether the file at `path` belongs to a package which is never published.
+pub(crate) fn in_unpublished(path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map_or(false, is_unpublished)
+}
+
+/// The names of the packages of `paths`, by path.
+///
+/// Empty unless the paths belong to at least two different packages, a single
+/// crate needs no namespacing.
+pub(crate) fn packages<'a>(paths: impl IntoIterator<Item = &'a Path>) -> HashMap<PathBuf, String> {
+    let mut dirs = HashMap::<PathBuf, Option<String>>::new();
+    let mut packages = HashMap::new();
+    for path in paths {
+        let dir = path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join("Cargo.toml").is_file());
+        let name = dir.and_then(|dir| {
+            dirs.entry(dir.to_owned())
+                .or_insert_with(|| package_name(dir))
+                .clone()
+        });
+        if let Some(name) = name {
+            packages.insert(path.to_owned(), name);
+        }
+    }
+    if packages.values().collect::<HashSet<_>>().len() < 2 {
+        packages.clear();
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_members() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        for (dir, manifest) in [
+            ("", "[workspace]\nmembers = [\"alpha\", \"beta\"]\n"),
+            ("alpha", "[package]\nname = \"alpha\"\n"),
+            ("beta", "[package]\nname = \"beta-core\"\n"),
+        ] {
+            fs::create_dir_all(root.join(dir).join("src")).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+        }
+        let alpha = root.join("alpha/src/lib.rs");
+        let beta = root.join("beta/src/lib.rs");
+        let readme = root.join("README.md");
+
+        let names = packages([alpha.as_path(), beta.as_path(), readme.as_path()]);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&alpha], "alpha");
+        assert_eq!(names[&beta], "beta-core");
+        // a single package is not namespaced
+        assert!(packages([alpha.as_path(), readme.as_path()]).is_empty());
+    }
+
+    #[test]
+    fn unpublished_packages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        for (dir, manifest) in [
+            ("shipped", "[package]\nname = \"shipped\"\n"),
+            (
+                "internal",
+                "[package]\nname = \"internal\"\npublish = false\n",
+            ),
+            ("private", "[package]\nname = \"private\"\npublish = []\n"),
+            (
+                "registry",
+                "[package]\nname = \"registry\"\npublish = [\"corp\"]\n",
+            ),
+        ] {
+            fs::create_dir_all(root.join(dir).join("src")).unwrap();
+            fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+        }
+        assert!(!is_unpublished(&root.join("shipped")));
+        assert!(is_unpublished(&root.join("internal")));
+        assert!(is_unpublished(&root.join("private")));
+        assert!(!is_unpublished(&root.join("registry")));
+        assert!(in_unpublished(&root.join("internal/src/lib.rs")));
+        assert!(!in_unpublished(&root.join("shipped/src/lib.rs")));
+    }
+}
@@ -0,0 +1,213 @@
+//! Skip items annotated with `#[spellcheck::skip]`.
+//!
+//! The chunks attached to or contained in an annotated item are dropped, with
+//! `#![spellcheck::skip]` at the top of a file or an inline module, all of
+//! its chunks. `rustc` only accepts the attribute once the tool is registered
+//! with `#![register_tool(spellcheck)]`, which is not stable yet, so it is
+//! also honored within `#[cfg_attr(spellcheck, spellcheck::skip)]`, which
+//! `rustc` never expands.
+
+use crate::Documentation;
+
+use std::ops::RangeInclusive;
+use syn::spanned::Spanned;
+
+/// Whether `path` is `spellcheck::skip`.
+fn is_skip(path: &syn::Path) -> bool {
+    path.leading_colon.is_none()
+        && path.segments.len() == 2
+        && path.segments[0].ident == "spellcheck"
+        && path.segments[1].ident == "skip"
+}
+
+/// Whether one of `attrs` is `spellcheck::skip`, also within `cfg_attr`.
+fn skipped(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if is_skip(&attr.path) {
+            return true;
+        }
+        if !attr.path.is_ident("cfg_attr") {
+            return false;
+        }
+        match attr.parse_meta() {
+            // the first one is the predicate
+            Ok(syn::Meta::List(list)) => list.nested.iter().skip(1).any(|nested| {
+                matches!(nested, syn::NestedMeta::Meta(syn::Meta::Path(path)) if is_skip(path))
+            }),
+            _ => false,
+        }
+    })
+}
+
+/// The attributes of `item`, the inner ones of an inline module included.
+fn attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Enum(item) => &item.attrs,
+        syn::Item::ExternCrate(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::ForeignMod(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Macro(item) => &item.attrs,
+        syn::Item::Macro2(item) => &item.attrs,
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        syn::Item::Trait(item) => &item.attrs,
+        syn::Item::TraitAlias(item) => &item.attrs,
+        syn::Item::Type(item) => &item.attrs,
+        syn::Item::Union(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// The lines spanned by `span`.
+fn lines(span: proc_macro2::Span) -> RangeInclusive<usize> {
+    span.start().line..=span.end().line
+}
+
+/// Collect the lines of the skipped ones of `items` and their nested items.
+fn walk(items: &[syn::Item], acc: &mut Vec<RangeInclusive<usize>>) {
+    for item in items {
+        if skipped(attrs(item)) {
+            acc.push(lines(item.span()));
+            continue;
+        }
+        match item {
+            syn::Item::Mod(syn::ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => walk(items, acc),
+            syn::Item::Impl(item) => {
+                for impl_item in &item.items {
+                    let attrs = match impl_item {
+                        syn::ImplItem::Const(item) => &item.attrs,
+                        syn::ImplItem::Method(item) => &item.attrs,
+                        syn::ImplItem::Type(item) => &item.attrs,
+                        syn::ImplItem::Macro(item) => &item.attrs,
+                        _ => continue,
+                    };
+                    if skipped(attrs) {
+                        acc.push(lines(impl_item.span()));
+                    }
+                }
+            }
+            syn::Item::Trait(item) => {
+                for trait_item in &item.items {
+                    let attrs = match trait_item {
+                        syn::TraitItem::Const(item) => &item.attrs,
+                        syn::TraitItem::Method(item) => &item.attrs,
+                        syn::TraitItem::Type(item) => &item.attrs,
+                        syn::TraitItem::Macro(item) => &item.attrs,
+                        _ => continue,
+                    };
+                    if skipped(attrs) {
+                        acc.push(lines(trait_item.span()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The lines of the skipped items of the rust source `content`, all of them
+/// if the whole file is skipped, none if it fails to parse.
+fn skipped_lines(content: &str) -> Vec<RangeInclusive<usize>> {
+    let file = match syn::parse_file(content) {
+        Ok(file) => file,
+        Err(e) => {
+            log::debug!("Failed to parse items, no item is skipped: {}", e);
+            return Vec::new();
+        }
+    };
+    if skipped(&file.attrs) {
+        return vec![0..=usize::MAX];
+    }
+    let mut acc = Vec::new();
+    walk(&file.items, &mut acc);
+    acc
+}
+
+/// Drop the chunks within items annotated `#[spellcheck::skip]`, all of
+/// `docs` are extracted from the rust source `content`.
+pub(crate) fn retain(docs: &mut Documentation, content: &str) {
+    // most files have no annotation, spare parsing them once more
+    if !content.contains("spellcheck::skip") {
+        return;
+    }
+    let skipped = skipped_lines(content);
+    if skipped.is_empty() {
+        return;
+    }
+    docs.retain(|origin, chunk| {
+        let line = match chunk.iter().next() {
+            Some((_range, span)) => span.start.line,
+            None => return true,
+        };
+        let keep = !skipped.iter().any(|lines| lines.contains(&line));
+        if !keep {
+            log::trace!("Skipping chunk at line {} of {}", line, origin);
+        }
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContentOrigin;
+
+    fn contents(content: &str) -> Vec<String> {
+        let origin = ContentOrigin::TestEntityRust;
+        let mut docs = Documentation::load_from_str(origin, content, true, true);
+        retain(&mut docs, content);
+        docs.iter()
+            .flat_map(|(_origin, chunks)| {
+                chunks.iter().map(|chunk| chunk.as_str().trim().to_owned())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn annotated_items() {
+        let content = r"//! The crate.
+
+/// Kept.
+pub struct Kept;
+
+/// Generated tabel.
+#[spellcheck::skip]
+pub const TABLE: &[u8] = &[];
+
+impl Kept {
+    /// Skiped as well.
+    #[cfg_attr(spellcheck, spellcheck::skip)]
+    pub fn skipped(&self) {
+        // neither is thsi
+    }
+
+    /// Also kept.
+    pub fn kept(&self) {}
+}
+
+mod vendored {
+    #![spellcheck::skip]
+    //! Vendord.
+
+    /// Nested.
+    fn nested() {}
+}
+";
+        assert_eq!(contents(content), vec!["The crate.", "Kept.", "Also kept."]);
+        assert!(
+            contents("#![spellcheck::skip]\n//! The crate.\n\n/// Kept.\nstruct Kept;\n")
+                .is_empty()
+        );
+        assert_eq!(
+            contents("/// Kept.\n#[cfg_attr(spellcheck, rustfmt::skip)]\nstruct Kept;\n"),
+            vec!["Kept."]
+        );
+    }
+}
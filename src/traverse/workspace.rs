@@ -0,0 +1,92 @@
+//! Members of a cargo workspace.
+//!
+//! Running in the root of a workspace checks all of its members as one
+//! combined documentation. Members are resolved the way cargo does, from the
+//! paths and glob patterns of `members`, without the ones below an `exclude`d
+//! path.
+
+use super::identity::canonical;
+use crate::errors::*;
+
+use std::path::{Path, PathBuf};
+
+/// Whether a `members` entry is a glob pattern rather than a path.
+fn is_pattern(entry: &str) -> bool {
+    entry.contains(|c| matches!(c, '*' | '?' | '['))
+}
+
+/// The directories of the members of `workspace`, which is declared by the
+/// manifest in `root`, sorted and without duplicates.
+///
+/// Paths listed literally are returned even if they do not exist, so missing
+/// members can be reported, patterns only resolve to existing directories.
+pub(crate) fn members(root: &Path, workspace: &cargo_toml::Workspace) -> Result<Vec<PathBuf>> {
+    let excluded = workspace
+        .exclude
+        .iter()
+        .map(|path| canonical(root.join(path)))
+        .collect::<Vec<_>>();
+    let mut members = Vec::with_capacity(workspace.members.len());
+    for entry in &workspace.members {
+        if !is_pattern(entry) {
+            members.push(canonical(root.join(entry)));
+            continue;
+        }
+        let pattern = root.join(entry);
+        let pattern = pattern.to_str().ok_or_else(|| {
+            eyre!(
+                "Failed to convert path to str for member pattern {}",
+                pattern.display()
+            )
+        })?;
+        for dir in glob::glob(pattern)? {
+            let dir = dir?;
+            if dir.is_dir() {
+                members.push(canonical(dir));
+            }
+        }
+    }
+    members.retain(|member| {
+        let keep = !excluded.iter().any(|excluded| member.starts_with(excluded));
+        if !keep {
+            log::debug!("🪆 Skipping excluded member {}", member.display());
+        }
+        keep
+    });
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs_err as fs;
+
+    #[test]
+    fn globs_and_excludes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_owned();
+        for dir in ["crates/alpha", "crates/beta", "crates/legacy", "tools/gen"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("crates/notes.txt"), "not a member").unwrap();
+        let root = canonical(root);
+        let workspace = toml::from_str::<cargo_toml::Workspace>(
+            r#"
+members = ["crates/*", "./tools/gen", "crates/alpha", "missing"]
+exclude = ["crates/legacy"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            members(&root, &workspace).unwrap(),
+            vec![
+                root.join("crates/alpha"),
+                root.join("crates/beta"),
+                root.join("missing"),
+                root.join("tools/gen"),
+            ]
+        );
+    }
+}